@@ -16,8 +16,8 @@ use {
     yttrium::sign::{
         client::{generate_client_id_key, Client},
         client_types::{
-            ConnectParams, RejectionReason, Session, SessionProposal,
-            TransportType,
+            ConnectParams, PairResult, RejectionReason, Session,
+            SessionProposal, TransportType,
         },
         protocol_types::{
             GenericJsonRpcResponseError, JsonRpcVersion, Metadata,
@@ -26,22 +26,27 @@ use {
             SessionRequestJsonRpcResultResponse, SessionRequestRequest,
             SettleNamespace,
         },
-        storage::{Jwk, Storage, StorageError, StoragePairing},
+        storage::{
+            Jwk, Storage, StorageError, StorageJsonRpcRequest, StoragePairing,
+        },
         IncomingSessionMessage, SecretKey, Topic, VerifyContext,
     },
 };
 
 // TODO loading indicator for session request button while pending
 
-// TODO expiring for sessions
-// TODO expiring for pairing_keys
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct MyState {
     key: SecretKey,
     verify_public_key: Option<Jwk>,
     sessions: Vec<Session>,
     pairing_keys: HashMap<Topic, (ProtocolRpcId, StoragePairing)>,
+    // Kept apart from `pairing_keys` so state saved before expiries were tracked still loads
+    #[serde(default)]
+    pairing_expiries: HashMap<Topic, u64>,
     partial_sessions: HashMap<Topic, [u8; 32]>,
+    #[serde(default)]
+    authenticate_requests: HashMap<Topic, ([u8; 32], u64)>,
 }
 
 struct MySessionStore {
@@ -80,7 +85,9 @@ fn read_local_storage(key: &str) -> Result<MyState, String> {
                     verify_public_key: None,
                     sessions: Vec::new(),
                     pairing_keys: HashMap::new(),
+                    pairing_expiries: HashMap::new(),
                     partial_sessions: HashMap::new(),
+                    authenticate_requests: HashMap::new(),
                 })
             }
         }
@@ -90,7 +97,9 @@ fn read_local_storage(key: &str) -> Result<MyState, String> {
             verify_public_key: None,
             sessions: Vec::new(),
             pairing_keys: HashMap::new(),
+            pairing_expiries: HashMap::new(),
             partial_sessions: HashMap::new(),
+            authenticate_requests: HashMap::new(),
         })
     }
 }
@@ -164,6 +173,7 @@ impl Storage for MySessionStore {
             .map(|session| session.topic.clone())
             .chain(state.pairing_keys.keys().cloned())
             .chain(state.partial_sessions.keys().cloned())
+            .chain(state.authenticate_requests.keys().cloned())
             .collect())
     }
 
@@ -193,9 +203,11 @@ impl Storage for MySessionStore {
         rpc_id: ProtocolRpcId,
         sym_key: [u8; 32],
         self_key: [u8; 32],
+        expiry: u64,
     ) -> Result<(), StorageError> {
         let mut state =
             read_local_storage(&self.key).map_err(StorageError::Runtime)?;
+        state.pairing_expiries.insert(topic.clone(), expiry);
         state
             .pairing_keys
             .insert(topic, (rpc_id, StoragePairing { sym_key, self_key }));
@@ -216,6 +228,26 @@ impl Storage for MySessionStore {
             .cloned())
     }
 
+    fn delete_expired_pairings(
+        &self,
+        now: u64,
+    ) -> Result<Vec<Topic>, StorageError> {
+        let mut state =
+            read_local_storage(&self.key).map_err(StorageError::Runtime)?;
+        let expired = state
+            .pairing_expiries
+            .iter()
+            .filter(|(_, expiry)| **expiry <= now)
+            .map(|(topic, _)| topic.clone())
+            .collect::<Vec<_>>();
+        for topic in &expired {
+            state.pairing_expiries.remove(topic);
+            state.pairing_keys.remove(topic);
+        }
+        write_local_storage(&self.key, state).map_err(StorageError::Runtime)?;
+        Ok(expired)
+    }
+
     fn save_partial_session(
         &self,
         topic: Topic,
@@ -228,6 +260,49 @@ impl Storage for MySessionStore {
         Ok(())
     }
 
+    fn save_authenticate_request(
+        &self,
+        topic: Topic,
+        self_key: [u8; 32],
+        expiry: u64,
+    ) -> Result<(), StorageError> {
+        let mut state =
+            read_local_storage(&self.key).map_err(StorageError::Runtime)?;
+        state.authenticate_requests.insert(topic, (self_key, expiry));
+        write_local_storage(&self.key, state).map_err(StorageError::Runtime)?;
+        Ok(())
+    }
+
+    fn get_authenticate_request_key(
+        &self,
+        topic: Topic,
+    ) -> Result<Option<[u8; 32]>, StorageError> {
+        Ok(read_local_storage(&self.key)
+            .map_err(StorageError::Runtime)?
+            .authenticate_requests
+            .get(&topic)
+            .map(|(self_key, _)| *self_key))
+    }
+
+    fn delete_expired_authenticate_requests(
+        &self,
+        now: u64,
+    ) -> Result<Vec<Topic>, StorageError> {
+        let mut state =
+            read_local_storage(&self.key).map_err(StorageError::Runtime)?;
+        let expired = state
+            .authenticate_requests
+            .iter()
+            .filter(|(_, (_, expiry))| *expiry <= now)
+            .map(|(topic, _)| topic.clone())
+            .collect::<Vec<_>>();
+        for topic in &expired {
+            state.authenticate_requests.remove(topic);
+        }
+        write_local_storage(&self.key, state).map_err(StorageError::Runtime)?;
+        Ok(expired)
+    }
+
     fn get_verify_public_key(&self) -> Result<Option<Jwk>, StorageError> {
         Ok(read_local_storage(&self.key)
             .map_err(StorageError::Runtime)?
@@ -266,6 +341,14 @@ impl Storage for MySessionStore {
         Ok(())
     }
 
+    fn get_pending_json_rpc_requests(
+        &self,
+        _method: String,
+    ) -> Result<Vec<StorageJsonRpcRequest>, StorageError> {
+        // Sample wallet doesn't need to store JSON-RPC history
+        Ok(Vec::new())
+    }
+
     fn delete_json_rpc_history_by_topic(
         &self,
         _topic: Topic,
@@ -316,8 +399,14 @@ pub fn App() -> impl IntoView {
             async move {
                 let mut client = client.lock().await;
                 match client.wallet_client.pair(&pairing_uri).await {
-                    Ok(pairing) => {
-                        signal.set(Some(pairing));
+                    Ok(PairResult::Proposal(proposal, attestation)) => {
+                        signal.set(Some((proposal, attestation)));
+                    }
+                    Ok(PairResult::Authenticate(request, attestation)) => {
+                        tracing::info!(
+                            "[wallet] session authenticate: {request:?}: {attestation:?}",
+                        );
+                        pairing_request_open.set(false);
                     }
                     Err(e) => {
                         show_error_toast(
@@ -753,6 +842,28 @@ pub fn App() -> impl IntoView {
                                                     "[wallet] session request response on topic: {topic}: {id}: {response:?}",
                                                 );
                                             }
+                                            IncomingSessionMessage::SessionAuthenticate(request, attestation) => {
+                                                tracing::info!(
+                                                    "[wallet] session authenticate on topic: {topic}: {request:?}: {attestation:?}",
+                                                );
+                                            }
+                                            IncomingSessionMessage::SessionAuthenticateResponse(id, topic, cacaos) => {
+                                                tracing::info!(
+                                                    "[wallet] session authenticate response on topic: {topic}: {id}: {cacaos:?}",
+                                                );
+                                            }
+                                            IncomingSessionMessage::SessionExpired(topic) => {
+                                                tracing::info!(
+                                                    "[wallet] session expired on topic: {topic}",
+                                                );
+                                            }
+                                            IncomingSessionMessage::SessionRequestBlocked(request, decision) => {
+                                                tracing::info!(
+                                                    "[wallet] session request blocked on topic: {topic}: {}: {}",
+                                                    request.id,
+                                                    decision.message
+                                                );
+                                            }
                                         }
                                     }
                                     None => break,
@@ -7,16 +7,33 @@ use {
         call::{
             Call,
             send::{
-                do_send_transactions, prepare_send_transaction,
+                do_send_transactions,
                 safe_test::{
                     self, DoSendTransactionParams, OwnerSignature,
                     PreparedSendTransaction,
+                    prepare_send_transactions_with_owners,
                 },
             },
         },
         config::Config,
+        erc7579::{
+            accounts::safe::{
+                ISafe7579Modules, ISafeOwnerManager, get_add_owner_call,
+                get_change_threshold_call, get_remove_owner_call,
+                get_replace_module_calls, get_swap_owner_call,
+                get_uninstall_module_call,
+            },
+            module::{
+                Module, ModuleType, SENTINEL_ADDRESS, get_install_module_call,
+            },
+            ownable_validator::{
+                self, OWNABLE_VALIDATOR_ADDRESS, OwnableValidator,
+            },
+            smart_sessions::client::SmartSessionsClient,
+        },
         smart_accounts::{
             account_address::AccountAddress,
+            multisig::SafeSigningSession,
             safe::{
                 Owners, PreparedSignature, SignOutputEnum, SignStep3Params,
                 prepare_sign, sign, sign_step_3,
@@ -24,7 +41,7 @@ use {
         },
     },
     alloy::{
-        primitives::{B256, Bytes, U64, U256},
+        primitives::{Address, B256, Bytes, U64, U256},
         providers::ProviderBuilder,
         rpc::types::UserOperationReceipt,
     },
@@ -33,21 +50,53 @@ use {
 #[cfg_attr(feature = "uniffi", derive(uniffi_macros::Object))]
 pub struct AccountClient {
     owner: AccountAddress,
+    account: Option<AccountAddress>,
     chain_id: u64,
     pub config: Config,
 }
 
 impl AccountClient {
     pub fn new(owner: AccountAddress, chain_id: u64, config: Config) -> Self {
-        Self { owner, chain_id, config }
+        Self { owner, account: None, chain_id, config }
+    }
+
+    /// Client for an existing `account` that `owner` is an owner of, e.g.
+    /// after the owner that created it was replaced
+    pub fn with_account(
+        owner: AccountAddress,
+        account: AccountAddress,
+        chain_id: u64,
+        config: Config,
+    ) -> Self {
+        Self { owner, account: Some(account), chain_id, config }
     }
 
     pub fn chain_id(&self) -> u64 {
         self.chain_id
     }
 
+    /// The account passed to `with_account`, otherwise the Safe derived from
+    /// `owner`
     pub async fn get_address(&self) -> eyre::Result<AccountAddress> {
-        safe_test::get_address(self.owner, self.config.clone()).await
+        match self.account {
+            Some(account) => Ok(account),
+            None => {
+                safe_test::get_address(self.owner, self.config.clone()).await
+            }
+        }
+    }
+
+    /// Session keys of this account; requires the SmartSessions validator
+    /// to be installed
+    pub async fn smart_sessions_client(
+        &self,
+    ) -> eyre::Result<SmartSessionsClient> {
+        Ok(SmartSessionsClient::new(
+            self.owner,
+            self.get_address().await?,
+            self.chain_id,
+            self.config.clone(),
+        ))
     }
 
     pub fn prepare_sign_message(
@@ -96,10 +145,11 @@ impl AccountClient {
         &self,
         calls: Vec<Call>,
     ) -> eyre::Result<PreparedSendTransaction> {
-        prepare_send_transaction(
+        safe_test::prepare_send_transactions(
             calls,
-            self.owner,
-            self.chain_id,
+            self.owner.into(),
+            self.account,
+            None,
             self.config.clone(),
         )
         .await
@@ -119,6 +169,291 @@ impl AccountClient {
         .await
     }
 
+    /// Prepares `calls` from the Safe of `owners`, whose `threshold` of them
+    /// must then sign the session's `hash`
+    pub async fn prepare_multisig_send_transactions(
+        &self,
+        calls: Vec<Call>,
+        owners: Owners,
+    ) -> eyre::Result<SafeSigningSession> {
+        let prepared = prepare_send_transactions_with_owners(
+            calls,
+            owners.clone(),
+            None,
+            None,
+            self.config.clone(),
+        )
+        .await?;
+        Ok(SafeSigningSession::new(owners, prepared))
+    }
+
+    /// Submits the user operation once the session's threshold is met
+    pub async fn do_send_multisig_transactions(
+        &self,
+        session: &SafeSigningSession,
+    ) -> eyre::Result<Bytes> {
+        self.do_send_transactions(
+            session.signatures()?,
+            session.do_send_transaction_params().clone(),
+        )
+        .await
+    }
+
+    /// Current owners of the account, which sign its user operations
+    pub async fn get_owners(&self) -> eyre::Result<Vec<Address>> {
+        let provider = ProviderBuilder::new()
+            .connect_http(self.config.endpoints.rpc.base_url.parse()?);
+        let owners =
+            ISafeOwnerManager::new(self.get_address().await?.into(), provider)
+                .getOwners()
+                .call()
+                .await?;
+        Ok(owners)
+    }
+
+    /// How many of the account's owners must sign
+    pub async fn get_threshold(&self) -> eyre::Result<U256> {
+        let provider = ProviderBuilder::new()
+            .connect_http(self.config.endpoints.rpc.base_url.parse()?);
+        let threshold =
+            ISafeOwnerManager::new(self.get_address().await?.into(), provider)
+                .getThreshold()
+                .call()
+                .await?;
+        Ok(threshold)
+    }
+
+    /// Adds `owner` to the account and sets the threshold to `threshold`
+    pub async fn prepare_add_owner(
+        &self,
+        owner: Address,
+        threshold: u8,
+    ) -> eyre::Result<PreparedSendTransaction> {
+        let account = self.get_address().await?.into();
+        self.prepare_send_transactions(vec![get_add_owner_call(
+            account, owner, threshold,
+        )])
+        .await
+    }
+
+    /// Removes `owner` from the account and sets the threshold to
+    /// `threshold`, which can't exceed the owners left
+    pub async fn prepare_remove_owner(
+        &self,
+        owner: Address,
+        threshold: u8,
+    ) -> eyre::Result<PreparedSendTransaction> {
+        let account = self.get_address().await?.into();
+        let owners = self.get_owners().await?;
+        let call = get_remove_owner_call(account, &owners, owner, threshold)
+            .ok_or_else(|| {
+                eyre::eyre!("{owner} is not an owner of {account}")
+            })?;
+        self.prepare_send_transactions(vec![call]).await
+    }
+
+    /// Replaces `old` with `new`, e.g. to recover from a lost key. Keep using
+    /// the account afterwards with `AccountClient::with_account`.
+    pub async fn prepare_replace_owner(
+        &self,
+        old: Address,
+        new: Address,
+    ) -> eyre::Result<PreparedSendTransaction> {
+        let account = self.get_address().await?.into();
+        let owners = self.get_owners().await?;
+        let call = get_swap_owner_call(account, &owners, old, new)
+            .ok_or_else(|| eyre::eyre!("{old} is not an owner of {account}"))?;
+        self.prepare_send_transactions(vec![call]).await
+    }
+
+    /// Sets how many of the account's owners must sign
+    pub async fn prepare_set_threshold(
+        &self,
+        threshold: u8,
+    ) -> eyre::Result<PreparedSendTransaction> {
+        let account = self.get_address().await?.into();
+        self.prepare_send_transactions(vec![get_change_threshold_call(
+            account, threshold,
+        )])
+        .await
+    }
+
+    /// Owners the OwnableValidator module keeps for the account, if the
+    /// account installed it
+    pub async fn get_ownable_validator_owners(
+        &self,
+    ) -> eyre::Result<Vec<Address>> {
+        let provider = ProviderBuilder::new()
+            .connect_http(self.config.endpoints.rpc.base_url.parse()?);
+        let owners = OwnableValidator::new(OWNABLE_VALIDATOR_ADDRESS, provider)
+            .getOwners(self.get_address().await?.into())
+            .call()
+            .await?;
+        Ok(owners)
+    }
+
+    /// How many of the OwnableValidator owners must sign
+    pub async fn get_ownable_validator_threshold(&self) -> eyre::Result<U256> {
+        let provider = ProviderBuilder::new()
+            .connect_http(self.config.endpoints.rpc.base_url.parse()?);
+        let threshold =
+            OwnableValidator::new(OWNABLE_VALIDATOR_ADDRESS, provider)
+                .threshold(self.get_address().await?.into())
+                .call()
+                .await?;
+        Ok(threshold)
+    }
+
+    /// Adds `owner` to the OwnableValidator owners
+    pub async fn prepare_add_ownable_validator_owner(
+        &self,
+        owner: Address,
+    ) -> eyre::Result<PreparedSendTransaction> {
+        self.prepare_send_transactions(vec![
+            ownable_validator::get_add_owner_call(owner),
+        ])
+        .await
+    }
+
+    /// Removes `owner` from the OwnableValidator owners, which must stay at
+    /// least as many as the threshold
+    pub async fn prepare_remove_ownable_validator_owner(
+        &self,
+        owner: Address,
+    ) -> eyre::Result<PreparedSendTransaction> {
+        let owners = self.get_ownable_validator_owners().await?;
+        let call = ownable_validator::get_remove_owner_call(&owners, owner)
+            .ok_or_else(|| {
+                eyre::eyre!("{owner} is not an OwnableValidator owner")
+            })?;
+        self.prepare_send_transactions(vec![call]).await
+    }
+
+    /// Replaces `old` with `new` among the OwnableValidator owners in one
+    /// user operation
+    pub async fn prepare_replace_ownable_validator_owner(
+        &self,
+        old: Address,
+        new: Address,
+    ) -> eyre::Result<PreparedSendTransaction> {
+        let owners = self.get_ownable_validator_owners().await?;
+        let calls =
+            ownable_validator::get_replace_owner_calls(&owners, old, new)
+                .ok_or_else(|| {
+                    eyre::eyre!("{old} is not an OwnableValidator owner")
+                })?;
+        self.prepare_send_transactions(calls).await
+    }
+
+    /// Sets how many of the OwnableValidator owners must sign
+    pub async fn prepare_set_ownable_validator_threshold(
+        &self,
+        threshold: u8,
+    ) -> eyre::Result<PreparedSendTransaction> {
+        self.prepare_send_transactions(vec![
+            ownable_validator::get_set_threshold_call(threshold),
+        ])
+        .await
+    }
+
+    /// Validators or executors installed on the account, in the order of
+    /// the account's sentinel list
+    pub async fn get_installed_modules(
+        &self,
+        module_type: ModuleType,
+    ) -> eyre::Result<Vec<Address>> {
+        const PAGE_SIZE: u64 = 100;
+
+        let provider = ProviderBuilder::new()
+            .connect_http(self.config.endpoints.rpc.base_url.parse()?);
+        let account =
+            ISafe7579Modules::new(self.get_address().await?.into(), provider);
+        let mut modules = Vec::new();
+        let mut cursor = SENTINEL_ADDRESS;
+        loop {
+            let (page, next) = match module_type {
+                ModuleType::Validator => {
+                    let page = account
+                        .getValidatorsPaginated(cursor, U256::from(PAGE_SIZE))
+                        .call()
+                        .await?;
+                    (page.array, page.next)
+                }
+                ModuleType::Executor => {
+                    let page = account
+                        .getExecutorsPaginated(cursor, U256::from(PAGE_SIZE))
+                        .call()
+                        .await?;
+                    (page.array, page.next)
+                }
+                ModuleType::Fallback | ModuleType::Hook => {
+                    return Err(eyre::eyre!(
+                        "only validators and executors can be listed"
+                    ));
+                }
+            };
+            modules.extend(page);
+            if next == SENTINEL_ADDRESS || next == Address::ZERO {
+                return Ok(modules);
+            }
+            cursor = next;
+        }
+    }
+
+    /// Installs `module` on the account
+    pub async fn prepare_install_module(
+        &self,
+        module: &Module,
+    ) -> eyre::Result<PreparedSendTransaction> {
+        let account = self.get_address().await?.into();
+        self.prepare_send_transactions(vec![get_install_module_call(
+            account, module,
+        )])
+        .await
+    }
+
+    /// Uninstalls `module` from the account
+    pub async fn prepare_uninstall_module(
+        &self,
+        module: &Module,
+    ) -> eyre::Result<PreparedSendTransaction> {
+        let account = self.get_address().await?.into();
+        let installed = self.get_installed_modules_of(module).await?;
+        let call = get_uninstall_module_call(account, module, &installed)
+            .ok_or_else(|| {
+                eyre::eyre!("{} is not installed", module.address)
+            })?;
+        self.prepare_send_transactions(vec![call]).await
+    }
+
+    /// Installs `new` and uninstalls `old` in one user operation, e.g. to
+    /// rotate the account's validator
+    pub async fn prepare_replace_module(
+        &self,
+        new: &Module,
+        old: &Module,
+    ) -> eyre::Result<PreparedSendTransaction> {
+        let account = self.get_address().await?.into();
+        let installed = self.get_installed_modules_of(old).await?;
+        let calls = get_replace_module_calls(account, new, old, &installed)
+            .ok_or_else(|| eyre::eyre!("{} is not installed", old.address))?;
+        self.prepare_send_transactions(calls).await
+    }
+
+    /// Modules in the same sentinel list as `module`; fallbacks and hooks
+    /// don't need one to be uninstalled
+    async fn get_installed_modules_of(
+        &self,
+        module: &Module,
+    ) -> eyre::Result<Vec<Address>> {
+        match module.r#type {
+            ModuleType::Validator | ModuleType::Executor => {
+                self.get_installed_modules(module.r#type).await
+            }
+            ModuleType::Fallback | ModuleType::Hook => Ok(Vec::new()),
+        }
+    }
+
     pub async fn wait_for_user_operation_receipt(
         &self,
         user_operation_hash: Bytes,
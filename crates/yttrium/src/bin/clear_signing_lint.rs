@@ -0,0 +1,90 @@
+//! Lints the clear signing descriptor registry.
+//!
+//! ```sh
+//! cargo run -p yttrium --features clear_signing --bin clear-signing-lint
+//! # Lint a signed runtime bundle on top of the bundled assets
+//! cargo run -p yttrium --features clear_signing --bin clear-signing-lint -- \
+//!     --bundle bundle.json --signature <hex> --public-key <hex>
+//! ```
+//!
+//! Exits with status 1 when any error is found; warnings only are printed.
+
+use {
+    std::process::ExitCode,
+    yttrium::clear_signing::{
+        RegistryTrust, configure_registry, lint_registry,
+        load_registry_bundle_file,
+    },
+};
+
+fn main() -> ExitCode {
+    let mut bundle = None;
+    let mut signature = None;
+    let mut public_key = None;
+    let mut errors_only = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bundle" => bundle = args.next(),
+            "--signature" => signature = args.next(),
+            "--public-key" => public_key = args.next(),
+            "--errors-only" => errors_only = true,
+            _ => {
+                eprintln!(
+                    "usage: clear-signing-lint [--errors-only] [--bundle <path> \
+                     --signature <hex> --public-key <hex>]"
+                );
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    if let Some(bundle) = bundle {
+        let (Some(signature), Some(public_key)) = (signature, public_key)
+        else {
+            eprintln!("--bundle needs --signature and --public-key");
+            return ExitCode::from(2);
+        };
+        let decoded = hex::decode(public_key.trim_start_matches("0x"))
+            .ok()
+            .and_then(|key| <[u8; 32]>::try_from(key).ok())
+            .zip(hex::decode(signature.trim_start_matches("0x")).ok());
+        let Some((public_key, signature)) = decoded else {
+            eprintln!("--signature and --public-key must be hex");
+            return ExitCode::from(2);
+        };
+        configure_registry(RegistryTrust {
+            public_key,
+            pinned_sha256: Vec::new(),
+        });
+        match load_registry_bundle_file(&bundle, &signature) {
+            Ok(info) => println!(
+                "loaded bundle {bundle} version {} ({} descriptors)",
+                info.version, info.descriptors
+            ),
+            Err(err) => {
+                eprintln!("cannot load bundle {bundle}: {err}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let report = lint_registry();
+    for issue in report.errors() {
+        println!("{issue}");
+    }
+    if !errors_only {
+        for issue in report.warnings() {
+            println!("{issue}");
+        }
+    }
+    println!(
+        "{} entries checked, {} errors, {} warnings",
+        report.entries,
+        report.errors().count(),
+        report.warnings().count()
+    );
+
+    if report.has_errors() { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+}
@@ -5,7 +5,7 @@ use {
         erc4337::get_user_operation_receipt,
         jsonrpc::{JSONRPCResponse, Request, Response},
         time::{Duration, Instant, sleep},
-        user_operation::UserOperationV07,
+        user_operation::{UserOperationV07, UserOperationV08},
     },
     alloy::{primitives::Bytes, rpc::types::UserOperationReceipt},
     alloy_provider::ProviderBuilder,
@@ -28,13 +28,30 @@ impl BundlerClient {
         &self,
         entry_point_address: EntryPointAddress,
         user_op: UserOperationV07,
+    ) -> eyre::Result<Bytes> {
+        self.send_serialized_user_operation(entry_point_address, &user_op).await
+    }
+
+    /// Sends an EntryPoint v0.8 user operation, including its `eip7702Auth`
+    pub async fn send_user_operation_v08(
+        &self,
+        entry_point_address: EntryPointAddress,
+        user_op: UserOperationV08,
+    ) -> eyre::Result<Bytes> {
+        self.send_serialized_user_operation(entry_point_address, &user_op).await
+    }
+
+    async fn send_serialized_user_operation(
+        &self,
+        entry_point_address: EntryPointAddress,
+        user_op: &impl serde::Serialize,
     ) -> eyre::Result<Bytes> {
         let send_body = crate::jsonrpc::Request {
             jsonrpc: "2.0".into(), // TODO use Arc<str>
             id: 1,
             method: "eth_sendUserOperation".into(), // TODO use Arc<str>
             params: vec![
-                serde_json::to_value(&user_op)?,
+                serde_json::to_value(user_op)?,
                 entry_point_address.to_string().into(),
             ],
         };
@@ -60,13 +77,37 @@ impl BundlerClient {
         &self,
         entry_point_address: EntryPointAddress,
         user_op: UserOperationV07,
+    ) -> eyre::Result<EstimateResult> {
+        self.estimate_serialized_user_operation_gas(
+            entry_point_address,
+            &user_op,
+        )
+        .await
+    }
+
+    pub async fn estimate_user_operation_gas_v08(
+        &self,
+        entry_point_address: EntryPointAddress,
+        user_op: UserOperationV08,
+    ) -> eyre::Result<EstimateResult> {
+        self.estimate_serialized_user_operation_gas(
+            entry_point_address,
+            &user_op,
+        )
+        .await
+    }
+
+    async fn estimate_serialized_user_operation_gas(
+        &self,
+        entry_point_address: EntryPointAddress,
+        user_op: &impl serde::Serialize,
     ) -> eyre::Result<EstimateResult> {
         let req_body = Request {
             jsonrpc: "2.0".into(), // TODO use Arc<str>
             id: 1,
             method: "eth_estimateUserOperationGas".into(), // TODO use Arc<str>
             params: vec![
-                serde_json::to_value(&user_op)?,
+                serde_json::to_value(user_op)?,
                 entry_point_address.to_string().into(),
             ],
         };
@@ -251,4 +292,40 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_estimate_gas_v08() -> eyre::Result<()> {
+        let entry_point_address =
+            entry_point::EntryPointConfig::V08_SEPOLIA.address();
+
+        let bundler_client = setup_gas_estimation_bundler_mock().await?;
+
+        let user_op = UserOperationV08 {
+            factory: Some(entry_point::INITCODE_EIP7702_MARKER),
+            eip7702_auth: Some(crate::user_operation::Eip7702Auth {
+                chain_id: U256::from(11155111),
+                address: Address::repeat_byte(0x11),
+                nonce: U256::from(1),
+                ..Default::default()
+            }),
+            ..UserOperationV08::mock()
+        };
+
+        let value = serde_json::to_value(&user_op)?;
+        ensure!(value["eip7702Auth"]["chainId"] == "0xaa36a7");
+        ensure!(value["eip7702Auth"]["nonce"] == "0x1");
+        ensure!(
+            serde_json::to_value(UserOperationV08::mock())?
+                .get("eip7702Auth")
+                .is_none()
+        );
+
+        let estimate_result = bundler_client
+            .estimate_user_operation_gas_v08(entry_point_address, user_op)
+            .await?;
+
+        ensure!(estimate_result.call_gas_limit == U256::from(100000));
+
+        Ok(())
+    }
 }
@@ -12,11 +12,12 @@ use {
         chain::ChainId,
         config::Config,
         entry_point::{ENTRYPOINT_ADDRESS_V07, EntryPointVersion},
+        erc7579::ownable_validator::get_ownable_validator_signature,
         smart_accounts::{
             account_address::AccountAddress,
             nonce::get_nonce,
             safe::{
-                DUMMY_SIGNATURE, Owners, SAFE_4337_MODULE_ADDRESS,
+                Owners, SAFE_4337_MODULE_ADDRESS,
                 SAFE_ERC_7579_LAUNCHPAD_ADDRESS, SAFE_PROXY_FACTORY_1_4_1,
                 SAFE_SINGLETON_1_4_1, Safe7579Launchpad, SafeOp, factory_data,
                 get_account_address, get_call_data, get_call_data_with_try,
@@ -177,6 +178,25 @@ pub async fn prepare_send_transactions(
     address: Option<AccountAddress>,
     authorization_list: Option<Vec<Authorization>>,
     config: Config,
+) -> eyre::Result<PreparedSendTransaction> {
+    prepare_send_transactions_with_owners(
+        calls,
+        Owners { owners: vec![owner], threshold: 1 },
+        address,
+        authorization_list,
+        config,
+    )
+    .await
+}
+
+/// Like `prepare_send_transactions`, for a Safe with several owners. The
+/// returned `hash` must be signed by `owners.threshold` of them.
+pub async fn prepare_send_transactions_with_owners(
+    calls: Vec<Call>,
+    owners: Owners,
+    address: Option<AccountAddress>,
+    authorization_list: Option<Vec<Authorization>>,
+    config: Config,
 ) -> eyre::Result<PreparedSendTransaction> {
     let pimlico_client = PimlicoBundlerClient::new(BundlerConfig::new(
         config.endpoints.bundler.base_url.parse()?,
@@ -194,7 +214,7 @@ pub async fn prepare_send_transactions(
 
     prepare_send_transactions_inner(
         calls,
-        Owners { owners: vec![owner], threshold: 1 },
+        owners,
         address,
         authorization_list,
         &provider,
@@ -227,6 +247,7 @@ pub async fn prepare_send_transactions_inner(
     let entry_point_address = entry_point_config.address();
 
     let factory_data_value = factory_data(owners.clone()).abi_encode();
+    let signature = dummy_signature(&owners);
 
     let contract_address = get_account_address(provider, owners.clone()).await;
     let account_address =
@@ -288,7 +309,7 @@ pub async fn prepare_send_transactions_inner(
         paymaster_post_op_gas_limit: None,
         paymaster_data: None,
         // authorization_list: None,
-        signature: DUMMY_SIGNATURE,
+        signature,
     };
 
     if let Some(_authorization_list) = authorization_list {
@@ -377,34 +398,63 @@ pub async fn encode_send_transactions(
         valid_until,
     }: DoSendTransactionParams,
 ) -> eyre::Result<UserOperationV07> {
-    if signatures.len() != 1 {
-        return Err(eyre::eyre!("Only one signature is supported for now"));
+    if signatures.is_empty() {
+        return Err(eyre::eyre!("At least one signature is required"));
     }
 
     // Sort signatures by owner address
     // It's possible the 7579-flavored version of Safe does this automatically for us:
     // https://github.com/rhinestonewtf/safe7579/compare/80a6c7a3d40dd7334a0fe4463b7112ca8fe5f60a...main#diff-8fa26e5e86315f14488e14d8d719a37dd681cb96410c74a21df54185c43036abR306
     // But doing it here for now just-in-case
-    let mut signatures = signatures;
-    signatures.sort_by_key(|a| a.owner);
-    let signature_bytes = signatures
-        .into_iter()
-        .map(|s| s.signature.as_bytes())
-        .collect::<Vec<_>>()
-        .concat();
-
-    let signature = DynSolValue::Tuple(vec![
+    let signature = user_operation_signature(
+        valid_after,
+        valid_until,
+        combine_owner_signatures(signatures),
+    );
+
+    Ok(UserOperationV07 { signature, ..user_op })
+}
+
+fn user_operation_signature(
+    valid_after: U48,
+    valid_until: U48,
+    owner_signatures: Bytes,
+) -> Bytes {
+    DynSolValue::Tuple(vec![
         // DynSolValue::Address(Address::ZERO),
         // DynSolValue::Address(OWNABLE_VALIDATOR_ADDRESS),
         // TODO are these orders potentially swapped? https://github.com/rhinestonewtf/safe7579/compare/80a6c7a3d40dd7334a0fe4463b7112ca8fe5f60a...main#diff-8fa26e5e86315f14488e14d8d719a37dd681cb96410c74a21df54185c43036abL307
         DynSolValue::Uint(Uint::from(valid_after), 48),
         DynSolValue::Uint(Uint::from(valid_until), 48),
-        DynSolValue::Bytes(signature_bytes),
+        DynSolValue::Bytes(owner_signatures.to_vec()),
     ])
     .abi_encode_packed()
-    .into();
+    .into()
+}
 
-    Ok(UserOperationV07 { signature, ..user_op })
+/// Placeholder for the user operation signature while estimating gas: one
+/// 65-byte signature per required owner, laid out like
+/// `combine_owner_signatures`, so the limits cover checking all of them
+pub fn dummy_signature(owners: &Owners) -> Bytes {
+    user_operation_signature(
+        U48::ZERO,
+        U48::ZERO,
+        get_ownable_validator_signature(
+            (0..owners.threshold).map(|_| Bytes::from([0xff; 65])).collect(),
+        ),
+    )
+}
+
+/// Concatenates the signatures in ascending owner order, as Safe's
+/// `checkSignatures` requires
+pub fn combine_owner_signatures(mut signatures: Vec<OwnerSignature>) -> Bytes {
+    signatures.sort_by_key(|signature| signature.owner);
+    get_ownable_validator_signature(
+        signatures
+            .into_iter()
+            .map(|signature| signature.signature.as_bytes().into())
+            .collect(),
+    )
 }
 
 pub async fn do_send_transactions(
@@ -843,6 +893,104 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_dummy_signature() {
+        use crate::smart_accounts::safe::{DUMMY_SIGNATURE, Owners};
+
+        let owners = |threshold| Owners { owners: Vec::new(), threshold };
+        assert_eq!(super::dummy_signature(&owners(1)), DUMMY_SIGNATURE);
+        // validAfter and validUntil, then one signature per required owner
+        assert_eq!(super::dummy_signature(&owners(2)).len(), 12 + 2 * 65);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "test_local_bundler")]
+    async fn test_send_transaction_2_of_3() -> eyre::Result<()> {
+        let config = Config::local();
+        let faucet = anvil_faucet(config.clone()).await;
+
+        let provider = ProviderBuilder::new()
+            .connect_http(config.endpoints.rpc.base_url.parse()?);
+
+        let destination = LocalSigner::random();
+
+        let signers = [
+            LocalSigner::random(),
+            LocalSigner::random(),
+            LocalSigner::random(),
+        ];
+        let owners = Owners {
+            owners: signers.iter().map(|signer| signer.address()).collect(),
+            threshold: 2,
+        };
+        let sender_address =
+            get_account_address(provider.clone(), owners.clone()).await;
+
+        use_faucet(
+            &provider,
+            faucet.clone(),
+            U256::from(1),
+            sender_address.into(),
+        )
+        .await;
+
+        let PreparedSendTransaction {
+            hash, do_send_transaction_params, ..
+        } = prepare_send_transactions_with_owners(
+            vec![Call {
+                to: destination.address(),
+                value: Uint::from(1),
+                input: Bytes::new(),
+            }],
+            owners,
+            None,
+            None,
+            config.clone(),
+        )
+        .await?;
+
+        let signatures = signers[1..]
+            .iter()
+            .map(|signer| {
+                Ok(OwnerSignature {
+                    owner: signer.address(),
+                    signature: signer.sign_hash_sync(&hash)?,
+                })
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+
+        // Gas was estimated with a signature as long as the combined one
+        let user_op = encode_send_transactions(
+            signatures.clone(),
+            do_send_transaction_params.clone(),
+        )
+        .await?;
+        assert_eq!(
+            user_op.signature.len(),
+            do_send_transaction_params.user_op.signature.len()
+        );
+
+        let user_operation_hash = do_send_transactions(
+            signatures,
+            do_send_transaction_params,
+            config.clone(),
+        )
+        .await?;
+        let receipt = BundlerClient::new(BundlerConfig::new(
+            config.endpoints.bundler.base_url.parse()?,
+        ))
+        .wait_for_user_operation_receipt(user_operation_hash)
+        .await?;
+        assert!(receipt.success);
+
+        assert_eq!(
+            provider.get_balance(destination.address()).await?,
+            Uint::from(1)
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     #[cfg(feature = "test_local_bundler")]
     async fn test_sign_message_deployed() {
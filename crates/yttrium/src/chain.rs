@@ -102,6 +102,21 @@ impl Chain {
         entry_point_version: EntryPointVersion::V07,
     };
 
+    pub const ETHEREUM_MAINNET_V08: Self = Self {
+        id: ChainId::ETHEREUM_MAINNET,
+        entry_point_version: EntryPointVersion::V08,
+    };
+
+    pub const ETHEREUM_SEPOLIA_V08: Self = Self {
+        id: ChainId::ETHEREUM_SEPOLIA,
+        entry_point_version: EntryPointVersion::V08,
+    };
+
+    pub const BASE_SEPOLIA_V08: Self = Self {
+        id: ChainId::BASE_SEPOLIA,
+        entry_point_version: EntryPointVersion::V08,
+    };
+
     pub const ETHEREUM_SEPOLIA_V06: Self = Self {
         id: ChainId::ETHEREUM_SEPOLIA,
         entry_point_version: EntryPointVersion::V06,
@@ -0,0 +1,337 @@
+//! EIP-5792 `wallet_sendCalls` batches and EIP-7702 authorizations. Each
+//! call of a batch is formatted like a `multiSend` transaction, and the
+//! batch summary nets what the sender's balances lose and gain.
+
+use {
+    super::{
+        descriptor::{TokenLookupKey, native_token_key},
+        engine::{
+            DisplayItem, DisplayModel, chain_name, format_amount_with_decimals,
+            to_checksum_address,
+        },
+        locale::Locale,
+        nested::{format_inner_call, format_native_value},
+        registry,
+        risk::{RiskCode, RiskWarning},
+        token_registry::lookup_token_by_caip19,
+    },
+    crate::call::Call,
+    alloy::{primitives::Address, rpc::types::Authorization},
+    num_bigint::{BigInt, BigUint, Sign},
+    std::{collections::HashMap, sync::OnceLock},
+};
+
+const DELEGATES_JSON: &str = include_str!("assets/delegates.json");
+static DELEGATES: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+// transfer(address,uint256)
+const ERC20_TRANSFER: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+// transferFrom(address,address,uint256)
+const ERC20_TRANSFER_FROM: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowDirection {
+    Outgoing,
+    Incoming,
+}
+
+/// Net change of the sender's balance of one asset across a batch.
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenFlow {
+    /// CAIP-19 asset, e.g. `eip155:1/slip44:60` or `eip155:1/erc20:0xa0b8...`.
+    pub asset: String,
+    pub direction: FlowDirection,
+    /// Absolute amount with symbol, e.g. `1.5 ETH`.
+    pub amount: String,
+}
+
+/// Clear signing preview of a `wallet_sendCalls` batch.
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchPreview {
+    /// Batch summary listing the net flows, with one model per call in
+    /// `calls`.
+    pub model: DisplayModel,
+    /// Only native value and ERC-20 `transfer`/`transferFrom` are counted;
+    /// what swaps or deposits return can't be known without simulating.
+    pub flows: Vec<TokenFlow>,
+    /// EIP-7702 delegation signed along with the batch, if any.
+    pub authorization: Option<DisplayModel>,
+}
+
+/// Formats the calls of a `wallet_sendCalls` request sent from `from`, and
+/// the EIP-7702 `authorization` that upgrades `from` first, if there is one.
+/// Calls that can't be resolved become raw previews with a warning.
+pub fn format_batch(
+    chain_id: u64,
+    from: &str,
+    calls: &[Call],
+    authorization: Option<&Authorization>,
+    locale: &Locale,
+) -> BatchPreview {
+    let models = calls
+        .iter()
+        .map(|call| {
+            format_inner_call(
+                chain_id,
+                Some(from),
+                &to_checksum_address(&call.to.into_array()),
+                &to_biguint(call),
+                call.input.as_ref(),
+                1,
+                locale,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let (flows, warnings) = net_flows(chain_id, from, calls, locale);
+    let mut items = vec![DisplayItem {
+        label: "Calls".to_string(),
+        value: models.len().to_string(),
+    }];
+    items.extend(flows.iter().map(|flow| {
+        DisplayItem {
+            label: match flow.direction {
+                FlowDirection::Outgoing => "Sends",
+                FlowDirection::Incoming => "Receives",
+            }
+            .to_string(),
+            value: flow.amount.clone(),
+        }
+    }));
+
+    BatchPreview {
+        model: DisplayModel {
+            intent: "Batch transaction".to_string(),
+            interpolated_intent: Some(format!(
+                "Execute {} calls",
+                models.len()
+            )),
+            items,
+            warnings,
+            risks: Vec::new(),
+            raw: None,
+            calls: models,
+        },
+        flows,
+        authorization: authorization.map(authorization_model),
+    }
+}
+
+/// Formats an EIP-7702 authorization. Delegating to a contract that isn't a
+/// known smart account implementation is flagged as
+/// `RiskCode::UnknownDelegate`. Nothing in the preview depends on the
+/// locale yet.
+pub fn format_authorization(
+    authorization: &Authorization,
+    _locale: &Locale,
+) -> DisplayModel {
+    authorization_model(authorization)
+}
+
+fn authorization_model(authorization: &Authorization) -> DisplayModel {
+    let chain = match u64::try_from(authorization.chain_id) {
+        Ok(0) => "All chains".to_string(),
+        Ok(chain_id) => chain_name(chain_id)
+            .map_or_else(|| chain_id.to_string(), str::to_string),
+        Err(_) => authorization.chain_id.to_string(),
+    };
+    let mut items = vec![
+        DisplayItem { label: "Chain".to_string(), value: chain },
+        DisplayItem {
+            label: "Nonce".to_string(),
+            value: authorization.nonce.to_string(),
+        },
+    ];
+    let mut risks = Vec::new();
+    if authorization.chain_id.is_zero() {
+        risks.push(RiskWarning::new(
+            RiskCode::CrossChainAuthorization,
+            "Valid on every chain, not only the one shown".to_string(),
+        ));
+    }
+
+    // Delegating to the zero address clears the account's code
+    if authorization.address == Address::ZERO {
+        return DisplayModel {
+            intent: "Remove account delegation".to_string(),
+            interpolated_intent: None,
+            items,
+            warnings: Vec::new(),
+            risks,
+            raw: None,
+            calls: Vec::new(),
+        };
+    }
+
+    let address = to_checksum_address(&authorization.address.into_array());
+    let name = known_delegate(&address.to_ascii_lowercase());
+    if name.is_none() {
+        risks.push(RiskWarning::new(
+            RiskCode::UnknownDelegate,
+            format!(
+                "Gives {address} full control of the account; it is not a \
+                 known smart account implementation"
+            ),
+        ));
+    }
+    items.insert(
+        0,
+        DisplayItem {
+            label: "Delegate".to_string(),
+            value: name.as_ref().map_or_else(
+                || address.clone(),
+                |name| format!("{name} ({address})"),
+            ),
+        },
+    );
+
+    DisplayModel {
+        intent: "Upgrade account".to_string(),
+        interpolated_intent: Some(format!(
+            "Delegate account to {}",
+            name.as_deref().unwrap_or(address.as_str())
+        )),
+        items,
+        warnings: Vec::new(),
+        risks,
+        raw: None,
+        calls: Vec::new(),
+    }
+}
+
+/// Name of a known EIP-7702 delegate, from the runtime registry bundle or
+/// the bundled `delegates.json`. `address` is lowercase.
+fn known_delegate(address: &str) -> Option<String> {
+    registry::delegate(address).or_else(|| {
+        DELEGATES
+            .get_or_init(|| {
+                serde_json::from_str(DELEGATES_JSON)
+                    .expect("clear signing delegates JSON must be valid")
+            })
+            .get(address)
+            .cloned()
+    })
+}
+
+fn to_biguint(call: &Call) -> BigUint {
+    BigUint::from_bytes_be(&call.value.to_be_bytes::<32>())
+}
+
+fn net_flows(
+    chain_id: u64,
+    from: &str,
+    calls: &[Call],
+    locale: &Locale,
+) -> (Vec<TokenFlow>, Vec<String>) {
+    let sender = from.trim().parse::<Address>().ok();
+    // (asset, token contract, net change), in order of first appearance
+    let mut net: Vec<(String, Option<Address>, BigInt)> = Vec::new();
+    let mut add =
+        |asset: String, token: Option<Address>, delta: BigInt| match net
+            .iter_mut()
+            .find(|(existing, ..)| *existing == asset)
+        {
+            Some((_, _, total)) => *total += delta,
+            None => net.push((asset, token, delta)),
+        };
+    let native_asset = native_token_key(chain_id)
+        .map(|key| key.as_str().to_string())
+        .unwrap_or_else(|_| format!("eip155:{chain_id}/slip44:60"));
+
+    for call in calls {
+        // Value sent to oneself doesn't leave the account
+        if !call.value.is_zero() && sender != Some(call.to) {
+            add(
+                native_asset.clone(),
+                None,
+                BigInt::from_biguint(Sign::Minus, to_biguint(call)),
+            );
+        }
+
+        let data = call.input.as_ref();
+        let asset = || {
+            TokenLookupKey::from_erc20(chain_id, &format!("{:#x}", call.to))
+                .as_str()
+                .to_string()
+        };
+        let word_address = |offset: usize| {
+            Address::from_slice(&data[offset + 12..offset + 32])
+        };
+        match data.first_chunk::<4>() {
+            Some(&ERC20_TRANSFER) if data.len() == 68 => {
+                if sender != Some(word_address(4)) {
+                    let amount = BigUint::from_bytes_be(&data[36..68]);
+                    add(
+                        asset(),
+                        Some(call.to),
+                        BigInt::from_biguint(Sign::Minus, amount),
+                    );
+                }
+            }
+            Some(&ERC20_TRANSFER_FROM) if data.len() == 100 => {
+                let (source, recipient) = (word_address(4), word_address(36));
+                let amount = BigUint::from_bytes_be(&data[68..100]);
+                if source == recipient {
+                    continue;
+                }
+                if sender == Some(source) {
+                    add(
+                        asset(),
+                        Some(call.to),
+                        BigInt::from_biguint(Sign::Minus, amount.clone()),
+                    );
+                }
+                if sender == Some(recipient) {
+                    add(
+                        asset(),
+                        Some(call.to),
+                        BigInt::from_biguint(Sign::Plus, amount),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut warnings = Vec::new();
+    let flows = net
+        .into_iter()
+        .filter_map(|(asset, token, total)| {
+            let direction = match total.sign() {
+                Sign::Minus => FlowDirection::Outgoing,
+                Sign::Plus => FlowDirection::Incoming,
+                Sign::NoSign => return None,
+            };
+            let amount = total.magnitude();
+            let amount = match token {
+                None => format_native_value(chain_id, amount, locale),
+                Some(token) => match lookup_token_by_caip19(&asset) {
+                    Some(meta) => format!(
+                        "{} {}",
+                        format_amount_with_decimals(
+                            amount,
+                            meta.decimals,
+                            locale
+                        ),
+                        meta.symbol
+                    ),
+                    None => {
+                        let token = to_checksum_address(&token.into_array());
+                        warnings.push(format!(
+                            "Unknown token {token}; amount in base units"
+                        ));
+                        format!(
+                            "{} {token}",
+                            format_amount_with_decimals(amount, 0, locale)
+                        )
+                    }
+                },
+            };
+            Some(TokenFlow { asset, direction, amount })
+        })
+        .collect();
+    (flows, warnings)
+}
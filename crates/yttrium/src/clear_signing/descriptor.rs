@@ -1,9 +1,12 @@
 //! Descriptor parsing and token lookup utilities for clear signing.
 
 use {
-    super::resolver::ResolvedDescriptor, num_bigint::BigUint,
-    serde::Deserialize, serde_json::Value as JsonValue,
-    std::collections::HashMap, thiserror::Error,
+    super::{locale::Locale, resolver::ResolvedDescriptor},
+    num_bigint::BigUint,
+    serde::Deserialize,
+    serde_json::Value as JsonValue,
+    std::collections::HashMap,
+    thiserror::Error,
 };
 
 #[derive(Debug, Error)]
@@ -18,7 +21,7 @@ pub(crate) fn build_descriptor(
     resolved: &ResolvedDescriptor<'_>,
 ) -> Result<Descriptor, DescriptorError> {
     let mut descriptor_value: JsonValue =
-        serde_json::from_str(resolved.descriptor_json)
+        serde_json::from_str(&resolved.descriptor_json)
             .map_err(|err| DescriptorError::Parse(err.to_string()))?;
 
     for include_json in &resolved.includes {
@@ -31,7 +34,7 @@ pub(crate) fn build_descriptor(
         object.remove("includes");
     }
 
-    if let Some(abi_json) = resolved.abi_json
+    if let Some(abi_json) = resolved.abi_json.as_deref()
         && needs_abi_injection(&descriptor_value)
     {
         let abi_value: JsonValue = serde_json::from_str(abi_json)
@@ -289,6 +292,61 @@ pub struct DisplayFormat {
     pub fields: Vec<DisplayField>,
     #[serde(default)]
     pub required: Vec<String>,
+    /// Translated intents and labels keyed by language tag (`de`, `pt-BR`).
+    #[serde(default)]
+    pub translations: HashMap<String, FormatTranslation>,
+}
+
+impl DisplayFormat {
+    /// Translation for `locale`, trying the full tag before its language.
+    pub(crate) fn translation(
+        &self,
+        locale: &Locale,
+    ) -> Option<&FormatTranslation> {
+        locale.fallbacks().find_map(|tag| {
+            self.translations
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(tag))
+                .map(|(_, translation)| translation)
+        })
+    }
+
+    /// Intent in `locale`, falling back to the untranslated one.
+    pub(crate) fn localized_intent(&self, locale: &Locale) -> &str {
+        self.translation(locale)
+            .and_then(|translation| translation.intent.as_deref())
+            .unwrap_or(&self.intent)
+    }
+
+    /// Interpolated intent template in `locale`, falling back to the
+    /// untranslated one.
+    pub(crate) fn localized_interpolated_intent(
+        &self,
+        locale: &Locale,
+    ) -> Option<&str> {
+        self.translation(locale)
+            .and_then(|translation| translation.interpolated_intent.as_deref())
+            .or(self.interpolated_intent.as_deref())
+    }
+}
+
+/// Overrides of a display format for one language. Anything left out stays
+/// in the descriptor's own language.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FormatTranslation {
+    #[serde(default)]
+    pub intent: Option<String>,
+    #[serde(default, rename = "interpolatedIntent")]
+    pub interpolated_intent: Option<String>,
+    /// Field labels keyed by the field path as written in the format.
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+}
+
+impl FormatTranslation {
+    pub(crate) fn label(&self, path: &str) -> Option<&str> {
+        self.labels.get(path).map(String::as_str)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -301,6 +359,11 @@ pub struct DisplayField {
     pub format: Option<String>,
     #[serde(default)]
     pub params: JsonValue,
+    #[serde(default)]
+    pub visible: Option<JsonValue>,
+    /// Nested fields, relative to each element matched by `path`.
+    #[serde(default)]
+    pub fields: Vec<DisplayField>,
     #[serde(rename = "$ref")]
     #[serde(default)]
     pub reference: Option<String>,
@@ -312,6 +375,48 @@ pub struct EffectiveField {
     pub label: String,
     pub format: Option<String>,
     pub params: JsonValue,
+    pub visible: FieldVisibility,
+}
+
+/// ERC-7730 field `visible` rule.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum FieldVisibility {
+    #[default]
+    Always,
+    /// Shown when present; a missing value is not an error.
+    Optional,
+    Never,
+    /// Hidden when the value equals one of the constants.
+    IfNotIn(Vec<JsonValue>),
+    /// Hidden, but the value must equal one of the constants.
+    MustMatch(Vec<JsonValue>),
+}
+
+impl FieldVisibility {
+    pub fn from_json(value: &JsonValue) -> Option<Self> {
+        match value {
+            JsonValue::String(rule) => match rule.as_str() {
+                "always" => Some(Self::Always),
+                "optional" => Some(Self::Optional),
+                "never" => Some(Self::Never),
+                _ => None,
+            },
+            JsonValue::Bool(true) => Some(Self::Always),
+            JsonValue::Bool(false) => Some(Self::Never),
+            JsonValue::Object(rule) => {
+                if let Some(values) =
+                    rule.get("ifNotIn").and_then(JsonValue::as_array)
+                {
+                    Some(Self::IfNotIn(values.clone()))
+                } else {
+                    rule.get("mustMatch")
+                        .and_then(JsonValue::as_array)
+                        .map(|values| Self::MustMatch(values.clone()))
+                }
+            }
+            _ => None,
+        }
+    }
 }
 
 pub fn resolve_effective_field(
@@ -323,6 +428,7 @@ pub fn resolve_effective_field(
     let mut label = field.label.clone();
     let mut format = field.format.clone();
     let mut params = field.params.clone();
+    let mut visible = field.visible.clone();
 
     if let Some(reference) = &field.reference {
         if let Some(name) = extract_definition_name(reference) {
@@ -336,6 +442,9 @@ pub fn resolve_effective_field(
                 if format.is_none() {
                     format = def.format.clone();
                 }
+                if visible.is_none() {
+                    visible = def.visible.clone();
+                }
                 params = merge_params(&def.params, &params);
             } else {
                 warnings.push(format!(
@@ -353,8 +462,18 @@ pub fn resolve_effective_field(
 
     let path = path?;
     let label = label.unwrap_or_else(|| path.clone());
+    let visible = match visible {
+        Some(rule) => FieldVisibility::from_json(&rule).unwrap_or_else(|| {
+            warnings.push(format!(
+                "Unsupported visible rule {} for field '{}'",
+                rule, path
+            ));
+            FieldVisibility::Always
+        }),
+        None => FieldVisibility::Always,
+    };
 
-    Some(EffectiveField { path, label, format, params })
+    Some(EffectiveField { path, label, format, params, visible })
 }
 
 pub fn extract_definition_name(reference: &str) -> Option<&str> {
@@ -448,6 +567,8 @@ impl DecodedArguments {
     }
 
     pub fn get(&self, key: &str) -> Option<&ArgumentValue> {
+        // ERC-7730 spells the call value `@.value`
+        let key = if key == "@.value" { "@value" } else { key };
         self.index_by_name
             .get(key)
             .and_then(|&idx| self.ordered.get(idx))
@@ -581,6 +702,18 @@ fn decode_input(
     }
 }
 
+/// Decodes an all-zero call, exposing the argument paths the engine sees for
+/// `function` without real calldata.
+pub(crate) fn placeholder_arguments(
+    function: &FunctionDescriptor,
+) -> DecodedArguments {
+    let words: usize = function.inputs.iter().map(argument_word_count).sum();
+    let calldata = vec![0u8; 4 + words * 32];
+    decode_arguments(function, &calldata)
+        .and_then(|decoded| decoded.with_value(Some(&[0u8][..])))
+        .unwrap_or_else(|_| DecodedArguments::new())
+}
+
 fn argument_word_count(input: &FunctionInput) -> usize {
     if input.r#type.starts_with("tuple") && !input.components.is_empty() {
         input.components.iter().map(argument_word_count).sum()
@@ -716,12 +849,31 @@ pub fn determine_token_key(
             normalize_address(&addr)
         };
 
+        if is_native_currency_address(field, &address) {
+            return native_token_key(chain_id);
+        }
+
         return Ok(TokenLookupKey::from_erc20(chain_id, &address));
     }
 
     Err(TokenLookupError::MissingToken { field: field.path.clone() })
 }
 
+// `nativeCurrencyAddress` is a single address or a list of addresses
+pub(crate) fn is_native_currency_address(
+    field: &EffectiveField,
+    address: &str,
+) -> bool {
+    match field.params.get("nativeCurrencyAddress") {
+        Some(JsonValue::String(native)) => normalize_address(native) == address,
+        Some(JsonValue::Array(natives)) => natives
+            .iter()
+            .filter_map(JsonValue::as_str)
+            .any(|native| normalize_address(native) == address),
+        _ => false,
+    }
+}
+
 pub fn native_token_key(
     chain_id: u64,
 ) -> Result<TokenLookupKey, TokenLookupError> {
@@ -764,6 +916,7 @@ mod tests {
             label: "Test".to_string(),
             format: Some("tokenAmount".to_string()),
             params,
+            visible: FieldVisibility::Always,
         };
 
         let decoded = DecodedArguments::new();
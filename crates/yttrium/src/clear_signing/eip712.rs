@@ -1,14 +1,20 @@
 use {
     super::{
         descriptor::{
-            DisplayField, DisplayFormat, EffectiveField,
+            DisplayField, DisplayFormat, EffectiveField, FieldVisibility,
+            FormatTranslation, is_native_currency_address,
             resolve_effective_field,
         },
         engine::{
-            DisplayItem, DisplayModel, format_amount_with_decimals,
-            interpolate_template, parse_biguint, resolve_metadata_value,
+            DisplayItem, DisplayModel, chain_name, format_amount_with_decimals,
+            format_duration, format_unit, interpolate_template, parse_biguint,
+            resolve_metadata_value,
         },
-        resolver::{self, ResolvedTypedDescriptor},
+        locale::Locale,
+        nested::{call_summary, format_inner_call, format_native_value},
+        providers::provided_address_label,
+        resolver::{self, ResolvedTypedDescriptor, ResolverError},
+        risk::typed_data_risks,
         token_registry::lookup_token_by_caip19,
     },
     num_bigint::BigUint,
@@ -16,7 +22,7 @@ use {
     serde_json::Value,
     std::collections::HashMap,
     thiserror::Error,
-    time::{OffsetDateTime, macros::format_description},
+    time::OffsetDateTime,
 };
 
 #[derive(Debug, Clone, Deserialize)]
@@ -48,18 +54,38 @@ pub enum Eip712Error {
     TokenRegistry(String),
 }
 
+/// Formats a clear signing preview of EIP-712 typed data, with intents,
+/// labels, amounts and dates in `locale` (see `format_with_value`).
 pub fn format_typed_data(
     data: &TypedData,
+    locale: &Locale,
 ) -> Result<DisplayModel, Eip712Error> {
     let chain_id = extract_chain_id(&data.domain)?;
     let verifying_contract = extract_verifying_contract(&data.domain)?;
 
-    let resolved = resolver::resolve_typed(chain_id, &verifying_contract)
-        .map_err(|err| Eip712Error::Resolver(err.to_string()))?;
+    // Contract-specific descriptors win; otherwise fall back to descriptors
+    // for common message schemas (permits, Safe, Seaport, CoW)
+    let resolved = match resolver::resolve_typed(chain_id, &verifying_contract)
+    {
+        Ok(resolved) => resolved,
+        Err(ResolverError::NotFound(key)) => {
+            encode_type(&data.types, &data.primary_type)
+                .and_then(|encoded| {
+                    resolver::resolve_typed_by_schema(chain_id, &encoded)
+                        .transpose()
+                })
+                .unwrap_or(Err(ResolverError::NotFound(key)))
+                .map_err(|err| Eip712Error::Resolver(err.to_string()))?
+        }
+        Err(err) => return Err(Eip712Error::Resolver(err.to_string())),
+    };
     let descriptor = parse_descriptor(&resolved)?;
     let mut warnings = Vec::new();
 
+    // Schema descriptors without deployments (e.g. ERC-2612 permits) apply to
+    // any verifying contract
     if let Some(context) = descriptor.context.as_ref()
+        && !context.eip712.deployments.is_empty()
         && !context.eip712.deployments.iter().any(|deployment| {
             deployment.chain_id == chain_id
                 && deployment.address.eq_ignore_ascii_case(&verifying_contract)
@@ -81,8 +107,10 @@ pub fn format_typed_data(
     };
 
     let mut items = Vec::new();
+    let mut calls = Vec::new();
     let address_book = &resolved.address_book;
     let mut rendered_values: HashMap<String, String> = HashMap::new();
+    let container = container_values(&verifying_contract);
 
     for required in &format.required {
         if get_value(&data.message, required).is_none() {
@@ -90,37 +118,73 @@ pub fn format_typed_data(
         }
     }
 
-    for field in &format.fields {
-        let Some(effective) = resolve_effective_field(
-            field,
-            &descriptor.display.definitions,
-            &mut warnings,
-        ) else {
-            continue;
-        };
-        let Some(value) = get_value(&data.message, &effective.path) else {
-            warnings.push(format!(
-                "No value found for field path '{}'",
-                effective.path
-            ));
+    let fields = expand_fields(
+        &format.fields,
+        &descriptor.display.definitions,
+        format.translation(locale),
+        &data.message,
+        None,
+        &mut warnings,
+    );
+    for effective in fields {
+        let Some(value) =
+            field_value(&data.message, &container, &effective.path)
+        else {
+            if !matches!(
+                effective.visible,
+                FieldVisibility::Optional | FieldVisibility::Never
+            ) {
+                warnings.push(format!(
+                    "No value found for field path '{}'",
+                    effective.path
+                ));
+            }
             continue;
         };
 
+        match &effective.visible {
+            FieldVisibility::Never => continue,
+            FieldVisibility::IfNotIn(constants)
+                if constants
+                    .iter()
+                    .any(|constant| value_matches(value, constant)) =>
+            {
+                continue;
+            }
+            FieldVisibility::MustMatch(constants) => {
+                if !constants
+                    .iter()
+                    .any(|constant| value_matches(value, constant))
+                {
+                    warnings.push(format!(
+                        "Field '{}' has unexpected value {}",
+                        effective.label,
+                        format_raw(value)
+                    ));
+                }
+                continue;
+            }
+            _ => {}
+        }
+
         let rendered = render_field(
             &effective,
             value,
             &data.message,
+            &container,
             &descriptor.metadata,
             chain_id,
             address_book,
+            &mut calls,
             &mut warnings,
+            locale,
         )?;
         rendered_values.insert(effective.path.clone(), rendered.clone());
         items.push(DisplayItem { label: effective.label, value: rendered });
     }
 
     let interpolated_intent =
-        if let Some(template) = format.interpolated_intent.as_deref() {
+        if let Some(template) = format.localized_interpolated_intent(locale) {
             match interpolate_template(template, &rendered_values) {
                 Ok(value) => Some(value),
                 Err(message) => {
@@ -133,19 +197,264 @@ pub fn format_typed_data(
         };
 
     Ok(DisplayModel {
-        intent: format.intent.clone(),
+        intent: format.localized_intent(locale).to_string(),
         interpolated_intent,
         items,
         warnings,
+        risks: typed_data_risks(&data.primary_type, &data.message),
         raw: None,
+        calls,
     })
 }
 
+/// EIP-712 `encodeType` of `primary_type`, e.g.
+/// `Mail(Person from,Person to,string contents)Person(string name,address wallet)`.
+pub(crate) fn encode_type(
+    types: &HashMap<String, Vec<TypeMember>>,
+    primary_type: &str,
+) -> Option<String> {
+    fn struct_name(ty: &str) -> &str {
+        ty.split('[').next().unwrap_or(ty)
+    }
+
+    fn collect<'a>(
+        types: &'a HashMap<String, Vec<TypeMember>>,
+        name: &'a str,
+        found: &mut Vec<&'a str>,
+    ) {
+        let Some(members) = types.get(name) else { return };
+        if found.contains(&name) {
+            return;
+        }
+        found.push(name);
+        for member in members {
+            collect(types, struct_name(&member.ty), found);
+        }
+    }
+
+    types.get(primary_type)?;
+    let mut found = Vec::new();
+    collect(types, primary_type, &mut found);
+    let mut dependencies = found.split_off(1);
+    dependencies.sort_unstable();
+
+    let encoded = found
+        .into_iter()
+        .chain(dependencies)
+        .map(|name| {
+            let members = types[name]
+                .iter()
+                .map(|member| format!("{} {}", member.ty, member.name))
+                .collect::<Vec<_>>();
+            format!("{name}({})", members.join(","))
+        })
+        .collect::<String>();
+    Some(encoded)
+}
+
+// Flattens nested field groups and `[]` paths into one field per value, with
+// absolute paths (e.g. `details.[1].amount`)
+fn expand_fields(
+    fields: &[DisplayField],
+    definitions: &HashMap<String, DisplayField>,
+    translation: Option<&FormatTranslation>,
+    message: &Value,
+    prefix: Option<&str>,
+    warnings: &mut Vec<String>,
+) -> Vec<EffectiveField> {
+    let mut expanded = Vec::new();
+    for field in fields {
+        if !field.fields.is_empty() {
+            let Some(path) = field.path.as_deref() else {
+                warnings.push("Field group without a path".to_string());
+                continue;
+            };
+            for (element, _) in expand_path(message, &join_path(prefix, path)) {
+                expanded.extend(expand_fields(
+                    &field.fields,
+                    definitions,
+                    translation,
+                    message,
+                    Some(&element),
+                    warnings,
+                ));
+            }
+            continue;
+        }
+
+        let Some(mut effective) =
+            resolve_effective_field(field, definitions, warnings)
+        else {
+            continue;
+        };
+        if let Some(label) = translation
+            .and_then(|translation| translation.label(&effective.path))
+        {
+            effective.label = label.to_string();
+        }
+        effective.path = join_path(prefix, &effective.path);
+        if let Some(token_path) =
+            effective.params.get("tokenPath").and_then(Value::as_str)
+        {
+            let token_path = join_path(prefix, token_path);
+            effective.params["tokenPath"] = Value::String(token_path);
+        }
+
+        if !effective.path.contains("[]") {
+            expanded.push(effective);
+            continue;
+        }
+        for (path, indices) in expand_path(message, &effective.path) {
+            let mut element = effective.clone();
+            element.path = path;
+            // `details.[].amount` pairs with `details.[].token` by index
+            if let Some(token_path) =
+                element.params.get("tokenPath").and_then(Value::as_str)
+            {
+                let mut token_path = token_path.to_string();
+                for index in &indices {
+                    token_path =
+                        token_path.replacen("[]", &format!("[{index}]"), 1);
+                }
+                element.params["tokenPath"] = Value::String(token_path);
+            }
+            expanded.push(element);
+        }
+    }
+    expanded
+}
+
+// Paths starting with `@.` or `$.` are absolute
+fn join_path(prefix: Option<&str>, path: &str) -> String {
+    match prefix {
+        Some(prefix) if !path.starts_with("@.") && !path.starts_with("$.") => {
+            format!("{prefix}.{path}")
+        }
+        _ => path.to_string(),
+    }
+}
+
+// Replaces each `[]` segment with the indices of the array it matches
+fn expand_path(message: &Value, path: &str) -> Vec<(String, Vec<usize>)> {
+    let Some((head, tail)) = path.split_once("[]") else {
+        return vec![(path.to_string(), Vec::new())];
+    };
+    let array_path = head.trim_end_matches('.');
+    let Some(elements) =
+        get_value(message, array_path).and_then(Value::as_array)
+    else {
+        return Vec::new();
+    };
+
+    let mut paths = Vec::new();
+    for index in 0..elements.len() {
+        let concrete = format!("{head}[{index}]{tail}");
+        for (path, mut indices) in expand_path(message, &concrete) {
+            indices.insert(0, index);
+            paths.push((path, indices));
+        }
+    }
+    paths
+}
+
+// Values of container paths (`@.to` is the verifying contract)
+fn container_values(verifying_contract: &str) -> Value {
+    serde_json::json!({ "to": verifying_contract })
+}
+
+fn field_value<'a>(
+    message: &'a Value,
+    container: &'a Value,
+    path: &str,
+) -> Option<&'a Value> {
+    if let Some(name) = path.trim().strip_prefix("@.")
+        && let Some(value) = container.get(name)
+    {
+        return Some(value);
+    }
+    get_value(message, path)
+}
+
+/// Static checks for the descriptor linter: the descriptor parses, is
+/// deployed at `deployment` (schema descriptors have none) and its fields
+/// resolve.
+pub(crate) fn check_typed_descriptor(
+    resolved: &ResolvedTypedDescriptor<'_>,
+    deployment: Option<(u64, &str)>,
+) -> Result<Vec<String>, Eip712Error> {
+    let descriptor = parse_descriptor(resolved)?;
+    let mut problems = Vec::new();
+
+    if let Some((chain_id, verifying_contract)) = deployment {
+        match descriptor.context.as_ref() {
+            Some(context)
+                if context.eip712.deployments.iter().any(|deployment| {
+                    deployment.chain_id == chain_id
+                        && deployment
+                            .address
+                            .eq_ignore_ascii_case(verifying_contract)
+                }) => {}
+            _ => problems.push(format!(
+                "Descriptor has no deployment for chain {chain_id} and \
+                 address {verifying_contract}"
+            )),
+        }
+    }
+
+    if descriptor.display.formats.is_empty() {
+        problems.push("Descriptor has no display formats".to_string());
+    }
+
+    let mut primary_types =
+        descriptor.display.formats.keys().collect::<Vec<_>>();
+    primary_types.sort_unstable();
+    for primary_type in primary_types {
+        let format = &descriptor.display.formats[primary_type];
+        check_fields(
+            &format.fields,
+            &descriptor.display.definitions,
+            primary_type,
+            &mut problems,
+        );
+    }
+
+    Ok(problems)
+}
+
+fn check_fields(
+    fields: &[DisplayField],
+    definitions: &HashMap<String, DisplayField>,
+    primary_type: &str,
+    problems: &mut Vec<String>,
+) {
+    for field in fields {
+        if !field.fields.is_empty() {
+            if field.path.is_none() {
+                problems
+                    .push(format!("{primary_type}: field group has no path"));
+            }
+            check_fields(&field.fields, definitions, primary_type, problems);
+            continue;
+        }
+        let mut warnings = Vec::new();
+        let effective =
+            resolve_effective_field(field, definitions, &mut warnings);
+        problems.extend(
+            warnings
+                .into_iter()
+                .map(|warning| format!("{primary_type}: {warning}")),
+        );
+        if effective.is_none() {
+            problems.push(format!("{primary_type}: field has no path"));
+        }
+    }
+}
+
 fn parse_descriptor(
     resolved: &ResolvedTypedDescriptor<'_>,
 ) -> Result<TypedDescriptor, Eip712Error> {
     let descriptor_value = resolver::merged_descriptor_value(
-        resolved.descriptor_json,
+        &resolved.descriptor_json,
         &resolved.includes,
     )
     .map_err(|err| Eip712Error::DescriptorParse(err.to_string()))?;
@@ -190,37 +499,152 @@ struct TypedDisplay {
     formats: HashMap<String, DisplayFormat>,
 }
 
+#[allow(clippy::too_many_arguments)]
 fn render_field(
     field: &EffectiveField,
     value: &Value,
     message: &Value,
+    container: &Value,
     metadata: &Value,
     chain_id: u64,
     address_book: &HashMap<String, String>,
+    calls: &mut Vec<DisplayModel>,
     warnings: &mut Vec<String>,
+    locale: &Locale,
 ) -> Result<String, Eip712Error> {
     match field.format.as_deref() {
         Some("tokenAmount") => format_token_amount(
-            field, value, message, metadata, chain_id, warnings,
+            field, value, message, container, metadata, chain_id, warnings,
+            locale,
         ),
-        Some("date") => Ok(format_date(value)),
+        Some("amount") => Ok(parse_biguint_from_value(value)
+            .map(|amount| format_native_value(chain_id, &amount, locale))
+            .unwrap_or_else(|| format_raw(value))),
+        Some("date") => Ok(format_date(value, locale)),
+        Some("duration") => Ok(parse_biguint_from_value(value)
+            .and_then(|seconds| format_duration(&seconds))
+            .unwrap_or_else(|| format_raw(value))),
+        Some("unit") => Ok(parse_biguint_from_value(value)
+            .and_then(|amount| format_unit(&amount, &field.params, locale))
+            .unwrap_or_else(|| format_raw(value))),
+        Some("chainId") => Ok(parse_biguint_from_value(value)
+            .and_then(|id| u64::try_from(id).ok())
+            .and_then(chain_name)
+            .map(str::to_string)
+            .unwrap_or_else(|| format_raw(value))),
         Some("number") => Ok(format_number(value)),
         Some("address") | Some("addressName") => {
-            Ok(format_address(value, address_book))
+            if is_sender_address(field, value) {
+                return Ok("Sender".to_string());
+            }
+            Ok(format_address_name(field, value, chain_id, address_book))
         }
+        Some("calldata") => Ok(format_calldata(
+            field, value, message, container, chain_id, calls, warnings, locale,
+        )),
         Some("enum") => format_enum(field, value, metadata),
         Some("raw") => Ok(format_raw(value)),
         _ => Ok(format_raw(value)),
     }
 }
 
+// Renders an embedded call (e.g. `SafeTx.data`) through the calldata engine;
+// the inner model is returned in `DisplayModel::calls`
+#[allow(clippy::too_many_arguments)]
+fn format_calldata(
+    field: &EffectiveField,
+    value: &Value,
+    message: &Value,
+    container: &Value,
+    chain_id: u64,
+    calls: &mut Vec<DisplayModel>,
+    warnings: &mut Vec<String>,
+    locale: &Locale,
+) -> String {
+    let param_value = |name: &str| {
+        field
+            .params
+            .get(name)
+            .and_then(Value::as_str)
+            .and_then(|path| field_value(message, container, path))
+    };
+
+    let Some(data) = value_as_string(value).and_then(|text| {
+        hex::decode(text.trim().trim_start_matches("0x")).ok()
+    }) else {
+        warnings.push(format!("Field '{}' is not hex calldata", field.label));
+        return format_raw(value);
+    };
+    let Some(callee) =
+        param_value("calleePath").and_then(extract_address_value)
+    else {
+        warnings.push(format!("No callee found for field '{}'", field.label));
+        return format_raw(value);
+    };
+    let amount = param_value("amountPath")
+        .and_then(parse_biguint_from_value)
+        .unwrap_or_default();
+
+    // The verifying contract (e.g. the Safe) makes the call
+    let sender = container.get("to").and_then(Value::as_str);
+    let call =
+        format_inner_call(chain_id, sender, &callee, &amount, &data, 1, locale);
+    let summary = call_summary(&call);
+    calls.push(call);
+    summary
+}
+
+fn is_sender_address(field: &EffectiveField, value: &Value) -> bool {
+    let Some(text) = value_as_string(value) else {
+        return false;
+    };
+    match field.params.get("senderAddress") {
+        Some(Value::String(sender)) => sender.eq_ignore_ascii_case(text.trim()),
+        Some(Value::Array(senders)) => senders.iter().any(|sender| {
+            sender
+                .as_str()
+                .is_some_and(|sender| sender.eq_ignore_ascii_case(text.trim()))
+        }),
+        _ => false,
+    }
+}
+
+// Token addresses (`"types": ["token"]`) fall back to the token symbol
+fn format_address_name(
+    field: &EffectiveField,
+    value: &Value,
+    chain_id: u64,
+    address_book: &HashMap<String, String>,
+) -> String {
+    let formatted = format_address(value, chain_id, address_book);
+    let is_token = field
+        .params
+        .get("types")
+        .and_then(Value::as_array)
+        .is_some_and(|types| types.iter().any(|ty| ty == "token"));
+    if !is_token || !formatted.starts_with("0x") {
+        return formatted;
+    }
+    extract_address_value(value)
+        .and_then(|address| {
+            lookup_token_by_caip19(&format!(
+                "eip155:{chain_id}/erc20:{address}"
+            ))
+        })
+        .map(|meta| meta.symbol.clone())
+        .unwrap_or(formatted)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn format_token_amount(
     field: &EffectiveField,
     value: &Value,
     message: &Value,
+    container: &Value,
     metadata: &Value,
     chain_id: u64,
     warnings: &mut Vec<String>,
+    locale: &Locale,
 ) -> Result<String, Eip712Error> {
     let field_path = field.path.clone();
     let amount = parse_biguint_from_value(value).ok_or_else(|| {
@@ -230,12 +654,13 @@ fn format_token_amount(
     if let Some(token_path) =
         field.params.get("tokenPath").and_then(|value| value.as_str())
     {
-        let token_value = get_value(message, token_path).ok_or_else(|| {
-            Eip712Error::TypedData(format!(
-                "token path '{}' not found for field '{}'",
-                token_path, field_path
-            ))
-        })?;
+        let token_value = field_value(message, container, token_path)
+            .ok_or_else(|| {
+                Eip712Error::TypedData(format!(
+                    "token path '{}' not found for field '{}'",
+                    token_path, field_path
+                ))
+            })?;
 
         let token_address =
             extract_address_value(token_value).ok_or_else(|| {
@@ -245,6 +670,15 @@ fn format_token_amount(
                 ))
             })?;
 
+        if is_native_currency_address(field, &token_address) {
+            if let Some(message) =
+                token_amount_message(field, &amount, metadata)
+            {
+                return Ok(message);
+            }
+            return Ok(format_native_value(chain_id, &amount, locale));
+        }
+
         let caip19 = format!(
             "eip155:{}/erc20:{}",
             chain_id,
@@ -262,7 +696,8 @@ fn format_token_amount(
             return Ok(format!("{} {}", message, meta.symbol));
         }
 
-        let formatted = format_amount_with_decimals(&amount, meta.decimals);
+        let formatted =
+            format_amount_with_decimals(&amount, meta.decimals, locale);
         return Ok(format!("{} {}", formatted, meta.symbol));
     }
 
@@ -317,7 +752,7 @@ fn format_enum(
     Ok(format_raw(value))
 }
 
-fn format_date(value: &Value) -> String {
+fn format_date(value: &Value, locale: &Locale) -> String {
     let Some(amount) = parse_biguint_from_value(value) else {
         return format_raw(value);
     };
@@ -331,13 +766,7 @@ fn format_date(value: &Value) -> String {
     let Ok(datetime) = OffsetDateTime::from_unix_timestamp(timestamp) else {
         return format_raw(value);
     };
-    let format = format_description!(
-        "[year]-[month]-[day] [hour]:[minute]:[second] UTC"
-    );
-    datetime
-        .to_offset(time::UtcOffset::UTC)
-        .format(&format)
-        .unwrap_or_else(|_| format_raw(value))
+    locale.format_datetime(datetime).unwrap_or_else(|| format_raw(value))
 }
 
 fn format_number(value: &Value) -> String {
@@ -352,6 +781,7 @@ fn format_number(value: &Value) -> String {
 
 fn format_address(
     value: &Value,
+    chain_id: u64,
     address_book: &HashMap<String, String>,
 ) -> String {
     let Some(address) = value_as_string(value) else {
@@ -369,6 +799,9 @@ fn format_address(
     let mut arr = [0u8; 20];
     arr.copy_from_slice(&bytes);
     let checksum = super::engine::to_checksum_address(&arr);
+    if let Some(label) = provided_address_label(chain_id, &checksum) {
+        return label;
+    }
     let normalized = cleaned.to_ascii_lowercase();
 
     if let Some(label) = address_book.get(&normalized) {
@@ -378,6 +811,21 @@ fn format_address(
     checksum
 }
 
+// Compares a message value with a descriptor constant (`ifNotIn`, `mustMatch`)
+fn value_matches(value: &Value, constant: &Value) -> bool {
+    if let (Some(number), Some(expected)) =
+        (parse_biguint_from_value(value), parse_biguint_from_value(constant))
+    {
+        return number == expected;
+    }
+    match (value_as_string(value), value_as_string(constant)) {
+        (Some(text), Some(expected)) => {
+            text.trim().eq_ignore_ascii_case(expected.trim())
+        }
+        _ => false,
+    }
+}
+
 fn format_raw(value: &Value) -> String {
     match value {
         Value::String(text) => text.clone(),
@@ -430,7 +878,23 @@ fn get_value<'a>(root: &'a Value, path: &str) -> Option<&'a Value> {
         return Some(current);
     }
     for segment in trimmed.split('.') {
-        current = current.get(segment)?;
+        current = match segment
+            .strip_prefix('[')
+            .and_then(|index| index.strip_suffix(']'))
+        {
+            Some(index) => {
+                let elements = current.as_array()?;
+                // `[-1]` is the last element
+                let index = match index.strip_prefix('-') {
+                    Some(back) => {
+                        elements.len().checked_sub(back.parse().ok()?)?
+                    }
+                    None => index.parse().ok()?,
+                };
+                elements.get(index)?
+            }
+            None => current.get(segment)?,
+        };
     }
     Some(current)
 }
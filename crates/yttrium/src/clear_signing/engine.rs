@@ -4,17 +4,21 @@ use {
     super::{
         descriptor::{
             ArgumentValue, DecodedArguments, DescriptorError, DisplayField,
-            DisplayFormat, EffectiveField, TokenLookupError, TokenLookupKey,
-            build_descriptor, decode_arguments, determine_token_key,
-            native_token_key, resolve_effective_field,
+            DisplayFormat, EffectiveField, FieldVisibility, TokenLookupError,
+            TokenLookupKey, build_descriptor, decode_arguments,
+            determine_token_key, native_token_key, resolve_effective_field,
         },
+        locale::Locale,
+        nested::{self, call_summary},
+        providers::provided_address_label,
         resolver::ResolvedCall,
+        risk::{RiskWarning, calldata_risks},
         token_registry::TokenMeta,
     },
     num_bigint::BigUint,
     std::collections::HashMap,
     thiserror::Error,
-    time::{OffsetDateTime, macros::format_description},
+    time::OffsetDateTime,
     tiny_keccak::{Hasher, Keccak},
 };
 
@@ -34,13 +38,19 @@ pub struct DisplayModel {
     pub interpolated_intent: Option<String>,
     pub items: Vec<DisplayItem>,
     pub warnings: Vec<String>,
+    /// Risky intents such as unlimited approvals, each with a severity and a
+    /// stable code.
+    pub risks: Vec<RiskWarning>,
     pub raw: Option<RawPreview>,
+    /// Inner calls (Safe transactions, batches, `calldata` fields), each with its own warnings.
+    pub calls: Vec<DisplayModel>,
 }
 
 struct FormatRender {
     items: Vec<DisplayItem>,
     warnings: Vec<String>,
     interpolated_intent: Option<String>,
+    calls: Vec<DisplayModel>,
 }
 
 /// Raw fallback preview details.
@@ -87,6 +97,23 @@ pub fn format_with_resolved_call(
     to: &str,
     value: Option<&[u8]>,
     calldata: &[u8],
+    locale: &Locale,
+) -> Result<DisplayModel, EngineError> {
+    format_resolved_call_at_depth(
+        resolved, chain_id, None, to, value, calldata, 0, locale,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn format_resolved_call_at_depth(
+    resolved: ResolvedCall<'_>,
+    chain_id: u64,
+    sender: Option<&str>,
+    to: &str,
+    value: Option<&[u8]>,
+    calldata: &[u8],
+    depth: usize,
+    locale: &Locale,
 ) -> Result<DisplayModel, EngineError> {
     eprintln!(
         "[engine] format_with_resolved chain_id={} to={} calldata_len={}",
@@ -122,7 +149,9 @@ pub fn format_with_resolved_call(
             interpolated_intent: None,
             items: Vec::new(),
             warnings,
+            risks: Vec::new(),
             raw: Some(raw_preview_from_calldata(&selector, calldata)),
+            calls: Vec::new(),
         });
     };
 
@@ -130,12 +159,17 @@ pub fn format_with_resolved_call(
     eprintln!("[engine] decoded arguments count {}", decoded.ordered().len());
     let decoded = decoded.with_value(value)?;
     eprintln!("[engine] decoded with value count {}", decoded.ordered().len());
+    let intent = display_formats
+        .get(&function.typed_signature)
+        .map_or("Transaction", |format_def| format_def.intent.as_str());
+    let risks = calldata_risks(function, &decoded, intent, to, sender);
 
     if let Some(format_def) = display_formats.get(&function.typed_signature) {
         let FormatRender {
             items,
             warnings: mut format_warnings,
             interpolated_intent,
+            calls,
         } = apply_display_format(
             format_def,
             &decoded,
@@ -145,14 +179,19 @@ pub fn format_with_resolved_call(
             address_book,
             descriptor.display.definitions(),
             &token_metadata,
+            calldata,
+            depth,
+            locale,
         )?;
         warnings.append(&mut format_warnings);
         Ok(DisplayModel {
-            intent: format_def.intent.clone(),
+            intent: format_def.localized_intent(locale).to_string(),
             interpolated_intent,
             items,
             warnings,
+            risks,
             raw: None,
+            calls,
         })
     } else {
         warnings.push(format!(
@@ -172,6 +211,7 @@ pub fn format_with_resolved_call(
             interpolated_intent: None,
             items,
             warnings,
+            risks,
             raw: Some(RawPreview {
                 selector: selector_hex,
                 args: decoded
@@ -180,6 +220,7 @@ pub fn format_with_resolved_call(
                     .map(|arg| arg.raw_word_hex())
                     .collect(),
             }),
+            calls: Vec::new(),
         })
     }
 }
@@ -195,10 +236,15 @@ fn apply_display_format(
     address_book: &HashMap<String, String>,
     definitions: &HashMap<String, DisplayField>,
     token_metadata: &HashMap<TokenLookupKey, TokenMeta>,
+    calldata: &[u8],
+    depth: usize,
+    locale: &Locale,
 ) -> Result<FormatRender, EngineError> {
     let mut items = Vec::new();
     let mut warnings = Vec::new();
+    let mut calls = Vec::new();
     let mut rendered_values: HashMap<String, String> = HashMap::new();
+    let translation = format.translation(locale);
 
     for required in &format.required {
         if decoded.get(required).is_none() {
@@ -207,13 +253,75 @@ fn apply_display_format(
     }
 
     for field in &format.fields {
-        let Some(effective) =
+        let Some(mut effective) =
             resolve_effective_field(field, definitions, &mut warnings)
         else {
             continue;
         };
+        if let Some(label) = translation
+            .and_then(|translation| translation.label(&effective.path))
+        {
+            effective.label = label.to_string();
+        }
 
         if let Some(value) = decoded.get(&effective.path) {
+            match &effective.visible {
+                FieldVisibility::Never => continue,
+                FieldVisibility::IfNotIn(constants)
+                    if constants
+                        .iter()
+                        .any(|constant| value_matches(value, constant)) =>
+                {
+                    continue;
+                }
+                FieldVisibility::MustMatch(constants) => {
+                    if !constants
+                        .iter()
+                        .any(|constant| value_matches(value, constant))
+                    {
+                        warnings.push(format!(
+                            "Field '{}' has unexpected value {}",
+                            effective.label,
+                            value.default_string()
+                        ));
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            if effective.format.as_deref() == Some("calldata") {
+                let rendered = match nested::format_calldata_field(
+                    &effective,
+                    value,
+                    decoded,
+                    chain_id,
+                    contract_address,
+                    calldata,
+                    depth,
+                    locale,
+                ) {
+                    Ok(call) => {
+                        let summary = call_summary(&call);
+                        calls.push(call);
+                        summary
+                    }
+                    Err(err) => {
+                        warnings.push(format!(
+                            "Cannot format calldata field '{}': {err}",
+                            effective.path
+                        ));
+                        value.default_string()
+                    }
+                };
+                items.push(DisplayItem {
+                    label: effective.label.clone(),
+                    value: rendered.clone(),
+                });
+                rendered_values.insert(effective.path.clone(), rendered);
+                continue;
+            }
+
             let rendered = render_field(
                 &effective,
                 value,
@@ -223,13 +331,17 @@ fn apply_display_format(
                 contract_address,
                 address_book,
                 token_metadata,
+                locale,
             )?;
             items.push(DisplayItem {
                 label: effective.label.clone(),
                 value: rendered.clone(),
             });
             rendered_values.insert(effective.path.clone(), rendered);
-        } else {
+        } else if !matches!(
+            effective.visible,
+            FieldVisibility::Optional | FieldVisibility::Never
+        ) {
             warnings.push(format!(
                 "No value found for field path '{}'",
                 effective.path
@@ -238,7 +350,7 @@ fn apply_display_format(
     }
 
     let interpolated_intent =
-        if let Some(template) = format.interpolated_intent.as_deref() {
+        if let Some(template) = format.localized_interpolated_intent(locale) {
             match interpolate_template(template, &rendered_values) {
                 Ok(value) => Some(value),
                 Err(message) => {
@@ -250,7 +362,7 @@ fn apply_display_format(
             None
         };
 
-    Ok(FormatRender { items, warnings, interpolated_intent })
+    Ok(FormatRender { items, warnings, interpolated_intent, calls })
 }
 
 pub(crate) fn interpolate_template(
@@ -305,9 +417,32 @@ fn render_field(
     contract_address: &str,
     address_book: &HashMap<String, String>,
     token_metadata: &HashMap<TokenLookupKey, TokenMeta>,
+    locale: &Locale,
 ) -> Result<String, EngineError> {
     match field.format.as_deref() {
-        Some("date") => Ok(format_date(value)),
+        Some("date") => {
+            match field.params.get("encoding").and_then(|v| v.as_str()) {
+                Some("blockheight") => Ok(format_block_height(value)),
+                _ => Ok(format_date(value, locale)),
+            }
+        }
+        Some("duration") => Ok(value
+            .as_uint()
+            .and_then(format_duration)
+            .unwrap_or_else(|| value.default_string())),
+        Some("unit") => Ok(value
+            .as_uint()
+            .and_then(|amount| format_unit(amount, &field.params, locale))
+            .unwrap_or_else(|| value.default_string())),
+        Some("chainId") => Ok(format_chain_id(value)),
+        Some("nftName") => Ok(format_nft_name(
+            field,
+            value,
+            decoded,
+            chain_id,
+            contract_address,
+            address_book,
+        )),
         Some("tokenAmount") => format_token_amount(
             field,
             value,
@@ -316,20 +451,188 @@ fn render_field(
             chain_id,
             contract_address,
             token_metadata,
+            locale,
         ),
         Some("amount") => {
-            Ok(format_native_amount(value, chain_id, token_metadata))
+            Ok(format_native_amount(value, chain_id, token_metadata, locale))
         }
         Some("address") | Some("addressName") => {
-            Ok(format_address(value, address_book))
+            if is_sender_address(field, value) {
+                return Ok("Sender".to_string());
+            }
+            Ok(format_address(value, chain_id, address_book))
         }
         Some("enum") => format_enum(field, value, metadata),
         Some("number") => Ok(format_number(value)),
+        Some("raw") => Ok(value.default_string()),
         _ => Ok(value.default_string()),
     }
 }
 
-fn format_date(value: &ArgumentValue) -> String {
+// Compares a decoded value with a descriptor constant (`ifNotIn`, `mustMatch`)
+fn value_matches(value: &ArgumentValue, constant: &serde_json::Value) -> bool {
+    match value {
+        ArgumentValue::Uint(amount) => match constant {
+            serde_json::Value::Number(number) => {
+                number.as_u64().is_some_and(|n| *amount == BigUint::from(n))
+            }
+            serde_json::Value::String(text) => {
+                parse_biguint(text.trim()).is_some_and(|n| *amount == n)
+            }
+            serde_json::Value::Bool(flag) => {
+                *amount == BigUint::from(u8::from(*flag))
+            }
+            _ => false,
+        },
+        ArgumentValue::Address(_) | ArgumentValue::Raw(_) => {
+            constant.as_str().is_some_and(|text| {
+                text.trim().eq_ignore_ascii_case(&value.default_string())
+            })
+        }
+    }
+}
+
+// `senderAddress` marks placeholder addresses that stand for the transaction sender
+fn is_sender_address(field: &EffectiveField, value: &ArgumentValue) -> bool {
+    if value.as_address().is_none() {
+        return false;
+    }
+    match field.params.get("senderAddress") {
+        Some(constant @ serde_json::Value::String(_)) => {
+            value_matches(value, constant)
+        }
+        Some(serde_json::Value::Array(constants)) => {
+            constants.iter().any(|constant| value_matches(value, constant))
+        }
+        _ => false,
+    }
+}
+
+fn format_block_height(value: &ArgumentValue) -> String {
+    match value.as_uint() {
+        Some(height) => format!("Block {height}"),
+        None => value.default_string(),
+    }
+}
+
+/// Formats seconds as `HH:MM:ss`; hours are not wrapped into days.
+pub(crate) fn format_duration(seconds: &BigUint) -> Option<String> {
+    let seconds = u64::try_from(seconds.clone()).ok()?;
+    Some(format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    ))
+}
+
+const SI_PREFIXES: [&str; 9] = ["", "k", "M", "G", "T", "P", "E", "Z", "Y"];
+
+/// `unit` format: the amount scaled by `decimals`, followed by `base`. With
+/// `prefix`, the largest SI prefix that keeps the integer part at or above 1 is used.
+pub(crate) fn format_unit(
+    amount: &BigUint,
+    params: &serde_json::Value,
+    locale: &Locale,
+) -> Option<String> {
+    let base = params.get("base").and_then(|v| v.as_str()).unwrap_or("");
+    let decimals = params.get("decimals").and_then(|v| v.as_u64()).unwrap_or(0);
+    let use_prefix =
+        params.get("prefix").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    let mut exponent = 0usize;
+    if use_prefix {
+        let scale = BigUint::from(10u32).pow(u32::try_from(decimals).ok()?);
+        let mut threshold = scale * BigUint::from(1000u32);
+        while exponent + 1 < SI_PREFIXES.len() && *amount >= threshold {
+            exponent += 1;
+            threshold *= BigUint::from(1000u32);
+        }
+    }
+
+    let decimals = u8::try_from(decimals + 3 * exponent as u64).ok()?;
+    Some(format!(
+        "{}{}{}",
+        format_amount_with_decimals(amount, decimals, locale),
+        SI_PREFIXES[exponent],
+        base
+    ))
+}
+
+pub(crate) fn chain_name(chain_id: u64) -> Option<&'static str> {
+    match chain_id {
+        1 => Some("Ethereum"),
+        10 => Some("Optimism"),
+        56 => Some("BNB Smart Chain"),
+        100 => Some("Gnosis"),
+        137 => Some("Polygon"),
+        250 => Some("Fantom"),
+        324 => Some("zkSync Era"),
+        8453 => Some("Base"),
+        42161 => Some("Arbitrum One"),
+        43114 => Some("Avalanche"),
+        59144 => Some("Linea"),
+        534352 => Some("Scroll"),
+        11155111 => Some("Sepolia"),
+        _ => None,
+    }
+}
+
+fn format_chain_id(value: &ArgumentValue) -> String {
+    value
+        .as_uint()
+        .and_then(|id| u64::try_from(id.clone()).ok())
+        .and_then(chain_name)
+        .map(str::to_string)
+        .unwrap_or_else(|| value.default_string())
+}
+
+fn format_nft_name(
+    field: &EffectiveField,
+    value: &ArgumentValue,
+    decoded: &DecodedArguments,
+    chain_id: u64,
+    contract_address: &str,
+    address_book: &HashMap<String, String>,
+) -> String {
+    let Some(token_id) = value.as_uint() else {
+        return value.default_string();
+    };
+
+    let collection = if let Some(collection) =
+        field.params.get("collection").and_then(|v| v.as_str())
+    {
+        parse_address(collection)
+    } else {
+        match field.params.get("collectionPath").and_then(|v| v.as_str()) {
+            Some("@.to") => parse_address(contract_address),
+            Some(path) => {
+                decoded.get(path).and_then(|v| v.as_address().copied())
+            }
+            None => None,
+        }
+    };
+
+    match collection {
+        Some(collection) => {
+            let name = format_address(
+                &ArgumentValue::Address(collection),
+                chain_id,
+                address_book,
+            );
+            format!("{name} #{token_id}")
+        }
+        // Without a collection the spec falls back to the raw token ID
+        None => token_id.to_string(),
+    }
+}
+
+fn parse_address(text: &str) -> Option<[u8; 20]> {
+    let hex_part = text.trim().strip_prefix("0x")?;
+    hex::decode(hex_part).ok()?.try_into().ok()
+}
+
+pub(crate) fn format_date(value: &ArgumentValue, locale: &Locale) -> String {
     let ArgumentValue::Uint(amount) = value else {
         return value.default_string();
     };
@@ -344,15 +647,10 @@ fn format_date(value: &ArgumentValue) -> String {
         return value.default_string();
     };
 
-    let format = format_description!(
-        "[year]-[month]-[day] [hour]:[minute]:[second] UTC"
-    );
-    datetime
-        .to_offset(time::UtcOffset::UTC)
-        .format(&format)
-        .unwrap_or_else(|_| value.default_string())
+    locale.format_datetime(datetime).unwrap_or_else(|| value.default_string())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn format_token_amount(
     field: &EffectiveField,
     value: &ArgumentValue,
@@ -361,6 +659,7 @@ fn format_token_amount(
     chain_id: u64,
     contract_address: &str,
     token_metadata: &HashMap<TokenLookupKey, TokenMeta>,
+    locale: &Locale,
 ) -> Result<String, EngineError> {
     let ArgumentValue::Uint(amount) = value else {
         return Ok(value.default_string());
@@ -379,12 +678,13 @@ fn format_token_amount(
     }
 
     let formatted_amount =
-        format_amount_with_decimals(amount, token_meta.decimals);
+        format_amount_with_decimals(amount, token_meta.decimals, locale);
     Ok(format!("{} {}", formatted_amount, token_meta.symbol))
 }
 
 fn format_address(
     value: &ArgumentValue,
+    chain_id: u64,
     local_address_book: &HashMap<String, String>,
 ) -> String {
     let Some(bytes) = value.as_address() else {
@@ -392,6 +692,9 @@ fn format_address(
     };
 
     let checksum = to_checksum_address(bytes);
+    if let Some(label) = provided_address_label(chain_id, &checksum) {
+        return label;
+    }
     let normalized = checksum.to_ascii_lowercase();
 
     if let Some(label) = local_address_book.get(&normalized) {
@@ -412,6 +715,7 @@ fn format_native_amount(
     value: &ArgumentValue,
     chain_id: u64,
     token_metadata: &HashMap<TokenLookupKey, TokenMeta>,
+    locale: &Locale,
 ) -> String {
     let ArgumentValue::Uint(amount) = value else {
         return value.default_string();
@@ -420,11 +724,12 @@ fn format_native_amount(
     if let Ok(key) = native_token_key(chain_id)
         && let Some(meta) = token_metadata.get(&key)
     {
-        let formatted = format_amount_with_decimals(amount, meta.decimals);
+        let formatted =
+            format_amount_with_decimals(amount, meta.decimals, locale);
         return format!("{} {}", formatted, meta.symbol);
     }
 
-    let formatted = format_amount_with_decimals(amount, 18);
+    let formatted = format_amount_with_decimals(amount, 18, locale);
     format!("{} NATIVE", formatted)
 }
 
@@ -495,21 +800,23 @@ fn lookup_token_meta(
     })
 }
 
+/// Formats `amount` scaled down by `decimals`, with the group and decimal
+/// separators of `locale`.
 pub(crate) fn format_amount_with_decimals(
     amount: &BigUint,
     decimals: u8,
+    locale: &Locale,
 ) -> String {
     if decimals == 0 {
-        return add_thousand_separators(&amount.to_string());
+        return locale.format_number(&amount.to_string(), None);
     }
 
     let factor = BigUint::from(10u32).pow(decimals as u32);
-    let integer = amount / &factor;
+    let integer = (amount / &factor).to_string();
     let remainder = amount % &factor;
 
-    let integer_part = add_thousand_separators(&integer.to_string());
     if remainder == BigUint::from(0u32) {
-        return integer_part;
+        return locale.format_number(&integer, None);
     }
 
     let mut fractional = remainder.to_string();
@@ -528,21 +835,10 @@ pub(crate) fn format_amount_with_decimals(
     }
 
     if fractional.is_empty() {
-        integer_part
+        locale.format_number(&integer, None)
     } else {
-        format!("{}.{}", integer_part, fractional)
-    }
-}
-
-pub(crate) fn add_thousand_separators(value: &str) -> String {
-    let mut reversed = String::with_capacity(value.len());
-    for (index, ch) in value.chars().rev().enumerate() {
-        if index > 0 && index % 3 == 0 {
-            reversed.push(',');
-        }
-        reversed.push(ch);
+        locale.format_number(&integer, Some(&fractional))
     }
-    reversed.chars().rev().collect()
 }
 
 fn resolve_metadata_biguint(
@@ -578,7 +874,7 @@ pub(crate) fn parse_biguint(text: &str) -> Option<BigUint> {
     }
 }
 
-fn raw_preview_from_calldata(
+pub(crate) fn raw_preview_from_calldata(
     selector: &[u8; 4],
     calldata: &[u8],
 ) -> RawPreview {
@@ -0,0 +1,341 @@
+//! Static checks for clear signing descriptors, run over the index the
+//! resolver sees (runtime registry bundle first, then the bundled assets).
+
+use {
+    super::{
+        descriptor::{
+            build_descriptor, determine_token_key, native_token_key,
+            placeholder_arguments, resolve_effective_field,
+        },
+        eip712::check_typed_descriptor,
+        resolver::{self, ResolvedDescriptor},
+        token_registry::lookup_token_by_caip19,
+    },
+    std::collections::HashMap,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintSeverity {
+    /// The descriptor can't be resolved or parsed, or references something
+    /// that doesn't exist.
+    Error,
+    /// The descriptor loads, but some calls won't be clear signed.
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    pub severity: LintSeverity,
+    /// Index key, e.g. `eip155:1:0xdac17f958d2ee523a2206206994597c13d831ec7`,
+    /// or the descriptor path for schema descriptors
+    pub entry: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            LintSeverity::Error => "error",
+            LintSeverity::Warning => "warning",
+        };
+        write!(f, "{severity}: {}: {}", self.entry, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LintReport {
+    /// Number of calldata and typed data index entries and schema
+    /// descriptors checked
+    pub entries: usize,
+    pub issues: Vec<LintIssue>,
+}
+
+impl LintReport {
+    pub fn errors(&self) -> impl Iterator<Item = &LintIssue> {
+        self.issues.iter().filter(|issue| issue.severity == LintSeverity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &LintIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == LintSeverity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+}
+
+/// Checks every calldata and typed data index entry and schema descriptor:
+/// descriptor and ABI
+/// parse, includes and `$ref` definitions resolve, display formats match an
+/// ABI function and token lookups resolve.
+pub fn lint_registry() -> LintReport {
+    let mut report = LintReport::default();
+
+    for key in resolver::index_keys() {
+        report.entries += 1;
+        let Some((chain_id, address)) = parse_index_key(&key) else {
+            report.issues.push(error(&key, "Malformed index key".to_string()));
+            continue;
+        };
+        match resolver::resolve(chain_id, address) {
+            Ok(resolved) => report
+                .issues
+                .extend(lint_descriptor(chain_id, address, &resolved)),
+            Err(err) => report.issues.push(error(&key, err.to_string())),
+        }
+    }
+
+    for key in resolver::typed_index_keys() {
+        report.entries += 1;
+        let Some((chain_id, address)) = parse_index_key(&key) else {
+            report.issues.push(error(&key, "Malformed index key".to_string()));
+            continue;
+        };
+        let problems = resolver::resolve_typed(chain_id, address)
+            .map_err(|err| err.to_string())
+            .and_then(|resolved| {
+                check_typed_descriptor(&resolved, Some((chain_id, address)))
+                    .map_err(|err| err.to_string())
+            });
+        match problems {
+            Ok(problems) => report.issues.extend(
+                problems.into_iter().map(|problem| error(&key, problem)),
+            ),
+            Err(message) => report.issues.push(error(&key, message)),
+        }
+    }
+
+    for path in resolver::schema_typed_descriptor_paths() {
+        report.entries += 1;
+        let problems = resolver::resolve_typed_schema_descriptor(path)
+            .map_err(|err| err.to_string())
+            .and_then(|resolved| {
+                check_typed_descriptor(&resolved, None)
+                    .map_err(|err| err.to_string())
+            });
+        match problems {
+            Ok(problems) => report.issues.extend(
+                problems.into_iter().map(|problem| error(path, problem)),
+            ),
+            Err(message) => report.issues.push(error(path, message)),
+        }
+    }
+
+    report
+}
+
+/// Checks one calldata descriptor bundle as it would be used for calls to
+/// `address` on `chain_id`.
+pub fn lint_descriptor(
+    chain_id: u64,
+    address: &str,
+    resolved: &ResolvedDescriptor<'_>,
+) -> Vec<LintIssue> {
+    let entry =
+        format!("eip155:{}:{}", chain_id, address.trim().to_ascii_lowercase());
+    let mut issues = Vec::new();
+
+    let descriptor = match build_descriptor(resolved) {
+        Ok(descriptor) => descriptor,
+        Err(err) => {
+            issues
+                .push(error(&entry, format!("Cannot parse descriptor: {err}")));
+            return issues;
+        }
+    };
+
+    if !descriptor.context.contract.is_bound_to(chain_id, address) {
+        issues.push(error(
+            &entry,
+            "Descriptor has no deployment for this chain and address"
+                .to_string(),
+        ));
+    }
+
+    let functions = match descriptor.context.contract.function_descriptors() {
+        Ok(functions) if functions.is_empty() => {
+            issues.push(error(&entry, "ABI has no functions".to_string()));
+            return issues;
+        }
+        Ok(functions) => functions,
+        Err(err) => {
+            issues.push(error(&entry, format!("Cannot parse ABI: {err}")));
+            return issues;
+        }
+    };
+
+    let mut by_selector = HashMap::new();
+    for function in &functions {
+        if let Some(other) =
+            by_selector.insert(function.selector, &function.typed_signature)
+            && *other != function.typed_signature
+        {
+            issues.push(warning(
+                &entry,
+                format!(
+                    "Selector 0x{} is shared by {} and {}",
+                    hex::encode(function.selector),
+                    other,
+                    function.typed_signature
+                ),
+            ));
+        }
+    }
+
+    let definitions = descriptor.display.definitions();
+    let mut formats =
+        descriptor.display.format_map().into_iter().collect::<Vec<_>>();
+    formats.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    if formats.is_empty() {
+        issues.push(warning(
+            &entry,
+            "Descriptor has no display formats".to_string(),
+        ));
+    }
+
+    for (signature, format) in formats {
+        let Some(function) = functions
+            .iter()
+            .find(|function| function.typed_signature == signature)
+        else {
+            issues.push(warning(
+                &entry,
+                format!("Display format {signature} matches no ABI function"),
+            ));
+            continue;
+        };
+        let decoded = placeholder_arguments(function);
+        let mut paths = Vec::new();
+
+        for field in &format.fields {
+            let mut warnings = Vec::new();
+            let effective =
+                resolve_effective_field(field, definitions, &mut warnings);
+            issues.extend(warnings.into_iter().map(|message| {
+                error(&entry, format!("{signature}: {message}"))
+            }));
+            let Some(effective) = effective else {
+                issues.push(error(
+                    &entry,
+                    format!("{signature}: field has no path"),
+                ));
+                continue;
+            };
+            paths.push(effective.path.clone());
+
+            let problem = match effective.format.as_deref() {
+                Some("tokenAmount") => {
+                    let token_path = effective
+                        .params
+                        .get("tokenPath")
+                        .and_then(|path| path.as_str());
+                    match (
+                        determine_token_key(
+                            &effective, &decoded, chain_id, address,
+                        ),
+                        token_path,
+                    ) {
+                        (Err(err), _) => Some(err.to_string()),
+                        // Constant tokens and `@.to` must be known up front;
+                        // path tokens are only known once the call is decoded
+                        (Ok(key), None | Some("@.to")) => {
+                            lookup_token_by_caip19(key.as_str()).is_none().then(
+                                || {
+                                    format!(
+                                        "token {} is not in the token registry",
+                                        key.as_str()
+                                    )
+                                },
+                            )
+                        }
+                        (Ok(_), Some(_)) => None,
+                    }
+                }
+                Some("amount") => {
+                    native_token_key(chain_id).err().map(|err| err.to_string())
+                }
+                _ => None,
+            };
+            if let Some(problem) = problem {
+                issues.push(warning(
+                    &entry,
+                    format!(
+                        "{signature}: field '{}': {problem}",
+                        effective.path
+                    ),
+                ));
+            }
+        }
+
+        let mut translations = format.translations.iter().collect::<Vec<_>>();
+        translations.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        for (tag, translation) in translations {
+            let mut unknown = translation
+                .labels
+                .keys()
+                .filter(|path| !paths.contains(path))
+                .collect::<Vec<_>>();
+            unknown.sort_unstable();
+            issues.extend(unknown.into_iter().map(|path| {
+                warning(
+                    &entry,
+                    format!(
+                        "{signature}: '{tag}' translation labels unknown \
+                         field '{path}'"
+                    ),
+                )
+            }));
+        }
+    }
+
+    issues
+}
+
+fn parse_index_key(key: &str) -> Option<(u64, &str)> {
+    let rest = key.strip_prefix("eip155:")?;
+    let (chain_id, address) = rest.split_once(':')?;
+    let chain_id = chain_id.parse().ok()?;
+    let hex_part = address.strip_prefix("0x")?;
+    (hex_part.len() == 40 && hex::decode(hex_part).is_ok())
+        .then_some((chain_id, address))
+}
+
+fn error(entry: &str, message: String) -> LintIssue {
+    LintIssue {
+        severity: LintSeverity::Error,
+        entry: entry.to_string(),
+        message,
+    }
+}
+
+fn warning(entry: &str, message: String) -> LintIssue {
+    LintIssue {
+        severity: LintSeverity::Warning,
+        entry: entry.to_string(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_index_key() {
+        assert_eq!(
+            parse_index_key(
+                "eip155:10:0x521b4c065bbdbe3e20b3727340730936912dfa46"
+            ),
+            Some((10, "0x521b4c065bbdbe3e20b3727340730936912dfa46"))
+        );
+        assert_eq!(parse_index_key("eip155:10:0x1234"), None);
+        assert_eq!(
+            parse_index_key(
+                "solana:10:0x521b4c065bbdbe3e20b3727340730936912dfa46"
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Locales for clear signing previews: which descriptor translations apply
+//! and how amounts and dates are written.
+
+use time::{
+    OffsetDateTime, format_description::BorrowedFormatItem,
+    macros::format_description,
+};
+
+/// A BCP 47 language tag such as `de`, `pt-BR` or `zh-Hant-TW`. Languages
+/// without their own conventions format like English.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Locale {
+    tag: String,
+}
+
+impl Locale {
+    /// Parses a language tag, also accepting POSIX forms like `de_DE.UTF-8`.
+    /// Empty or malformed tags fall back to English.
+    pub fn parse(tag: &str) -> Self {
+        let tag = tag.trim().split(['.', '@']).next().unwrap_or_default();
+        let mut subtags = tag.split(['-', '_']);
+        let language = subtags.next().unwrap_or_default();
+        if !(2..=3).contains(&language.len())
+            || !language.chars().all(|ch| ch.is_ascii_alphabetic())
+        {
+            return Self::default();
+        }
+
+        let mut normalized = language.to_ascii_lowercase();
+        for subtag in subtags {
+            if subtag.is_empty()
+                || !subtag.chars().all(|ch| ch.is_ascii_alphanumeric())
+            {
+                break;
+            }
+            normalized.push('-');
+            match subtag.len() {
+                // Region: `BR`
+                2 => normalized.push_str(&subtag.to_ascii_uppercase()),
+                // Script: `Hant`
+                4 => {
+                    normalized.push_str(&subtag[..1].to_ascii_uppercase());
+                    normalized.push_str(&subtag[1..].to_ascii_lowercase());
+                }
+                _ => normalized.push_str(&subtag.to_ascii_lowercase()),
+            }
+        }
+        Self { tag: normalized }
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Primary language subtag, e.g. `pt` for `pt-BR`.
+    pub fn language(&self) -> &str {
+        self.tag.split('-').next().unwrap_or_default()
+    }
+
+    /// Tags to look translations up by, most specific first:
+    /// `zh-Hant-TW`, `zh-Hant`, `zh`.
+    pub(crate) fn fallbacks(&self) -> impl Iterator<Item = &str> {
+        let tag = self.tag.as_str();
+        std::iter::successors(Some(tag), |current| {
+            current.rsplit_once('-').map(|(parent, _)| parent)
+        })
+    }
+
+    /// Joins integer digits and optional fraction digits with this locale's
+    /// group and decimal separators.
+    pub(crate) fn format_number(
+        &self,
+        integer: &str,
+        fraction: Option<&str>,
+    ) -> String {
+        let (group, decimal) = self.separators();
+        let mut reversed = String::with_capacity(integer.len() * 2);
+        for (index, ch) in integer.chars().rev().enumerate() {
+            if index > 0 && index % 3 == 0 {
+                reversed.extend(group.chars().rev());
+            }
+            reversed.push(ch);
+        }
+        let mut formatted = reversed.chars().rev().collect::<String>();
+        if let Some(fraction) = fraction {
+            formatted.push(decimal);
+            formatted.push_str(fraction);
+        }
+        formatted
+    }
+
+    /// Formats a timestamp in UTC with this locale's date order. English
+    /// keeps the unambiguous ISO 8601 form.
+    pub(crate) fn format_datetime(
+        &self,
+        datetime: OffsetDateTime,
+    ) -> Option<String> {
+        let format: &[BorrowedFormatItem<'_>] = match self.language() {
+            "de" | "ru" | "uk" | "pl" | "cs" | "sk" | "tr" | "fi" | "nb"
+            | "no" | "da" | "ro" | "bg" => format_description!(
+                "[day].[month].[year] [hour]:[minute]:[second] UTC"
+            ),
+            "fr" | "es" | "it" | "pt" | "el" | "vi" | "id" => {
+                format_description!(
+                    "[day]/[month]/[year] [hour]:[minute]:[second] UTC"
+                )
+            }
+            "nl" => format_description!(
+                "[day]-[month]-[year] [hour]:[minute]:[second] UTC"
+            ),
+            "ja" | "zh" => format_description!(
+                "[year]/[month]/[day] [hour]:[minute]:[second] UTC"
+            ),
+            "ko" | "hu" => format_description!(
+                "[year]. [month]. [day]. [hour]:[minute]:[second] UTC"
+            ),
+            _ => format_description!(
+                "[year]-[month]-[day] [hour]:[minute]:[second] UTC"
+            ),
+        };
+        datetime.to_offset(time::UtcOffset::UTC).format(format).ok()
+    }
+
+    // (group, decimal)
+    fn separators(&self) -> (&'static str, char) {
+        match (self.language(), self.tag.as_str()) {
+            ("de", "de-CH" | "de-LI") => ("\u{2019}", '.'),
+            (
+                "de" | "es" | "it" | "pt" | "nl" | "id" | "tr" | "da" | "el"
+                | "ro" | "vi",
+                _,
+            ) => (".", ','),
+            ("fr", _) => ("\u{202f}", ','),
+            (
+                "ru" | "uk" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no"
+                | "hu" | "bg",
+                _,
+            ) => ("\u{a0}", ','),
+            _ => (",", '.'),
+        }
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self { tag: "en".to_string() }
+    }
+}
+
+impl std::fmt::Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Locale::parse("de").tag(), "de");
+        assert_eq!(Locale::parse("pt_br").tag(), "pt-BR");
+        assert_eq!(Locale::parse("de_DE.UTF-8").tag(), "de-DE");
+        assert_eq!(Locale::parse("zh-hant-tw").tag(), "zh-Hant-TW");
+        assert_eq!(Locale::parse(""), Locale::default());
+        assert_eq!(Locale::parse("not a locale"), Locale::default());
+    }
+
+    #[test]
+    fn test_fallbacks() {
+        let locale = Locale::parse("zh-Hant-TW");
+        assert_eq!(
+            locale.fallbacks().collect::<Vec<_>>(),
+            ["zh-Hant-TW", "zh-Hant", "zh"]
+        );
+        assert_eq!(locale.language(), "zh");
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(
+            Locale::default().format_number("1234567", Some("5")),
+            "1,234,567.5"
+        );
+        assert_eq!(
+            Locale::parse("de").format_number("1234567", Some("5")),
+            "1.234.567,5"
+        );
+        assert_eq!(
+            Locale::parse("fr-FR").format_number("1234", None),
+            "1\u{202f}234"
+        );
+        assert_eq!(
+            Locale::parse("de-CH").format_number("1234", Some("25")),
+            "1\u{2019}234.25"
+        );
+        assert_eq!(Locale::parse("xx").format_number("123", None), "123");
+    }
+
+    #[test]
+    fn test_format_datetime() {
+        let datetime = OffsetDateTime::from_unix_timestamp(1762956494).unwrap();
+        assert_eq!(
+            Locale::default().format_datetime(datetime).unwrap(),
+            "2025-11-12 14:08:14 UTC"
+        );
+        assert_eq!(
+            Locale::parse("de-AT").format_datetime(datetime).unwrap(),
+            "12.11.2025 14:08:14 UTC"
+        );
+        assert_eq!(
+            Locale::parse("ja").format_datetime(datetime).unwrap(),
+            "2025/11/12 14:08:14 UTC"
+        );
+    }
+}
@@ -1,31 +1,80 @@
+mod batch;
 mod descriptor;
 mod eip712;
 mod engine;
+mod lint;
+mod locale;
+mod nested;
+mod providers;
+mod registry;
 mod resolver;
+mod risk;
 mod token_registry;
+#[cfg(feature = "clear_signing")]
+mod transaction;
 
+pub use registry::{
+    RegistryBundleInfo, RegistryError, RegistryTrust, configure_registry,
+    load_registry_bundle, load_registry_bundle_file, registry_bundle_info,
+    reset_registry,
+};
 use resolver::ResolverError;
+#[cfg(feature = "clear_signing")]
+pub use transaction::{
+    SOLANA_MAINNET, SUI_MAINNET, TRON_MAINNET, TransactionError,
+    format_transaction,
+};
 pub use {
+    batch::{
+        BatchPreview, FlowDirection, TokenFlow, format_authorization,
+        format_batch,
+    },
     eip712::{Eip712Error, TypeMember, TypedData, format_typed_data},
     engine::{
         DisplayItem, DisplayModel, EngineError, RawPreview,
         format_with_resolved_call,
     },
+    lint::{
+        LintIssue, LintReport, LintSeverity, lint_descriptor, lint_registry,
+    },
+    locale::Locale,
+    nested::MAX_CALL_DEPTH,
+    providers::{
+        AddressLabelProvider, TokenMetadataProvider,
+        set_address_label_provider, set_token_metadata_provider,
+    },
     resolver::{ResolvedCall, ResolvedDescriptor},
+    risk::{LONG_LIVED_PERMIT_SECONDS, RiskCode, RiskSeverity, RiskWarning},
     token_registry::{TokenMeta, lookup_token_by_caip19},
 };
 
 /// Formats a clear signing preview including an optional native value.
+/// Inner calls of Safe transactions, batches and `calldata` fields are
+/// formatted recursively into `DisplayModel::calls`, up to `MAX_CALL_DEPTH`.
+///
+/// Intents and labels use the descriptor's translations for `locale` when it
+/// has them; amounts and dates always follow `locale`.
 pub fn format_with_value(
     chain_id: u64,
     to: &str,
     value: Option<&[u8]>,
     calldata: &[u8],
+    locale: &Locale,
 ) -> Result<DisplayModel, EngineError> {
-    let resolved = resolver::resolve_call(chain_id, to, calldata, value)
-        .map_err(map_resolver_error)?;
+    nested::format_call(chain_id, None, to, value, calldata, 0, locale)
+}
 
-    format_with_resolved_call(resolved, chain_id, to, value, calldata)
+/// Formats a clear signing preview of a call sent from `from`. Knowing the
+/// sender enables risk rules such as swaps paying out to another address.
+pub fn format_with_sender(
+    chain_id: u64,
+    from: &str,
+    to: &str,
+    value: Option<&[u8]>,
+    calldata: &[u8],
+    locale: &Locale,
+) -> Result<DisplayModel, EngineError> {
+    nested::format_call(chain_id, Some(from), to, value, calldata, 0, locale)
 }
 
 /// Formats a clear signing preview without an explicit call value.
@@ -33,8 +82,9 @@ pub fn format(
     chain_id: u64,
     to: &str,
     calldata: &[u8],
+    locale: &Locale,
 ) -> Result<DisplayModel, EngineError> {
-    format_with_value(chain_id, to, None, calldata)
+    format_with_value(chain_id, to, None, calldata, locale)
 }
 
 fn map_resolver_error(err: ResolverError) -> EngineError {
@@ -0,0 +1,797 @@
+//! Recursive formatting of calls that wrap other calls: Safe `execTransaction`,
+//! `multiSend`, `multicall` and Universal Router `execute`, plus the ERC-7730
+//! `calldata` field format.
+
+use {
+    super::{
+        descriptor::{
+            ArgumentValue, DecodedArguments, EffectiveField, native_token_key,
+        },
+        engine::{
+            DisplayItem, DisplayModel, EngineError, RawPreview,
+            format_amount_with_decimals, format_date,
+            format_resolved_call_at_depth, raw_preview_from_calldata,
+            to_checksum_address,
+        },
+        locale::Locale,
+        providers::provided_address_label,
+        resolver,
+        token_registry::lookup_token_by_caip19,
+    },
+    num_bigint::BigUint,
+};
+
+/// Maximum nesting depth of inner calls that are resolved and formatted.
+/// Deeper calls are shown as raw previews with a warning.
+pub const MAX_CALL_DEPTH: usize = 3;
+
+// execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)
+const SAFE_EXEC_TRANSACTION: [u8; 4] = [0x6a, 0x76, 0x12, 0x02];
+// multiSend(bytes)
+const MULTI_SEND: [u8; 4] = [0x8d, 0x80, 0xff, 0x0a];
+// multicall(bytes[])
+const MULTICALL: [u8; 4] = [0xac, 0x96, 0x50, 0xd8];
+// multicall(uint256,bytes[])
+const MULTICALL_WITH_DEADLINE: [u8; 4] = [0x5a, 0xe4, 0x01, 0xdc];
+// execute(bytes,bytes[],uint256)
+const UNIVERSAL_ROUTER_EXECUTE_WITH_DEADLINE: [u8; 4] =
+    [0x35, 0x93, 0x56, 0x4c];
+// execute(bytes,bytes[])
+const UNIVERSAL_ROUTER_EXECUTE: [u8; 4] = [0x24, 0x85, 0x6b, 0xc3];
+
+const UNIVERSAL_ROUTER_COMMAND_MASK: u8 = 0x3f;
+const UNIVERSAL_ROUTER_FLAG_ALLOW_REVERT: u8 = 0x80;
+const UNIVERSAL_ROUTER_EXECUTE_SUB_PLAN: u8 = 0x21;
+
+/// Formats a call at the given nesting depth. Known wrapper selectors are
+/// decoded here and their inner calls formatted recursively, everything else
+/// goes through the descriptor resolver.
+pub(crate) fn format_call(
+    chain_id: u64,
+    sender: Option<&str>,
+    to: &str,
+    value: Option<&[u8]>,
+    calldata: &[u8],
+    depth: usize,
+    locale: &Locale,
+) -> Result<DisplayModel, EngineError> {
+    if let Some(model) =
+        format_wrapper(chain_id, sender, to, value, calldata, depth, locale)?
+    {
+        return Ok(model);
+    }
+
+    let resolved = resolver::resolve_call(chain_id, to, calldata, value)
+        .map_err(super::map_resolver_error)?;
+    format_resolved_call_at_depth(
+        resolved, chain_id, sender, to, value, calldata, depth, locale,
+    )
+}
+
+/// Renders an ERC-7730 `calldata` field. `value` must be a top-level `bytes`
+/// argument of `calldata`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn format_calldata_field(
+    field: &EffectiveField,
+    value: &ArgumentValue,
+    decoded: &DecodedArguments,
+    chain_id: u64,
+    contract_address: &str,
+    calldata: &[u8],
+    depth: usize,
+    locale: &Locale,
+) -> Result<DisplayModel, EngineError> {
+    let ArgumentValue::Raw(offset_word) = value else {
+        return Err(EngineError::Calldata(format!(
+            "field '{}' is not a bytes argument",
+            field.path
+        )));
+    };
+    let args = calldata.get(4..).unwrap_or_default();
+    let inner = bytes_at(args, word_to_usize(offset_word)?)?;
+
+    let callee = match param_address(field, "callee", decoded)? {
+        Some(address) => to_checksum_address(&address),
+        None => contract_address.to_string(),
+    };
+
+    let amount = match param_value(field, "amount", decoded)? {
+        Some(value) => value.as_uint().cloned().ok_or_else(|| {
+            EngineError::Calldata(format!(
+                "amount for field '{}' is not a number",
+                field.path
+            ))
+        })?,
+        None => BigUint::from(0u32),
+    };
+
+    // With a selector parameter the bytes only hold the ABI-encoded arguments
+    let inner = match param_value(field, "selector", decoded)? {
+        Some(ArgumentValue::Raw(word)) => {
+            let mut data = word[..4].to_vec();
+            data.extend_from_slice(inner);
+            data
+        }
+        Some(other) => {
+            return Err(EngineError::Calldata(format!(
+                "selector for field '{}' is not bytes4: {}",
+                field.path,
+                other.default_string()
+            )));
+        }
+        None => inner.to_vec(),
+    };
+
+    // The contract holding the field makes the inner call
+    Ok(format_inner_call(
+        chain_id,
+        Some(contract_address),
+        &callee,
+        &amount,
+        &inner,
+        depth + 1,
+        locale,
+    ))
+}
+
+/// Text used in place of an inner call in the parent's items and intent.
+pub(crate) fn call_summary(model: &DisplayModel) -> String {
+    model.interpolated_intent.clone().unwrap_or_else(|| model.intent.clone())
+}
+
+fn format_wrapper(
+    chain_id: u64,
+    sender: Option<&str>,
+    to: &str,
+    value: Option<&[u8]>,
+    calldata: &[u8],
+    depth: usize,
+    locale: &Locale,
+) -> Result<Option<DisplayModel>, EngineError> {
+    let Some((selector, args)) = calldata.split_first_chunk::<4>() else {
+        return Ok(None);
+    };
+
+    let model = match *selector {
+        SAFE_EXEC_TRANSACTION => {
+            format_safe_transaction(chain_id, to, args, depth, locale)?
+        }
+        MULTI_SEND => format_multi_send(chain_id, sender, args, depth, locale)?,
+        MULTICALL => format_multicall(
+            chain_id, sender, to, None, args, 0, depth, locale,
+        )?,
+        MULTICALL_WITH_DEADLINE => {
+            let deadline = read_uint(args, 0)?;
+            format_multicall(
+                chain_id,
+                sender,
+                to,
+                Some(deadline),
+                args,
+                32,
+                depth,
+                locale,
+            )?
+        }
+        UNIVERSAL_ROUTER_EXECUTE => {
+            format_universal_router(chain_id, value, args, None, depth, locale)?
+        }
+        UNIVERSAL_ROUTER_EXECUTE_WITH_DEADLINE => {
+            let deadline = read_uint(args, 64)?;
+            format_universal_router(
+                chain_id,
+                value,
+                args,
+                Some(deadline),
+                depth,
+                locale,
+            )?
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(model))
+}
+
+fn format_safe_transaction(
+    chain_id: u64,
+    safe: &str,
+    args: &[u8],
+    depth: usize,
+    locale: &Locale,
+) -> Result<DisplayModel, EngineError> {
+    let to = to_checksum_address(&read_address(args, 0)?);
+    let value = read_uint(args, 32)?;
+    let data = read_bytes(args, 64)?;
+    let operation = read_operation(&read_uint(args, 96)?)?;
+    let gas_price = read_uint(args, 192)?;
+    let gas_token = read_address(args, 224)?;
+    let refund_receiver = read_address(args, 256)?;
+
+    let mut items = vec![
+        DisplayItem {
+            label: "To".to_string(),
+            value: format_address(chain_id, &to),
+        },
+        DisplayItem {
+            label: "Value".to_string(),
+            value: format_native_value(chain_id, &value, locale),
+        },
+        DisplayItem {
+            label: "Operation".to_string(),
+            value: operation.label().to_string(),
+        },
+    ];
+    if gas_price != BigUint::from(0u32) {
+        items.push(DisplayItem {
+            label: "Gas token".to_string(),
+            value: format_address(chain_id, &to_checksum_address(&gas_token)),
+        });
+        items.push(DisplayItem {
+            label: "Refund receiver".to_string(),
+            value: format_address(
+                chain_id,
+                &to_checksum_address(&refund_receiver),
+            ),
+        });
+    }
+
+    let mut warnings = Vec::new();
+    if operation == Operation::DelegateCall {
+        warnings.push(format!("Safe transaction delegate-calls {to}"));
+    }
+
+    let inner = format_inner_call(
+        chain_id,
+        Some(safe),
+        &to,
+        &value,
+        data,
+        depth + 1,
+        locale,
+    );
+    Ok(DisplayModel {
+        intent: "Execute Safe transaction".to_string(),
+        interpolated_intent: Some(format!(
+            "Execute Safe transaction: {}",
+            call_summary(&inner)
+        )),
+        items,
+        warnings,
+        risks: Vec::new(),
+        raw: None,
+        calls: vec![inner],
+    })
+}
+
+// multiSend is delegate-called, so its calls are made by `sender`
+fn format_multi_send(
+    chain_id: u64,
+    sender: Option<&str>,
+    args: &[u8],
+    depth: usize,
+    locale: &Locale,
+) -> Result<DisplayModel, EngineError> {
+    let packed = read_bytes(args, 0)?;
+
+    // Each transaction is packed as operation (1) | to (20) | value (32) | length (32) | data
+    let mut calls = Vec::new();
+    let mut cursor = 0;
+    while cursor < packed.len() {
+        let header = packed.get(cursor..cursor + 85).ok_or_else(|| {
+            EngineError::Calldata(format!(
+                "multiSend transaction {} is truncated",
+                calls.len()
+            ))
+        })?;
+        let operation = read_operation(&BigUint::from(header[0]))?;
+        let mut to = [0u8; 20];
+        to.copy_from_slice(&header[1..21]);
+        let value = BigUint::from_bytes_be(&header[21..53]);
+        let length = word_to_usize(&header[53..85])?;
+        let data = packed
+            .get(cursor + 85..)
+            .and_then(|rest| rest.get(..length))
+            .ok_or_else(|| {
+                EngineError::Calldata(format!(
+                    "multiSend transaction {} data is truncated",
+                    calls.len()
+                ))
+            })?;
+        cursor += 85 + length;
+
+        let to = to_checksum_address(&to);
+        let mut call = format_inner_call(
+            chain_id,
+            sender,
+            &to,
+            &value,
+            data,
+            depth + 1,
+            locale,
+        );
+        if operation == Operation::DelegateCall {
+            call.warnings.insert(0, format!("Delegate call to {to}"));
+        }
+        calls.push(call);
+    }
+
+    Ok(DisplayModel {
+        intent: "Batch transaction".to_string(),
+        interpolated_intent: Some(format!("Execute {} calls", calls.len())),
+        items: vec![DisplayItem {
+            label: "Calls".to_string(),
+            value: calls.len().to_string(),
+        }],
+        warnings: Vec::new(),
+        risks: Vec::new(),
+        raw: None,
+        calls,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn format_multicall(
+    chain_id: u64,
+    sender: Option<&str>,
+    to: &str,
+    deadline: Option<BigUint>,
+    args: &[u8],
+    calls_offset: usize,
+    depth: usize,
+    locale: &Locale,
+) -> Result<DisplayModel, EngineError> {
+    let zero = BigUint::from(0u32);
+    let calls = read_bytes_array(args, calls_offset)?
+        .into_iter()
+        .map(|data| {
+            format_inner_call(
+                chain_id,
+                sender,
+                to,
+                &zero,
+                data,
+                depth + 1,
+                locale,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut items = vec![DisplayItem {
+        label: "Calls".to_string(),
+        value: calls.len().to_string(),
+    }];
+    if let Some(deadline) = deadline {
+        items.push(DisplayItem {
+            label: "Deadline".to_string(),
+            value: format_date(&ArgumentValue::Uint(deadline), locale),
+        });
+    }
+
+    Ok(DisplayModel {
+        intent: "Multicall".to_string(),
+        interpolated_intent: Some(format!("Execute {} calls", calls.len())),
+        items,
+        warnings: Vec::new(),
+        risks: Vec::new(),
+        raw: None,
+        calls,
+    })
+}
+
+fn format_universal_router(
+    chain_id: u64,
+    value: Option<&[u8]>,
+    args: &[u8],
+    deadline: Option<BigUint>,
+    depth: usize,
+    locale: &Locale,
+) -> Result<DisplayModel, EngineError> {
+    let calls = format_router_commands(args, depth)?;
+
+    let mut items = vec![DisplayItem {
+        label: "Commands".to_string(),
+        value: calls.len().to_string(),
+    }];
+    if let Some(value) = value {
+        items.push(DisplayItem {
+            label: "Value".to_string(),
+            value: format_native_value(
+                chain_id,
+                &BigUint::from_bytes_be(value),
+                locale,
+            ),
+        });
+    }
+    if let Some(deadline) = deadline {
+        items.push(DisplayItem {
+            label: "Deadline".to_string(),
+            value: format_date(&ArgumentValue::Uint(deadline), locale),
+        });
+    }
+
+    Ok(DisplayModel {
+        intent: "Execute Universal Router commands".to_string(),
+        interpolated_intent: None,
+        items,
+        warnings: Vec::new(),
+        risks: Vec::new(),
+        raw: None,
+        calls,
+    })
+}
+
+// `args` is the ABI encoding of `(bytes commands, bytes[] inputs)`, which is
+// shared by `execute` and the `EXECUTE_SUB_PLAN` command
+fn format_router_commands(
+    args: &[u8],
+    depth: usize,
+) -> Result<Vec<DisplayModel>, EngineError> {
+    let commands = read_bytes(args, 0)?;
+    let inputs = read_bytes_array(args, 32)?;
+    if commands.len() != inputs.len() {
+        return Err(EngineError::Calldata(format!(
+            "Universal Router has {} commands but {} inputs",
+            commands.len(),
+            inputs.len()
+        )));
+    }
+
+    let mut models = Vec::with_capacity(commands.len());
+    for (&command, input) in commands.iter().zip(inputs) {
+        let kind = command & UNIVERSAL_ROUTER_COMMAND_MASK;
+        let mut warnings = Vec::new();
+        if command & UNIVERSAL_ROUTER_FLAG_ALLOW_REVERT != 0 {
+            warnings.push(
+                "Command may revert without reverting the transaction"
+                    .to_string(),
+            );
+        }
+        let intent = match universal_router_command_name(kind) {
+            Some(name) => name.to_string(),
+            None => {
+                warnings.push(format!(
+                    "Unknown Universal Router command 0x{kind:02x}"
+                ));
+                "Unknown command".to_string()
+            }
+        };
+
+        let mut calls = Vec::new();
+        if kind == UNIVERSAL_ROUTER_EXECUTE_SUB_PLAN {
+            if depth < MAX_CALL_DEPTH {
+                calls = format_router_commands(input, depth + 1)?;
+            } else {
+                warnings.push(depth_limit_warning());
+            }
+        }
+
+        models.push(DisplayModel {
+            intent,
+            interpolated_intent: None,
+            items: Vec::new(),
+            warnings,
+            risks: Vec::new(),
+            raw: Some(RawPreview {
+                selector: format!("0x{command:02x}"),
+                args: input
+                    .chunks(32)
+                    .map(|chunk| format!("0x{}", hex::encode(chunk)))
+                    .collect(),
+            }),
+            calls,
+        });
+    }
+    Ok(models)
+}
+
+fn universal_router_command_name(command: u8) -> Option<&'static str> {
+    let name = match command {
+        0x00 => "Uniswap V3 swap (exact input)",
+        0x01 => "Uniswap V3 swap (exact output)",
+        0x02 => "Permit2 transfer",
+        0x03 => "Permit2 batch permit",
+        0x04 => "Sweep tokens",
+        0x05 => "Transfer tokens",
+        0x06 => "Pay portion of balance",
+        0x08 => "Uniswap V2 swap (exact input)",
+        0x09 => "Uniswap V2 swap (exact output)",
+        0x0a => "Permit2 permit",
+        0x0b => "Wrap ETH",
+        0x0c => "Unwrap WETH",
+        0x0d => "Permit2 batch transfer",
+        0x0e => "Check ERC-20 balance",
+        0x10 => "Uniswap V4 swap",
+        0x11 => "Uniswap V3 position manager permit",
+        0x12 => "Uniswap V3 position manager call",
+        0x13 => "Initialize Uniswap V4 pool",
+        0x14 => "Uniswap V4 position manager call",
+        UNIVERSAL_ROUTER_EXECUTE_SUB_PLAN => "Execute sub-plan",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Formats an inner call, never failing: calls that cannot be resolved are
+/// returned as raw previews with the reason as a warning.
+pub(crate) fn format_inner_call(
+    chain_id: u64,
+    sender: Option<&str>,
+    to: &str,
+    value: &BigUint,
+    data: &[u8],
+    depth: usize,
+    locale: &Locale,
+) -> DisplayModel {
+    if data.is_empty() {
+        return DisplayModel {
+            intent: "Send".to_string(),
+            interpolated_intent: Some(format!(
+                "Send {} to {}",
+                format_native_value(chain_id, value, locale),
+                format_address(chain_id, to)
+            )),
+            items: vec![
+                DisplayItem {
+                    label: "To".to_string(),
+                    value: format_address(chain_id, to),
+                },
+                DisplayItem {
+                    label: "Amount".to_string(),
+                    value: format_native_value(chain_id, value, locale),
+                },
+            ],
+            warnings: Vec::new(),
+            risks: Vec::new(),
+            raw: None,
+            calls: Vec::new(),
+        };
+    }
+
+    if depth > MAX_CALL_DEPTH {
+        return unresolved_call(
+            chain_id,
+            to,
+            value,
+            data,
+            depth_limit_warning(),
+            locale,
+        );
+    }
+
+    let value_word = uint_word(value);
+    match format_call(
+        chain_id,
+        sender,
+        to,
+        Some(value_word.as_slice()),
+        data,
+        depth,
+        locale,
+    ) {
+        Ok(model) => model,
+        Err(err) => {
+            unresolved_call(chain_id, to, value, data, err.to_string(), locale)
+        }
+    }
+}
+
+fn unresolved_call(
+    chain_id: u64,
+    to: &str,
+    value: &BigUint,
+    data: &[u8],
+    warning: String,
+    locale: &Locale,
+) -> DisplayModel {
+    let mut items = vec![DisplayItem {
+        label: "To".to_string(),
+        value: format_address(chain_id, to),
+    }];
+    if value != &BigUint::from(0u32) {
+        items.push(DisplayItem {
+            label: "Value".to_string(),
+            value: format_native_value(chain_id, value, locale),
+        });
+    }
+
+    let raw = match data.first_chunk::<4>() {
+        Some(selector) => raw_preview_from_calldata(selector, data),
+        None => RawPreview {
+            selector: format!("0x{}", hex::encode(data)),
+            args: Vec::new(),
+        },
+    };
+
+    DisplayModel {
+        intent: "Unknown call".to_string(),
+        interpolated_intent: None,
+        items,
+        warnings: vec![warning],
+        risks: Vec::new(),
+        raw: Some(raw),
+        calls: Vec::new(),
+    }
+}
+
+fn depth_limit_warning() -> String {
+    format!("Nested call exceeds the maximum depth of {MAX_CALL_DEPTH}")
+}
+
+fn format_address(chain_id: u64, address: &str) -> String {
+    // Self-calls carry the caller's `to`, which may not be checksummed
+    let checksum = hex::decode(address.trim().trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| <[u8; 20]>::try_from(bytes).ok())
+        .map(|bytes| to_checksum_address(&bytes))
+        .unwrap_or_else(|| address.to_string());
+    provided_address_label(chain_id, &checksum).unwrap_or(checksum)
+}
+
+pub(crate) fn format_native_value(
+    chain_id: u64,
+    amount: &BigUint,
+    locale: &Locale,
+) -> String {
+    let meta = native_token_key(chain_id)
+        .ok()
+        .and_then(|key| lookup_token_by_caip19(key.as_str()));
+    match meta {
+        Some(meta) => format!(
+            "{} {}",
+            format_amount_with_decimals(amount, meta.decimals, locale),
+            meta.symbol
+        ),
+        None => format!(
+            "{} NATIVE",
+            format_amount_with_decimals(amount, 18, locale)
+        ),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Call,
+    DelegateCall,
+}
+
+impl Operation {
+    fn label(self) -> &'static str {
+        match self {
+            Operation::Call => "Call",
+            Operation::DelegateCall => "Delegate call",
+        }
+    }
+}
+
+fn read_operation(value: &BigUint) -> Result<Operation, EngineError> {
+    match u8::try_from(value) {
+        Ok(0) => Ok(Operation::Call),
+        Ok(1) => Ok(Operation::DelegateCall),
+        _ => Err(EngineError::Calldata(format!(
+            "unsupported Safe operation {value}"
+        ))),
+    }
+}
+
+// Looks up the argument referenced by the `{name}Path` parameter. Paths into
+// the container (`@.to`) are not arguments and resolve to `None`.
+fn param_value<'a>(
+    field: &EffectiveField,
+    name: &str,
+    decoded: &'a DecodedArguments,
+) -> Result<Option<&'a ArgumentValue>, EngineError> {
+    let Some(path) =
+        field.params.get(format!("{name}Path")).and_then(|path| path.as_str())
+    else {
+        return Ok(None);
+    };
+    let path = match path {
+        "@.to" => return Ok(None),
+        path => path.strip_prefix("#.").unwrap_or(path),
+    };
+    decoded.get(path).map(Some).ok_or_else(|| {
+        EngineError::Calldata(format!(
+            "{name} path '{path}' not found for field '{}'",
+            field.path
+        ))
+    })
+}
+
+// Either a constant address or a path to an address argument
+fn param_address(
+    field: &EffectiveField,
+    name: &str,
+    decoded: &DecodedArguments,
+) -> Result<Option<[u8; 20]>, EngineError> {
+    let not_address = || {
+        EngineError::Calldata(format!(
+            "{name} for field '{}' is not an address",
+            field.path
+        ))
+    };
+
+    if let Some(constant) = field.params.get(name).and_then(|v| v.as_str()) {
+        return hex::decode(constant.trim_start_matches("0x"))
+            .ok()
+            .and_then(|bytes| <[u8; 20]>::try_from(bytes).ok())
+            .map(Some)
+            .ok_or_else(not_address);
+    }
+
+    match param_value(field, name, decoded)? {
+        Some(value) => {
+            value.as_address().copied().map(Some).ok_or_else(not_address)
+        }
+        None => Ok(None),
+    }
+}
+
+fn uint_word(value: &BigUint) -> Vec<u8> {
+    let bytes = value.to_bytes_be();
+    let mut word = vec![0u8; 32usize.saturating_sub(bytes.len())];
+    word.extend_from_slice(&bytes);
+    word
+}
+
+fn word_at(args: &[u8], offset: usize) -> Result<&[u8], EngineError> {
+    args.get(offset..offset.saturating_add(32)).ok_or_else(|| {
+        EngineError::Calldata(format!(
+            "calldata length {} too small to read word at {offset}",
+            args.len()
+        ))
+    })
+}
+
+fn word_to_usize(word: &[u8]) -> Result<usize, EngineError> {
+    let value = BigUint::from_bytes_be(word);
+    usize::try_from(&value).map_err(|_| {
+        EngineError::Calldata(format!("offset or length {value} out of range"))
+    })
+}
+
+fn read_uint(args: &[u8], offset: usize) -> Result<BigUint, EngineError> {
+    Ok(BigUint::from_bytes_be(word_at(args, offset)?))
+}
+
+fn read_address(args: &[u8], offset: usize) -> Result<[u8; 20], EngineError> {
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&word_at(args, offset)?[12..]);
+    Ok(address)
+}
+
+// Dynamic `bytes` whose head (offset) word is at `offset`
+fn read_bytes(args: &[u8], offset: usize) -> Result<&[u8], EngineError> {
+    bytes_at(args, word_to_usize(word_at(args, offset)?)?)
+}
+
+// Length-prefixed bytes starting at `position`
+fn bytes_at(args: &[u8], position: usize) -> Result<&[u8], EngineError> {
+    let length = word_to_usize(word_at(args, position)?)?;
+    let start = position + 32;
+    args.get(start..start.saturating_add(length)).ok_or_else(|| {
+        EngineError::Calldata(format!(
+            "bytes of length {length} at {position} exceed calldata"
+        ))
+    })
+}
+
+// Dynamic `bytes[]` whose head (offset) word is at `offset`
+fn read_bytes_array(
+    args: &[u8],
+    offset: usize,
+) -> Result<Vec<&[u8]>, EngineError> {
+    let position = word_to_usize(word_at(args, offset)?)?;
+    let count = word_to_usize(word_at(args, position)?)?;
+    let base = position + 32;
+    if count > args.len().saturating_sub(base) / 32 {
+        return Err(EngineError::Calldata(format!(
+            "bytes[] of length {count} at {position} exceeds calldata"
+        )));
+    }
+    (0..count)
+        .map(|index| {
+            let element = word_to_usize(word_at(args, base + index * 32)?)?;
+            bytes_at(args, base.saturating_add(element))
+        })
+        .collect()
+}
@@ -0,0 +1,64 @@
+//! Host-provided token metadata and address labels. Installed providers are
+//! consulted before the runtime registry and the bundled assets.
+
+use {
+    super::token_registry::TokenMeta,
+    std::sync::{Arc, PoisonError, RwLock},
+};
+
+/// Supplies metadata for tokens, e.g. from the wallet's own token list.
+#[cfg_attr(feature = "uniffi", uniffi::export(with_foreign))]
+pub trait TokenMetadataProvider: Send + Sync {
+    /// `caip19` is lowercase, e.g. `eip155:1/erc20:0xa0b8...` or `eip155:1/slip44:60`.
+    fn token_metadata(&self, caip19: String) -> Option<TokenMeta>;
+}
+
+/// Supplies display names for addresses, e.g. contacts or ENS names.
+#[cfg_attr(feature = "uniffi", uniffi::export(with_foreign))]
+pub trait AddressLabelProvider: Send + Sync {
+    /// `address` is EIP-55 checksummed.
+    fn address_label(&self, chain_id: u64, address: String) -> Option<String>;
+}
+
+static TOKEN_METADATA_PROVIDER: RwLock<Option<Arc<dyn TokenMetadataProvider>>> =
+    RwLock::new(None);
+static ADDRESS_LABEL_PROVIDER: RwLock<Option<Arc<dyn AddressLabelProvider>>> =
+    RwLock::new(None);
+
+/// Installs (or with `None`, removes) the process-wide token metadata provider.
+pub fn set_token_metadata_provider(
+    provider: Option<Arc<dyn TokenMetadataProvider>>,
+) {
+    *TOKEN_METADATA_PROVIDER.write().unwrap_or_else(PoisonError::into_inner) =
+        provider;
+}
+
+/// Installs (or with `None`, removes) the process-wide address label provider.
+pub fn set_address_label_provider(
+    provider: Option<Arc<dyn AddressLabelProvider>>,
+) {
+    *ADDRESS_LABEL_PROVIDER.write().unwrap_or_else(PoisonError::into_inner) =
+        provider;
+}
+
+pub(crate) fn provided_token_metadata(caip19: &str) -> Option<TokenMeta> {
+    // Clone the provider out so the lock isn't held across the (possibly foreign) call
+    let provider = TOKEN_METADATA_PROVIDER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()?;
+    provider.token_metadata(caip19.to_string())
+}
+
+pub(crate) fn provided_address_label(
+    chain_id: u64,
+    checksum_address: &str,
+) -> Option<String> {
+    let provider = ADDRESS_LABEL_PROVIDER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()?;
+    provider
+        .address_label(chain_id, checksum_address.to_string())
+        .filter(|label| !label.trim().is_empty())
+}
@@ -0,0 +1,536 @@
+//! Runtime-loadable descriptor registry. Signed bundles are verified against a
+//! configured Ed25519 public key (and optionally pinned SHA-256 hashes) before
+//! they take precedence over the assets bundled in the binary.
+
+use {
+    super::{
+        resolver::{
+            IndexEntry, bundled_abi_content, bundled_descriptor_content,
+            bundled_typed_descriptor_content,
+        },
+        token_registry::TokenMeta,
+    },
+    ed25519_dalek::{Signature, VerifyingKey},
+    serde::Deserialize,
+    serde_json::Value,
+    sha2::{Digest, Sha256},
+    std::{
+        collections::HashMap,
+        path::Path,
+        sync::{Arc, PoisonError, RwLock},
+    },
+    thiserror::Error,
+};
+
+/// Keys and hashes runtime bundles are checked against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryTrust {
+    /// Ed25519 public key bundles must be signed with.
+    pub public_key: [u8; 32],
+    /// When not empty, only bundles whose SHA-256 is listed are accepted.
+    pub pinned_sha256: Vec<[u8; 32]>,
+}
+
+/// Describes the runtime bundle currently in use.
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryBundleInfo {
+    pub version: u64,
+    pub sha256: String,
+    pub descriptors: u32,
+    pub tokens: u32,
+}
+
+/// Errors returned when loading a runtime registry bundle.
+#[derive(Debug, Error)]
+pub enum RegistryError {
+    #[error("no registry public key configured")]
+    NotConfigured,
+    #[error("invalid registry public key: {0}")]
+    InvalidPublicKey(String),
+    #[error("invalid bundle signature: {0}")]
+    InvalidSignature(String),
+    #[error("bundle hash {0} is not pinned")]
+    HashNotPinned(String),
+    #[error("bundle version {version} is older than loaded version {loaded}")]
+    Downgrade { version: u64, loaded: u64 },
+    #[error("bundle parse error: {0}")]
+    Parse(String),
+    #[error("io error: {0}")]
+    Io(String),
+}
+
+// Bundle format. Descriptors, ABIs and includes are keyed by the same paths
+// and names the bundled `index.json` and `includes` entries use, so a bundle
+// can also reference assets that ship with the binary.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleJson {
+    version: u64,
+    #[serde(default)]
+    index: HashMap<String, IndexEntry>,
+    #[serde(default)]
+    typed_index: HashMap<String, String>,
+    #[serde(default)]
+    descriptors: HashMap<String, Value>,
+    #[serde(default)]
+    abis: HashMap<String, Value>,
+    #[serde(default)]
+    includes: HashMap<String, Value>,
+    #[serde(default)]
+    tokens: HashMap<String, TokenJson>,
+    #[serde(default)]
+    address_book: HashMap<String, HashMap<String, String>>,
+    #[serde(default)]
+    delegates: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenJson {
+    symbol: String,
+    decimals: u8,
+    name: String,
+}
+
+#[derive(Debug)]
+struct RegistryBundle {
+    info: RegistryBundleInfo,
+    index: HashMap<String, IndexEntry>,
+    typed_index: HashMap<String, String>,
+    descriptors: HashMap<String, String>,
+    abis: HashMap<String, String>,
+    includes: HashMap<String, String>,
+    tokens: HashMap<String, TokenMeta>,
+    address_book: HashMap<String, HashMap<String, String>>,
+    delegates: HashMap<String, String>,
+}
+
+static TRUST: RwLock<Option<RegistryTrust>> = RwLock::new(None);
+
+// Lookups hold their own reference, so replacing or resetting the bundle never
+// invalidates a lookup in progress
+static BUNDLE: RwLock<Option<Arc<RegistryBundle>>> = RwLock::new(None);
+
+/// Sets the key and pins runtime bundles are verified against. Any loaded
+/// bundle is dropped, since it was verified with the previous configuration.
+pub fn configure_registry(trust: RegistryTrust) {
+    *TRUST.write().unwrap_or_else(PoisonError::into_inner) = Some(trust);
+    reset_registry();
+}
+
+/// Verifies `bundle` against the configured trust and `signature` (Ed25519
+/// over the raw bundle bytes), then uses it ahead of the bundled assets.
+pub fn load_registry_bundle(
+    bundle: &[u8],
+    signature: &[u8],
+) -> Result<RegistryBundleInfo, RegistryError> {
+    let trust = TRUST
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .ok_or(RegistryError::NotConfigured)?;
+    let parsed = verify_bundle(&trust, bundle, signature)?;
+
+    let mut current = BUNDLE.write().unwrap_or_else(PoisonError::into_inner);
+    if let Some(loaded) = current.as_ref()
+        && parsed.info.version < loaded.info.version
+    {
+        return Err(RegistryError::Downgrade {
+            version: parsed.info.version,
+            loaded: loaded.info.version,
+        });
+    }
+    let info = parsed.info.clone();
+    *current = Some(Arc::new(parsed));
+    Ok(info)
+}
+
+/// Reads a bundle from disk and loads it with `load_registry_bundle`.
+pub fn load_registry_bundle_file(
+    path: impl AsRef<Path>,
+    signature: &[u8],
+) -> Result<RegistryBundleInfo, RegistryError> {
+    let bundle = std::fs::read(path.as_ref()).map_err(|err| {
+        RegistryError::Io(format!("{}: {err}", path.as_ref().display()))
+    })?;
+    load_registry_bundle(&bundle, signature)
+}
+
+/// Stops using the runtime bundle and falls back to the bundled assets.
+pub fn reset_registry() {
+    *BUNDLE.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Returns the runtime bundle in use, if any.
+pub fn registry_bundle_info() -> Option<RegistryBundleInfo> {
+    bundle().map(|bundle| bundle.info.clone())
+}
+
+fn verify_bundle(
+    trust: &RegistryTrust,
+    bundle: &[u8],
+    signature: &[u8],
+) -> Result<RegistryBundle, RegistryError> {
+    let public_key = VerifyingKey::from_bytes(&trust.public_key)
+        .map_err(|err| RegistryError::InvalidPublicKey(err.to_string()))?;
+    let signature = Signature::from_slice(signature)
+        .map_err(|err| RegistryError::InvalidSignature(err.to_string()))?;
+    public_key
+        .verify_strict(bundle, &signature)
+        .map_err(|err| RegistryError::InvalidSignature(err.to_string()))?;
+
+    let hash: [u8; 32] = Sha256::digest(bundle).into();
+    if !trust.pinned_sha256.is_empty() && !trust.pinned_sha256.contains(&hash) {
+        return Err(RegistryError::HashNotPinned(hex::encode(hash)));
+    }
+
+    parse_bundle(bundle, hex::encode(hash))
+}
+
+fn parse_bundle(
+    bundle: &[u8],
+    sha256: String,
+) -> Result<RegistryBundle, RegistryError> {
+    let json: BundleJson = serde_json::from_slice(bundle)
+        .map_err(|err| RegistryError::Parse(err.to_string()))?;
+
+    let to_strings = |values: HashMap<String, Value>| {
+        values
+            .into_iter()
+            .map(|(key, value)| (key, value.to_string()))
+            .collect::<HashMap<_, _>>()
+    };
+    let descriptors = to_strings(json.descriptors);
+    let abis = to_strings(json.abis);
+    let includes = to_strings(json.includes);
+
+    // Reject dangling references up front rather than failing on lookup. A
+    // previously loaded bundle doesn't count, since this one replaces it.
+    for (key, entry) in &json.index {
+        if !descriptors.contains_key(&entry.descriptor)
+            && bundled_descriptor_content(&entry.descriptor).is_none()
+        {
+            return Err(RegistryError::Parse(format!(
+                "index entry {key} references missing descriptor {}",
+                entry.descriptor
+            )));
+        }
+        if let Some(abi) = &entry.abi
+            && !abis.contains_key(abi)
+            && bundled_abi_content(abi).is_none()
+        {
+            return Err(RegistryError::Parse(format!(
+                "index entry {key} references missing ABI {abi}"
+            )));
+        }
+    }
+    for (key, path) in &json.typed_index {
+        if !descriptors.contains_key(path)
+            && bundled_typed_descriptor_content(path).is_none()
+        {
+            return Err(RegistryError::Parse(format!(
+                "typed index entry {key} references missing descriptor {path}"
+            )));
+        }
+    }
+
+    let tokens = json
+        .tokens
+        .into_iter()
+        .map(|(key, token)| {
+            (
+                key.trim().to_ascii_lowercase(),
+                TokenMeta {
+                    symbol: token.symbol,
+                    decimals: token.decimals,
+                    name: token.name,
+                },
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+    let address_book = json
+        .address_book
+        .into_iter()
+        .map(|(chain, entries)| {
+            let entries = entries
+                .into_iter()
+                .map(|(address, label)| {
+                    (address.trim().to_ascii_lowercase(), label)
+                })
+                .collect();
+            (chain.trim().to_ascii_lowercase(), entries)
+        })
+        .collect();
+
+    let delegates = json
+        .delegates
+        .into_iter()
+        .map(|(address, name)| (address.trim().to_ascii_lowercase(), name))
+        .collect();
+
+    let index = json
+        .index
+        .into_iter()
+        .map(|(key, entry)| (key.to_ascii_lowercase(), entry))
+        .collect();
+    let typed_index = json
+        .typed_index
+        .into_iter()
+        .map(|(key, path)| (key.to_ascii_lowercase(), path))
+        .collect();
+
+    Ok(RegistryBundle {
+        info: RegistryBundleInfo {
+            version: json.version,
+            sha256,
+            descriptors: descriptors.len() as u32,
+            tokens: tokens.len() as u32,
+        },
+        index,
+        typed_index,
+        descriptors,
+        abis,
+        includes,
+        tokens,
+        address_book,
+        delegates,
+    })
+}
+
+fn bundle() -> Option<Arc<RegistryBundle>> {
+    BUNDLE.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+pub(crate) fn index_entry(key: &str) -> Option<IndexEntry> {
+    bundle()?.index.get(key).cloned()
+}
+
+pub(crate) fn typed_index_entry(key: &str) -> Option<String> {
+    bundle()?.typed_index.get(key).cloned()
+}
+
+pub(crate) fn index_keys() -> Vec<String> {
+    bundle()
+        .map(|bundle| bundle.index.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+pub(crate) fn typed_index_keys() -> Vec<String> {
+    bundle()
+        .map(|bundle| bundle.typed_index.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+pub(crate) fn descriptor(path: &str) -> Option<String> {
+    bundle()?.descriptors.get(path).cloned()
+}
+
+pub(crate) fn abi(path: &str) -> Option<String> {
+    bundle()?.abis.get(path).cloned()
+}
+
+pub(crate) fn include(name: &str) -> Option<String> {
+    bundle()?.includes.get(name).cloned()
+}
+
+pub(crate) fn token(caip19: &str) -> Option<TokenMeta> {
+    bundle()?.tokens.get(caip19).cloned()
+}
+
+pub(crate) fn address_book(chain_key: &str) -> Option<HashMap<String, String>> {
+    bundle()?.address_book.get(chain_key).cloned()
+}
+
+pub(crate) fn delegate(address: &str) -> Option<String> {
+    bundle()?.delegates.get(address).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        ed25519_dalek::{Signer, SigningKey},
+        serde_json::json,
+        tiny_keccak::{Hasher, Keccak},
+    };
+
+    const TEST_ADDRESS: &str = "0x000000000000000000000000000000000000c0de";
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[7u8; 32])
+    }
+
+    fn trust() -> RegistryTrust {
+        RegistryTrust {
+            public_key: signing_key().verifying_key().to_bytes(),
+            pinned_sha256: Vec::new(),
+        }
+    }
+
+    fn bundle_bytes(version: u64) -> Vec<u8> {
+        json!({
+            "version": version,
+            "index": {
+                format!("eip155:1:{TEST_ADDRESS}"): {
+                    "descriptor": "runtime/ping.json"
+                }
+            },
+            "descriptors": {
+                "runtime/ping.json": {
+                    "context": {
+                        "contract": {
+                            "deployments": [
+                                { "chainId": 1, "address": TEST_ADDRESS }
+                            ],
+                            "abi": [{
+                                "name": "ping",
+                                "type": "function",
+                                "inputs": [
+                                    { "name": "count", "type": "uint256" }
+                                ]
+                            }]
+                        }
+                    },
+                    "display": {
+                        "formats": {
+                            "ping(uint256)": {
+                                "intent": "Ping",
+                                "fields": [{
+                                    "path": "count",
+                                    "label": "Count",
+                                    "format": "number"
+                                }]
+                            }
+                        }
+                    }
+                }
+            },
+            "tokens": {
+                "eip155:1/erc20:0x000000000000000000000000000000000000c0de": {
+                    "symbol": "PING",
+                    "decimals": 6,
+                    "name": "Ping"
+                }
+            }
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    fn sign(bundle: &[u8]) -> Vec<u8> {
+        signing_key().sign(bundle).to_bytes().to_vec()
+    }
+
+    #[test]
+    fn test_verify_bundle() {
+        let bundle = bundle_bytes(1);
+        let parsed = verify_bundle(&trust(), &bundle, &sign(&bundle)).unwrap();
+        assert_eq!(parsed.info.version, 1);
+        assert_eq!(parsed.info.descriptors, 1);
+        assert_eq!(parsed.info.tokens, 1);
+        assert_eq!(parsed.info.sha256, hex::encode(Sha256::digest(&bundle)));
+    }
+
+    #[test]
+    fn test_verify_bundle_rejects_bad_signature() {
+        let bundle = bundle_bytes(1);
+        let mut signature = sign(&bundle);
+        signature[0] ^= 1;
+        assert!(matches!(
+            verify_bundle(&trust(), &bundle, &signature),
+            Err(RegistryError::InvalidSignature(_))
+        ));
+
+        let mut tampered = bundle.clone();
+        tampered.push(b' ');
+        assert!(matches!(
+            verify_bundle(&trust(), &tampered, &sign(&bundle)),
+            Err(RegistryError::InvalidSignature(_))
+        ));
+
+        assert!(matches!(
+            verify_bundle(&trust(), &bundle, &[0u8; 10]),
+            Err(RegistryError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_bundle_hash_pinning() {
+        let bundle = bundle_bytes(1);
+        let pinned =
+            RegistryTrust { pinned_sha256: vec![[0u8; 32]], ..trust() };
+        assert!(matches!(
+            verify_bundle(&pinned, &bundle, &sign(&bundle)),
+            Err(RegistryError::HashNotPinned(_))
+        ));
+
+        let pinned = RegistryTrust {
+            pinned_sha256: vec![Sha256::digest(&bundle).into()],
+            ..trust()
+        };
+        assert!(verify_bundle(&pinned, &bundle, &sign(&bundle)).is_ok());
+    }
+
+    #[test]
+    fn test_parse_bundle_rejects_dangling_index_entry() {
+        let bundle = json!({
+            "version": 1,
+            "index": {
+                "eip155:1:0x01": { "descriptor": "runtime/missing.json" }
+            }
+        })
+        .to_string()
+        .into_bytes();
+        assert!(matches!(
+            verify_bundle(&trust(), &bundle, &sign(&bundle)),
+            Err(RegistryError::Parse(_))
+        ));
+    }
+
+    // Exercises the process-wide registry, so everything touching it lives in one test
+    #[test]
+    fn test_load_and_resolve() {
+        let mut selector = [0u8; 32];
+        let mut hasher = Keccak::v256();
+        hasher.update(b"ping(uint256)");
+        hasher.finalize(&mut selector);
+        let mut calldata = selector[..4].to_vec();
+        calldata.extend_from_slice(&[0u8; 31]);
+        calldata.push(5);
+
+        let format = || super::super::format(1, TEST_ADDRESS, &calldata);
+        assert!(format().is_err());
+
+        let bundle = bundle_bytes(2);
+        configure_registry(trust());
+        let info = load_registry_bundle(&bundle, &sign(&bundle)).unwrap();
+        assert_eq!(registry_bundle_info(), Some(info));
+
+        let model = format().unwrap();
+        assert_eq!(model.intent, "Ping");
+        assert_eq!(model.items[0].value, "5");
+        assert_eq!(
+            super::super::lookup_token_by_caip19(
+                "eip155:1/erc20:0x000000000000000000000000000000000000c0de"
+            )
+            .map(|token| token.symbol),
+            Some("PING".to_string())
+        );
+        // Bundled assets are still used for keys the bundle doesn't cover
+        assert!(
+            super::super::lookup_token_by_caip19("eip155:1/slip44:60")
+                .is_some()
+        );
+
+        let older = bundle_bytes(1);
+        assert!(matches!(
+            load_registry_bundle(&older, &sign(&older)),
+            Err(RegistryError::Downgrade { version: 1, loaded: 2 })
+        ));
+
+        reset_registry();
+        assert!(registry_bundle_info().is_none());
+        assert!(format().is_err());
+    }
+}
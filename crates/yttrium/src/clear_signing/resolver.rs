@@ -7,11 +7,14 @@ use {
             build_descriptor, decode_arguments, determine_token_key,
             native_token_key, resolve_effective_field,
         },
+        eip712::{TypeMember, encode_type},
+        registry,
         token_registry::{TokenMeta, lookup_token_by_caip19},
     },
     serde::Deserialize,
     serde_json::Value,
     std::{
+        borrow::Cow,
         collections::{HashMap, HashSet},
         sync::OnceLock,
     },
@@ -19,9 +22,9 @@ use {
 };
 
 pub struct ResolvedDescriptor<'a> {
-    pub descriptor_json: &'a str,
-    pub abi_json: Option<&'a str>,
-    pub includes: Vec<&'a str>,
+    pub descriptor_json: Cow<'a, str>,
+    pub abi_json: Option<Cow<'a, str>>,
+    pub includes: Vec<Cow<'a, str>>,
 }
 
 pub struct ResolvedCall<'a> {
@@ -42,11 +45,11 @@ pub enum ResolverError {
     DescriptorParse(String),
 }
 
-#[derive(Debug, Deserialize)]
-struct IndexEntry {
-    descriptor: String,
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct IndexEntry {
+    pub(crate) descriptor: String,
     #[serde(default)]
-    abi: Option<String>,
+    pub(crate) abi: Option<String>,
 }
 
 type IndexMap = HashMap<String, IndexEntry>;
@@ -106,6 +109,25 @@ const TYPED_DESCRIPTOR_1INCH_AGG_ROUTER_V6: &str =
     include_str!("assets/descriptors/1inch/eip712-AggregationRouterV6.json");
 const TYPED_DESCRIPTOR_UNISWAP_PERMIT2: &str =
     include_str!("assets/descriptors/uniswap/eip712-uniswap-permit2.json");
+const TYPED_DESCRIPTOR_SEAPORT: &str =
+    include_str!("assets/descriptors/opensea/eip712-seaport.json");
+const TYPED_DESCRIPTOR_COW_ORDER: &str =
+    include_str!("assets/descriptors/cowswap/eip712-cow-order.json");
+const TYPED_DESCRIPTOR_ERC2612_PERMIT: &str =
+    include_str!("assets/descriptors/generic/eip712-erc2612-permit.json");
+const TYPED_DESCRIPTOR_DAI_PERMIT: &str =
+    include_str!("assets/descriptors/generic/eip712-dai-permit.json");
+const TYPED_DESCRIPTOR_SAFE: &str =
+    include_str!("assets/descriptors/generic/eip712-safe.json");
+
+/// Typed data descriptors matched by message schema rather than verifying
+/// contract, for messages signed against many contracts (token permits, Safe
+/// accounts).
+const SCHEMA_TYPED_DESCRIPTORS: &[&str] = &[
+    "descriptors/generic/eip712-erc2612-permit.json",
+    "descriptors/generic/eip712-dai-permit.json",
+    "descriptors/generic/eip712-safe.json",
+];
 type TypedIndexMap = HashMap<String, String>;
 static TYPED_INDEX: OnceLock<TypedIndexMap> = OnceLock::new();
 const ADDRESS_BOOK_JSON: &str = include_str!("assets/address_book.json");
@@ -126,6 +148,28 @@ fn typed_index() -> &'static TypedIndexMap {
     })
 }
 
+/// Calldata index keys (`eip155:{chain}:{address}`), runtime registry entries first.
+pub(crate) fn index_keys() -> Vec<String> {
+    merge_keys(registry::index_keys(), index().keys())
+}
+
+/// Typed data index keys, runtime registry entries first.
+pub(crate) fn typed_index_keys() -> Vec<String> {
+    merge_keys(registry::typed_index_keys(), typed_index().keys())
+}
+
+fn merge_keys(
+    mut keys: Vec<String>,
+    bundled: impl Iterator<Item = &'static String>,
+) -> Vec<String> {
+    let mut bundled =
+        bundled.filter(|key| !keys.contains(key)).cloned().collect::<Vec<_>>();
+    bundled.sort_unstable();
+    keys.sort_unstable();
+    keys.append(&mut bundled);
+    keys
+}
+
 /// Resolves a descriptor bundle and associated assets for the given chain and address.
 pub fn resolve(
     chain_id: u64,
@@ -134,8 +178,8 @@ pub fn resolve(
     eprintln!("[resolver] resolve request chain_id={} to={}", chain_id, to);
     let key = format!("eip155:{}:{}", chain_id, normalize_address(to));
     eprintln!("[resolver] lookup key {}", key);
-    let entry = index()
-        .get(&key)
+    let entry = registry::index_entry(&key)
+        .or_else(|| index().get(&key).cloned())
         .ok_or_else(|| ResolverError::NotFound(key.clone()))?;
 
     let descriptor =
@@ -158,7 +202,7 @@ pub fn resolve(
         None => None,
     };
 
-    let includes = extract_includes(descriptor)?;
+    let includes = extract_includes(&descriptor)?;
     if !includes.is_empty() {
         eprintln!("[resolver] includes count {}", includes.len());
     }
@@ -249,7 +293,13 @@ pub fn resolve_call(
     Ok(ResolvedCall { descriptor: resolved, token_metadata, address_book })
 }
 
-fn descriptor_content(path: &str) -> Option<&'static str> {
+fn descriptor_content(path: &str) -> Option<Cow<'static, str>> {
+    registry::descriptor(path)
+        .map(Cow::Owned)
+        .or_else(|| bundled_descriptor_content(path).map(Cow::Borrowed))
+}
+
+pub(crate) fn bundled_descriptor_content(path: &str) -> Option<&'static str> {
     match path {
         "descriptors/erc20_usdt.json" => Some(DESCRIPTOR_ERC20_USDT),
         "descriptors/erc20_usdc.json" => {
@@ -295,7 +345,13 @@ fn descriptor_content(path: &str) -> Option<&'static str> {
     }
 }
 
-fn abi_content(path: &str) -> Option<&'static str> {
+fn abi_content(path: &str) -> Option<Cow<'static, str>> {
+    registry::abi(path)
+        .map(Cow::Owned)
+        .or_else(|| bundled_abi_content(path).map(Cow::Borrowed))
+}
+
+pub(crate) fn bundled_abi_content(path: &str) -> Option<&'static str> {
     match path {
         "abis/erc20.json" => Some(ABI_ERC20),
         "abis/uniswap_v3_router_v1.json" => Some(ABI_UNISWAP_V3_ROUTER_V1),
@@ -359,8 +415,8 @@ fn normalize_address(address: &str) -> String {
 }
 
 pub struct ResolvedTypedDescriptor<'a> {
-    pub descriptor_json: &'a str,
-    pub includes: Vec<&'a str>,
+    pub descriptor_json: Cow<'a, str>,
+    pub includes: Vec<Cow<'a, str>>,
     pub address_book: HashMap<String, String>,
 }
 
@@ -373,21 +429,20 @@ pub fn resolve_typed(
         chain_id,
         normalize_address(verifying_contract)
     );
-    let path = typed_index()
-        .get(&key)
+    let path = registry::typed_index_entry(&key)
+        .or_else(|| typed_index().get(&key).cloned())
         .ok_or_else(|| ResolverError::NotFound(key.clone()))?;
 
-    let descriptor = typed_descriptor_content(path).ok_or_else(|| {
-        ResolverError::InvalidIndexEntry { path: path.clone() }
-    })?;
+    let descriptor = typed_descriptor_content(&path)
+        .ok_or(ResolverError::InvalidIndexEntry { path })?;
 
-    let includes = extract_includes(descriptor)?;
+    let includes = extract_includes(&descriptor)?;
 
     let address_book = build_typed_address_book(
-        descriptor,
+        &descriptor,
         &includes,
         chain_id,
-        verifying_contract,
+        Some(verifying_contract),
     )?;
 
     Ok(ResolvedTypedDescriptor {
@@ -397,7 +452,81 @@ pub fn resolve_typed(
     })
 }
 
-fn typed_descriptor_content(path: &str) -> Option<&'static str> {
+/// Resolves a schema descriptor whose `context.eip712.schemas` contains
+/// `encoded_type`, the EIP-712 `encodeType` of the message's primary type.
+pub fn resolve_typed_by_schema(
+    chain_id: u64,
+    encoded_type: &str,
+) -> Result<Option<ResolvedTypedDescriptor<'static>>, ResolverError> {
+    for path in SCHEMA_TYPED_DESCRIPTORS {
+        let descriptor = typed_descriptor_content(path).ok_or_else(|| {
+            ResolverError::InvalidIndexEntry { path: path.to_string() }
+        })?;
+        let value: Value = serde_json::from_str(&descriptor)
+            .map_err(|err| ResolverError::DescriptorParse(err.to_string()))?;
+        let schemas = value
+            .pointer("/context/eip712/schemas")
+            .cloned()
+            .map(serde_json::from_value::<Vec<TypedSchema>>)
+            .transpose()
+            .map_err(|err| ResolverError::DescriptorParse(err.to_string()))?
+            .unwrap_or_default();
+        if !schemas.iter().any(|schema| {
+            encode_type(&schema.types, &schema.primary_type).as_deref()
+                == Some(encoded_type)
+        }) {
+            continue;
+        }
+
+        let includes = extract_includes(&descriptor)?;
+        // The verifying contract is the token or account, not the descriptor
+        // owner, so it isn't labelled
+        let address_book =
+            build_typed_address_book(&descriptor, &includes, chain_id, None)?;
+        return Ok(Some(ResolvedTypedDescriptor {
+            descriptor_json: descriptor,
+            includes,
+            address_book,
+        }));
+    }
+    Ok(None)
+}
+
+pub(crate) fn schema_typed_descriptor_paths() -> &'static [&'static str] {
+    SCHEMA_TYPED_DESCRIPTORS
+}
+
+// Loads a schema descriptor without matching it, for the linter
+pub(crate) fn resolve_typed_schema_descriptor(
+    path: &str,
+) -> Result<ResolvedTypedDescriptor<'static>, ResolverError> {
+    let descriptor = typed_descriptor_content(path).ok_or_else(|| {
+        ResolverError::InvalidIndexEntry { path: path.to_string() }
+    })?;
+    let includes = extract_includes(&descriptor)?;
+    Ok(ResolvedTypedDescriptor {
+        descriptor_json: descriptor,
+        includes,
+        address_book: HashMap::new(),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct TypedSchema {
+    types: HashMap<String, Vec<TypeMember>>,
+    #[serde(rename = "primaryType")]
+    primary_type: String,
+}
+
+fn typed_descriptor_content(path: &str) -> Option<Cow<'static, str>> {
+    registry::descriptor(path)
+        .map(Cow::Owned)
+        .or_else(|| bundled_typed_descriptor_content(path).map(Cow::Borrowed))
+}
+
+pub(crate) fn bundled_typed_descriptor_content(
+    path: &str,
+) -> Option<&'static str> {
     match path {
         "descriptors/1inch/eip712-1inch-limit-order.json" => {
             Some(TYPED_DESCRIPTOR_1INCH_LIMIT_ORDER)
@@ -408,13 +537,26 @@ fn typed_descriptor_content(path: &str) -> Option<&'static str> {
         "descriptors/uniswap/eip712-uniswap-permit2.json" => {
             Some(TYPED_DESCRIPTOR_UNISWAP_PERMIT2)
         }
+        "descriptors/opensea/eip712-seaport.json" => {
+            Some(TYPED_DESCRIPTOR_SEAPORT)
+        }
+        "descriptors/cowswap/eip712-cow-order.json" => {
+            Some(TYPED_DESCRIPTOR_COW_ORDER)
+        }
+        "descriptors/generic/eip712-erc2612-permit.json" => {
+            Some(TYPED_DESCRIPTOR_ERC2612_PERMIT)
+        }
+        "descriptors/generic/eip712-dai-permit.json" => {
+            Some(TYPED_DESCRIPTOR_DAI_PERMIT)
+        }
+        "descriptors/generic/eip712-safe.json" => Some(TYPED_DESCRIPTOR_SAFE),
         _ => None,
     }
 }
 
 fn extract_includes(
     descriptor_json: &str,
-) -> Result<Vec<&'static str>, ResolverError> {
+) -> Result<Vec<Cow<'static, str>>, ResolverError> {
     let value: Value = serde_json::from_str(descriptor_json)
         .map_err(|err| ResolverError::DescriptorParse(err.to_string()))?;
 
@@ -453,14 +595,18 @@ fn extract_includes(
     Ok(includes)
 }
 
-fn include_content(name: &str) -> Option<&'static str> {
-    match name {
-        "common-test-router.json" => Some(INCLUDE_COMMON_TEST_ROUTER),
-        "common-AggregationRouterV4.json" => Some(INCLUDE_1INCH_COMMON_V4),
-        "common-AggregationRouterV6.json" => Some(INCLUDE_1INCH_COMMON_V6),
-        "uniswap-common-eip712.json" => Some(INCLUDE_UNISWAP_COMMON_EIP712),
-        _ => None,
+fn include_content(name: &str) -> Option<Cow<'static, str>> {
+    if let Some(content) = registry::include(name) {
+        return Some(Cow::Owned(content));
     }
+    let content = match name {
+        "common-test-router.json" => INCLUDE_COMMON_TEST_ROUTER,
+        "common-AggregationRouterV4.json" => INCLUDE_1INCH_COMMON_V4,
+        "common-AggregationRouterV6.json" => INCLUDE_1INCH_COMMON_V6,
+        "uniswap-common-eip712.json" => INCLUDE_UNISWAP_COMMON_EIP712,
+        _ => return None,
+    };
+    Some(Cow::Borrowed(content))
 }
 
 fn descriptor_address_book(descriptor: &Descriptor) -> HashMap<String, String> {
@@ -547,6 +693,12 @@ fn merge_address_book_entries(
 
 fn merge_registry_entries(map: &mut HashMap<String, String>, chain_id: u64) {
     let key = format!("eip155:{chain_id}").to_ascii_lowercase();
+    // Runtime registry labels take precedence over the bundled ones
+    if let Some(entries) = registry::address_book(&key) {
+        for (address, label) in entries {
+            map.entry(address).or_insert(label);
+        }
+    }
     if let Some(entries) = registry_address_book().get(&key) {
         for (address, label) in entries {
             map.entry(address.clone()).or_insert_with(|| label.clone());
@@ -584,9 +736,9 @@ fn load_address_book_registry() -> ChainAddressBook {
 
 fn build_typed_address_book(
     descriptor_json: &str,
-    includes: &[&str],
+    includes: &[Cow<'_, str>],
     chain_id: u64,
-    verifying_contract: &str,
+    verifying_contract: Option<&str>,
 ) -> Result<HashMap<String, String>, ResolverError> {
     let descriptor_value = merged_descriptor_value(descriptor_json, includes)?;
     let mut map = HashMap::new();
@@ -610,8 +762,10 @@ fn build_typed_address_book(
                 }
             }
 
-            map.entry(normalize_address(verifying_contract))
-                .or_insert_with(|| label.clone());
+            if let Some(verifying_contract) = verifying_contract {
+                map.entry(normalize_address(verifying_contract))
+                    .or_insert_with(|| label.clone());
+            }
         }
 
         merge_address_book_entries(&mut map, metadata.get("addressBook"));
@@ -624,7 +778,7 @@ fn build_typed_address_book(
 
 pub(crate) fn merged_descriptor_value(
     descriptor_json: &str,
-    includes: &[&str],
+    includes: &[Cow<'_, str>],
 ) -> Result<Value, ResolverError> {
     let mut descriptor_value: Value = serde_json::from_str(descriptor_json)
         .map_err(|err| ResolverError::DescriptorParse(err.to_string()))?;
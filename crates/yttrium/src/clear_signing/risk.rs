@@ -0,0 +1,390 @@
+//! Risk rules evaluated on top of decoded calldata arguments and typed data
+//! messages. Unlike `DisplayModel::warnings`, which report problems with the
+//! preview itself, risks flag intents the signer should double check.
+
+use {
+    super::descriptor::{ArgumentValue, DecodedArguments, FunctionDescriptor},
+    num_bigint::BigUint,
+    serde_json::Value,
+    std::time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Permits valid for longer than this are flagged as long-lived.
+pub const LONG_LIVED_PERMIT_SECONDS: u64 = 30 * 24 * 60 * 60;
+
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RiskSeverity {
+    Info,
+    Warning,
+    /// Likely loss of funds.
+    Critical,
+}
+
+/// Stable identifier of a risk rule, for UIs to map to their own copy.
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RiskCode {
+    /// Approval or permit for `type(uint256).max` (or Permit2's
+    /// `type(uint160).max`).
+    UnlimitedApproval,
+    /// `setApprovalForAll(operator, true)`.
+    ApprovalForAll,
+    /// Permit valid for longer than `LONG_LIVED_PERMIT_SECONDS`, or without
+    /// expiry.
+    LongLivedPermit,
+    /// Tokens transferred to the token contract itself.
+    TransferToTokenContract,
+    /// Swap output sent to an address other than the signer.
+    RecipientNotSender,
+    /// Control of an account handed to another key, e.g. a Solana token
+    /// account's owner authority.
+    AuthorityChange,
+    /// EIP-7702 delegation to a contract that isn't a known smart account
+    /// implementation.
+    UnknownDelegate,
+    /// EIP-7702 authorization for chain ID 0, valid on every chain.
+    CrossChainAuthorization,
+}
+
+impl RiskCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UnlimitedApproval => "unlimited_approval",
+            Self::ApprovalForAll => "approval_for_all",
+            Self::LongLivedPermit => "long_lived_permit",
+            Self::TransferToTokenContract => "transfer_to_token_contract",
+            Self::RecipientNotSender => "recipient_not_sender",
+            Self::AuthorityChange => "authority_change",
+            Self::UnknownDelegate => "unknown_delegate",
+            Self::CrossChainAuthorization => "cross_chain_authorization",
+        }
+    }
+
+    pub fn severity(&self) -> RiskSeverity {
+        match self {
+            Self::TransferToTokenContract | Self::UnknownDelegate => {
+                RiskSeverity::Critical
+            }
+            Self::UnlimitedApproval
+            | Self::ApprovalForAll
+            | Self::LongLivedPermit
+            | Self::RecipientNotSender
+            | Self::AuthorityChange
+            | Self::CrossChainAuthorization => RiskSeverity::Warning,
+        }
+    }
+}
+
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RiskWarning {
+    pub code: RiskCode,
+    pub severity: RiskSeverity,
+    pub message: String,
+}
+
+impl RiskWarning {
+    pub(crate) fn new(code: RiskCode, message: String) -> Self {
+        Self { code, severity: code.severity(), message }
+    }
+}
+
+/// Evaluates the calldata rules for a decoded call to `to`. `sender` is the
+/// account the call is made from, when known.
+pub(crate) fn calldata_risks(
+    function: &FunctionDescriptor,
+    decoded: &DecodedArguments,
+    intent: &str,
+    to: &str,
+    sender: Option<&str>,
+) -> Vec<RiskWarning> {
+    let name = function
+        .typed_signature
+        .split_once('(')
+        .map_or(function.typed_signature.as_str(), |(name, _)| name);
+    let arguments = decoded
+        .ordered()
+        .iter()
+        .filter(|argument| argument.name.as_deref() != Some("@value"))
+        .collect::<Vec<_>>();
+    let address_at = |index: usize| {
+        arguments.get(index).and_then(|argument| argument.value.as_address())
+    };
+    let mut risks = Vec::new();
+
+    match name {
+        "approve" | "increaseAllowance"
+            if arguments.iter().any(|argument| {
+                argument.value.as_uint().is_some_and(is_unlimited)
+            }) =>
+        {
+            risks.push(RiskWarning::new(
+                RiskCode::UnlimitedApproval,
+                "Approves an unlimited amount".to_string(),
+            ));
+        }
+        "setApprovalForAll"
+            if arguments
+                .get(1)
+                .is_some_and(|approved| is_nonzero(&approved.value)) =>
+        {
+            risks.push(RiskWarning::new(
+                RiskCode::ApprovalForAll,
+                "Approves the operator for all tokens of the collection"
+                    .to_string(),
+            ));
+        }
+        "permit" => {
+            let deadline = arguments.iter().find_map(|argument| {
+                argument
+                    .name
+                    .as_deref()
+                    .filter(|name| is_deadline_name(name))
+                    .and(argument.value.as_uint())
+            });
+            if let Some(message) = deadline.and_then(long_lived_message) {
+                risks
+                    .push(RiskWarning::new(RiskCode::LongLivedPermit, message));
+            }
+        }
+        _ => {}
+    }
+
+    // transfer(to, amount), transferFrom(from, to, amount) and ERC-721/1155
+    // safeTransferFrom(from, to, id, ...)
+    let recipient = match name {
+        "transfer" => address_at(0),
+        "transferFrom" | "safeTransferFrom" => address_at(1),
+        _ => None,
+    };
+    if recipient.is_some_and(|recipient| same_address(recipient, to)) {
+        risks.push(RiskWarning::new(
+            RiskCode::TransferToTokenContract,
+            "Sends tokens to the token contract, where they are lost"
+                .to_string(),
+        ));
+    }
+
+    if let Some(sender) = sender
+        && intent.to_ascii_lowercase().contains("swap")
+    {
+        let recipient = arguments.iter().find_map(|argument| {
+            let name = argument.name.as_deref()?;
+            let field = name.rsplit('.').next().unwrap_or(name);
+            is_recipient_name(field).then_some(argument.value.as_address())?
+        });
+        if let Some(recipient) = recipient
+            && !is_placeholder_address(recipient)
+            && !same_address(recipient, sender)
+            && !same_address(recipient, to)
+        {
+            risks.push(RiskWarning::new(
+                RiskCode::RecipientNotSender,
+                format!(
+                    "Swap output goes to 0x{}, not the sender",
+                    hex::encode(recipient)
+                ),
+            ));
+        }
+    }
+
+    risks
+}
+
+/// Evaluates the typed data rules for an EIP-712 message.
+pub(crate) fn typed_data_risks(
+    primary_type: &str,
+    message: &Value,
+) -> Vec<RiskWarning> {
+    let mut risks = Vec::new();
+
+    if primary_type.contains("Permit") {
+        let mut amounts = Vec::new();
+        let mut deadlines = Vec::new();
+        collect_permit_values(message, &mut amounts, &mut deadlines);
+
+        // DAI-style permits approve everything with `allowed: true`
+        let allowed = message.get("allowed").and_then(Value::as_bool);
+        if allowed == Some(true) || amounts.iter().any(is_unlimited) {
+            risks.push(RiskWarning::new(
+                RiskCode::UnlimitedApproval,
+                "Permits spending an unlimited amount".to_string(),
+            ));
+        }
+        // A DAI `expiry` of 0 never expires
+        let never_expires = allowed.is_some()
+            && message
+                .get("expiry")
+                .and_then(value_as_biguint)
+                .is_some_and(|expiry| expiry == zero());
+        let message = if never_expires {
+            Some("Permit never expires".to_string())
+        } else {
+            deadlines.iter().find_map(long_lived_message)
+        };
+        if let Some(message) = message {
+            risks.push(RiskWarning::new(RiskCode::LongLivedPermit, message));
+        }
+    }
+
+    // Orders naming both the signer and the receiver (e.g. 1inch `maker`)
+    let signer = ["maker", "offerer", "owner"]
+        .iter()
+        .find_map(|key| message.get(*key).and_then(value_as_address));
+    let receiver = message.get("receiver").and_then(value_as_address);
+    if let (Some(signer), Some(receiver)) = (signer, receiver)
+        && !is_placeholder_address(&receiver)
+        && receiver != signer
+    {
+        risks.push(RiskWarning::new(
+            RiskCode::RecipientNotSender,
+            format!(
+                "Order output goes to 0x{}, not the signer",
+                hex::encode(receiver)
+            ),
+        ));
+    }
+
+    risks
+}
+
+// Allowance amounts and deadlines of ERC-2612 and Permit2 messages, including
+// the `details` of Permit2 batches
+fn collect_permit_values(
+    value: &Value,
+    amounts: &mut Vec<BigUint>,
+    deadlines: &mut Vec<BigUint>,
+) {
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                if key == "value" || key == "amount" {
+                    amounts.extend(value_as_biguint(field));
+                } else if is_deadline_name(key) {
+                    deadlines.extend(value_as_biguint(field));
+                } else {
+                    collect_permit_values(field, amounts, deadlines);
+                }
+            }
+        }
+        Value::Array(elements) => {
+            for element in elements {
+                collect_permit_values(element, amounts, deadlines);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn long_lived_message(deadline: &BigUint) -> Option<String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let limit = BigUint::from(now + LONG_LIVED_PERMIT_SECONDS);
+    (deadline > &limit).then(|| {
+        let days = (deadline - BigUint::from(now)) / BigUint::from(86_400u32);
+        format!("Permit stays valid for {days} days")
+    })
+}
+
+pub(crate) fn is_unlimited(amount: &BigUint) -> bool {
+    let max_uint160 = (BigUint::from(1u32) << 160) - 1u32;
+    let max_uint256 = (BigUint::from(1u32) << 256) - 1u32;
+    *amount == max_uint160 || *amount == max_uint256
+}
+
+fn is_deadline_name(name: &str) -> bool {
+    matches!(
+        name.rsplit('.').next().unwrap_or(name),
+        "deadline" | "sigDeadline" | "expiration" | "expiry"
+    )
+}
+
+fn is_recipient_name(name: &str) -> bool {
+    matches!(
+        name,
+        "recipient" | "receiver" | "dstReceiver" | "to" | "beneficiary"
+    )
+}
+
+// address(0) and the router `msg.sender`/`address(this)` sentinels
+// (0x…01, 0x…02)
+fn is_placeholder_address(address: &[u8; 20]) -> bool {
+    address[..19].iter().all(|byte| *byte == 0) && address[19] <= 2
+}
+
+fn same_address(address: &[u8; 20], other: &str) -> bool {
+    hex::decode(other.trim().trim_start_matches("0x"))
+        .is_ok_and(|bytes| bytes.as_slice() == address.as_slice())
+}
+
+fn value_as_biguint(value: &Value) -> Option<BigUint> {
+    match value {
+        Value::Number(number) => number.as_u64().map(BigUint::from),
+        Value::String(text) => match text.strip_prefix("0x") {
+            Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+            None => BigUint::parse_bytes(text.as_bytes(), 10),
+        },
+        _ => None,
+    }
+}
+
+fn value_as_address(value: &Value) -> Option<[u8; 20]> {
+    let text = value.as_str()?;
+    hex::decode(text.trim().trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| <[u8; 20]>::try_from(bytes).ok())
+}
+
+// `bool` arguments decode as raw words
+fn is_nonzero(value: &ArgumentValue) -> bool {
+    match value {
+        ArgumentValue::Address(bytes) => bytes.iter().any(|byte| *byte != 0),
+        ArgumentValue::Uint(number) => *number != zero(),
+        ArgumentValue::Raw(word) => word.iter().any(|byte| *byte != 0),
+    }
+}
+
+fn zero() -> BigUint {
+    BigUint::from(0u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_risk_codes_are_stable() {
+        assert_eq!(RiskCode::UnlimitedApproval.as_str(), "unlimited_approval");
+        assert_eq!(
+            RiskCode::TransferToTokenContract.severity(),
+            RiskSeverity::Critical
+        );
+        assert!(RiskSeverity::Critical > RiskSeverity::Warning);
+    }
+
+    #[test]
+    fn test_typed_data_risks() {
+        let message = serde_json::json!({
+            "details": [{ "amount": "1461501637330902918203684832716283019655932542975", "expiration": "0" }],
+            "spender": "0x851116d9223fabed8e56c0e6b8ad0c31d98b3507",
+            "sigDeadline": "99999999999"
+        });
+        let codes = typed_data_risks("PermitBatch", &message)
+            .into_iter()
+            .map(|risk| risk.code)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            codes,
+            vec![RiskCode::UnlimitedApproval, RiskCode::LongLivedPermit]
+        );
+
+        let order = serde_json::json!({
+            "maker": "0xabc0000000000000000000000000000000000001",
+            "receiver": "0x0000000000000000000000000000000000000000"
+        });
+        assert!(typed_data_risks("OrderStructure", &order).is_empty());
+    }
+}
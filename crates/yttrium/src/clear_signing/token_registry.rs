@@ -1,12 +1,14 @@
 //! Token registry helpers for the clear signing engine.
 
 use {
+    super::{providers, registry},
     serde::Deserialize,
     std::{collections::HashMap, sync::OnceLock},
 };
 
 const TOKEN_REGISTRY_JSON: &str = include_str!("assets/tokens-min.json");
 
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TokenMeta {
     pub symbol: String,
@@ -24,9 +26,15 @@ struct TokenRegistryEntry {
 static TOKEN_REGISTRY: OnceLock<HashMap<String, TokenMeta>> = OnceLock::new();
 
 /// Returns token metadata associated with a CAIP-19 identifier, if present.
+/// The host's `TokenMetadataProvider` is asked first, then a loaded runtime
+/// registry bundle, then the bundled tokens.
 pub fn lookup_token_by_caip19(caip19: &str) -> Option<TokenMeta> {
     let key = normalize(caip19);
-    TOKEN_REGISTRY.get_or_init(load_registry).get(&key).cloned()
+    providers::provided_token_metadata(&key)
+        .or_else(|| registry::token(&key))
+        .or_else(|| {
+            TOKEN_REGISTRY.get_or_init(load_registry).get(&key).cloned()
+        })
 }
 
 fn load_registry() -> HashMap<String, TokenMeta> {
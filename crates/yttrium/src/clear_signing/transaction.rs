@@ -0,0 +1,268 @@
+//! Clear signing for non-EVM transactions. Each namespace decodes its own
+//! wire format into the same `DisplayModel` as EVM calls: one model per
+//! instruction, contract or command, with the usual warnings and risks.
+
+mod solana;
+mod sui;
+mod tron;
+
+use {
+    super::{
+        engine::{DisplayItem, DisplayModel, format_amount_with_decimals},
+        locale::Locale,
+        token_registry::lookup_token_by_caip19,
+    },
+    num_bigint::BigUint,
+    thiserror::Error,
+};
+
+pub use {solana::SOLANA_MAINNET, sui::SUI_MAINNET, tron::TRON_MAINNET};
+
+/// Errors returned when formatting non-EVM transactions.
+#[derive(Debug, Error)]
+pub enum TransactionError {
+    #[error("unsupported namespace: {0}")]
+    UnsupportedNamespace(String),
+    #[error("transaction decode error: {0}")]
+    Decode(String),
+}
+
+/// Formats a clear signing preview of a Solana, Sui or TRON transaction.
+/// `chain_id` is a CAIP-2 chain such as `SOLANA_MAINNET`, `SUI_MAINNET` or
+/// `TRON_MAINNET`, and `transaction` is:
+/// - Solana: a wire transaction (signatures and message) or a bare message
+/// - Sui: BCS `TransactionData`
+/// - TRON: the protobuf `raw_data` passed to `tron_signTransaction`
+pub fn format_transaction(
+    chain_id: &str,
+    transaction: &[u8],
+    locale: &Locale,
+) -> Result<DisplayModel, TransactionError> {
+    let namespace =
+        chain_id.split_once(':').map_or(chain_id, |(namespace, _)| namespace);
+    match namespace {
+        "solana" => solana::format(chain_id, transaction, locale),
+        "sui" => sui::format(chain_id, transaction, locale),
+        "tron" => tron::format(chain_id, transaction, locale),
+        _ => Err(TransactionError::UnsupportedNamespace(namespace.to_string())),
+    }
+}
+
+fn decode_error(message: impl Into<String>) -> TransactionError {
+    TransactionError::Decode(message.into())
+}
+
+/// Cursor over serialized transaction bytes.
+#[derive(Debug, Clone)]
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8], TransactionError> {
+        let end = self.position.checked_add(length).ok_or_else(|| {
+            decode_error(format!("length {length} overflows"))
+        })?;
+        let bytes = self.bytes.get(self.position..end).ok_or_else(|| {
+            decode_error(format!(
+                "unexpected end of data at byte {} (wanted {length} more)",
+                self.position
+            ))
+        })?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], TransactionError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8, TransactionError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32_le(&mut self) -> Result<u32, TransactionError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64_le(&mut self) -> Result<u64, TransactionError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    /// Little-endian base-128 integer of at most `max_bytes` bytes: Solana
+    /// `compact-u16` (3) and protobuf varints (10).
+    fn varint(&mut self, max_bytes: usize) -> Result<u64, TransactionError> {
+        let mut value = 0u64;
+        for index in 0..max_bytes {
+            let byte = self.u8()?;
+            value |= u64::from(byte & 0x7f) << (7 * index);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(decode_error(format!(
+            "integer at byte {} is longer than {max_bytes} bytes",
+            self.position
+        )))
+    }
+
+    fn length(&mut self, max_bytes: usize) -> Result<usize, TransactionError> {
+        usize::try_from(self.varint(max_bytes)?)
+            .map_err(|_| decode_error("length does not fit in memory"))
+    }
+
+    fn finish(&self) -> Result<(), TransactionError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(decode_error(format!(
+                "{} trailing bytes",
+                self.bytes.len() - self.position
+            )))
+        }
+    }
+}
+
+fn item(label: &str, value: impl Into<String>) -> DisplayItem {
+    DisplayItem { label: label.to_string(), value: value.into() }
+}
+
+fn model(intent: &str, items: Vec<DisplayItem>) -> DisplayModel {
+    DisplayModel {
+        intent: intent.to_string(),
+        interpolated_intent: None,
+        items,
+        warnings: Vec::new(),
+        risks: Vec::new(),
+        raw: None,
+        calls: Vec::new(),
+    }
+}
+
+/// A transaction with a single call is previewed as that call; otherwise
+/// the calls are nested under a summary. `items` and `warnings` describe
+/// the transaction as a whole.
+fn combine(
+    intent: &str,
+    mut calls: Vec<DisplayModel>,
+    items: Vec<DisplayItem>,
+    warnings: Vec<String>,
+) -> DisplayModel {
+    if calls.len() == 1 {
+        let mut call = calls.remove(0);
+        let mut all_warnings = warnings;
+        all_warnings.append(&mut call.warnings);
+        call.items.extend(items);
+        call.warnings = all_warnings;
+        return call;
+    }
+
+    let mut summary = model(
+        intent,
+        [item("Calls", calls.len().to_string())]
+            .into_iter()
+            .chain(items)
+            .collect(),
+    );
+    summary.interpolated_intent =
+        Some(format!("Execute {} calls", calls.len()));
+    summary.warnings = warnings;
+    summary.calls = calls;
+    summary
+}
+
+/// Native currency amount, using the token registry entry for
+/// `{chain_id}/slip44:{coin_type}` when there is one.
+fn native_amount(
+    chain_id: &str,
+    coin_type: u32,
+    (symbol, decimals): (&str, u8),
+    amount: &BigUint,
+    locale: &Locale,
+) -> String {
+    let (symbol, decimals) =
+        lookup_token_by_caip19(&format!("{chain_id}/slip44:{coin_type}"))
+            .map_or((symbol.to_string(), decimals), |meta| {
+                (meta.symbol, meta.decimals)
+            });
+    format!(
+        "{} {symbol}",
+        format_amount_with_decimals(amount, decimals, locale)
+    )
+}
+
+/// Token amount for the CAIP-19 asset `{chain_id}/{asset}`. Unknown tokens
+/// are shown in base units, or with `decimals` when the transaction carries
+/// them, plus a warning.
+fn token_amount(
+    chain_id: &str,
+    asset: &str,
+    decimals: Option<u8>,
+    amount: &BigUint,
+    warnings: &mut Vec<String>,
+    locale: &Locale,
+) -> String {
+    if let Some(meta) = lookup_token_by_caip19(&format!("{chain_id}/{asset}")) {
+        return format!(
+            "{} {}",
+            format_amount_with_decimals(amount, meta.decimals, locale),
+            meta.symbol
+        );
+    }
+    let token = asset.split_once(':').map_or(asset, |(_, token)| token);
+    match decimals {
+        Some(decimals) => {
+            warnings.push(format!("Unknown token {token}"));
+            format_amount_with_decimals(amount, decimals, locale)
+        }
+        None => {
+            warnings
+                .push(format!("Unknown token {token}; amount in base units"));
+            format_amount_with_decimals(amount, 0, locale)
+        }
+    }
+}
+
+/// Token symbol for the CAIP-19 asset `{chain_id}/{asset}`, or `address`.
+fn token_name(chain_id: &str, asset: &str, address: &str) -> String {
+    lookup_token_by_caip19(&format!("{chain_id}/{asset}"))
+        .map_or_else(|| address.to_string(), |meta| meta.symbol)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint() {
+        let mut reader = Reader::new(&[0xac, 0x02, 0x7f]);
+        assert_eq!(reader.varint(3).unwrap(), 300);
+        assert_eq!(reader.varint(3).unwrap(), 127);
+        assert!(reader.finish().is_ok());
+        assert!(Reader::new(&[0xff, 0xff, 0xff, 0x01]).varint(3).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_namespace() {
+        let err = format_transaction(
+            "bip122:000000000019d6689c085ae1",
+            &[],
+            &Locale::default(),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, TransactionError::UnsupportedNamespace(ns) if ns == "bip122")
+        );
+    }
+}
@@ -0,0 +1,621 @@
+//! Solana legacy and v0 transactions: system transfers, SPL Token and
+//! Token-2022 instructions, associated token account creation and memos.
+
+use {
+    super::{
+        Reader, TransactionError, combine, decode_error,
+        format_amount_with_decimals, item, model, native_amount, token_amount,
+        token_name,
+    },
+    crate::clear_signing::{
+        engine::DisplayModel,
+        locale::Locale,
+        risk::{RiskCode, RiskWarning},
+    },
+    num_bigint::BigUint,
+};
+
+/// CAIP-2 chain of Solana mainnet.
+pub const SOLANA_MAINNET: &str = "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp";
+
+const SYSTEM_PROGRAM: &str = "11111111111111111111111111111111";
+const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+const TOKEN_2022_PROGRAM: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+const ASSOCIATED_TOKEN_PROGRAM: &str =
+    "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+const COMPUTE_BUDGET_PROGRAM: &str =
+    "ComputeBudget111111111111111111111111111111";
+const MEMO_PROGRAM: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+const MEMO_V1_PROGRAM: &str = "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo";
+
+const SOL: (&str, u8) = ("SOL", 9);
+const SOL_COIN_TYPE: u32 = 501;
+
+struct Message {
+    required_signatures: usize,
+    account_keys: Vec<String>,
+    instructions: Vec<Instruction>,
+    /// Accounts loaded from address lookup tables (v0 messages)
+    lookup_accounts: usize,
+}
+
+struct Instruction {
+    program_index: u8,
+    accounts: Vec<u8>,
+    data: Vec<u8>,
+}
+
+/// Accounts of one instruction, by position.
+struct Accounts<'a> {
+    message: &'a Message,
+    instruction: &'a Instruction,
+}
+
+impl Accounts<'_> {
+    fn get(&self, position: usize) -> String {
+        let Some(index) = self.instruction.accounts.get(position) else {
+            return "(missing account)".to_string();
+        };
+        self.message
+            .account_keys
+            .get(usize::from(*index))
+            .cloned()
+            // Indices past the static keys point into lookup tables, which
+            // can't be resolved offline
+            .unwrap_or_else(|| format!("Lookup table account #{index}"))
+    }
+}
+
+pub(super) fn format(
+    chain_id: &str,
+    transaction: &[u8],
+    locale: &Locale,
+) -> Result<DisplayModel, TransactionError> {
+    let message = parse(transaction)?;
+    let fee_payer = message
+        .account_keys
+        .first()
+        .cloned()
+        .ok_or_else(|| decode_error("message has no accounts"))?;
+
+    let mut warnings = Vec::new();
+    if message.lookup_accounts > 0 {
+        warnings.push(format!(
+            "{} accounts are loaded from address lookup tables and shown by \
+             index",
+            message.lookup_accounts
+        ));
+    }
+
+    let mut calls = Vec::new();
+    for instruction in &message.instructions {
+        let program = message
+            .account_keys
+            .get(usize::from(instruction.program_index))
+            .ok_or_else(|| {
+                decode_error(format!(
+                    "program index {} is out of range",
+                    instruction.program_index
+                ))
+            })?;
+        // Compute unit limits and prices only affect the fee
+        if program == COMPUTE_BUDGET_PROGRAM {
+            continue;
+        }
+        let accounts = Accounts { message: &message, instruction };
+        let data = instruction.data.as_slice();
+        let call = match program.as_str() {
+            SYSTEM_PROGRAM => format_system(chain_id, data, &accounts, locale),
+            TOKEN_PROGRAM | TOKEN_2022_PROGRAM => {
+                format_token(chain_id, data, &accounts, locale)
+            }
+            ASSOCIATED_TOKEN_PROGRAM => {
+                format_associated_token(chain_id, data, &accounts)
+            }
+            MEMO_PROGRAM | MEMO_V1_PROGRAM => Some(model(
+                "Add memo",
+                vec![item("Memo", String::from_utf8_lossy(data))],
+            )),
+            _ => None,
+        };
+        calls.push(call.unwrap_or_else(|| unknown_instruction(program, data)));
+    }
+
+    Ok(combine(
+        "Solana transaction",
+        calls,
+        vec![item("Fee payer", fee_payer)],
+        warnings,
+    ))
+}
+
+// A wire transaction starts with its signatures, a bare message with the
+// message header
+fn parse(transaction: &[u8]) -> Result<Message, TransactionError> {
+    parse_signed(transaction)
+        .or_else(|_| parse_message(&mut Reader::new(transaction)))
+}
+
+fn parse_signed(transaction: &[u8]) -> Result<Message, TransactionError> {
+    let mut reader = Reader::new(transaction);
+    let signatures = reader.length(3)?;
+    reader.take(signatures * 64)?;
+    let message = parse_message(&mut reader)?;
+    if message.required_signatures != signatures {
+        return Err(decode_error(format!(
+            "{signatures} signatures for a message requiring {}",
+            message.required_signatures
+        )));
+    }
+    Ok(message)
+}
+
+fn parse_message(reader: &mut Reader<'_>) -> Result<Message, TransactionError> {
+    let mut prefix = reader.u8()?;
+    let versioned = prefix & 0x80 != 0;
+    if versioned {
+        let version = prefix & 0x7f;
+        if version != 0 {
+            return Err(decode_error(format!(
+                "unsupported message version {version}"
+            )));
+        }
+        prefix = reader.u8()?;
+    }
+    let required_signatures = usize::from(prefix);
+    // Read-only signed and unsigned account counts
+    reader.take(2)?;
+
+    let key_count = reader.length(3)?;
+    let account_keys = (0..key_count)
+        .map(|_| reader.take(32).map(|key| bs58::encode(key).into_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    // Recent blockhash
+    reader.take(32)?;
+
+    let instruction_count = reader.length(3)?;
+    let mut instructions = Vec::new();
+    for _ in 0..instruction_count {
+        let program_index = reader.u8()?;
+        let account_count = reader.length(3)?;
+        let accounts = reader.take(account_count)?.to_vec();
+        let data_length = reader.length(3)?;
+        let data = reader.take(data_length)?.to_vec();
+        instructions.push(Instruction { program_index, accounts, data });
+    }
+
+    let mut lookup_accounts = 0;
+    if versioned {
+        let table_count = reader.length(3)?;
+        for _ in 0..table_count {
+            reader.take(32)?;
+            let writable = reader.length(3)?;
+            reader.take(writable)?;
+            let readonly = reader.length(3)?;
+            reader.take(readonly)?;
+            lookup_accounts += writable + readonly;
+        }
+    }
+    reader.finish()?;
+
+    Ok(Message {
+        required_signatures,
+        account_keys,
+        instructions,
+        lookup_accounts,
+    })
+}
+
+fn format_system(
+    chain_id: &str,
+    data: &[u8],
+    accounts: &Accounts<'_>,
+    locale: &Locale,
+) -> Option<DisplayModel> {
+    let mut reader = Reader::new(data);
+    match reader.u32_le().ok()? {
+        // CreateAccount { lamports, space, owner }
+        0 => {
+            let lamports = BigUint::from(reader.u64_le().ok()?);
+            let space = reader.u64_le().ok()?;
+            let owner = bs58::encode(reader.take(32).ok()?).into_string();
+            Some(model(
+                "Create account",
+                vec![
+                    item("Account", accounts.get(1)),
+                    item(
+                        "Deposit",
+                        native_amount(
+                            chain_id,
+                            SOL_COIN_TYPE,
+                            SOL,
+                            &lamports,
+                            locale,
+                        ),
+                    ),
+                    item("Space", format!("{space} bytes")),
+                    item("Owner program", owner),
+                ],
+            ))
+        }
+        // Transfer { lamports }
+        2 => {
+            let lamports = BigUint::from(reader.u64_le().ok()?);
+            let amount =
+                native_amount(chain_id, SOL_COIN_TYPE, SOL, &lamports, locale);
+            let to = accounts.get(1);
+            let mut call = model(
+                "Send SOL",
+                vec![
+                    item("From", accounts.get(0)),
+                    item("To", to.clone()),
+                    item("Amount", amount.clone()),
+                ],
+            );
+            call.interpolated_intent = Some(format!("Send {amount} to {to}"));
+            Some(call)
+        }
+        _ => None,
+    }
+}
+
+fn format_token(
+    chain_id: &str,
+    data: &[u8],
+    accounts: &Accounts<'_>,
+    locale: &Locale,
+) -> Option<DisplayModel> {
+    let mut reader = Reader::new(data);
+    let tag = reader.u8().ok()?;
+    let mut warnings = Vec::new();
+    let mut risks = Vec::new();
+
+    let mut call = match tag {
+        // Transfer { amount }: source, destination, authority
+        // TransferChecked { amount, decimals }: source, mint, destination,
+        // authority
+        3 | 12 => {
+            let amount = reader.u64_le().ok()?;
+            let (mint, destination) = if tag == 12 {
+                (Some(accounts.get(1)), accounts.get(2))
+            } else {
+                (None, accounts.get(1))
+            };
+            let decimals =
+                if tag == 12 { Some(reader.u8().ok()?) } else { None };
+            let amount = spl_amount(
+                chain_id,
+                mint.as_deref(),
+                decimals,
+                amount,
+                &mut warnings,
+                locale,
+            );
+            let mut call = model(
+                "Send tokens",
+                vec![
+                    item("From", accounts.get(0)),
+                    item("To", destination.clone()),
+                    item("Amount", amount.clone()),
+                ],
+            );
+            call.interpolated_intent =
+                Some(format!("Send {amount} to {destination}"));
+            call
+        }
+        // Approve { amount }: source, delegate, owner
+        // ApproveChecked { amount, decimals }: source, mint, delegate, owner
+        4 | 13 => {
+            let raw_amount = reader.u64_le().ok()?;
+            let (mint, delegate) = if tag == 13 {
+                (Some(accounts.get(1)), accounts.get(2))
+            } else {
+                (None, accounts.get(1))
+            };
+            let decimals =
+                if tag == 13 { Some(reader.u8().ok()?) } else { None };
+            let amount = spl_amount(
+                chain_id,
+                mint.as_deref(),
+                decimals,
+                raw_amount,
+                &mut warnings,
+                locale,
+            );
+            if raw_amount == u64::MAX {
+                risks.push(RiskWarning::new(
+                    RiskCode::UnlimitedApproval,
+                    "Approves an unlimited amount".to_string(),
+                ));
+            }
+            let mut call = model(
+                "Approve token delegate",
+                vec![
+                    item("Account", accounts.get(0)),
+                    item("Delegate", delegate.clone()),
+                    item("Amount", amount.clone()),
+                ],
+            );
+            call.interpolated_intent =
+                Some(format!("Approve {delegate} to spend {amount}"));
+            call
+        }
+        // Revoke: source, owner
+        5 => model(
+            "Revoke token delegate",
+            vec![item("Account", accounts.get(0))],
+        ),
+        // SetAuthority { authority_type, new_authority: COption<Pubkey> }
+        6 => {
+            let authority = match reader.u8().ok()? {
+                0 => "Mint tokens",
+                1 => "Freeze account",
+                2 => "Account owner",
+                3 => "Close account",
+                _ => "Other",
+            };
+            let new_authority = match reader.u8().ok()? {
+                0 => "None".to_string(),
+                _ => bs58::encode(reader.take(32).ok()?).into_string(),
+            };
+            risks.push(RiskWarning::new(
+                RiskCode::AuthorityChange,
+                format!(
+                    "Hands the '{authority}' authority of {} to {new_authority}",
+                    accounts.get(0)
+                ),
+            ));
+            model(
+                "Change token authority",
+                vec![
+                    item("Account", accounts.get(0)),
+                    item("Authority", authority),
+                    item("New authority", new_authority),
+                ],
+            )
+        }
+        // Burn { amount }, BurnChecked { amount, decimals }: account, mint,
+        // owner
+        8 | 15 => {
+            let amount = reader.u64_le().ok()?;
+            let decimals =
+                if tag == 15 { Some(reader.u8().ok()?) } else { None };
+            let mint = accounts.get(1);
+            model(
+                "Burn tokens",
+                vec![
+                    item("Account", accounts.get(0)),
+                    item(
+                        "Amount",
+                        spl_amount(
+                            chain_id,
+                            Some(mint.as_str()),
+                            decimals,
+                            amount,
+                            &mut warnings,
+                            locale,
+                        ),
+                    ),
+                ],
+            )
+        }
+        // CloseAccount: account, destination, owner
+        9 => model(
+            "Close token account",
+            vec![
+                item("Account", accounts.get(0)),
+                item("Rent to", accounts.get(1)),
+            ],
+        ),
+        _ => return None,
+    };
+    call.warnings = warnings;
+    call.risks = risks;
+    Some(call)
+}
+
+// Create and CreateIdempotent: funder, associated account, wallet, mint
+fn format_associated_token(
+    chain_id: &str,
+    data: &[u8],
+    accounts: &Accounts<'_>,
+) -> Option<DisplayModel> {
+    if !matches!(data, [] | [0] | [1]) {
+        return None;
+    }
+    let mint = accounts.get(3);
+    Some(model(
+        "Create token account",
+        vec![
+            item("Owner", accounts.get(2)),
+            item(
+                "Token",
+                token_name(chain_id, &format!("token:{mint}"), &mint),
+            ),
+            item("Account", accounts.get(1)),
+            item("Paid by", accounts.get(0)),
+        ],
+    ))
+}
+
+// Plain `Transfer`/`Approve` don't name the mint, so the amount stays in
+// base units unless the transaction is `*Checked`
+fn spl_amount(
+    chain_id: &str,
+    mint: Option<&str>,
+    decimals: Option<u8>,
+    amount: u64,
+    warnings: &mut Vec<String>,
+    locale: &Locale,
+) -> String {
+    let amount = BigUint::from(amount);
+    match mint {
+        Some(mint) => token_amount(
+            chain_id,
+            &format!("token:{mint}"),
+            decimals,
+            &amount,
+            warnings,
+            locale,
+        ),
+        None => {
+            warnings.push(
+                "Instruction doesn't name the token mint; amount in base units"
+                    .to_string(),
+            );
+            format_amount_with_decimals(&amount, 0, locale)
+        }
+    }
+}
+
+fn unknown_instruction(program: &str, data: &[u8]) -> DisplayModel {
+    let mut call = model(
+        "Call program",
+        vec![
+            item("Program", program),
+            item("Data", format!("0x{}", hex::encode(data))),
+        ],
+    );
+    call.warnings
+        .push(format!("Unknown program {program}; instruction shown raw"));
+    call
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const OWNER: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    const RECIPIENT: &str = "HN7cABqLq46Es1jh92dQQisAq662SmxELLLsHHe4YWrH";
+
+    fn key(key: &str) -> Vec<u8> {
+        bs58::decode(key).into_vec().expect("base58 key")
+    }
+
+    // Legacy wire transaction with one signature and the given account keys
+    fn transaction(
+        keys: &[&str],
+        instructions: &[(u8, &[u8], Vec<u8>)],
+    ) -> Vec<u8> {
+        let mut bytes = vec![1];
+        bytes.extend([0u8; 64]);
+        bytes.extend([1, 0, 0]);
+        bytes.push(keys.len() as u8);
+        for account in keys {
+            bytes.extend(key(account));
+        }
+        bytes.extend([7u8; 32]);
+        bytes.push(instructions.len() as u8);
+        for (program, accounts, data) in instructions {
+            bytes.push(*program);
+            bytes.push(accounts.len() as u8);
+            bytes.extend(*accounts);
+            bytes.push(data.len() as u8);
+            bytes.extend(data);
+        }
+        bytes
+    }
+
+    fn format_mainnet(bytes: &[u8]) -> DisplayModel {
+        super::super::format_transaction(
+            SOLANA_MAINNET,
+            bytes,
+            &Locale::default(),
+        )
+        .expect("format succeeds")
+    }
+
+    #[test]
+    fn test_sol_transfer() {
+        let mut data = 2u32.to_le_bytes().to_vec();
+        data.extend(1_500_000_000u64.to_le_bytes());
+        let bytes = transaction(
+            &[OWNER, RECIPIENT, SYSTEM_PROGRAM],
+            &[(2, &[0, 1], data)],
+        );
+
+        let model = format_mainnet(&bytes);
+        assert_eq!(model.intent, "Send SOL");
+        assert_eq!(
+            model.interpolated_intent.as_deref(),
+            Some(format!("Send 1.5 SOL to {RECIPIENT}").as_str())
+        );
+        assert_eq!(model.items[2].value, "1.5 SOL");
+        assert_eq!(model.items[3].label, "Fee payer");
+        assert!(model.warnings.is_empty());
+
+        // The bare message formats the same
+        assert_eq!(format_mainnet(&bytes[65..]), model);
+    }
+
+    #[test]
+    fn test_create_ata_and_transfer_checked() {
+        let ata = "3wyAj7Rt1TWVPZVteFJPLa26JmLvdb1CAKEFZm3NY75E";
+        let destination = "7UX2i7SucgLMQcfZ75s3VXmZZY4YRUyJN9X1RgfMoDUi";
+        let mut transfer = vec![12];
+        transfer.extend(2_500_000u64.to_le_bytes());
+        transfer.push(6);
+        let bytes = transaction(
+            &[
+                OWNER,
+                destination,
+                ata,
+                RECIPIENT,
+                USDC_MINT,
+                SYSTEM_PROGRAM,
+                TOKEN_PROGRAM,
+                ASSOCIATED_TOKEN_PROGRAM,
+                COMPUTE_BUDGET_PROGRAM,
+            ],
+            &[
+                (8, &[], vec![2, 0x40, 0x0d, 0x03, 0x00]),
+                (7, &[0, 1, 3, 4, 5, 6], vec![1]),
+                (6, &[2, 4, 1, 0], transfer),
+            ],
+        );
+
+        let model = format_mainnet(&bytes);
+        assert_eq!(model.intent, "Solana transaction");
+        assert_eq!(model.items[0].value, "2");
+        assert_eq!(model.calls.len(), 2);
+        assert_eq!(model.calls[0].intent, "Create token account");
+        assert_eq!(model.calls[0].items[0].value, RECIPIENT);
+        assert_eq!(model.calls[0].items[1].value, "USDC");
+        assert_eq!(model.calls[1].intent, "Send tokens");
+        assert_eq!(model.calls[1].items[2].value, "2.5 USDC");
+        assert!(model.calls[1].warnings.is_empty());
+    }
+
+    #[test]
+    fn test_unlimited_delegate_and_unknown_program() {
+        let mut approve = vec![4];
+        approve.extend(u64::MAX.to_le_bytes());
+        let bytes = transaction(
+            &[OWNER, RECIPIENT, USDC_MINT, TOKEN_2022_PROGRAM],
+            &[(3, &[1, 2, 0], approve), (2, &[0], vec![0xde, 0xad])],
+        );
+
+        let model = format_mainnet(&bytes);
+        let approval = &model.calls[0];
+        assert_eq!(approval.intent, "Approve token delegate");
+        assert_eq!(approval.risks[0].code, RiskCode::UnlimitedApproval);
+        assert_eq!(approval.warnings.len(), 1);
+        let unknown = &model.calls[1];
+        assert_eq!(unknown.intent, "Call program");
+        assert_eq!(unknown.items[1].value, "0xdead");
+    }
+
+    #[test]
+    fn test_truncated_transaction() {
+        let bytes = transaction(&[OWNER, SYSTEM_PROGRAM], &[]);
+        assert!(
+            super::super::format_transaction(
+                SOLANA_MAINNET,
+                &bytes[..bytes.len() - 4],
+                &Locale::default()
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,682 @@
+//! Sui programmable transactions (BCS `TransactionData::V1`): coin
+//! transfers built from `SplitCoins` + `TransferObjects`, Move calls and
+//! package publishes and upgrades.
+
+use {
+    super::{
+        TransactionError, combine, decode_error, format_amount_with_decimals,
+        item, model, native_amount,
+    },
+    crate::clear_signing::{engine::DisplayModel, locale::Locale},
+    num_bigint::BigUint,
+    serde::Deserialize,
+};
+
+/// CAIP-2 chain of Sui mainnet.
+pub const SUI_MAINNET: &str = "sui:mainnet";
+
+const SUI: (&str, u8) = ("SUI", 9);
+const SUI_COIN_TYPE: u32 = 784;
+/// Nesting limit for type tags such as `vector<vector<Coin<T>>>`
+const MAX_TYPE_DEPTH: usize = 8;
+
+enum Input {
+    Pure(Vec<u8>),
+    Object(String),
+}
+
+#[derive(Clone, Copy, Deserialize)]
+enum Argument {
+    GasCoin,
+    Input(u16),
+    Result(u16),
+    NestedResult(u16, u16),
+}
+
+enum Command {
+    MoveCall {
+        package: String,
+        module: String,
+        function: String,
+        type_arguments: Vec<String>,
+        arguments: Vec<Argument>,
+    },
+    TransferObjects {
+        objects: Vec<Argument>,
+        recipient: Argument,
+    },
+    SplitCoins {
+        coin: Argument,
+        amounts: Vec<Argument>,
+    },
+    Publish {
+        modules: usize,
+    },
+    Upgrade {
+        package: String,
+    },
+    // `MergeCoins` and `MakeMoveVec` only prepare arguments for other
+    // commands
+    Plumbing,
+}
+
+struct Transaction {
+    inputs: Vec<Input>,
+    commands: Vec<Command>,
+    sender: String,
+    gas_budget: u64,
+}
+
+pub(super) fn format(
+    chain_id: &str,
+    transaction: &[u8],
+    locale: &Locale,
+) -> Result<DisplayModel, TransactionError> {
+    let transaction = parse(transaction)?;
+
+    let calls = transaction
+        .commands
+        .iter()
+        .filter_map(|command| {
+            format_command(chain_id, &transaction, command, locale)
+        })
+        .collect();
+
+    Ok(combine(
+        "Sui transaction",
+        calls,
+        vec![
+            item("Sender", transaction.sender.clone()),
+            item(
+                "Gas budget",
+                native_amount(
+                    chain_id,
+                    SUI_COIN_TYPE,
+                    SUI,
+                    &BigUint::from(transaction.gas_budget),
+                    locale,
+                ),
+            ),
+        ],
+        Vec::new(),
+    ))
+}
+
+fn format_command(
+    chain_id: &str,
+    transaction: &Transaction,
+    command: &Command,
+    locale: &Locale,
+) -> Option<DisplayModel> {
+    match command {
+        Command::TransferObjects { objects, recipient } => Some(
+            format_transfer(chain_id, transaction, objects, *recipient, locale),
+        ),
+        Command::MoveCall {
+            package,
+            module,
+            function,
+            type_arguments,
+            arguments,
+        } => {
+            let mut items = vec![
+                item("Package", package.clone()),
+                item("Function", format!("{module}::{function}")),
+            ];
+            if !type_arguments.is_empty() {
+                items.push(item("Type arguments", type_arguments.join(", ")));
+            }
+            items.extend(arguments.iter().enumerate().map(
+                |(index, argument)| {
+                    item(
+                        &format!("Argument {}", index + 1),
+                        describe_argument(transaction, *argument, locale),
+                    )
+                },
+            ));
+            let mut call = model(&format!("Call {module}::{function}"), items);
+            call.warnings.push(format!(
+                "No descriptor for {package}::{module}::{function}; arguments \
+                 shown raw"
+            ));
+            Some(call)
+        }
+        Command::Publish { modules } => {
+            let mut call = model(
+                "Publish Move package",
+                vec![item("Modules", modules.to_string())],
+            );
+            call.warnings.push("Publishes new on-chain code".to_string());
+            Some(call)
+        }
+        Command::Upgrade { package } => {
+            let mut call = model(
+                "Upgrade Move package",
+                vec![item("Package", package.clone())],
+            );
+            call.warnings
+                .push(format!("Replaces the code of package {package}"));
+            Some(call)
+        }
+        Command::SplitCoins { .. } | Command::Plumbing => None,
+    }
+}
+
+fn format_transfer(
+    chain_id: &str,
+    transaction: &Transaction,
+    objects: &[Argument],
+    recipient: Argument,
+    locale: &Locale,
+) -> DisplayModel {
+    let recipient = match input(transaction, recipient) {
+        Some(Input::Pure(bytes)) if bytes.len() == 32 => address(bytes),
+        _ => describe_argument(transaction, recipient, locale),
+    };
+
+    let mut sui = BigUint::from(0u32);
+    let mut whole_gas_coin = false;
+    let mut others = Vec::new();
+    for object in objects {
+        match (object, split_amount(transaction, *object)) {
+            (Argument::GasCoin, _) => whole_gas_coin = true,
+            (_, Some((Argument::GasCoin, amount))) => sui += amount,
+            (_, Some((coin, amount))) => others.push(format!(
+                "{amount} base units of {}",
+                describe_argument(transaction, coin, locale)
+            )),
+            (_, None) => {
+                others.push(describe_argument(transaction, *object, locale))
+            }
+        }
+    }
+
+    let mut items = vec![item("To", recipient.clone())];
+    let amount = if whole_gas_coin {
+        // The gas coin is sent after gas is paid from it
+        Some("Entire SUI balance".to_string())
+    } else if sui > BigUint::from(0u32) {
+        Some(native_amount(chain_id, SUI_COIN_TYPE, SUI, &sui, locale))
+    } else {
+        None
+    };
+    if let Some(amount) = &amount {
+        items.push(item("Amount", amount.clone()));
+    }
+    items.extend(others.iter().map(|object| item("Object", object.clone())));
+
+    match amount {
+        Some(amount) if others.is_empty() => {
+            let mut call = model("Send SUI", items);
+            call.interpolated_intent =
+                Some(format!("Send {amount} to {recipient}"));
+            call
+        }
+        _ => model("Transfer objects", items),
+    }
+}
+
+fn input(transaction: &Transaction, argument: Argument) -> Option<&Input> {
+    match argument {
+        Argument::Input(index) => transaction.inputs.get(usize::from(index)),
+        _ => None,
+    }
+}
+
+// Coin and amount of a `SplitCoins` result
+fn split_amount(
+    transaction: &Transaction,
+    argument: Argument,
+) -> Option<(Argument, BigUint)> {
+    let (command, index) = match argument {
+        Argument::Result(command) => (command, 0),
+        Argument::NestedResult(command, index) => (command, index),
+        _ => return None,
+    };
+    let Command::SplitCoins { coin, amounts } =
+        transaction.commands.get(usize::from(command))?
+    else {
+        return None;
+    };
+    if matches!(argument, Argument::Result(_)) && amounts.len() != 1 {
+        return None;
+    }
+    let amount = amounts.get(usize::from(index))?;
+    let Some(Input::Pure(bytes)) = input(transaction, *amount) else {
+        return None;
+    };
+    let amount = u64::from_le_bytes(bytes.as_slice().try_into().ok()?);
+    Some((*coin, BigUint::from(amount)))
+}
+
+fn describe_argument(
+    transaction: &Transaction,
+    argument: Argument,
+    locale: &Locale,
+) -> String {
+    match argument {
+        Argument::GasCoin => "Gas coin".to_string(),
+        Argument::Input(index) => match input(transaction, argument) {
+            Some(Input::Pure(bytes)) => format!("0x{}", hex::encode(bytes)),
+            Some(Input::Object(id)) => id.clone(),
+            None => format!("Missing input #{index}"),
+        },
+        Argument::Result(command) => {
+            match split_amount(transaction, argument) {
+                Some((Argument::GasCoin, amount)) => {
+                    format!(
+                        "{} SUI",
+                        format_amount_with_decimals(&amount, 9, locale)
+                    )
+                }
+                _ => format!("Result of command #{command}"),
+            }
+        }
+        Argument::NestedResult(command, index) => {
+            match split_amount(transaction, argument) {
+                Some((Argument::GasCoin, amount)) => {
+                    format!(
+                        "{} SUI",
+                        format_amount_with_decimals(&amount, 9, locale)
+                    )
+                }
+                _ => format!("Result {index} of command #{command}"),
+            }
+        }
+    }
+}
+
+fn address(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+// Framework addresses are written short, e.g. `0x2::coin::Coin`
+fn short_address(bytes: &[u8]) -> String {
+    let hex = hex::encode(bytes);
+    let trimmed = hex.trim_start_matches('0');
+    format!("0x{}", if trimmed.is_empty() { "0" } else { trimmed })
+}
+
+fn parse(transaction: &[u8]) -> Result<Transaction, TransactionError> {
+    let wire::TransactionData::V1(data) = bcs::from_bytes(transaction)
+        .map_err(|err| {
+            decode_error(format!("invalid TransactionData: {err}"))
+        })?;
+    let wire::TransactionKind::ProgrammableTransaction(programmable) =
+        data.kind;
+
+    let inputs = programmable
+        .inputs
+        .into_iter()
+        .map(|input| match input {
+            wire::CallArg::Pure(bytes) => Input::Pure(bytes),
+            wire::CallArg::Object(object) => {
+                Input::Object(address(object.id()))
+            }
+        })
+        .collect();
+    let commands = programmable
+        .commands
+        .into_iter()
+        .map(command)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Transaction {
+        inputs,
+        commands,
+        sender: address(&data.sender),
+        gas_budget: data.gas_data.budget,
+    })
+}
+
+fn command(command: wire::Command) -> Result<Command, TransactionError> {
+    Ok(match command {
+        wire::Command::MoveCall(call) => Command::MoveCall {
+            package: short_address(&call.package),
+            type_arguments: call
+                .type_arguments
+                .iter()
+                .map(|tag| type_name(tag, 0))
+                .collect::<Result<Vec<_>, _>>()?,
+            module: call.module,
+            function: call.function,
+            arguments: call.arguments,
+        },
+        wire::Command::TransferObjects(objects, recipient) => {
+            Command::TransferObjects { objects, recipient }
+        }
+        wire::Command::SplitCoins(coin, amounts) => {
+            Command::SplitCoins { coin, amounts }
+        }
+        wire::Command::Publish(modules, _) => {
+            Command::Publish { modules: modules.len() }
+        }
+        wire::Command::Upgrade(_, _, package, _) => {
+            Command::Upgrade { package: short_address(&package) }
+        }
+        wire::Command::MergeCoins(..) | wire::Command::MakeMoveVec(..) => {
+            Command::Plumbing
+        }
+    })
+}
+
+fn type_name(
+    tag: &wire::TypeTag,
+    depth: usize,
+) -> Result<String, TransactionError> {
+    if depth > MAX_TYPE_DEPTH {
+        return Err(decode_error("type argument is nested too deeply"));
+    }
+    Ok(match tag {
+        wire::TypeTag::Bool => "bool".to_string(),
+        wire::TypeTag::U8 => "u8".to_string(),
+        wire::TypeTag::U16 => "u16".to_string(),
+        wire::TypeTag::U32 => "u32".to_string(),
+        wire::TypeTag::U64 => "u64".to_string(),
+        wire::TypeTag::U128 => "u128".to_string(),
+        wire::TypeTag::U256 => "u256".to_string(),
+        wire::TypeTag::Address => "address".to_string(),
+        wire::TypeTag::Signer => "signer".to_string(),
+        wire::TypeTag::Vector(inner) => {
+            format!("vector<{}>", type_name(inner, depth + 1)?)
+        }
+        wire::TypeTag::Struct(tag) => {
+            let address = short_address(&tag.address);
+            let parameters = tag
+                .type_params
+                .iter()
+                .map(|parameter| type_name(parameter, depth + 1))
+                .collect::<Result<Vec<_>, _>>()?;
+            if parameters.is_empty() {
+                format!("{address}::{}::{}", tag.module, tag.name)
+            } else {
+                format!(
+                    "{address}::{}::{}<{}>",
+                    tag.module,
+                    tag.name,
+                    parameters.join(", ")
+                )
+            }
+        }
+    })
+}
+
+/// BCS layout of `TransactionData`. Variant order is part of the encoding;
+/// fields that previews don't show are still decoded so the whole
+/// transaction is checked.
+mod wire {
+    use {super::Argument, serde::Deserialize};
+
+    pub(super) type Address = [u8; 32];
+    // (id, version, digest)
+    pub(super) type ObjectRef = (Address, u64, Vec<u8>);
+
+    #[derive(Deserialize)]
+    pub(super) enum TransactionData {
+        V1(TransactionDataV1),
+    }
+
+    #[derive(Deserialize)]
+    pub(super) struct TransactionDataV1 {
+        pub kind: TransactionKind,
+        pub sender: Address,
+        pub gas_data: GasData,
+        _expiration: TransactionExpiration,
+    }
+
+    // System transactions come after and are rejected
+    #[derive(Deserialize)]
+    pub(super) enum TransactionKind {
+        ProgrammableTransaction(ProgrammableTransaction),
+    }
+
+    #[derive(Deserialize)]
+    pub(super) struct ProgrammableTransaction {
+        pub inputs: Vec<CallArg>,
+        pub commands: Vec<Command>,
+    }
+
+    #[derive(Deserialize)]
+    pub(super) enum CallArg {
+        Pure(Vec<u8>),
+        Object(ObjectArg),
+    }
+
+    #[derive(Deserialize)]
+    pub(super) enum ObjectArg {
+        ImmOrOwnedObject(ObjectRef),
+        SharedObject {
+            id: Address,
+            _initial_shared_version: u64,
+            _mutable: bool,
+        },
+        Receiving(ObjectRef),
+    }
+
+    impl ObjectArg {
+        pub(super) fn id(&self) -> &Address {
+            match self {
+                Self::ImmOrOwnedObject((id, _, _))
+                | Self::SharedObject { id, .. }
+                | Self::Receiving((id, _, _)) => id,
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub(super) enum Command {
+        MoveCall(Box<ProgrammableMoveCall>),
+        TransferObjects(Vec<Argument>, Argument),
+        SplitCoins(Argument, Vec<Argument>),
+        MergeCoins(
+            #[allow(dead_code)] Argument,
+            #[allow(dead_code)] Vec<Argument>,
+        ),
+        // Modules and dependencies
+        Publish(Vec<Vec<u8>>, #[allow(dead_code)] Vec<Address>),
+        MakeMoveVec(
+            #[allow(dead_code)] Option<TypeTag>,
+            #[allow(dead_code)] Vec<Argument>,
+        ),
+        // Modules, dependencies, package and upgrade ticket
+        Upgrade(
+            #[allow(dead_code)] Vec<Vec<u8>>,
+            #[allow(dead_code)] Vec<Address>,
+            Address,
+            #[allow(dead_code)] Argument,
+        ),
+    }
+
+    #[derive(Deserialize)]
+    pub(super) struct ProgrammableMoveCall {
+        pub package: Address,
+        pub module: String,
+        pub function: String,
+        pub type_arguments: Vec<TypeTag>,
+        pub arguments: Vec<Argument>,
+    }
+
+    #[derive(Deserialize)]
+    pub(super) enum TypeTag {
+        Bool,
+        U8,
+        U64,
+        U128,
+        Address,
+        Signer,
+        Vector(Box<TypeTag>),
+        Struct(Box<StructTag>),
+        U16,
+        U32,
+        U256,
+    }
+
+    #[derive(Deserialize)]
+    pub(super) struct StructTag {
+        pub address: Address,
+        pub module: String,
+        pub name: String,
+        pub type_params: Vec<TypeTag>,
+    }
+
+    #[derive(Deserialize)]
+    pub(super) struct GasData {
+        _payment: Vec<ObjectRef>,
+        _owner: Address,
+        _price: u64,
+        pub budget: u64,
+    }
+
+    #[derive(Deserialize)]
+    pub(super) enum TransactionExpiration {
+        None,
+        Epoch(#[allow(dead_code)] u64),
+        ValidDuring {
+            _min_epoch: Option<u64>,
+            _max_epoch: Option<u64>,
+            _min_timestamp_seconds: Option<u64>,
+            _max_timestamp_seconds: Option<u64>,
+            _chain: Vec<u8>,
+            _nonce: u32,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SENDER: [u8; 32] = [0x11; 32];
+    const RECIPIENT: [u8; 32] = [0x22; 32];
+
+    fn uleb(bytes: &mut Vec<u8>, value: usize) {
+        assert!(value < 0x80);
+        bytes.push(value as u8);
+    }
+
+    fn string(bytes: &mut Vec<u8>, value: &str) {
+        uleb(bytes, value.len());
+        bytes.extend(value.as_bytes());
+    }
+
+    fn transaction(inputs: &[Vec<u8>], commands: &[Vec<u8>]) -> Vec<u8> {
+        let mut bytes = vec![0, 0];
+        uleb(&mut bytes, inputs.len());
+        for input in inputs {
+            bytes.extend(input);
+        }
+        uleb(&mut bytes, commands.len());
+        for command in commands {
+            bytes.extend(command);
+        }
+        bytes.extend(SENDER);
+        // One gas payment object, owner, price and budget
+        bytes.push(1);
+        bytes.extend([0x33; 32]);
+        bytes.extend(7u64.to_le_bytes());
+        bytes.push(32);
+        bytes.extend([0x44; 32]);
+        bytes.extend(SENDER);
+        bytes.extend(1_000u64.to_le_bytes());
+        bytes.extend(5_000_000u64.to_le_bytes());
+        // No expiration
+        bytes.push(0);
+        bytes
+    }
+
+    fn pure(value: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0];
+        uleb(&mut bytes, value.len());
+        bytes.extend(value);
+        bytes
+    }
+
+    fn format_mainnet(bytes: &[u8]) -> DisplayModel {
+        super::super::format_transaction(SUI_MAINNET, bytes, &Locale::default())
+            .expect("format succeeds")
+    }
+
+    #[test]
+    fn test_split_and_transfer_sui() {
+        let inputs = [pure(&2_500_000_000u64.to_le_bytes()), pure(&RECIPIENT)];
+        // SplitCoins(GasCoin, [Input(0)]), TransferObjects([Result(0)], Input(1))
+        let commands = [vec![2, 0, 1, 1, 0, 0], vec![1, 1, 2, 0, 0, 1, 1, 0]];
+
+        let model = format_mainnet(&transaction(&inputs, &commands));
+        assert_eq!(model.intent, "Send SUI");
+        assert_eq!(model.items[0].value, address(&RECIPIENT));
+        assert_eq!(model.items[1].value, "2.5 SUI");
+        assert_eq!(model.items[2].value, address(&SENDER));
+        assert_eq!(model.items[3].value, "0.005 SUI");
+        assert_eq!(
+            model.interpolated_intent,
+            Some(format!("Send 2.5 SUI to {}", address(&RECIPIENT)))
+        );
+    }
+
+    #[test]
+    fn test_move_call() {
+        let mut pool = vec![1, 1];
+        pool.extend([0x55; 32]);
+        pool.extend(9u64.to_le_bytes());
+        pool.push(1);
+        let inputs = [pool, pure(&[1])];
+
+        let mut call = vec![0];
+        call.extend({
+            let mut package = [0u8; 32];
+            package[31] = 0xde;
+            package
+        });
+        string(&mut call, "pool");
+        string(&mut call, "swap");
+        // One type argument: 0x2::sui::SUI
+        call.extend([1, 7]);
+        call.extend({
+            let mut framework = [0u8; 32];
+            framework[31] = 2;
+            framework
+        });
+        string(&mut call, "sui");
+        string(&mut call, "SUI");
+        call.push(0);
+        // Arguments: Input(0), Input(1)
+        call.extend([2, 1, 0, 0, 1, 1, 0]);
+
+        let model = format_mainnet(&transaction(&inputs, &[call]));
+        assert_eq!(model.intent, "Call pool::swap");
+        assert_eq!(model.items[0].value, "0xde");
+        assert_eq!(model.items[2].value, "0x2::sui::SUI");
+        assert_eq!(model.items[3].value, address(&[0x55; 32]));
+        assert_eq!(model.items[4].value, "0x01");
+        assert_eq!(model.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_rejects_system_transactions() {
+        assert!(
+            super::super::format_transaction(
+                SUI_MAINNET,
+                &[0, 1, 0],
+                &Locale::default()
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_rejects_oversized_publish() {
+        // Publish with no modules and a dependency count far past the data
+        let command = vec![4, 0, 0xff, 0xff, 0xff, 0xff, 0x0f];
+        let mut bytes = vec![0, 0, 0, 1];
+        bytes.extend(command);
+        assert!(
+            super::super::format_transaction(
+                SUI_MAINNET,
+                &bytes,
+                &Locale::default()
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,523 @@
+//! TRON transactions (protobuf `Transaction.raw`): TRX and TRC-10
+//! transfers, TRC-20 `transfer`/`approve`, other contract calls and
+//! Stake 2.0 freezes.
+
+use {
+    super::{
+        Reader, TransactionError, combine, decode_error, item, model,
+        native_amount, token_amount, token_name,
+    },
+    crate::clear_signing::{
+        engine::DisplayModel,
+        locale::Locale,
+        risk::{RiskCode, RiskWarning, is_unlimited},
+    },
+    num_bigint::BigUint,
+};
+
+/// CAIP-2 chain of TRON mainnet.
+pub const TRON_MAINNET: &str = "tron:0x2b6653dc";
+
+const TRX: (&str, u8) = ("TRX", 6);
+const TRX_COIN_TYPE: u32 = 195;
+/// Prefix of TRON mainnet addresses
+const ADDRESS_PREFIX: u8 = 0x41;
+
+const TRANSFER_CONTRACT: u64 = 1;
+const TRANSFER_ASSET_CONTRACT: u64 = 2;
+const TRIGGER_SMART_CONTRACT: u64 = 31;
+const FREEZE_BALANCE_V2_CONTRACT: u64 = 54;
+const UNFREEZE_BALANCE_V2_CONTRACT: u64 = 55;
+
+const TRC20_TRANSFER: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+const TRC20_APPROVE: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+
+enum Value<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+/// Fields of a protobuf message, by field number. Fixed-width fields are
+/// skipped since none of the contracts shown here use them.
+struct Message<'a> {
+    fields: Vec<(u64, Value<'a>)>,
+}
+
+impl<'a> Message<'a> {
+    fn parse(bytes: &'a [u8]) -> Result<Self, TransactionError> {
+        let mut reader = Reader::new(bytes);
+        let mut fields = Vec::new();
+        while !reader.is_empty() {
+            let key = reader.varint(10)?;
+            let (field, wire_type) = (key >> 3, key & 0x07);
+            match wire_type {
+                0 => fields.push((field, Value::Varint(reader.varint(10)?))),
+                1 => {
+                    reader.take(8)?;
+                }
+                2 => {
+                    let length = reader.length(10)?;
+                    fields.push((field, Value::Bytes(reader.take(length)?)));
+                }
+                5 => {
+                    reader.take(4)?;
+                }
+                _ => {
+                    return Err(decode_error(format!(
+                        "unsupported protobuf wire type {wire_type}"
+                    )));
+                }
+            }
+        }
+        Ok(Self { fields })
+    }
+
+    // Protobuf keeps the last occurrence of a singular field
+    fn varint(&self, field: u64) -> Option<u64> {
+        self.fields.iter().rev().find_map(|(number, value)| match value {
+            Value::Varint(value) if *number == field => Some(*value),
+            _ => None,
+        })
+    }
+
+    fn bytes(&self, field: u64) -> Option<&'a [u8]> {
+        self.fields.iter().rev().find_map(|(number, value)| match value {
+            Value::Bytes(bytes) if *number == field => Some(*bytes),
+            _ => None,
+        })
+    }
+
+    fn repeated(&self, field: u64) -> impl Iterator<Item = &'a [u8]> + '_ {
+        self.fields.iter().filter_map(move |(number, value)| match value {
+            Value::Bytes(bytes) if *number == field => Some(*bytes),
+            _ => None,
+        })
+    }
+}
+
+pub(super) fn format(
+    chain_id: &str,
+    transaction: &[u8],
+    locale: &Locale,
+) -> Result<DisplayModel, TransactionError> {
+    let raw = Message::parse(transaction)?;
+    let calls = raw
+        .repeated(11)
+        .map(|contract| format_contract(chain_id, contract, locale))
+        .collect::<Result<Vec<_>, _>>()?;
+    if calls.is_empty() {
+        return Err(decode_error("transaction has no contract"));
+    }
+
+    let mut items = Vec::new();
+    if let Some(fee_limit) = raw.varint(18) {
+        items.push(item(
+            "Fee limit",
+            native_amount(
+                chain_id,
+                TRX_COIN_TYPE,
+                TRX,
+                &BigUint::from(fee_limit),
+                locale,
+            ),
+        ));
+    }
+    if let Some(memo) = raw.bytes(10).filter(|memo| !memo.is_empty()) {
+        items.push(item("Memo", String::from_utf8_lossy(memo)));
+    }
+
+    Ok(combine("TRON transaction", calls, items, Vec::new()))
+}
+
+// Contract { type, parameter: Any { type_url, value } }
+fn format_contract(
+    chain_id: &str,
+    contract: &[u8],
+    locale: &Locale,
+) -> Result<DisplayModel, TransactionError> {
+    let contract = Message::parse(contract)?;
+    let kind = contract.varint(1).unwrap_or_default();
+    let parameter = Message::parse(contract.bytes(2).unwrap_or_default())?;
+    let value = Message::parse(parameter.bytes(2).unwrap_or_default())?;
+    let trx = |amount: u64| {
+        native_amount(
+            chain_id,
+            TRX_COIN_TYPE,
+            TRX,
+            &BigUint::from(amount),
+            locale,
+        )
+    };
+
+    Ok(match kind {
+        // TransferContract { owner_address, to_address, amount }
+        TRANSFER_CONTRACT => {
+            let to = address(value.bytes(2).unwrap_or_default());
+            let amount = trx(value.varint(3).unwrap_or_default());
+            let mut call = model(
+                "Send TRX",
+                vec![
+                    item("From", address(value.bytes(1).unwrap_or_default())),
+                    item("To", to.clone()),
+                    item("Amount", amount.clone()),
+                ],
+            );
+            call.interpolated_intent = Some(format!("Send {amount} to {to}"));
+            call
+        }
+        // TransferAssetContract { asset_name, owner_address, to_address,
+        // amount }; `asset_name` holds the TRC-10 token ID
+        TRANSFER_ASSET_CONTRACT => {
+            let token =
+                String::from_utf8_lossy(value.bytes(1).unwrap_or_default())
+                    .into_owned();
+            let to = address(value.bytes(3).unwrap_or_default());
+            let mut warnings = Vec::new();
+            let amount = token_amount(
+                chain_id,
+                &format!("trc10:{token}"),
+                None,
+                &BigUint::from(value.varint(4).unwrap_or_default()),
+                &mut warnings,
+                locale,
+            );
+            let mut call = model(
+                "Send tokens",
+                vec![
+                    item("From", address(value.bytes(2).unwrap_or_default())),
+                    item("To", to.clone()),
+                    item("Amount", amount.clone()),
+                ],
+            );
+            call.interpolated_intent = Some(format!("Send {amount} to {to}"));
+            call.warnings = warnings;
+            call
+        }
+        // TriggerSmartContract { owner_address, contract_address,
+        // call_value, data }
+        TRIGGER_SMART_CONTRACT => format_trigger(
+            chain_id,
+            address(value.bytes(1).unwrap_or_default()),
+            address(value.bytes(2).unwrap_or_default()),
+            value.varint(3).unwrap_or_default(),
+            value.bytes(4).unwrap_or_default(),
+            locale,
+        ),
+        // FreezeBalanceV2Contract and UnfreezeBalanceV2Contract
+        // { owner_address, balance, resource }
+        FREEZE_BALANCE_V2_CONTRACT | UNFREEZE_BALANCE_V2_CONTRACT => {
+            let resource = match value.varint(3).unwrap_or_default() {
+                0 => "Bandwidth",
+                1 => "Energy",
+                _ => "TRON Power",
+            };
+            let amount = trx(value.varint(2).unwrap_or_default());
+            let (intent, verb) = if kind == FREEZE_BALANCE_V2_CONTRACT {
+                ("Stake TRX", "Stake")
+            } else {
+                ("Unstake TRX", "Unstake")
+            };
+            let mut call = model(
+                intent,
+                vec![
+                    item(
+                        "Account",
+                        address(value.bytes(1).unwrap_or_default()),
+                    ),
+                    item("Amount", amount.clone()),
+                    item("Resource", resource),
+                ],
+            );
+            call.interpolated_intent =
+                Some(format!("{verb} {amount} for {resource}"));
+            call
+        }
+        _ => {
+            let mut call = model(
+                "TRON contract",
+                vec![item("Contract type", kind.to_string())],
+            );
+            call.warnings.push(format!(
+                "Unsupported contract type {kind}; parameters not shown"
+            ));
+            call
+        }
+    })
+}
+
+fn format_trigger(
+    chain_id: &str,
+    owner: String,
+    contract: String,
+    call_value: u64,
+    data: &[u8],
+    locale: &Locale,
+) -> DisplayModel {
+    let selector = data.get(..4);
+    let arguments = (data.len() == 68).then(|| {
+        let recipient = [[ADDRESS_PREFIX].as_slice(), &data[16..36]].concat();
+        (address(&recipient), BigUint::from_bytes_be(&data[36..68]))
+    });
+    let asset = format!("trc20:{contract}");
+
+    let mut call = match (selector, arguments) {
+        (Some(selector), Some((to, raw_amount)))
+            if selector == TRC20_TRANSFER =>
+        {
+            let mut warnings = Vec::new();
+            let amount = token_amount(
+                chain_id,
+                &asset,
+                None,
+                &raw_amount,
+                &mut warnings,
+                locale,
+            );
+            let mut call = model(
+                "Send tokens",
+                vec![
+                    item("From", owner),
+                    item("To", to.clone()),
+                    item("Amount", amount.clone()),
+                    item("Token", token_name(chain_id, &asset, &contract)),
+                ],
+            );
+            call.interpolated_intent = Some(format!("Send {amount} to {to}"));
+            call.warnings = warnings;
+            if to == contract {
+                call.risks.push(RiskWarning::new(
+                    RiskCode::TransferToTokenContract,
+                    "Sends tokens to the token contract, where they are lost"
+                        .to_string(),
+                ));
+            }
+            call
+        }
+        (Some(selector), Some((spender, raw_amount)))
+            if selector == TRC20_APPROVE =>
+        {
+            let mut warnings = Vec::new();
+            let unlimited = is_unlimited(&raw_amount);
+            let amount = if unlimited {
+                format!("Unlimited {}", token_name(chain_id, &asset, &contract))
+            } else {
+                token_amount(
+                    chain_id,
+                    &asset,
+                    None,
+                    &raw_amount,
+                    &mut warnings,
+                    locale,
+                )
+            };
+            let mut call = model(
+                "Approve token spending",
+                vec![
+                    item("Owner", owner),
+                    item("Spender", spender.clone()),
+                    item("Amount", amount.clone()),
+                    item("Token", token_name(chain_id, &asset, &contract)),
+                ],
+            );
+            call.interpolated_intent =
+                Some(format!("Allow {spender} to spend {amount}"));
+            call.warnings = warnings;
+            if unlimited {
+                call.risks.push(RiskWarning::new(
+                    RiskCode::UnlimitedApproval,
+                    "Approves an unlimited amount".to_string(),
+                ));
+            }
+            call
+        }
+        _ => {
+            let mut call = model(
+                "Call contract",
+                vec![
+                    item("From", owner),
+                    item("Contract", contract.clone()),
+                    item("Data", format!("0x{}", hex::encode(data))),
+                ],
+            );
+            call.warnings.push(format!(
+                "No descriptor for contract {contract}; calldata shown raw"
+            ));
+            call
+        }
+    };
+    if call_value > 0 {
+        call.items.push(item(
+            "Value",
+            native_amount(
+                chain_id,
+                TRX_COIN_TYPE,
+                TRX,
+                &BigUint::from(call_value),
+                locale,
+            ),
+        ));
+    }
+    call
+}
+
+/// Base58Check form (`T...`) of a 21-byte TRON address; anything else is
+/// shown as hex.
+fn address(bytes: &[u8]) -> String {
+    if bytes.len() != 21 || bytes[0] != ADDRESS_PREFIX {
+        return format!("0x{}", hex::encode(bytes));
+    }
+    bs58::encode(bytes).with_check().into_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDT: &str = "41a614f803b6fd780986a42c78ec9c7f77e6ded13c";
+
+    fn varint(bytes: &mut Vec<u8>, mut value: u64) {
+        while value >= 0x80 {
+            bytes.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        bytes.push(value as u8);
+    }
+
+    fn field_varint(bytes: &mut Vec<u8>, field: u64, value: u64) {
+        varint(bytes, field << 3);
+        varint(bytes, value);
+    }
+
+    fn field_bytes(bytes: &mut Vec<u8>, field: u64, value: &[u8]) {
+        varint(bytes, (field << 3) | 2);
+        varint(bytes, value.len() as u64);
+        bytes.extend(value);
+    }
+
+    fn account(byte: u8) -> Vec<u8> {
+        [[ADDRESS_PREFIX].as_slice(), &[byte; 20]].concat()
+    }
+
+    fn raw_data(
+        kind: u64,
+        parameter: &[u8],
+        fee_limit: Option<u64>,
+    ) -> Vec<u8> {
+        let mut any = Vec::new();
+        field_bytes(&mut any, 1, b"type.googleapis.com/protocol.Contract");
+        field_bytes(&mut any, 2, parameter);
+        let mut contract = Vec::new();
+        field_varint(&mut contract, 1, kind);
+        field_bytes(&mut contract, 2, &any);
+
+        let mut raw = Vec::new();
+        field_bytes(&mut raw, 1, &[0x12, 0x34]);
+        field_varint(&mut raw, 8, 1_762_956_494_000);
+        field_bytes(&mut raw, 11, &contract);
+        if let Some(fee_limit) = fee_limit {
+            field_varint(&mut raw, 18, fee_limit);
+        }
+        raw
+    }
+
+    fn trc20_call(selector: [u8; 4], to: &[u8], amount: &BigUint) -> Vec<u8> {
+        let mut data = selector.to_vec();
+        data.extend([0u8; 12]);
+        data.extend(&to[1..]);
+        let amount = amount.to_bytes_be();
+        data.extend(vec![0u8; 32 - amount.len()]);
+        data.extend(amount);
+
+        let mut parameter = Vec::new();
+        field_bytes(&mut parameter, 1, &account(0x11));
+        field_bytes(&mut parameter, 2, &hex::decode(USDT).unwrap());
+        field_bytes(&mut parameter, 4, &data);
+        raw_data(TRIGGER_SMART_CONTRACT, &parameter, Some(100_000_000))
+    }
+
+    fn format_mainnet(bytes: &[u8]) -> DisplayModel {
+        super::super::format_transaction(
+            TRON_MAINNET,
+            bytes,
+            &Locale::default(),
+        )
+        .expect("format succeeds")
+    }
+
+    #[test]
+    fn test_address() {
+        assert_eq!(
+            address(&hex::decode(USDT).unwrap()),
+            "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t"
+        );
+        assert_eq!(address(&[0xab; 20]), format!("0x{}", "ab".repeat(20)));
+    }
+
+    #[test]
+    fn test_trx_transfer() {
+        let mut parameter = Vec::new();
+        field_bytes(&mut parameter, 1, &account(0x11));
+        field_bytes(&mut parameter, 2, &account(0x22));
+        field_varint(&mut parameter, 3, 1_500_000);
+
+        let model =
+            format_mainnet(&raw_data(TRANSFER_CONTRACT, &parameter, None));
+        let to = address(&account(0x22));
+        assert_eq!(model.intent, "Send TRX");
+        assert_eq!(model.items[0].value, address(&account(0x11)));
+        assert_eq!(model.items[1].value, to);
+        assert_eq!(model.items[2].value, "1.5 TRX");
+        assert_eq!(
+            model.interpolated_intent,
+            Some(format!("Send 1.5 TRX to {to}"))
+        );
+    }
+
+    #[test]
+    fn test_trc20_transfer_and_approve() {
+        let transfer = format_mainnet(&trc20_call(
+            TRC20_TRANSFER,
+            &account(0x22),
+            &BigUint::from(10_000_000u32),
+        ));
+        assert_eq!(transfer.intent, "Send tokens");
+        assert_eq!(transfer.items[2].value, "10 USDT");
+        assert_eq!(transfer.items[3].value, "USDT");
+        assert_eq!(transfer.items[4].label, "Fee limit");
+        assert_eq!(transfer.items[4].value, "100 TRX");
+        assert!(transfer.risks.is_empty());
+
+        let to_contract = format_mainnet(&trc20_call(
+            TRC20_TRANSFER,
+            &hex::decode(USDT).unwrap(),
+            &BigUint::from(1u32),
+        ));
+        assert_eq!(
+            to_contract.risks[0].code,
+            RiskCode::TransferToTokenContract
+        );
+
+        let approval = format_mainnet(&trc20_call(
+            TRC20_APPROVE,
+            &account(0x33),
+            &((BigUint::from(1u32) << 256) - 1u32),
+        ));
+        assert_eq!(approval.intent, "Approve token spending");
+        assert_eq!(approval.items[2].value, "Unlimited USDT");
+        assert_eq!(approval.risks[0].code, RiskCode::UnlimitedApproval);
+    }
+
+    #[test]
+    fn test_requires_a_contract() {
+        let mut raw = Vec::new();
+        field_bytes(&mut raw, 1, &[0x12, 0x34]);
+        assert!(
+            super::super::format_transaction(
+                TRON_MAINNET,
+                &raw,
+                &Locale::default()
+            )
+            .is_err()
+        );
+    }
+}
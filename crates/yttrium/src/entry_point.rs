@@ -47,9 +47,18 @@ pub const ENTRYPOINT_ADDRESS_V06: Address =
     address!("5FF137D4b0FDCD49DcA30c7CF57E578a026d2789");
 pub const ENTRYPOINT_ADDRESS_V07: Address =
     address!("0000000071727De22E5E9d8BAf0edAc6f37da032");
+pub const ENTRYPOINT_ADDRESS_V08: Address =
+    address!("4337084D9E255Ff0702461CF8895CE9E3b5Ff108");
+
+/// `factory` of an EntryPoint v0.8 user operation whose sender is an
+/// EIP-7702 delegated EOA; `factoryData`, if any, is passed to the delegate
+/// on first use.
+pub const INITCODE_EIP7702_MARKER: Address =
+    address!("7702000000000000000000000000000000000000");
 
 pub const ENTRYPOINT_V06_TYPE: &str = "v0.6";
 pub const ENTRYPOINT_V07_TYPE: &str = "v0.7";
+pub const ENTRYPOINT_V08_TYPE: &str = "v0.8";
 
 sol! {
     #[sol(rpc)]
@@ -98,6 +107,21 @@ impl EntryPointConfig {
         version: EntryPointVersion::V07,
     };
 
+    pub const V08_MAINNET: EntryPointConfig = EntryPointConfig {
+        chain_id: ChainId::ETHEREUM_MAINNET,
+        version: EntryPointVersion::V08,
+    };
+
+    pub const V08_SEPOLIA: EntryPointConfig = EntryPointConfig {
+        chain_id: ChainId::ETHEREUM_SEPOLIA,
+        version: EntryPointVersion::V08,
+    };
+
+    pub const V08_LOCAL_FOUNDRY_SEPOLIA: EntryPointConfig = EntryPointConfig {
+        chain_id: ChainId::LOCAL_FOUNDRY_ETHEREUM_SEPOLIA,
+        version: EntryPointVersion::V08,
+    };
+
     pub fn address(&self) -> EntryPointAddress {
         // assuming that the entrypoint address is the same for all chains, so
         // not matching based on `chain_id` (anymore)
@@ -108,6 +132,9 @@ impl EntryPointConfig {
             EntryPointVersion::V07 => {
                 EntryPointAddress::new(ENTRYPOINT_ADDRESS_V07)
             }
+            EntryPointVersion::V08 => {
+                EntryPointAddress::new(ENTRYPOINT_ADDRESS_V08)
+            }
         }
     }
 
@@ -120,6 +147,9 @@ impl EntryPointConfig {
 pub enum EntryPointVersion {
     V06,
     V07,
+    /// Same packed user operation as v0.7, hashed as EIP-712 typed data and
+    /// with EIP-7702 support.
+    V08,
 }
 
 impl EntryPointVersion {
@@ -127,6 +157,7 @@ impl EntryPointVersion {
         match self {
             EntryPointVersion::V06 => ENTRYPOINT_V06_TYPE.to_string(),
             EntryPointVersion::V07 => ENTRYPOINT_V07_TYPE.to_string(),
+            EntryPointVersion::V08 => ENTRYPOINT_V08_TYPE.to_string(),
         }
     }
 
@@ -137,6 +168,10 @@ impl EntryPointVersion {
     pub fn is_v07(&self) -> bool {
         self == &EntryPointVersion::V07
     }
+
+    pub fn is_v08(&self) -> bool {
+        self == &EntryPointVersion::V08
+    }
 }
 
 impl From<EntryPointVersion> for String {
@@ -150,6 +185,7 @@ impl From<String> for EntryPointVersion {
         match value.as_str() {
             ENTRYPOINT_V06_TYPE => EntryPointVersion::V06,
             ENTRYPOINT_V07_TYPE => EntryPointVersion::V07,
+            ENTRYPOINT_V08_TYPE => EntryPointVersion::V08,
             _ => panic!("invalid version string"),
         }
     }
@@ -213,6 +249,25 @@ mod tests {
             );
         };
 
+        {
+            let expected_v08_address = ENTRYPOINT_ADDRESS_V08;
+            let sepolia_v08_address = EntryPointConfig::V08_SEPOLIA.address();
+            eyre::ensure!(
+                sepolia_v08_address.to_address() == expected_v08_address,
+                format!("unexpected address: {:?}", sepolia_v08_address)
+            );
+        };
+
+        {
+            let v08_type = ENTRYPOINT_V08_TYPE.to_string();
+            let v08 = EntryPointVersion::from(v08_type);
+            eyre::ensure!(v08.is_v08());
+            eyre::ensure!(
+                v08 == EntryPointVersion::V08,
+                format!("unexpected type: {:?}", v08)
+            );
+        };
+
         {
             let v06_type = ENTRYPOINT_V06_TYPE.to_string();
             let v06 = EntryPointVersion::from(v06_type);
@@ -41,6 +41,27 @@ impl GetSenderAddress {
     }
 }
 
+/// `getSenderAddress` is unchanged in v0.8, except that EIP-7702 senders
+/// (`factory` is `INITCODE_EIP7702_MARKER`) are the delegated EOA itself
+/// and have no counterfactual address to look up.
+pub async fn get_sender_address_v08<P, N>(
+    provider: &P,
+    factory: Address,
+    factory_data: Bytes,
+    entrypoint: super::EntryPointAddress,
+) -> eyre::Result<AccountAddress>
+where
+    P: alloy::contract::private::Provider<N>,
+    N: alloy::contract::private::Network,
+{
+    if factory == super::INITCODE_EIP7702_MARKER {
+        return Err(eyre::eyre!(
+            "EIP-7702 user operations are sent from the delegated EOA"
+        ));
+    }
+    get_sender_address_v07(provider, factory, factory_data, entrypoint).await
+}
+
 pub async fn get_sender_address_v07<P, N>(
     provider: &P,
     factory: Address,
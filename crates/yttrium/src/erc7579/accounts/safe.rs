@@ -1,4 +1,45 @@
-use alloy::primitives::{Address, aliases::U192};
+use {
+    crate::{
+        call::Call,
+        erc7579::module::{self, Module, ModuleType, get_previous_entry},
+    },
+    alloy::{
+        primitives::{Address, Bytes, U256, aliases::U192},
+        sol,
+        sol_types::{SolCall, SolValue},
+    },
+};
+
+// https://github.com/rhinestonewtf/safe7579/blob/main/src/core/ModuleManager.sol
+sol! {
+    #[sol(rpc)]
+    contract ISafe7579Modules {
+        function getValidatorsPaginated(
+            address cursor,
+            uint256 pageSize
+        ) external view returns (address[] memory array, address next);
+
+        function getExecutorsPaginated(
+            address cursor,
+            uint256 pageSize
+        ) external view returns (address[] memory array, address next);
+    }
+}
+
+// User operations of Safes deployed by `smart_accounts::safe` are validated
+// by the Safe's own owners, so these change who can sign for the account
+// https://github.com/safe-global/safe-smart-account/blob/main/contracts/base/OwnerManager.sol
+sol! {
+    #[sol(rpc)]
+    contract ISafeOwnerManager {
+        function addOwnerWithThreshold(address owner, uint256 _threshold) external;
+        function removeOwner(address prevOwner, address owner, uint256 _threshold) external;
+        function swapOwner(address prevOwner, address oldOwner, address newOwner) external;
+        function changeThreshold(uint256 _threshold) external;
+        function getThreshold() external view returns (uint256);
+        function getOwners() external view returns (address[] memory);
+    }
+}
 
 // encodeValidatorNonce in Rhinestone SDK
 pub fn encode_validator_key(validator_module_address: Address) -> U192 {
@@ -9,13 +50,144 @@ pub fn encode_validator_key(validator_module_address: Address) -> U192 {
     })
 }
 
+// Owner management is `authorized`, i.e. the Safe calls itself
+fn safe_self_call(account: Address, input: impl SolCall) -> Call {
+    Call { to: account, value: U256::ZERO, input: input.abi_encode().into() }
+}
+
+pub fn get_add_owner_call(
+    account: Address,
+    owner: Address,
+    threshold: u8,
+) -> Call {
+    safe_self_call(
+        account,
+        ISafeOwnerManager::addOwnerWithThresholdCall {
+            owner,
+            _threshold: U256::from(threshold),
+        },
+    )
+}
+
+/// `owners` are the Safe's owners, in the order `getOwners` returns them.
+/// `None` if `owner` isn't one of them.
+pub fn get_remove_owner_call(
+    account: Address,
+    owners: &[Address],
+    owner: Address,
+    threshold: u8,
+) -> Option<Call> {
+    Some(safe_self_call(
+        account,
+        ISafeOwnerManager::removeOwnerCall {
+            prevOwner: get_previous_entry(owners, owner)?,
+            owner,
+            _threshold: U256::from(threshold),
+        },
+    ))
+}
+
+/// Replaces `old` with `new` in place, e.g. to rotate a lost key. `None` if
+/// `old` isn't in `owners`.
+pub fn get_swap_owner_call(
+    account: Address,
+    owners: &[Address],
+    old: Address,
+    new: Address,
+) -> Option<Call> {
+    Some(safe_self_call(
+        account,
+        ISafeOwnerManager::swapOwnerCall {
+            prevOwner: get_previous_entry(owners, old)?,
+            oldOwner: old,
+            newOwner: new,
+        },
+    ))
+}
+
+pub fn get_change_threshold_call(account: Address, threshold: u8) -> Call {
+    safe_self_call(
+        account,
+        ISafeOwnerManager::changeThresholdCall {
+            _threshold: U256::from(threshold),
+        },
+    )
+}
+
+/// Safe7579 keeps validators and executors in sentinel lists, so
+/// uninstalling one takes `abi.encode(prevModule, deInitData)`. `installed`
+/// are the validators or executors of the account, as returned by
+/// `getValidatorsPaginated` or `getExecutorsPaginated`; it's not needed for
+/// fallbacks and hooks. `None` if `module` isn't in `installed`.
+pub fn get_uninstall_module_call(
+    account: Address,
+    module: &Module,
+    installed: &[Address],
+) -> Option<Call> {
+    let de_init_data = match module.r#type {
+        ModuleType::Validator | ModuleType::Executor => {
+            let previous = get_previous_entry(installed, module.address)?;
+            Bytes::from(
+                (previous, module.de_init_data.clone()).abi_encode_params(),
+            )
+        }
+        ModuleType::Fallback | ModuleType::Hook => module.de_init_data.clone(),
+    };
+    Some(module::get_uninstall_module_call(account, module, de_init_data))
+}
+
+/// Installs `new` and uninstalls `old` in one batch, e.g. to rotate the
+/// validator. `installed` are the modules of `old`'s type. Installing pushes
+/// `new` to the head of its list, which is taken into account when finding
+/// the entry before `old`.
+pub fn get_replace_module_calls(
+    account: Address,
+    new: &Module,
+    old: &Module,
+    installed: &[Address],
+) -> Option<Vec<Call>> {
+    let installed = if new.r#type == old.r#type
+        && matches!(new.r#type, ModuleType::Validator | ModuleType::Executor)
+    {
+        std::iter::once(new.address).chain(installed.iter().copied()).collect()
+    } else {
+        installed.to_vec()
+    };
+    Some(vec![
+        module::get_install_module_call(account, new),
+        get_uninstall_module_call(account, old, &installed)?,
+    ])
+}
+
 #[cfg(test)]
 mod test {
     use {
         super::*,
+        crate::erc7579::module::{IERC7579Account, SENTINEL_ADDRESS},
         alloy::primitives::{address, aliases::B192, bytes, fixed_bytes},
     };
 
+    fn validator(address: Address) -> Module {
+        Module {
+            address,
+            module: address,
+            init_data: bytes!("01"),
+            de_init_data: bytes!("02"),
+            additional_context: Bytes::default(),
+            r#type: ModuleType::Validator,
+            hook: None,
+        }
+    }
+
+    fn decode_uninstall(call: &Call) -> (U256, Address, Address, Bytes) {
+        let call =
+            IERC7579Account::uninstallModuleCall::abi_decode(&call.input)
+                .unwrap();
+        let (previous, de_init_data) =
+            <(Address, Bytes)>::abi_decode_params(&call.deInitData).unwrap();
+        (call.moduleTypeId, call.module, previous, de_init_data)
+    }
+
     #[test]
     fn test_encode_validator_key() {
         assert_eq!(
@@ -39,4 +211,119 @@ mod test {
             bytes!("abababababababababababababababababababab00000000").to_vec()
         );
     }
+
+    #[test]
+    fn test_owner_calls() {
+        let account = Address::repeat_byte(0xaa);
+        let owners = [Address::repeat_byte(0x11), Address::repeat_byte(0x22)];
+
+        let call = get_add_owner_call(account, Address::repeat_byte(0x33), 2);
+        assert_eq!(call.to, account);
+        let add = ISafeOwnerManager::addOwnerWithThresholdCall::abi_decode(
+            &call.input,
+        )
+        .unwrap();
+        assert_eq!(
+            (add.owner, add._threshold),
+            (Address::repeat_byte(0x33), U256::from(2))
+        );
+
+        let call =
+            get_remove_owner_call(account, &owners, owners[1], 1).unwrap();
+        let remove =
+            ISafeOwnerManager::removeOwnerCall::abi_decode(&call.input)
+                .unwrap();
+        assert_eq!(
+            (remove.prevOwner, remove.owner, remove._threshold),
+            (owners[0], owners[1], U256::from(1))
+        );
+        assert!(
+            get_remove_owner_call(
+                account,
+                &owners,
+                Address::repeat_byte(0x33),
+                1
+            )
+            .is_none()
+        );
+
+        let call = get_swap_owner_call(
+            account,
+            &owners,
+            owners[0],
+            Address::repeat_byte(0x33),
+        )
+        .unwrap();
+        let swap =
+            ISafeOwnerManager::swapOwnerCall::abi_decode(&call.input).unwrap();
+        assert_eq!(
+            (swap.prevOwner, swap.oldOwner, swap.newOwner),
+            (SENTINEL_ADDRESS, owners[0], Address::repeat_byte(0x33))
+        );
+
+        let call = get_change_threshold_call(account, 2);
+        assert_eq!(call.to, account);
+        assert_eq!(
+            ISafeOwnerManager::changeThresholdCall::abi_decode(&call.input)
+                .unwrap()
+                ._threshold,
+            U256::from(2)
+        );
+    }
+
+    #[test]
+    fn test_uninstall_validator() {
+        let account = Address::repeat_byte(0xaa);
+        let (first, second) =
+            (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        let installed = [first, second];
+
+        let call =
+            get_uninstall_module_call(account, &validator(first), &installed)
+                .unwrap();
+        assert_eq!(call.to, account);
+        assert_eq!(
+            decode_uninstall(&call),
+            (U256::from(1), first, SENTINEL_ADDRESS, bytes!("02"))
+        );
+
+        let call =
+            get_uninstall_module_call(account, &validator(second), &installed)
+                .unwrap();
+        assert_eq!(decode_uninstall(&call).2, first);
+
+        assert!(
+            get_uninstall_module_call(
+                account,
+                &validator(Address::repeat_byte(0x33)),
+                &installed
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_replace_validator() {
+        let account = Address::repeat_byte(0xaa);
+        let (old, new) =
+            (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+
+        let calls = get_replace_module_calls(
+            account,
+            &validator(new),
+            &validator(old),
+            &[old],
+        )
+        .unwrap();
+        let install =
+            IERC7579Account::installModuleCall::abi_decode(&calls[0].input)
+                .unwrap();
+        assert_eq!(install.module, new);
+        assert_eq!(install.initData, bytes!("01"));
+        // `new` is now the head of the list, in front of `old`
+        assert_eq!(
+            decode_uninstall(&calls[1]),
+            (U256::from(1), old, new, bytes!("02"))
+        );
+    }
 }
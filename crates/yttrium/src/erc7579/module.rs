@@ -1,4 +1,39 @@
-use alloy::primitives::{Address, Bytes};
+use {
+    crate::call::Call,
+    alloy::{
+        primitives::{Address, Bytes, U256, address},
+        sol,
+        sol_types::SolCall,
+    },
+};
+
+// https://github.com/erc7579/erc7579-implementation/blob/main/src/interfaces/IERC7579Account.sol
+sol! {
+    #[sol(rpc)]
+    contract IERC7579Account {
+        function installModule(
+            uint256 moduleTypeId,
+            address module,
+            bytes calldata initData
+        ) external payable;
+
+        function uninstallModule(
+            uint256 moduleTypeId,
+            address module,
+            bytes calldata deInitData
+        ) external payable;
+
+        function isModuleInstalled(
+            uint256 moduleTypeId,
+            address module,
+            bytes calldata additionalContext
+        ) external view returns (bool);
+    }
+}
+
+// https://github.com/rhinestonewtf/sentinellist/blob/main/src/SentinelList.sol
+pub const SENTINEL_ADDRESS: Address =
+    address!("0000000000000000000000000000000000000001");
 
 pub struct Module {
     pub address: Address,
@@ -10,9 +45,65 @@ pub struct Module {
     pub hook: Option<Address>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ModuleType {
     Validator,
     Executor,
     Fallback,
     Hook,
 }
+
+impl ModuleType {
+    // https://eips.ethereum.org/EIPS/eip-7579#modules
+    pub fn type_id(&self) -> U256 {
+        U256::from(match self {
+            Self::Validator => 1,
+            Self::Executor => 2,
+            Self::Fallback => 3,
+            Self::Hook => 4,
+        })
+    }
+}
+
+/// Installs `module` on `account` with its `init_data`
+pub fn get_install_module_call(account: Address, module: &Module) -> Call {
+    Call {
+        to: account,
+        value: U256::ZERO,
+        input: IERC7579Account::installModuleCall {
+            moduleTypeId: module.r#type.type_id(),
+            module: module.address,
+            initData: module.init_data.clone(),
+        }
+        .abi_encode()
+        .into(),
+    }
+}
+
+/// Uninstalls `module` from `account`, passing `de_init_data` as is. Some
+/// accounts expect more than the module's own `de_init_data`, e.g.
+/// `erc7579::accounts::safe::get_uninstall_module_call`.
+pub fn get_uninstall_module_call(
+    account: Address,
+    module: &Module,
+    de_init_data: Bytes,
+) -> Call {
+    Call {
+        to: account,
+        value: U256::ZERO,
+        input: IERC7579Account::uninstallModuleCall {
+            moduleTypeId: module.r#type.type_id(),
+            module: module.address,
+            deInitData: de_init_data,
+        }
+        .abi_encode()
+        .into(),
+    }
+}
+
+/// Entry before `item` in a `SentinelList` whose entries are `list`, in the
+/// order the contract returns them. `None` if `item` isn't in the list.
+pub fn get_previous_entry(list: &[Address], item: Address) -> Option<Address> {
+    let index = list.iter().position(|entry| *entry == item)?;
+    Some(index.checked_sub(1).map_or(SENTINEL_ADDRESS, |index| list[index]))
+}
@@ -1,9 +1,10 @@
 use {
-    super::module::{Module, ModuleType},
-    crate::smart_accounts::safe::Owners,
+    super::module::{Module, ModuleType, get_previous_entry},
+    crate::{call::Call, smart_accounts::safe::Owners},
     alloy::{
         primitives::{Address, Bytes, U256, address, bytes},
-        sol_types::SolValue,
+        sol,
+        sol_types::{SolCall, SolValue},
     },
 };
 
@@ -11,6 +12,21 @@ use {
 pub const OWNABLE_VALIDATOR_ADDRESS: Address =
     address!("2483DA3A338895199E5e538530213157e931Bf06");
 
+// https://github.com/rhinestonewtf/core-modules/blob/main/src/OwnableValidator/OwnableValidator.sol
+sol! {
+    #[sol(rpc)]
+    contract OwnableValidator {
+        function setThreshold(uint256 _threshold) external;
+        function addOwner(address owner) external;
+        function removeOwner(address prevOwner, address owner) external;
+        function getOwners(address account)
+            external
+            view
+            returns (address[] memory ownersArray);
+        function threshold(address account) external view returns (uint256);
+    }
+}
+
 // encodeValidationData: https://github.com/rhinestonewtf/module-sdk/blob/1f2f2c5380614ad07b6e1ccbb5a9ed55374c673c/src/module/ownable-validator/usage.ts#L171
 pub fn encode_owners(owners: &Owners) -> Bytes {
     let mut owner_addresses = owners.owners.clone();
@@ -43,3 +59,106 @@ pub fn get_ownable_validator_mock_signature(owners: &Owners) -> Bytes {
         (0..owners.threshold).map(|_| MOCK_SIGNATURE).collect(),
     )
 }
+
+// Calls managing the owners the OwnableValidator keeps for an account. These
+// are separate from a Safe's own owners, which
+// `accounts::safe::get_add_owner_call` and friends manage.
+fn ownable_validator_call(input: impl SolCall) -> Call {
+    Call {
+        to: OWNABLE_VALIDATOR_ADDRESS,
+        value: U256::ZERO,
+        input: input.abi_encode().into(),
+    }
+}
+
+// getAddOwnableValidatorOwnerAction: https://github.com/rhinestonewtf/module-sdk/blob/main/src/module/ownable-validator/usage.ts
+pub fn get_add_owner_call(owner: Address) -> Call {
+    ownable_validator_call(OwnableValidator::addOwnerCall { owner })
+}
+
+/// `owners` are the account's current owners, in the order `getOwners`
+/// returns them. `None` if `owner` isn't one of them.
+// getRemoveOwnableValidatorOwnerAction: https://github.com/rhinestonewtf/module-sdk/blob/main/src/module/ownable-validator/usage.ts
+pub fn get_remove_owner_call(
+    owners: &[Address],
+    owner: Address,
+) -> Option<Call> {
+    Some(ownable_validator_call(OwnableValidator::removeOwnerCall {
+        prevOwner: get_previous_entry(owners, owner)?,
+        owner,
+    }))
+}
+
+/// Replaces `old` with `new` in one batch, e.g. to rotate a lost key.
+/// `addOwner` pushes `new` to the head of the list, which is taken into
+/// account when finding the owner before `old`.
+pub fn get_replace_owner_calls(
+    owners: &[Address],
+    old: Address,
+    new: Address,
+) -> Option<Vec<Call>> {
+    let owners =
+        std::iter::once(new).chain(owners.iter().copied()).collect::<Vec<_>>();
+    Some(vec![get_add_owner_call(new), get_remove_owner_call(&owners, old)?])
+}
+
+pub fn get_set_threshold_call(threshold: u8) -> Call {
+    ownable_validator_call(OwnableValidator::setThresholdCall {
+        _threshold: U256::from(threshold),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::erc7579::module::SENTINEL_ADDRESS};
+
+    #[test]
+    fn test_remove_owner() {
+        let owners = [Address::repeat_byte(0x11), Address::repeat_byte(0x22)];
+
+        let call = get_remove_owner_call(&owners, owners[0]).unwrap();
+        assert_eq!(call.to, OWNABLE_VALIDATOR_ADDRESS);
+        let remove =
+            OwnableValidator::removeOwnerCall::abi_decode(&call.input).unwrap();
+        assert_eq!(remove.prevOwner, SENTINEL_ADDRESS);
+        assert_eq!(remove.owner, owners[0]);
+
+        let call = get_remove_owner_call(&owners, owners[1]).unwrap();
+        let remove =
+            OwnableValidator::removeOwnerCall::abi_decode(&call.input).unwrap();
+        assert_eq!(remove.prevOwner, owners[0]);
+
+        assert!(
+            get_remove_owner_call(&owners, Address::repeat_byte(0x33))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_replace_owner() {
+        let (old, new) =
+            (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        let calls = get_replace_owner_calls(&[old], old, new).unwrap();
+        assert_eq!(
+            OwnableValidator::addOwnerCall::abi_decode(&calls[0].input)
+                .unwrap()
+                .owner,
+            new
+        );
+        let remove =
+            OwnableValidator::removeOwnerCall::abi_decode(&calls[1].input)
+                .unwrap();
+        assert_eq!((remove.prevOwner, remove.owner), (new, old));
+    }
+
+    #[test]
+    fn test_set_threshold() {
+        let call = get_set_threshold_call(2);
+        assert_eq!(
+            OwnableValidator::setThresholdCall::abi_decode(&call.input)
+                .unwrap()
+                ._threshold,
+            U256::from(2)
+        );
+    }
+}
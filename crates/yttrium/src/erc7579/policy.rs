@@ -1,13 +1,454 @@
 use {
-    super::smart_sessions::PolicyData,
-    alloy::primitives::{Address, Bytes, address},
+    super::smart_sessions::{
+        ActionData, ERC7739Data, PolicyData, Session, get_permission_id,
+    },
+    alloy::{
+        primitives::{Address, B256, Bytes, FixedBytes, U256, address},
+        sol,
+        sol_types::SolValue,
+    },
+    thiserror::Error,
 };
 
 // https://github.com/rhinestonewtf/module-sdk/blob/main/src/module/smart-sessions/policies/sudo-policy/constants.ts
 pub const SUDO_POLICY_ADDRESS: Address =
     address!("0000003111cD8e92337C100F22B7A9dbf8DEE301");
 
+// https://github.com/rhinestonewtf/module-sdk/blob/main/src/module/smart-sessions/policies/spending-limits-policy/constants.ts
+pub const SPENDING_LIMITS_POLICY_ADDRESS: Address =
+    address!("00000088D48cF102A8Cdb0137A9b173f957c6343");
+
+// https://github.com/rhinestonewtf/module-sdk/blob/main/src/module/smart-sessions/policies/time-frame-policy/constants.ts
+pub const TIME_FRAME_POLICY_ADDRESS: Address =
+    address!("8177451511dE0577b911C254E9551D981C26dc72");
+
+// https://github.com/rhinestonewtf/module-sdk/blob/main/src/module/smart-sessions/policies/usage-limit-policy/constants.ts
+pub const USAGE_LIMIT_POLICY_ADDRESS: Address =
+    address!("1F34eF8311345A3A4a4566aF321b313052F51493");
+
+// https://github.com/rhinestonewtf/module-sdk/blob/main/src/module/smart-sessions/policies/value-limit-policy/constants.ts
+pub const VALUE_LIMIT_POLICY_ADDRESS: Address =
+    address!("730DA93267E7E513e932301B47F2ac7D062abC83");
+
+// https://github.com/rhinestonewtf/module-sdk/blob/main/src/module/smart-sessions/policies/universal-action-policy/constants.ts
+pub const UNIVERSAL_ACTION_POLICY_ADDRESS: Address =
+    address!("0000006DDA6c463511C4e9B05CFc34C1247fCF1F");
+
+/// Maximum number of argument rules of a universal action policy
+pub const MAX_PARAM_RULES: usize = 16;
+
+// https://github.com/erc7579/smartsessions/blob/main/contracts/external/policies/UniActionPolicy.sol
+sol! {
+    struct LimitUsage {
+        uint256 limit;
+        uint256 used;
+    }
+
+    struct ParamRuleData {
+        uint8 condition;
+        uint64 offset;
+        bool isLimited;
+        bytes32 reference;
+        LimitUsage usage;
+    }
+
+    struct ParamRules {
+        uint256 length;
+        ParamRuleData[16] rules;
+    }
+
+    struct ActionConfig {
+        uint256 valueLimitPerUse;
+        ParamRules paramRules;
+    }
+}
+
 // https://github.com/rhinestonewtf/module-sdk/blob/1f2f2c5380614ad07b6e1ccbb5a9ed55374c673c/src/module/smart-sessions/policies/sudo-policy/installation.ts#L4
 pub fn get_sudo_policy() -> PolicyData {
     PolicyData { policy: SUDO_POLICY_ADDRESS, initData: Bytes::default() }
 }
+
+/// Caps how much of an ERC-20 token the session can `transfer` or
+/// `approve` in total. Use it as an action policy of the token contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpendingLimit {
+    pub token: Address,
+    pub limit: U256,
+}
+
+// https://github.com/rhinestonewtf/module-sdk/blob/main/src/module/smart-sessions/policies/spending-limits-policy/installation.ts
+pub fn get_spending_limits_policy(limits: &[SpendingLimit]) -> PolicyData {
+    let (tokens, limits): (Vec<Address>, Vec<U256>) =
+        limits.iter().map(|limit| (limit.token, limit.limit)).unzip();
+    PolicyData {
+        policy: SPENDING_LIMITS_POLICY_ADDRESS,
+        initData: (tokens, limits).abi_encode_params().into(),
+    }
+}
+
+/// Only allows the session between `valid_after` and `valid_until`, both
+/// UNIX timestamps in seconds; `0` leaves that side open.
+// https://github.com/rhinestonewtf/module-sdk/blob/main/src/module/smart-sessions/policies/time-frame-policy/installation.ts
+pub fn get_time_frame_policy(
+    valid_until: u128,
+    valid_after: u128,
+) -> PolicyData {
+    PolicyData {
+        policy: TIME_FRAME_POLICY_ADDRESS,
+        initData: (valid_until, valid_after).abi_encode_packed().into(),
+    }
+}
+
+/// Caps how many times the session can be used, or the action called when
+/// used as an action policy.
+// https://github.com/rhinestonewtf/module-sdk/blob/main/src/module/smart-sessions/policies/usage-limit-policy/installation.ts
+pub fn get_usage_limit_policy(limit: u128) -> PolicyData {
+    PolicyData {
+        policy: USAGE_LIMIT_POLICY_ADDRESS,
+        initData: limit.abi_encode_packed().into(),
+    }
+}
+
+/// Caps the total native value the session can send.
+// https://github.com/rhinestonewtf/module-sdk/blob/main/src/module/smart-sessions/policies/value-limit-policy/installation.ts
+pub fn get_value_limit_policy(limit: U256) -> PolicyData {
+    PolicyData {
+        policy: VALUE_LIMIT_POLICY_ADDRESS,
+        initData: limit.abi_encode().into(),
+    }
+}
+
+/// How a calldata argument is compared with a rule's `reference`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamCondition {
+    Equal,
+    GreaterThan,
+    LessThan,
+    GreaterThanOrEqual,
+    LessThanOrEqual,
+    NotEqual,
+}
+
+impl ParamCondition {
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            Self::Equal => 0,
+            Self::GreaterThan => 1,
+            Self::LessThan => 2,
+            Self::GreaterThanOrEqual => 3,
+            Self::LessThanOrEqual => 4,
+            Self::NotEqual => 5,
+        }
+    }
+}
+
+/// Rule on one 32-byte calldata argument of the action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParamRule {
+    pub condition: ParamCondition,
+    /// Byte offset of the argument, after the 4-byte selector
+    pub offset: u64,
+    pub reference: B256,
+    /// Caps the sum of this argument over every use, e.g. an amount
+    pub usage_limit: Option<U256>,
+}
+
+impl ParamRule {
+    /// Rule on the `index`-th static argument of the action
+    pub fn new(index: u64, condition: ParamCondition, reference: B256) -> Self {
+        Self { condition, offset: index * 32, reference, usage_limit: None }
+    }
+
+    pub fn with_usage_limit(mut self, limit: U256) -> Self {
+        self.usage_limit = Some(limit);
+        self
+    }
+}
+
+/// Builder for the universal action policy, which checks the value and
+/// arguments of each call to an action.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UniversalActionPolicy {
+    pub value_limit_per_use: U256,
+    pub rules: Vec<ParamRule>,
+}
+
+impl UniversalActionPolicy {
+    pub fn new(value_limit_per_use: U256) -> Self {
+        Self { value_limit_per_use, rules: Vec::new() }
+    }
+
+    pub fn with_rule(mut self, rule: ParamRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn action_config(&self) -> Result<ActionConfig, PolicyError> {
+        if self.rules.len() > MAX_PARAM_RULES {
+            return Err(PolicyError::TooManyParamRules(self.rules.len()));
+        }
+        let mut rules: [ParamRuleData; MAX_PARAM_RULES] =
+            std::array::from_fn(|_| ParamRuleData {
+                condition: 0,
+                offset: 0,
+                isLimited: false,
+                reference: B256::ZERO,
+                usage: LimitUsage { limit: U256::ZERO, used: U256::ZERO },
+            });
+        for (data, rule) in rules.iter_mut().zip(&self.rules) {
+            *data = ParamRuleData {
+                condition: rule.condition.to_u8(),
+                offset: rule.offset,
+                isLimited: rule.usage_limit.is_some(),
+                reference: rule.reference,
+                usage: LimitUsage {
+                    limit: rule.usage_limit.unwrap_or_default(),
+                    used: U256::ZERO,
+                },
+            };
+        }
+        Ok(ActionConfig {
+            valueLimitPerUse: self.value_limit_per_use,
+            paramRules: ParamRules {
+                length: U256::from(self.rules.len()),
+                rules,
+            },
+        })
+    }
+
+    // https://github.com/rhinestonewtf/module-sdk/blob/main/src/module/smart-sessions/policies/universal-action-policy/installation.ts
+    pub fn policy_data(&self) -> Result<PolicyData, PolicyError> {
+        Ok(PolicyData {
+            policy: UNIVERSAL_ACTION_POLICY_ADDRESS,
+            initData: self.action_config()?.abi_encode().into(),
+        })
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PolicyError {
+    #[error("{0} param rules, at most {MAX_PARAM_RULES} are supported")]
+    TooManyParamRules(usize),
+    #[error("action {target} {selector} has no policies")]
+    NoActionPolicies { target: Address, selector: FixedBytes<4> },
+    #[error("permitting ERC-4337 paymasters requires a user operation policy")]
+    NoUserOpPolicies,
+}
+
+/// Composes policies into the `userOpPolicies` and `actions` of a
+/// `Session`. SmartSessions rejects actions without policies, so every
+/// action needs at least one, e.g. `get_sudo_policy()` to allow any call.
+#[derive(Clone, Default)]
+pub struct SessionPolicies {
+    pub user_op_policies: Vec<PolicyData>,
+    pub actions: Vec<ActionData>,
+    pub permit_erc4337_paymaster: bool,
+}
+
+impl SessionPolicies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checked once per user operation, whatever it calls
+    pub fn with_user_op_policy(mut self, policy: PolicyData) -> Self {
+        self.user_op_policies.push(policy);
+        self
+    }
+
+    /// Allows calling `selector` on `target`, subject to `policies`.
+    /// Policies of an action that was already added are appended to it.
+    pub fn with_action(
+        mut self,
+        target: Address,
+        selector: FixedBytes<4>,
+        policies: impl IntoIterator<Item = PolicyData>,
+    ) -> Self {
+        match self.actions.iter_mut().find(|action| {
+            action.actionTarget == target
+                && action.actionTargetSelector == selector
+        }) {
+            Some(action) => action.actionPolicies.extend(policies),
+            None => self.actions.push(ActionData {
+                actionTargetSelector: selector,
+                actionTarget: target,
+                actionPolicies: policies.into_iter().collect(),
+            }),
+        }
+        self
+    }
+
+    pub fn with_erc4337_paymaster(mut self, permit: bool) -> Self {
+        self.permit_erc4337_paymaster = permit;
+        self
+    }
+
+    /// Builds the session validated by `session_validator`, e.g. the
+    /// OwnableValidator with `encode_owners` of the session keys.
+    pub fn into_session(
+        self,
+        session_validator: Address,
+        session_validator_init_data: Bytes,
+        salt: B256,
+    ) -> Result<Session, PolicyError> {
+        if let Some(action) =
+            self.actions.iter().find(|action| action.actionPolicies.is_empty())
+        {
+            return Err(PolicyError::NoActionPolicies {
+                target: action.actionTarget,
+                selector: action.actionTargetSelector,
+            });
+        }
+        if self.permit_erc4337_paymaster && self.user_op_policies.is_empty() {
+            return Err(PolicyError::NoUserOpPolicies);
+        }
+        Ok(Session {
+            sessionValidator: session_validator,
+            sessionValidatorInitData: session_validator_init_data,
+            salt,
+            userOpPolicies: self.user_op_policies,
+            erc7739Policies: ERC7739Data {
+                allowedERC7739Content: vec![],
+                erc1271Policies: vec![],
+            },
+            actions: self.actions,
+            permitERC4337Paymaster: self.permit_erc4337_paymaster,
+        })
+    }
+
+    /// Permission ID the session will be enabled under
+    pub fn permission_id(
+        &self,
+        session_validator: Address,
+        session_validator_init_data: Bytes,
+        salt: B256,
+    ) -> Result<B256, PolicyError> {
+        self.clone()
+            .into_session(session_validator, session_validator_init_data, salt)
+            .map(|session| get_permission_id(&session))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        alloy::primitives::{bytes, fixed_bytes},
+    };
+
+    #[test]
+    fn test_init_data() {
+        assert_eq!(
+            get_time_frame_policy(2, 1).initData,
+            bytes!(
+                "0000000000000000000000000000000200000000000000000000000000000001"
+            )
+        );
+        assert_eq!(
+            get_usage_limit_policy(10).initData,
+            bytes!("0000000000000000000000000000000a")
+        );
+        assert_eq!(
+            get_value_limit_policy(U256::from(1)).initData.to_vec(),
+            U256::from(1).abi_encode()
+        );
+
+        let token = Address::repeat_byte(0x11);
+        let spending = get_spending_limits_policy(&[SpendingLimit {
+            token,
+            limit: U256::from(5),
+        }]);
+        let (tokens, limits) =
+            <(Vec<Address>, Vec<U256>)>::abi_decode_params(&spending.initData)
+                .unwrap();
+        assert_eq!(tokens, vec![token]);
+        assert_eq!(limits, vec![U256::from(5)]);
+    }
+
+    #[test]
+    fn test_universal_action_policy() {
+        let recipient = Address::repeat_byte(0x22);
+        let policy = UniversalActionPolicy::new(U256::ZERO)
+            .with_rule(ParamRule::new(
+                0,
+                ParamCondition::Equal,
+                recipient.into_word(),
+            ))
+            .with_rule(
+                ParamRule::new(
+                    1,
+                    ParamCondition::LessThanOrEqual,
+                    U256::from(100).to_be_bytes::<32>().into(),
+                )
+                .with_usage_limit(U256::from(1000)),
+            );
+        let data = policy.policy_data().unwrap();
+        assert_eq!(data.policy, UNIVERSAL_ACTION_POLICY_ADDRESS);
+        // valueLimitPerUse, length and 16 rules of 6 words
+        assert_eq!(data.initData.len(), 32 * (2 + MAX_PARAM_RULES * 6));
+
+        let config = ActionConfig::abi_decode(&data.initData).unwrap();
+        assert_eq!(config.paramRules.length, U256::from(2));
+        assert_eq!(config.paramRules.rules[0].reference, recipient.into_word());
+        assert_eq!(config.paramRules.rules[1].offset, 32);
+        assert_eq!(config.paramRules.rules[1].condition, 4);
+        assert!(config.paramRules.rules[1].isLimited);
+        assert_eq!(config.paramRules.rules[1].usage.limit, U256::from(1000));
+
+        let too_many = (0..=MAX_PARAM_RULES as u64).fold(
+            UniversalActionPolicy::default(),
+            |policy, index| {
+                policy.with_rule(ParamRule::new(
+                    index,
+                    ParamCondition::Equal,
+                    B256::ZERO,
+                ))
+            },
+        );
+        assert_eq!(
+            too_many.policy_data().err(),
+            Some(PolicyError::TooManyParamRules(MAX_PARAM_RULES + 1))
+        );
+    }
+
+    #[test]
+    fn test_session_policies() {
+        let token = Address::repeat_byte(0x33);
+        let transfer = fixed_bytes!("a9059cbb");
+        let session = SessionPolicies::new()
+            .with_user_op_policy(get_usage_limit_policy(10))
+            .with_action(
+                token,
+                transfer,
+                [get_spending_limits_policy(&[SpendingLimit {
+                    token,
+                    limit: U256::from(5),
+                }])],
+            )
+            .with_action(token, transfer, [get_time_frame_policy(100, 0)])
+            .with_erc4337_paymaster(true)
+            .into_session(Address::ZERO, Bytes::new(), B256::ZERO)
+            .unwrap();
+        assert_eq!(session.userOpPolicies.len(), 1);
+        assert_eq!(session.actions.len(), 1);
+        assert_eq!(session.actions[0].actionPolicies.len(), 2);
+
+        assert_eq!(
+            SessionPolicies::new()
+                .with_action(token, transfer, [])
+                .into_session(Address::ZERO, Bytes::new(), B256::ZERO)
+                .err(),
+            Some(PolicyError::NoActionPolicies {
+                target: token,
+                selector: transfer
+            })
+        );
+        assert_eq!(
+            SessionPolicies::new()
+                .with_erc4337_paymaster(true)
+                .into_session(Address::ZERO, Bytes::new(), B256::ZERO)
+                .err(),
+            Some(PolicyError::NoUserOpPolicies)
+        );
+    }
+}
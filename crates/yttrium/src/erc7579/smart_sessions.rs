@@ -1,12 +1,15 @@
 use {
     super::module::{Module, ModuleType},
+    crate::call::Call,
     alloy::{
         primitives::{Address, B256, Bytes, FixedBytes, address, keccak256},
         sol,
-        sol_types::SolValue,
+        sol_types::{SolCall, SolStruct, SolValue, eip712_domain},
     },
 };
 
+pub mod client;
+
 // https://github.com/erc7579/smartsessions/blob/main/contracts/DataTypes.sol
 sol! {
     struct ChainDigest {
@@ -58,14 +61,68 @@ sol! {
     }
 
     function enableSessionSig(EnableSession session, bytes signature);
-}
 
-sol! {
     type PermissionId is bytes32;
 
+    // https://github.com/erc7579/smartsessions/blob/main/contracts/ISmartSession.sol
     #[sol(rpc)]
     contract ISmartSession {
         function isPermissionEnabled(PermissionId permissionId, address account) external view returns (bool);
+        function enableSessions(Session[] calldata sessions) external returns (PermissionId[] memory permissionIds);
+        function removeSession(PermissionId permissionId) external;
+        function getSessionDigest(PermissionId permissionId, address account, Session memory data, uint8 mode) external view returns (bytes32);
+        function getNonce(PermissionId permissionId, address account) external view returns (uint256);
+    }
+}
+
+// Typed data the account owner signs to enable sessions in `Enable` mode.
+// The digest of each chain's session is read from `getSessionDigest`.
+// https://github.com/erc7579/smartsessions/blob/main/contracts/lib/HashLib.sol
+mod eip712 {
+    alloy::sol! {
+        struct PolicyData {
+            address policy;
+            bytes initData;
+        }
+
+        struct ActionData {
+            bytes4 actionTargetSelector;
+            address actionTarget;
+            PolicyData[] actionPolicies;
+        }
+
+        struct ERC7739Context {
+            bytes32 appDomainSeparator;
+            string[] contentNames;
+        }
+
+        struct ERC7739Data {
+            ERC7739Context[] allowedERC7739Content;
+            PolicyData[] erc1271Policies;
+        }
+
+        struct SessionEIP712 {
+            address account;
+            address smartSession;
+            uint8 mode;
+            address sessionValidator;
+            bytes32 salt;
+            bytes sessionValidatorInitData;
+            PolicyData[] userOpPolicies;
+            ERC7739Data erc7739Policies;
+            ActionData[] actions;
+            bool permitERC4337Paymaster;
+            uint256 nonce;
+        }
+
+        struct ChainSessionEIP712 {
+            uint64 chainId;
+            SessionEIP712 session;
+        }
+
+        struct MultiChainSessionEIP712 {
+            ChainSessionEIP712[] sessionsAndChainIds;
+        }
     }
 }
 
@@ -102,6 +159,31 @@ pub fn get_smart_sessions_validator(
     }
 }
 
+/// Call the account makes to enable `sessions` on an installed
+/// SmartSessions validator
+pub fn get_enable_sessions_call(sessions: &[Session]) -> Call {
+    Call {
+        to: SMART_SESSIONS_ADDRESS,
+        value: Default::default(),
+        input: ISmartSession::enableSessionsCall {
+            sessions: sessions.to_vec(),
+        }
+        .abi_encode()
+        .into(),
+    }
+}
+
+/// Call the account makes to revoke the session `permission_id`
+pub fn get_remove_session_call(permission_id: B256) -> Call {
+    Call {
+        to: SMART_SESSIONS_ADDRESS,
+        value: Default::default(),
+        input: ISmartSession::removeSessionCall { permissionId: permission_id }
+            .abi_encode()
+            .into(),
+    }
+}
+
 pub fn get_permission_id(session: &Session) -> B256 {
     keccak256(
         (
@@ -140,6 +222,22 @@ impl SmartSessionMode {
     }
 }
 
+/// Modes that enable a session in the user operation that first uses it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SmartSessionEnableMode {
+    Enable,
+    UnsafeEnable,
+}
+
+impl From<SmartSessionEnableMode> for SmartSessionMode {
+    fn from(mode: SmartSessionEnableMode) -> Self {
+        match mode {
+            SmartSessionEnableMode::Enable => Self::Enable,
+            SmartSessionEnableMode::UnsafeEnable => Self::UnsafeEnable,
+        }
+    }
+}
+
 impl TryFrom<&u8> for SmartSessionMode {
     type Error = &'static str;
 
@@ -156,6 +254,71 @@ pub fn encode_use_signature(permission_id: B256, signature: Bytes) -> Bytes {
     )
 }
 
+/// Signature enabling `enable_session` in the user operation that first
+/// uses it; `signature` is the session key's signature of that operation.
+pub fn encode_enable_signature(
+    mode: SmartSessionEnableMode,
+    enable_session: &EnableSession,
+    signature: &Bytes,
+) -> Bytes {
+    let data = (enable_session, signature).abi_encode_params();
+    [
+        [SmartSessionMode::from(mode).to_u8()].as_slice(),
+        flz_compress_literals(&data).as_slice(),
+    ]
+    .concat()
+    .into()
+}
+
+/// Digest the account owner signs for `EnableSession.permissionEnableSig`,
+/// covering the session digests of every chain it is enabled on.
+pub fn get_multichain_digest(hashes_and_chain_ids: &[ChainDigest]) -> B256 {
+    use eip712::{ChainSessionEIP712, MultiChainSessionEIP712};
+
+    let chain_session_type_hash =
+        keccak256(ChainSessionEIP712::eip712_encode_type().as_bytes());
+    let chain_sessions = hashes_and_chain_ids
+        .iter()
+        .flat_map(|digest| {
+            keccak256(
+                (chain_session_type_hash, digest.chainId, digest.sessionDigest)
+                    .abi_encode(),
+            )
+            .0
+        })
+        .collect::<Vec<u8>>();
+    let struct_hash = keccak256(
+        (
+            keccak256(MultiChainSessionEIP712::eip712_encode_type().as_bytes()),
+            keccak256(chain_sessions),
+        )
+            .abi_encode(),
+    );
+    let domain = eip712_domain! {
+        name: "SmartSession",
+        version: "1",
+    };
+    keccak256(
+        [
+            [0x19, 0x01].as_slice(),
+            domain.separator().as_slice(),
+            struct_hash.as_slice(),
+        ]
+        .concat(),
+    )
+}
+
+/// Encodes `data` as FastLZ literal runs, which `LibZip.flzDecompress`
+/// accepts. SmartSessions decompresses enable signatures, but they are too
+/// short for real compression to be worth its code.
+fn flz_compress_literals(data: &[u8]) -> Vec<u8> {
+    data.chunks(32)
+        .flat_map(|chunk| {
+            std::iter::once(chunk.len() as u8 - 1).chain(chunk.iter().copied())
+        })
+        .collect()
+}
+
 fn encode_smart_session_signature(
     mode: &SmartSessionMode,
     permission_id: B256,
@@ -170,3 +333,41 @@ fn encode_smart_session_signature(
         _ => unimplemented!("mode: {mode:?}"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, alloy::primitives::fixed_bytes};
+
+    #[test]
+    fn test_flz_compress_literals() {
+        let data = (0..40).collect::<Vec<u8>>();
+        let compressed = flz_compress_literals(&data);
+        assert_eq!(compressed.len(), 42);
+        assert_eq!(compressed[0], 31);
+        assert_eq!(&compressed[1..33], &data[..32]);
+        assert_eq!(compressed[33], 7);
+        assert_eq!(&compressed[34..], &data[32..]);
+    }
+
+    #[test]
+    fn test_multichain_digest_type() {
+        assert_eq!(
+            eip712::MultiChainSessionEIP712::eip712_encode_type(),
+            "MultiChainSessionEIP712(ChainSessionEIP712[] sessionsAndChainIds)ActionData(bytes4 actionTargetSelector,address actionTarget,PolicyData[] actionPolicies)ChainSessionEIP712(uint64 chainId,SessionEIP712 session)ERC7739Context(bytes32 appDomainSeparator,string[] contentNames)ERC7739Data(ERC7739Context[] allowedERC7739Content,PolicyData[] erc1271Policies)PolicyData(address policy,bytes initData)SessionEIP712(address account,address smartSession,uint8 mode,address sessionValidator,bytes32 salt,bytes sessionValidatorInitData,PolicyData[] userOpPolicies,ERC7739Data erc7739Policies,ActionData[] actions,bool permitERC4337Paymaster,uint256 nonce)"
+        );
+
+        let digest = ChainDigest {
+            chainId: 1,
+            sessionDigest: fixed_bytes!(
+                "0000000000000000000000000000000000000000000000000000000000000001"
+            ),
+        };
+        assert_ne!(
+            get_multichain_digest(std::slice::from_ref(&digest)),
+            get_multichain_digest(&[
+                digest.clone(),
+                ChainDigest { chainId: 10, ..digest }
+            ])
+        );
+    }
+}
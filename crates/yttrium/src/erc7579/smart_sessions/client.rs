@@ -0,0 +1,426 @@
+use {
+    super::{
+        ChainDigest, EnableSession, ISmartSession, SMART_SESSIONS_ADDRESS,
+        Session, SmartSessionEnableMode, SmartSessionMode,
+        encode_enable_signature, encode_use_signature,
+        get_enable_sessions_call, get_multichain_digest, get_permission_id,
+        get_remove_session_call,
+    },
+    crate::{
+        bundler::{
+            client::BundlerClient,
+            config::BundlerConfig,
+            pimlico::{
+                client::BundlerClient as PimlicoBundlerClient,
+                paymaster::client::PaymasterClient,
+            },
+        },
+        call::{
+            Call,
+            send::safe_test::{
+                PreparedSendTransaction, prepare_send_transactions,
+            },
+        },
+        config::Config,
+        entry_point::ENTRYPOINT_ADDRESS_V07,
+        erc7579::{
+            accounts::safe::encode_validator_key,
+            ownable_validator::get_ownable_validator_mock_signature,
+        },
+        smart_accounts::{
+            account_address::AccountAddress,
+            nonce::get_nonce_with_key,
+            safe::{Owners, get_call_data},
+        },
+        user_operation::{
+            UserOperationV07, hash::get_user_operation_hash_v07,
+            user_operation_hash::UserOperationHash,
+        },
+    },
+    alloy::{
+        primitives::{Address, B256, Bytes, U256, eip191_hash_message},
+        providers::ProviderBuilder,
+        signers::SignerSync,
+    },
+};
+
+/// Manages the session keys of a Safe with the SmartSessions validator
+/// installed. Granting and revoking are user operations signed by the
+/// account owner; using a session is a user operation signed by its key.
+/// SmartSessions can't enumerate sessions, so callers store the sessions
+/// they granted once the user operation is confirmed.
+pub struct SmartSessionsClient {
+    owner: AccountAddress,
+    account: AccountAddress,
+    chain_id: u64,
+    config: Config,
+}
+
+#[derive(Clone)]
+pub struct SessionStatus {
+    pub permission_id: B256,
+    pub session: Session,
+    pub enabled: bool,
+}
+
+/// `EnableSession` awaiting the account owner's signature of `hash`.
+#[derive(Clone)]
+pub struct PreparedEnableSession {
+    pub enable_session: EnableSession,
+    pub hash: B256,
+}
+
+impl PreparedEnableSession {
+    /// `signature` is the owner's signature of `hash` for the account's
+    /// `validator`, which Safe7579 routes the ERC-1271 check to.
+    pub fn sign(self, validator: Address, signature: Bytes) -> EnableSession {
+        EnableSession {
+            permissionEnableSig: [validator.as_slice(), signature.as_ref()]
+                .concat()
+                .into(),
+            ..self.enable_session
+        }
+    }
+}
+
+/// User operation executed with a session key, awaiting its signature.
+#[derive(Clone)]
+pub struct PreparedSessionUserOperation {
+    pub user_op: UserOperationV07,
+    pub hash: UserOperationHash,
+    pub permission_id: B256,
+    /// Enables the session in the same user operation, if set
+    pub enable_session: Option<EnableSession>,
+}
+
+impl PreparedSessionUserOperation {
+    pub fn sign(
+        self,
+        session_key: &impl SignerSync,
+    ) -> eyre::Result<UserOperationV07> {
+        let signature = session_key
+            .sign_hash_sync(&eip191_hash_message(self.hash.0))?
+            .as_bytes()
+            .into();
+        let signature = self.encode_signature(signature);
+        Ok(UserOperationV07 { signature, ..self.user_op })
+    }
+
+    fn encode_signature(&self, signature: Bytes) -> Bytes {
+        match &self.enable_session {
+            Some(enable_session) => encode_enable_signature(
+                SmartSessionEnableMode::Enable,
+                enable_session,
+                &signature,
+            ),
+            None => encode_use_signature(self.permission_id, signature),
+        }
+    }
+}
+
+impl SmartSessionsClient {
+    pub fn new(
+        owner: AccountAddress,
+        account: AccountAddress,
+        chain_id: u64,
+        config: Config,
+    ) -> Self {
+        Self { owner, account, chain_id, config }
+    }
+
+    pub fn account(&self) -> AccountAddress {
+        self.account
+    }
+
+    /// Prepares the owner's user operation enabling `sessions`. Sign its
+    /// `hash` and send it with `AccountClient::do_send_transactions`, then
+    /// store `sessions` once its receipt confirms success.
+    pub async fn prepare_grant_sessions(
+        &self,
+        sessions: &[Session],
+    ) -> eyre::Result<PreparedSendTransaction> {
+        self.prepare_owner_calls(vec![get_enable_sessions_call(sessions)]).await
+    }
+
+    /// Prepares the owner's user operation removing the session
+    /// `permission_id`, like `prepare_grant_sessions`.
+    pub async fn prepare_revoke_session(
+        &self,
+        permission_id: B256,
+    ) -> eyre::Result<PreparedSendTransaction> {
+        self.prepare_owner_calls(vec![get_remove_session_call(permission_id)])
+            .await
+    }
+
+    async fn prepare_owner_calls(
+        &self,
+        calls: Vec<Call>,
+    ) -> eyre::Result<PreparedSendTransaction> {
+        prepare_send_transactions(
+            calls,
+            self.owner.into(),
+            Some(self.account),
+            None,
+            self.config.clone(),
+        )
+        .await
+    }
+
+    pub async fn is_permission_enabled(
+        &self,
+        permission_id: B256,
+    ) -> eyre::Result<bool> {
+        let provider = ProviderBuilder::new()
+            .connect_http(self.config.endpoints.rpc.base_url.parse()?);
+        let enabled = ISmartSession::new(SMART_SESSIONS_ADDRESS, provider)
+            .isPermissionEnabled(permission_id, self.account.into())
+            .call()
+            .await?;
+        Ok(enabled)
+    }
+
+    /// Whether each of `sessions`, as stored by the caller, is currently
+    /// enabled on-chain. Duplicate sessions are listed once.
+    pub async fn list_sessions(
+        &self,
+        sessions: &[Session],
+    ) -> eyre::Result<Vec<SessionStatus>> {
+        let mut statuses = Vec::with_capacity(sessions.len());
+        for session in sessions {
+            let permission_id = get_permission_id(session);
+            if statuses.iter().any(|status: &SessionStatus| {
+                status.permission_id == permission_id
+            }) {
+                continue;
+            }
+            statuses.push(SessionStatus {
+                permission_id,
+                session: session.clone(),
+                enabled: self.is_permission_enabled(permission_id).await?,
+            });
+        }
+        Ok(statuses)
+    }
+
+    /// Prepares enabling `session` in the first user operation that uses
+    /// it, without a separate owner transaction. The owner signs `hash` as
+    /// an EIP-191 message.
+    pub async fn prepare_enable_session(
+        &self,
+        session: Session,
+    ) -> eyre::Result<PreparedEnableSession> {
+        let provider = ProviderBuilder::new()
+            .connect_http(self.config.endpoints.rpc.base_url.parse()?);
+        let session_digest =
+            ISmartSession::new(SMART_SESSIONS_ADDRESS, provider)
+                .getSessionDigest(
+                    get_permission_id(&session),
+                    self.account.into(),
+                    session.clone(),
+                    SmartSessionMode::Enable.to_u8(),
+                )
+                .call()
+                .await?;
+
+        let hashes_and_chain_ids = vec![ChainDigest {
+            chainId: self.chain_id,
+            sessionDigest: session_digest,
+        }];
+        let hash = get_multichain_digest(&hashes_and_chain_ids);
+
+        Ok(PreparedEnableSession {
+            enable_session: EnableSession {
+                chainDigestIndex: 0,
+                hashesAndChainIds: hashes_and_chain_ids,
+                sessionToEnable: session,
+                permissionEnableSig: Bytes::new(),
+            },
+            hash,
+        })
+    }
+
+    /// Prepares a sponsored user operation executing `calls` under the
+    /// session `permission_id`, whose validator is the OwnableValidator
+    /// with `session_owners`. Pass the signed `enable_session` when the
+    /// session isn't enabled yet.
+    pub async fn prepare_user_operation(
+        &self,
+        calls: Vec<Call>,
+        permission_id: B256,
+        session_owners: &Owners,
+        enable_session: Option<EnableSession>,
+    ) -> eyre::Result<PreparedSessionUserOperation> {
+        let provider = ProviderBuilder::new()
+            .connect_http(self.config.endpoints.rpc.base_url.parse()?);
+        let pimlico_client = PimlicoBundlerClient::new(BundlerConfig::new(
+            self.config.endpoints.bundler.base_url.parse()?,
+        ));
+        let paymaster_client = PaymasterClient::new(BundlerConfig::new(
+            self.config.endpoints.paymaster.base_url.parse()?,
+        ));
+
+        // The nonce key selects the validator Safe7579 validates with
+        let nonce = get_nonce_with_key(
+            &provider,
+            self.account,
+            &ENTRYPOINT_ADDRESS_V07.into(),
+            encode_validator_key(SMART_SESSIONS_ADDRESS),
+        )
+        .await?;
+        let gas_price =
+            pimlico_client.estimate_user_operation_gas_price().await?.fast;
+
+        let mut prepared = PreparedSessionUserOperation {
+            user_op: UserOperationV07 {
+                sender: self.account,
+                nonce,
+                factory: None,
+                factory_data: None,
+                call_data: get_call_data(calls),
+                call_gas_limit: U256::ZERO,
+                verification_gas_limit: U256::ZERO,
+                pre_verification_gas: U256::ZERO,
+                max_fee_per_gas: gas_price.max_fee_per_gas,
+                max_priority_fee_per_gas: gas_price.max_priority_fee_per_gas,
+                paymaster: None,
+                paymaster_verification_gas_limit: None,
+                paymaster_post_op_gas_limit: None,
+                paymaster_data: None,
+                signature: Bytes::new(),
+            },
+            hash: UserOperationHash::default(),
+            permission_id,
+            enable_session,
+        };
+        prepared.user_op.signature = prepared.encode_signature(
+            get_ownable_validator_mock_signature(session_owners),
+        );
+
+        let sponsor_user_op_result = paymaster_client
+            .sponsor_user_operation_v07(
+                &prepared.user_op.clone().into(),
+                &ENTRYPOINT_ADDRESS_V07.into(),
+                None,
+            )
+            .await?;
+        prepared.user_op = UserOperationV07 {
+            call_gas_limit: sponsor_user_op_result.call_gas_limit,
+            verification_gas_limit: sponsor_user_op_result
+                .verification_gas_limit,
+            pre_verification_gas: sponsor_user_op_result.pre_verification_gas,
+            paymaster: Some(sponsor_user_op_result.paymaster),
+            paymaster_verification_gas_limit: Some(
+                sponsor_user_op_result.paymaster_verification_gas_limit,
+            ),
+            paymaster_post_op_gas_limit: Some(
+                sponsor_user_op_result.paymaster_post_op_gas_limit,
+            ),
+            paymaster_data: Some(sponsor_user_op_result.paymaster_data),
+            ..prepared.user_op
+        };
+        prepared.hash = get_user_operation_hash_v07(
+            &prepared.user_op,
+            &ENTRYPOINT_ADDRESS_V07,
+            self.chain_id,
+        );
+        Ok(prepared)
+    }
+
+    /// Sends a user operation signed with `PreparedSessionUserOperation::sign`
+    pub async fn send_user_operation(
+        &self,
+        user_op: UserOperationV07,
+    ) -> eyre::Result<Bytes> {
+        BundlerClient::new(BundlerConfig::new(
+            self.config.endpoints.bundler.base_url.parse()?,
+        ))
+        .send_user_operation(ENTRYPOINT_ADDRESS_V07.into(), user_op)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::erc7579::{
+            ownable_validator::OWNABLE_VALIDATOR_ADDRESS,
+            smart_sessions::ERC7739Data,
+        },
+        alloy::signers::local::LocalSigner,
+    };
+
+    fn session() -> Session {
+        Session {
+            sessionValidator: OWNABLE_VALIDATOR_ADDRESS,
+            sessionValidatorInitData: Bytes::new(),
+            salt: B256::ZERO,
+            userOpPolicies: vec![],
+            erc7739Policies: ERC7739Data {
+                allowedERC7739Content: vec![],
+                erc1271Policies: vec![],
+            },
+            actions: vec![],
+            permitERC4337Paymaster: false,
+        }
+    }
+
+    #[test]
+    fn test_sign_use_mode() {
+        let session_key = LocalSigner::random();
+        let permission_id = get_permission_id(&session());
+        let prepared = PreparedSessionUserOperation {
+            user_op: UserOperationV07::mock(),
+            hash: UserOperationHash(B256::repeat_byte(0x11)),
+            permission_id,
+            enable_session: None,
+        };
+        let user_op = prepared.clone().sign(&session_key).unwrap();
+
+        // mode, permission ID and the 65-byte ECDSA signature
+        assert_eq!(user_op.signature.len(), 1 + 32 + 65);
+        assert_eq!(user_op.signature[0], SmartSessionMode::Use.to_u8());
+        assert_eq!(&user_op.signature[1..33], permission_id.as_slice());
+        let signature =
+            alloy::primitives::Signature::from_raw(&user_op.signature[33..])
+                .unwrap();
+        assert_eq!(
+            signature
+                .recover_address_from_prehash(&eip191_hash_message(
+                    prepared.hash.0
+                ))
+                .unwrap(),
+            session_key.address()
+        );
+    }
+
+    #[test]
+    fn test_sign_enable_mode() {
+        let validator = Address::repeat_byte(0x22);
+        let enable_session = PreparedEnableSession {
+            enable_session: EnableSession {
+                chainDigestIndex: 0,
+                hashesAndChainIds: vec![],
+                sessionToEnable: session(),
+                permissionEnableSig: Bytes::new(),
+            },
+            hash: B256::ZERO,
+        }
+        .sign(validator, Bytes::from(vec![0xab; 65]));
+        assert_eq!(
+            &enable_session.permissionEnableSig[..20],
+            validator.as_slice()
+        );
+        assert_eq!(enable_session.permissionEnableSig.len(), 20 + 65);
+
+        let user_op = PreparedSessionUserOperation {
+            user_op: UserOperationV07::mock(),
+            hash: UserOperationHash(B256::repeat_byte(0x11)),
+            permission_id: get_permission_id(&session()),
+            enable_session: Some(enable_session),
+        }
+        .sign(&LocalSigner::random())
+        .unwrap();
+        assert_eq!(user_op.signature[0], SmartSessionMode::Enable.to_u8());
+    }
+}
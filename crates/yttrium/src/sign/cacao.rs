@@ -0,0 +1,650 @@
+use {
+    crate::sign::protocol_types::{
+        AuthPayload, Cacao, CacaoHeader, CacaoPayload, CacaoSignature,
+        SettleNamespace, SettleNamespaces,
+    },
+    data_encoding::BASE64URL_NOPAD,
+    k256::ecdsa::{RecoveryId, Signature, VerifyingKey},
+    serde::{Deserialize, Serialize},
+    std::collections::{BTreeMap, HashMap},
+    tiny_keccak::{Hasher, Keccak},
+};
+
+pub const CACAO_HEADER_TYPE: &str = "caip122";
+pub const SIGNATURE_TYPE_EIP191: &str = "eip191";
+pub const SIGNATURE_TYPE_EIP1271: &str = "eip1271";
+
+const RECAP_PREFIX: &str = "urn:recap:";
+const RECAP_NAMESPACE: &str = "eip155";
+const RECAP_REQUEST_ABILITY: &str = "request";
+const RECAP_BASE_STATEMENT: &str = "I further authorize the stated URI to perform the following actions on my behalf:";
+const DID_PKH_PREFIX: &str = "did:pkh:";
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi_macros::Error))]
+#[error("Cacao error: {0}")]
+pub enum CacaoError {
+    #[error("Invalid issuer: {0}")]
+    InvalidIssuer(String),
+
+    #[error("Invalid ReCap: {0}")]
+    InvalidRecap(String),
+
+    #[error("Unsupported signature type: {0}")]
+    UnsupportedSignatureType(String),
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("Signature does not match issuer address")]
+    AddressMismatch,
+
+    #[error("Cacao {0} does not match the request")]
+    PayloadMismatch(String),
+}
+
+/// ReCap object as defined by ERC-5573, restricted to what 1CA needs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Recap {
+    pub att: BTreeMap<String, BTreeMap<String, Vec<serde_json::Value>>>,
+}
+
+/// Issuer of a cacao parsed from its `did:pkh` form, e.g.
+/// `did:pkh:eip155:1:0xabc`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DidPkh {
+    pub namespace: String,
+    pub reference: String,
+    pub address: String,
+}
+
+impl DidPkh {
+    pub fn parse(iss: &str) -> Result<Self, CacaoError> {
+        let account = iss
+            .strip_prefix(DID_PKH_PREFIX)
+            .ok_or_else(|| CacaoError::InvalidIssuer(iss.to_owned()))?;
+        let mut parts = account.splitn(3, ':');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(namespace), Some(reference), Some(address))
+                if !namespace.is_empty()
+                    && !reference.is_empty()
+                    && !address.is_empty() =>
+            {
+                Ok(Self {
+                    namespace: namespace.to_owned(),
+                    reference: reference.to_owned(),
+                    address: address.to_owned(),
+                })
+            }
+            _ => Err(CacaoError::InvalidIssuer(iss.to_owned())),
+        }
+    }
+
+    pub fn chain_id(&self) -> String {
+        format!("{}:{}", self.namespace, self.reference)
+    }
+
+    pub fn account(&self) -> String {
+        format!("{}:{}:{}", self.namespace, self.reference, self.address)
+    }
+}
+
+pub fn did_pkh(account: &str) -> String {
+    format!("{DID_PKH_PREFIX}{account}")
+}
+
+/// Encode the methods the dapp wants to call as an `urn:recap:` resource
+pub fn encode_recap(methods: &[String]) -> Result<String, CacaoError> {
+    let actions = methods
+        .iter()
+        .map(|method| {
+            (
+                format!("{RECAP_REQUEST_ABILITY}/{method}"),
+                vec![serde_json::json!({})],
+            )
+        })
+        .collect();
+    let recap =
+        Recap { att: BTreeMap::from([(RECAP_NAMESPACE.to_owned(), actions)]) };
+    let json = serde_json::to_vec(&recap)
+        .map_err(|e| CacaoError::InvalidRecap(e.to_string()))?;
+    Ok(format!("{RECAP_PREFIX}{}", BASE64URL_NOPAD.encode(&json)))
+}
+
+pub fn decode_recap(resource: &str) -> Result<Recap, CacaoError> {
+    let encoded = resource
+        .strip_prefix(RECAP_PREFIX)
+        .ok_or_else(|| CacaoError::InvalidRecap(resource.to_owned()))?;
+    // Some SDKs pad the base64url payload, tolerate it
+    let decoded = BASE64URL_NOPAD
+        .decode(encoded.trim_end_matches('=').as_bytes())
+        .map_err(|e| CacaoError::InvalidRecap(e.to_string()))?;
+    serde_json::from_slice(&decoded)
+        .map_err(|e| CacaoError::InvalidRecap(e.to_string()))
+}
+
+/// The ReCap is always the last resource when present
+pub fn recap_from_resources(
+    resources: Option<&[String]>,
+) -> Result<Option<Recap>, CacaoError> {
+    match resources.and_then(|resources| resources.last()) {
+        Some(resource) if resource.starts_with(RECAP_PREFIX) => {
+            decode_recap(resource).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// JSON-RPC methods granted by the `request/*` abilities of the ReCap
+pub fn methods_from_recap(recap: &Recap) -> Vec<String> {
+    recap
+        .att
+        .get(RECAP_NAMESPACE)
+        .map(|actions| {
+            actions
+                .keys()
+                .filter_map(|ability| {
+                    ability
+                        .strip_prefix(RECAP_REQUEST_ABILITY)
+                        .and_then(|a| a.strip_prefix('/'))
+                        .map(ToOwned::to_owned)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Human-readable statement for the ReCap, appended to the SIWE statement
+pub fn format_statement_from_recap(statement: &str, recap: &Recap) -> String {
+    if statement.contains(RECAP_BASE_STATEMENT) {
+        return statement.to_owned();
+    }
+
+    let mut counter = 0;
+    let recap_statement = recap
+        .att
+        .iter()
+        .map(|(resource, actions)| {
+            let mut abilities = BTreeMap::<&str, Vec<&str>>::new();
+            for action in actions.keys() {
+                let (ability, action) =
+                    action.split_once('/').unwrap_or((action, ""));
+                abilities.entry(ability).or_default().push(action);
+            }
+            abilities
+                .into_iter()
+                .map(|(ability, actions)| {
+                    counter += 1;
+                    format!(
+                        "({counter}) '{ability}': '{}' for '{resource}'.",
+                        actions.join("', '")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+                .replace(".,", ".")
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if statement.is_empty() {
+        format!("{RECAP_BASE_STATEMENT} {recap_statement}")
+    } else {
+        format!("{statement} {RECAP_BASE_STATEMENT} {recap_statement}")
+    }
+}
+
+/// Build the payload the wallet signs for one of its accounts, from the
+/// request sent by the dapp. `account` is a CAIP-10 account ID.
+pub fn build_cacao_payload(
+    auth_payload: &AuthPayload,
+    account: &str,
+) -> CacaoPayload {
+    CacaoPayload {
+        iss: did_pkh(account),
+        domain: auth_payload.domain.clone(),
+        aud: auth_payload.aud.clone(),
+        version: auth_payload.version.clone(),
+        nonce: auth_payload.nonce.clone(),
+        iat: auth_payload.iat.clone(),
+        nbf: auth_payload.nbf.clone(),
+        exp: auth_payload.exp.clone(),
+        statement: auth_payload.statement.clone(),
+        request_id: auth_payload.request_id.clone(),
+        resources: auth_payload.resources.clone(),
+    }
+}
+
+/// Format the CAIP-122 (SIWE) message for the payload
+pub fn format_message(payload: &CacaoPayload) -> Result<String, CacaoError> {
+    let issuer = DidPkh::parse(&payload.iss)?;
+    let statement = payload.statement.clone().unwrap_or_default();
+    let statement = match recap_from_resources(payload.resources.as_deref())? {
+        Some(recap) => format_statement_from_recap(&statement, &recap),
+        None => statement,
+    };
+
+    // EIP-4361 requires the address in its EIP-55 mixed-case form
+    let address = if issuer.namespace == RECAP_NAMESPACE {
+        to_checksum(&issuer.address)?
+    } else {
+        issuer.address.clone()
+    };
+
+    let mut lines = vec![
+        format!(
+            "{} wants you to sign in with your Ethereum account:",
+            payload.domain
+        ),
+        address,
+        String::new(),
+        statement,
+        String::new(),
+        format!("URI: {}", payload.aud),
+        format!("Version: {}", payload.version),
+        format!("Chain ID: {}", issuer.reference),
+        format!("Nonce: {}", payload.nonce),
+        format!("Issued At: {}", payload.iat),
+    ];
+    if let Some(exp) = &payload.exp {
+        lines.push(format!("Expiration Time: {exp}"));
+    }
+    if let Some(nbf) = &payload.nbf {
+        lines.push(format!("Not Before: {nbf}"));
+    }
+    if let Some(request_id) = &payload.request_id {
+        lines.push(format!("Request ID: {request_id}"));
+    }
+    if let Some(resources) = &payload.resources {
+        lines.push(format!(
+            "Resources:{}",
+            resources.iter().map(|r| format!("\n- {r}")).collect::<String>()
+        ));
+    }
+    Ok(lines.join("\n"))
+}
+
+pub fn build_cacao(payload: CacaoPayload, signature: CacaoSignature) -> Cacao {
+    Cacao {
+        h: CacaoHeader { t: CACAO_HEADER_TYPE.to_owned() },
+        p: payload,
+        s: signature,
+    }
+}
+
+/// Verify the cacao signature against its issuer.
+///
+/// Only `eip191` can be verified offline; `eip1271` requires an RPC call to
+/// the account contract and is reported as unsupported, so it must be
+/// rejected rather than trusted.
+pub fn verify_cacao(cacao: &Cacao) -> Result<(), CacaoError> {
+    match cacao.s.t.as_str() {
+        SIGNATURE_TYPE_EIP191 => {
+            let issuer = DidPkh::parse(&cacao.p.iss)?;
+            let message = format_message(&cacao.p)?;
+            let recovered = recover_eip191_address(&message, &cacao.s.s)?;
+            if recovered.eq_ignore_ascii_case(&issuer.address) {
+                Ok(())
+            } else {
+                Err(CacaoError::AddressMismatch)
+            }
+        }
+        other => Err(CacaoError::UnsupportedSignatureType(other.to_owned())),
+    }
+}
+
+/// Verify a cacao returned for `auth_payload`: the signature must be valid,
+/// and the cacao must sign in to the requested domain and URI, with the
+/// requested nonce, on one of the requested chains
+pub fn validate_cacao(
+    cacao: &Cacao,
+    auth_payload: &AuthPayload,
+) -> Result<(), CacaoError> {
+    verify_cacao(cacao)?;
+    if cacao.p.domain != auth_payload.domain {
+        return Err(CacaoError::PayloadMismatch("domain".to_owned()));
+    }
+    if cacao.p.aud != auth_payload.aud {
+        return Err(CacaoError::PayloadMismatch("aud".to_owned()));
+    }
+    if cacao.p.nonce != auth_payload.nonce {
+        return Err(CacaoError::PayloadMismatch("nonce".to_owned()));
+    }
+    let issuer = DidPkh::parse(&cacao.p.iss)?;
+    if !auth_payload.chains.contains(&issuer.chain_id()) {
+        return Err(CacaoError::PayloadMismatch("chain".to_owned()));
+    }
+    Ok(())
+}
+
+/// Session namespaces granted by a set of cacaos: one account per cacao
+/// issuer, and the methods from the ReCap of each cacao
+pub fn namespaces_from_cacaos(
+    cacaos: &[Cacao],
+) -> Result<SettleNamespaces, CacaoError> {
+    let mut namespaces = HashMap::<String, SettleNamespace>::new();
+    for cacao in cacaos {
+        let issuer = DidPkh::parse(&cacao.p.iss)?;
+        let methods = recap_from_resources(cacao.p.resources.as_deref())?
+            .map(|recap| methods_from_recap(&recap))
+            .unwrap_or_default();
+        let namespace = namespaces
+            .entry(issuer.namespace.clone())
+            .or_insert_with(|| SettleNamespace {
+                accounts: vec![],
+                methods: vec![],
+                events: vec![
+                    "chainChanged".to_owned(),
+                    "accountsChanged".to_owned(),
+                ],
+                chains: vec![],
+            });
+        push_unique(&mut namespace.accounts, issuer.account());
+        push_unique(&mut namespace.chains, issuer.chain_id());
+        for method in methods {
+            push_unique(&mut namespace.methods, method);
+        }
+    }
+    Ok(namespaces)
+}
+
+fn push_unique(values: &mut Vec<String>, value: String) {
+    if !values.contains(&value) {
+        values.push(value);
+    }
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(data);
+    let mut output = [0u8; 32];
+    hasher.finalize(&mut output);
+    output
+}
+
+/// EIP-55 checksummed form of a `0x`-prefixed hex address, whatever its case
+fn to_checksum(address: &str) -> Result<String, CacaoError> {
+    let lower = address
+        .strip_prefix("0x")
+        .filter(|hex| {
+            hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit())
+        })
+        .ok_or_else(|| CacaoError::InvalidIssuer(address.to_owned()))?
+        .to_ascii_lowercase();
+    let hash = keccak256(lower.as_bytes());
+    let checksummed = lower
+        .chars()
+        .enumerate()
+        .map(|(index, c)| {
+            let shift = if index % 2 == 0 { 4 } else { 0 };
+            if (hash[index / 2] >> shift) & 0x0f >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect::<String>();
+    Ok(format!("0x{checksummed}"))
+}
+
+fn recover_eip191_address(
+    message: &str,
+    signature: &str,
+) -> Result<String, CacaoError> {
+    let signature =
+        hex::decode(signature.strip_prefix("0x").unwrap_or(signature))
+            .map_err(|e| CacaoError::InvalidSignature(e.to_string()))?;
+    if signature.len() != 65 {
+        return Err(CacaoError::InvalidSignature(format!(
+            "expected 65 bytes, got {}",
+            signature.len()
+        )));
+    }
+    let recovery_id = match signature[64] {
+        v @ (0 | 1) => v,
+        v @ (27 | 28) => v - 27,
+        v => {
+            return Err(CacaoError::InvalidSignature(format!(
+                "invalid recovery id: {v}"
+            )));
+        }
+    };
+    let recovery_id = RecoveryId::from_byte(recovery_id).ok_or_else(|| {
+        CacaoError::InvalidSignature("invalid recovery id".to_owned())
+    })?;
+    let signature = Signature::from_slice(&signature[..64])
+        .map_err(|e| CacaoError::InvalidSignature(e.to_string()))?;
+
+    let prefixed = [
+        format!("\x19Ethereum Signed Message:\n{}", message.len()).as_bytes(),
+        message.as_bytes(),
+    ]
+    .concat();
+    let verifying_key = VerifyingKey::recover_from_prehash(
+        &keccak256(&prefixed),
+        &signature,
+        recovery_id,
+    )
+    .map_err(|e| CacaoError::InvalidSignature(e.to_string()))?;
+
+    let public_key = verifying_key.to_encoded_point(false);
+    let hash = keccak256(&public_key.as_bytes()[1..]);
+    Ok(format!("0x{}", hex::encode(&hash[12..])))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, k256::ecdsa::SigningKey};
+
+    fn auth_payload() -> AuthPayload {
+        AuthPayload {
+            payload_type: None,
+            chains: vec!["eip155:1".to_owned()],
+            domain: "app.example.com".to_owned(),
+            aud: "https://app.example.com/login".to_owned(),
+            nonce: "32891756".to_owned(),
+            version: "1".to_owned(),
+            iat: "2021-09-30T16:25:24Z".to_owned(),
+            nbf: None,
+            exp: None,
+            statement: Some("Sign in to Example".to_owned()),
+            request_id: None,
+            resources: Some(vec![
+                encode_recap(&[
+                    "personal_sign".to_owned(),
+                    "eth_sendTransaction".to_owned(),
+                ])
+                .unwrap(),
+            ]),
+            signature_types: None,
+        }
+    }
+
+    fn sign(key: &SigningKey, message: &str) -> String {
+        let prefixed = [
+            format!("\x19Ethereum Signed Message:\n{}", message.len())
+                .as_bytes(),
+            message.as_bytes(),
+        ]
+        .concat();
+        let (signature, recovery_id) =
+            key.sign_prehash_recoverable(&keccak256(&prefixed)).unwrap();
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(recovery_id.to_byte() + 27);
+        format!("0x{}", hex::encode(bytes))
+    }
+
+    fn address(key: &SigningKey) -> String {
+        let public_key = key.verifying_key().to_encoded_point(false);
+        let hash = keccak256(&public_key.as_bytes()[1..]);
+        format!("0x{}", hex::encode(&hash[12..]))
+    }
+
+    #[test]
+    fn test_parse_did_pkh() {
+        let did = DidPkh::parse("did:pkh:eip155:1:0xabc").unwrap();
+        assert_eq!(did.chain_id(), "eip155:1");
+        assert_eq!(did.account(), "eip155:1:0xabc");
+        assert!(DidPkh::parse("eip155:1:0xabc").is_err());
+        assert!(DidPkh::parse("did:pkh:eip155:1").is_err());
+    }
+
+    #[test]
+    fn test_recap_roundtrip() {
+        let resource = encode_recap(&["personal_sign".to_owned()]).unwrap();
+        let recap = decode_recap(&resource).unwrap();
+        assert_eq!(methods_from_recap(&recap), vec!["personal_sign"]);
+    }
+
+    #[test]
+    fn test_format_statement_from_recap() {
+        let recap = decode_recap(
+            &encode_recap(&[
+                "personal_sign".to_owned(),
+                "eth_sendTransaction".to_owned(),
+            ])
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            format_statement_from_recap("Sign in", &recap),
+            "Sign in I further authorize the stated URI to perform the following actions on my behalf: (1) 'request': 'eth_sendTransaction', 'personal_sign' for 'eip155'."
+        );
+    }
+
+    #[test]
+    fn test_format_message() {
+        let mut payload = build_cacao_payload(
+            &auth_payload(),
+            "eip155:1:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+        );
+        payload.resources = None;
+        assert_eq!(
+            format_message(&payload).unwrap(),
+            "app.example.com wants you to sign in with your Ethereum account:
+0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed
+
+Sign in to Example
+
+URI: https://app.example.com/login
+Version: 1
+Chain ID: 1
+Nonce: 32891756
+Issued At: 2021-09-30T16:25:24Z"
+        );
+
+        payload.iss = did_pkh("eip155:1:0xabc");
+        assert_eq!(
+            format_message(&payload),
+            Err(CacaoError::InvalidIssuer("0xabc".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_verify_eip191_cacao() {
+        let key = SigningKey::random(&mut rand::thread_rng());
+        let payload = build_cacao_payload(
+            &auth_payload(),
+            &format!("eip155:1:{}", address(&key)),
+        );
+        let message = format_message(&payload).unwrap();
+        let cacao = build_cacao(
+            payload,
+            CacaoSignature {
+                t: SIGNATURE_TYPE_EIP191.to_owned(),
+                s: sign(&key, &message),
+                m: None,
+            },
+        );
+        assert_eq!(verify_cacao(&cacao), Ok(()));
+
+        let namespaces = namespaces_from_cacaos(&[cacao.clone()]).unwrap();
+        let eip155 = namespaces.get("eip155").unwrap();
+        assert_eq!(eip155.chains, vec!["eip155:1"]);
+        assert_eq!(
+            eip155.methods,
+            vec!["eth_sendTransaction", "personal_sign"]
+        );
+
+        let mut tampered = cacao;
+        tampered.p.nonce = "1".to_owned();
+        assert_eq!(verify_cacao(&tampered), Err(CacaoError::AddressMismatch));
+    }
+
+    #[test]
+    fn test_verify_unsupported_signature_type() {
+        let cacao = build_cacao(
+            build_cacao_payload(&auth_payload(), "eip155:1:0xabc"),
+            CacaoSignature {
+                t: SIGNATURE_TYPE_EIP1271.to_owned(),
+                s: "0x".to_owned(),
+                m: None,
+            },
+        );
+        assert_eq!(
+            verify_cacao(&cacao),
+            Err(CacaoError::UnsupportedSignatureType(
+                SIGNATURE_TYPE_EIP1271.to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_validate_cacao() {
+        let key = SigningKey::random(&mut rand::thread_rng());
+        let signed = |payload: CacaoPayload| {
+            let message = format_message(&payload).unwrap();
+            build_cacao(
+                payload,
+                CacaoSignature {
+                    t: SIGNATURE_TYPE_EIP191.to_owned(),
+                    s: sign(&key, &message),
+                    m: None,
+                },
+            )
+        };
+        let account = format!("eip155:1:{}", address(&key));
+        let request = auth_payload();
+
+        let payload = build_cacao_payload(&request, &account);
+        assert_eq!(validate_cacao(&signed(payload.clone()), &request), Ok(()));
+
+        let mut other_nonce = payload.clone();
+        other_nonce.nonce = "1".to_owned();
+        assert_eq!(
+            validate_cacao(&signed(other_nonce), &request),
+            Err(CacaoError::PayloadMismatch("nonce".to_owned()))
+        );
+
+        let mut other_domain = payload;
+        other_domain.domain = "evil.example.com".to_owned();
+        assert_eq!(
+            validate_cacao(&signed(other_domain), &request),
+            Err(CacaoError::PayloadMismatch("domain".to_owned()))
+        );
+
+        let other_chain = build_cacao_payload(
+            &request,
+            &format!("eip155:10:{}", address(&key)),
+        );
+        assert_eq!(
+            validate_cacao(&signed(other_chain), &request),
+            Err(CacaoError::PayloadMismatch("chain".to_owned()))
+        );
+
+        let unverifiable = build_cacao(
+            build_cacao_payload(&request, &account),
+            CacaoSignature {
+                t: SIGNATURE_TYPE_EIP1271.to_owned(),
+                s: "0x".to_owned(),
+                m: None,
+            },
+        );
+        assert_eq!(
+            validate_cacao(&unverifiable, &request),
+            Err(CacaoError::UnsupportedSignatureType(
+                SIGNATURE_TYPE_EIP1271.to_owned()
+            ))
+        );
+    }
+}
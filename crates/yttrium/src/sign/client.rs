@@ -1,37 +1,63 @@
 use {
     crate::sign::{
+        cacao::{
+            CACAO_HEADER_TYPE, encode_recap, namespaces_from_cacaos,
+            validate_cacao,
+        },
         client_errors::{
-            ApproveError, ConnectError, DisconnectError, EmitError,
-            ExtendError, PairError, RejectError, RequestError, RespondError,
-            UpdateError,
+            ApproveError, ApproveSessionAuthenticateError, AuthenticateError,
+            ConnectError, DisconnectError, EmitError, ExtendError, PairError,
+            PingError, RejectError, RejectSessionAuthenticateError,
+            RequestError, RespondError, UpdateError,
         },
         client_types::{
-            ConnectParams, ConnectResult, PairingInfo, RejectionReason,
-            Session, SessionProposal, TransportType,
+            AuthenticateParams, ConnectParams, ConnectResult, PairResult,
+            PairingInfo, PendingSessionRequest, RejectionReason, Session,
+            SessionAuthenticateRequest, SessionProposal, TransportType,
         },
         envelope_type0::decrypt_type0_envelope_with_hashes,
+        expiry,
+        incoming::{self, HandleError, IncomingMessage, PendingPings},
+        link_mode::{self, LinkModeError},
+        namespaces::{
+            validate_approved_namespaces, validate_proposal_namespaces,
+        },
         pairing_uri,
         protocol_types::{
-            Controller, GenericJsonRpcResponseError, JsonRpcRequest,
-            JsonRpcRequestParams, JsonRpcVersion, Metadata, Proposal,
-            ProposalJsonRpc, ProposalResponse, ProposalResultResponseJsonRpc,
-            Proposer, ProtocolRpcId, Relay, SessionDelete,
+            AuthPayload, Cacao, Controller, GenericJsonRpcResponseError,
+            GenericJsonRpcResponseErrorData, JsonRpcRequest,
+            JsonRpcRequestParams, JsonRpcVersion, Metadata, Participant,
+            Proposal, ProposalJsonRpc, ProposalResponse,
+            ProposalResultResponseJsonRpc, Proposer, ProtocolRpcId, Relay,
+            SessionAuthenticate, SessionAuthenticateJsonRpc,
+            SessionAuthenticateResponse,
+            SessionAuthenticateResultResponseJsonRpc, SessionDelete,
             SessionDeleteJsonRpc, SessionExtend, SessionExtendJsonRpc,
-            SessionRequest, SessionRequestJsonRpc,
-            SessionRequestJsonRpcResponse, SessionSettle, SessionUpdate,
-            SettleNamespace,
+            SessionPing, SessionPingJsonRpc, SessionRequest,
+            SessionRequestJsonRpc, SessionRequestJsonRpcResponse,
+            SessionSettle, SessionUpdate, SettleNamespace, methods,
         },
         relay::{Attestation, AttestationCallback, IncomingSessionMessage},
-        storage::Storage,
+        relay_pool::{
+            DEFAULT_RELAY_URL, RelayConnectionState, RelayEndpoint, RelayPool,
+            RelayPoolError, SharedRelayPool,
+        },
+        storage::{Storage, StorageError},
         utils::{
-            DecryptedHash, EncryptedHash, diffie_hellman, is_expired,
-            serialize_and_encrypt_message_type0_envelope,
+            DecryptedHash, EncryptedHash, compute_max_expiry, diffie_hellman,
+            is_expired, serialize_and_encrypt_message_type0_envelope,
             serialize_and_encrypt_message_type0_envelope_with_ids,
+            serialize_and_encrypt_message_type1_envelope,
+            serialize_and_encrypt_session_authenticate_rejection,
             topic_from_sym_key,
         },
         verify::{
             VERIFY_SERVER_URL,
-            validate::{VerifyContext, handle_verify},
+            policy::{OriginDecision, OriginPolicy},
+            validate::{
+                VerifyContext, VerifyKeyRotation, VerifyMode, VerifyState,
+                handle_verify,
+            },
         },
     },
     relay_rpc::{
@@ -39,18 +65,42 @@ use {
         domain::{ProjectId, Topic},
         rpc::{
             AnalyticsData, ApproveSession, FetchMessages, FetchResponse,
-            Params, ProposeSession, Publish, Response,
+            Params, ProposeSession, Publish, Response, Subscribe,
         },
     },
     serde::de::DeserializeOwned,
-    std::{collections::HashMap, sync::Arc},
+    std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+    },
     tracing::debug,
     x25519_dalek::PublicKey,
 };
 
+// How long `Client::ping()` waits for the peer, matching the `wc_sessionPing` TTL
+pub const PING_TIMEOUT: crate::time::Duration =
+    crate::time::Duration::from_secs(30);
+
 pub fn get_relay_url() -> String {
-    std::env::var("WC_SIGN_RELAY_URL")
-        .unwrap_or_else(|_| "wss://relay.walletconnect.org".to_owned())
+    get_relay_endpoints().swap_remove(0).url
+}
+
+// `WC_SIGN_RELAY_URL` may be a comma-separated list of relays, in order of preference
+pub fn get_relay_endpoints() -> Vec<RelayEndpoint> {
+    let endpoints = std::env::var("WC_SIGN_RELAY_URL")
+        .map(|urls| {
+            urls.split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(|url| RelayEndpoint::new(url.to_owned()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if endpoints.is_empty() {
+        vec![RelayEndpoint::new(DEFAULT_RELAY_URL.to_owned())]
+    } else {
+        endpoints
+    }
 }
 
 // Abstraction for requests that may need Verify API attestation (internal)
@@ -80,6 +130,10 @@ pub struct Client {
     signing_key_bytes: [u8; 32],
     pending_request_rx: Option<RpcRequestReceiver>,
     pending_online_rx: Option<tokio::sync::mpsc::UnboundedReceiver<()>>,
+    pending_pings: PendingPings,
+    verify_state: VerifyState,
+    relay_pool: SharedRelayPool,
+    connection_state_tx: tokio::sync::watch::Sender<RelayConnectionState>,
     probe_group: Option<String>,
 }
 
@@ -94,9 +148,7 @@ pub struct Client {
 // TODO
 // - session pings, update, events, emit, extend
 // - emit events for session pings, update, events, extend, disconnect
-// - session expiry & renew
-//   - expire implemented simply by filtering out expired sessions in `Client::add_sessions()` ?
-//     - long-lived clients might suffer here. Maybe filter each reconnect?
+// - session renew
 
 // TODO error improvement
 // - bundle size optimization: error enums only for actionable errors higher-up
@@ -118,8 +170,6 @@ pub struct Client {
 
 // TODO
 // - Verify API
-// - 1CA
-// - Link Mode
 // - Events SDK & Analytics/TVF
 //   - Additional events for measuring latency/reconnect performance/client network environment/etc. so we can tune. E.g. "should we retry to connect?"
 // - Network state hinting (offline/online)
@@ -169,6 +219,14 @@ impl Client {
                 signing_key_bytes: SigningKey::from_bytes(&key).to_bytes(),
                 pending_request_rx: Some(request_rx),
                 pending_online_rx: Some(online_rx),
+                pending_pings: PendingPings::default(),
+                verify_state: VerifyState::default(),
+                relay_pool: Arc::new(std::sync::Mutex::new(
+                    RelayPool::new(get_relay_endpoints()).unwrap_or_default(),
+                )),
+                connection_state_tx: tokio::sync::watch::Sender::new(
+                    RelayConnectionState::Disconnected,
+                ),
                 probe_group: None,
             },
             rx,
@@ -179,6 +237,61 @@ impl Client {
         self.probe_group = Some(probe_group);
     }
 
+    /// Replaces the relays to connect to, in order of preference. Defaults to `get_relay_endpoints()`.
+    /// Takes effect on the next connection attempt. Rejects an empty list.
+    pub fn set_relay_endpoints(
+        &mut self,
+        endpoints: Vec<RelayEndpoint>,
+    ) -> Result<(), RelayPoolError> {
+        let relay_pool = RelayPool::new(endpoints)?;
+        *self
+            .relay_pool
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = relay_pool;
+        Ok(())
+    }
+
+    /// `VerifyMode::Offline` avoids waiting on the verify server when it is slow or unreachable.
+    pub fn set_verify_mode(&self, mode: VerifyMode) {
+        self.verify_state.set_mode(mode);
+    }
+
+    /// Proposals and requests from origins blocked by this policy are rejected automatically.
+    pub fn set_origin_policy(&self, origin_policy: OriginPolicy) {
+        self.verify_state.set_origin_policy(origin_policy);
+    }
+
+    /// The origin policy decision for a `VerifyContext`, e.g. to show why a proposal or request is allowed.
+    pub fn evaluate_origin(
+        &self,
+        verify_context: &VerifyContext,
+        app_metadata_url: &str,
+    ) -> OriginDecision {
+        self.verify_state.evaluate_origin(verify_context, app_metadata_url)
+    }
+
+    /// Subscribe to verify public key rotations.
+    pub fn verify_key_rotations(
+        &self,
+    ) -> tokio::sync::broadcast::Receiver<VerifyKeyRotation> {
+        self.verify_state.subscribe_key_rotations()
+    }
+
+    /// Watch the relay connection state, including which relay is active.
+    pub fn connection_state(
+        &self,
+    ) -> tokio::sync::watch::Receiver<RelayConnectionState> {
+        self.connection_state_tx.subscribe()
+    }
+
+    fn active_relay(&self) -> Relay {
+        let relay_pool = self
+            .relay_pool
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        Relay { protocol: relay_pool.active().protocol.clone() }
+    }
+
     #[tracing::instrument(skip_all, fields(group = self.probe_group.clone()))]
     pub fn start(&mut self) {
         if let (Some(request_rx), Some(online_rx)) =
@@ -196,7 +309,8 @@ impl Client {
 
             crate::spawn::spawn(
                 crate::sign::relay::connect_loop_state_machine(
-                    get_relay_url(),
+                    self.relay_pool.clone(),
+                    self.connection_state_tx.clone(),
                     project_id,
                     signing_key,
                     session_store,
@@ -204,13 +318,35 @@ impl Client {
                     tx,
                     request_rx,
                     online_rx,
-                    cleanup_rx,
+                    cleanup_rx.clone(),
+                    self.pending_pings.clone(),
+                    self.verify_state.clone(),
                     self.probe_group.clone(),
                 ),
             );
+
+            crate::spawn::spawn(expiry::sweep_loop(
+                self.storage.clone(),
+                self.tx.downgrade(),
+                self.request_tx.downgrade(),
+                cleanup_rx,
+                self.probe_group.clone(),
+            ));
         }
     }
 
+    /// Deletes expired sessions and pairings now, instead of waiting for the next periodic sweep.
+    /// Useful e.g. when the app returns to the foreground. Emits `SessionExpired` for each deleted session.
+    #[tracing::instrument(skip_all, fields(group = self.probe_group.clone()))]
+    pub fn prune_expired(&self) -> Result<(), StorageError> {
+        expiry::sweep(
+            self.storage.as_ref(),
+            &self.tx,
+            &self.request_tx,
+            self.probe_group.clone(),
+        )
+    }
+
     /// Call this when the app and user are ready to receive session requests.
     /// Skip calling this if you intend to shortly call another SDK method, as those other methods will themselves call this.
     /// TODO actually call this from other methods
@@ -225,11 +361,43 @@ impl Client {
         }
     }
 
+    /// Handle a `wc_*` message received over Link Mode, i.e. a universal link opened by the peer.
+    /// Resulting events are emitted the same way as for messages received over the relay.
     #[tracing::instrument(skip_all, fields(group = self.probe_group.clone()))]
-    pub async fn pair(
+    pub async fn dispatch_link_mode_url(
         &mut self,
-        uri: &str,
-    ) -> Result<(SessionProposal, VerifyContext), PairError> {
+        url: &str,
+    ) -> Result<(), LinkModeError> {
+        let link_mode_message = link_mode::decode_url(url)?;
+        let result = incoming::handle_message(
+            self.storage.clone(),
+            self.http_client.clone(),
+            IncomingMessage {
+                topic: link_mode_message.topic,
+                message: link_mode_message.message,
+                attestation: None,
+                tag: link_mode_message.tag,
+                transport_type: TransportType::LinkMode,
+            },
+            self.tx.clone(),
+            self.request_tx.clone(),
+            self.pending_pings.clone(),
+            self.verify_state.clone(),
+            self.probe_group.clone(),
+        )
+        .await;
+        match result {
+            Ok(()) => Ok(()),
+            Err(HandleError::AlreadyHandled) => {
+                tracing::debug!("Link Mode message already handled, ignoring");
+                Ok(())
+            }
+            Err(e) => Err(LinkModeError::Handle(e.to_string())),
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(group = self.probe_group.clone()))]
+    pub async fn pair(&mut self, uri: &str) -> Result<PairResult, PairError> {
         // TODO implement
         // https://github.com/WalletConnect/walletconnect-monorepo/blob/5bef698dcf0ae910548481959a6a5d87eaf7aaa5/packages/sign-client/src/controllers/engine.ts#L330
 
@@ -238,10 +406,23 @@ impl Client {
 
         let pairing_uri = pairing_uri::parse(uri)
             .map_err(|e| PairError::Internal(e.to_string()))?;
+        if pairing_uri.expiry_timestamp.is_some_and(is_expired) {
+            return Err(PairError::Expired);
+        }
+        // All pairings share the active relay, so the advertised protocol must match it
+        let supported = self
+            .relay_pool
+            .lock()
+            .map_err(|e| PairError::ShouldNeverHappen(e.to_string()))?
+            .serves_protocol(&pairing_uri.relay_protocol);
+        if !supported {
+            return Err(PairError::UnsupportedRelayProtocol(
+                pairing_uri.relay_protocol,
+            ));
+        }
 
         tracing::debug!("Pairing with URI: {uri}");
 
-        // TODO consider: immediately throw if expired? - maybe not necessary since FetchMessages returns empty array?
         // TODO update relay method to not remove message & approveSession removes it
 
         let response = self
@@ -253,6 +434,21 @@ impl Client {
 
         tracing::debug!("Pairing Response: {:?}", response);
 
+        // One-Click Auth dapps may send a fallback wc_sessionPropose alongside wc_sessionAuthenticate, which is left unanswered
+        if let Some(message) =
+            response.messages.iter().find(|message| message.tag == 1116)
+        {
+            let (request, attestation) = self
+                .handle_session_authenticate_message(
+                    &pairing_uri.topic,
+                    pairing_uri.sym_key,
+                    &message.message,
+                    message.attestation.clone(),
+                )
+                .await?;
+            return Ok(PairResult::Authenticate(request, attestation));
+        }
+
         let message = response
             .messages
             .iter()
@@ -339,6 +535,7 @@ impl Client {
             decrypted_hash,
             self.http_client.clone(),
             self.storage.clone(),
+            &self.verify_state,
             message.attestation.clone(),
             encrypted_hash.clone(),
             proposal.proposer.metadata.url.clone(),
@@ -346,22 +543,41 @@ impl Client {
         )
         .await;
 
-        Ok((
-            SessionProposal {
-                session_proposal_rpc_id: request.id,
-                pairing_topic: pairing_uri.topic,
-                pairing_sym_key: pairing_uri.sym_key,
-                proposer_public_key,
-                relays: proposal.relays,
-                required_namespaces: proposal.required_namespaces,
-                optional_namespaces: proposal.optional_namespaces,
-                metadata: proposal.proposer.metadata,
-                session_properties: proposal.session_properties,
-                scoped_properties: proposal.scoped_properties,
-                expiry_timestamp: proposal.expiry_timestamp,
-            },
-            attestation,
-        ))
+        let session_proposal = SessionProposal {
+            session_proposal_rpc_id: request.id,
+            pairing_topic: pairing_uri.topic,
+            pairing_sym_key: pairing_uri.sym_key,
+            proposer_public_key,
+            relays: proposal.relays,
+            required_namespaces: proposal.required_namespaces,
+            optional_namespaces: proposal.optional_namespaces,
+            metadata: proposal.proposer.metadata,
+            session_properties: proposal.session_properties,
+            scoped_properties: proposal.scoped_properties,
+            expiry_timestamp: proposal.expiry_timestamp,
+        };
+
+        let decision = self
+            .verify_state
+            .evaluate_origin(&attestation, &session_proposal.metadata.url);
+        if decision.blocked {
+            tracing::debug!(
+                "Session proposal {} blocked by origin policy: {}",
+                request.id,
+                decision.message
+            );
+            if let Err(e) = self
+                .reject(session_proposal, RejectionReason::UserRejected)
+                .await
+            {
+                tracing::warn!(
+                    "Failed to reject blocked session proposal: {e}"
+                );
+            }
+            return Err(PairError::OriginBlocked(decision));
+        }
+
+        Ok(PairResult::Proposal(session_proposal, attestation))
     }
 
     #[tracing::instrument(skip_all, fields(group = self.probe_group.clone()))]
@@ -374,7 +590,8 @@ impl Client {
         self.is_valid_connect(&params)?;
 
         // Always create new pairing topic (reuse is deprecated)
-        let pairing_info = Self::create_pairing().await?;
+        let relay = self.active_relay();
+        let pairing_info = Self::create_pairing(relay.clone()).await?;
         let uri = pairing_info.uri.clone();
         let sym_key = pairing_info.sym_key.clone().try_into().unwrap();
         let expiry_timestamp = pairing_info.expiry;
@@ -383,7 +600,7 @@ impl Client {
         let self_public_key = PublicKey::from(&self_key);
 
         let session_proposal = Proposal {
-            relays: vec![Relay { protocol: "irn".to_string() }],
+            relays: vec![relay],
             required_namespaces: HashMap::new(), // Deprecated, now empty
             optional_namespaces: params.optional_namespaces,
             proposer: Proposer {
@@ -464,6 +681,7 @@ impl Client {
             rpc_id,
             sym_key,
             self_key.to_bytes(),
+            expiry_timestamp,
         );
         tracing::debug!(probe = "pairing_saved",);
 
@@ -471,7 +689,7 @@ impl Client {
         Ok(ConnectResult { topic: pairing_info.topic.clone(), uri })
     }
 
-    async fn create_pairing() -> Result<PairingInfo, ConnectError> {
+    async fn create_pairing(relay: Relay) -> Result<PairingInfo, ConnectError> {
         let sym_key = x25519_dalek::StaticSecret::random();
         let pairing_topic = topic_from_sym_key(sym_key.as_bytes());
         let expiry = crate::time::SystemTime::now()
@@ -479,7 +697,6 @@ impl Client {
             .unwrap()
             .as_secs()
             + 5 * 60;
-        let relay = Relay { protocol: "irn".to_string() };
         let uri = pairing_uri::format(&pairing_topic, &sym_key, &relay, expiry);
         let pairing_info = PairingInfo {
             topic: pairing_topic.clone(),
@@ -501,13 +718,13 @@ impl Client {
     // TODO implement and move to utils
     fn is_valid_connect(
         &self,
-        _params: &ConnectParams,
+        params: &ConnectParams,
     ) -> Result<(), ConnectError> {
-        // TODO: Implement validation logic
-        // - Check if namespaces are valid
+        // TODO: Implement remaining validation logic
         // - Validate metadata
         // - Check other constraints
-        Ok(())
+        validate_proposal_namespaces(&params.optional_namespaces)
+            .map_err(ConnectError::InvalidNamespaces)
     }
 
     #[tracing::instrument(skip_all, fields(group = self.probe_group.clone()))]
@@ -520,7 +737,12 @@ impl Client {
         // TODO implement
         // https://github.com/WalletConnect/walletconnect-monorepo/blob/5bef698dcf0ae910548481959a6a5d87eaf7aaa5/packages/sign-client/src/controllers/engine.ts#L341
 
-        // TODO check is valid: validate namespaces, validate metadata, validate expiry timestamp
+        // TODO check is valid: validate metadata, validate expiry timestamp
+        validate_approved_namespaces(
+            &proposal.required_namespaces,
+            &approved_namespaces,
+        )
+        .map_err(ApproveError::InvalidNamespaces)?;
 
         let self_key = x25519_dalek::StaticSecret::random();
         let self_public_key = PublicKey::from(&self_key);
@@ -528,12 +750,13 @@ impl Client {
             diffie_hellman(&proposal.proposer_public_key.into(), &self_key);
         let session_topic = topic_from_sym_key(&shared_secret);
         debug!("session topic: {}", session_topic);
+        let relay = self.active_relay();
 
         let response_result_json_rpc = ProposalResultResponseJsonRpc {
             id: proposal.session_proposal_rpc_id,
             jsonrpc: JsonRpcVersion::version_2(),
             result: ProposalResponse {
-                relay: Relay { protocol: "irn".to_string() },
+                relay: relay.clone(),
                 responder_public_key: hex::encode(self_public_key.to_bytes()),
             },
         };
@@ -559,7 +782,7 @@ impl Client {
 
         let session_settlement_request_id = ProtocolRpcId::generate();
         let session_settlement_request_params = SessionSettle {
-            relay: Relay { protocol: "irn".to_string() },
+            relay: relay.clone(),
             namespaces: approved_namespaces.clone(),
             controller: Controller {
                 public_key: hex::encode(self_public_key.to_bytes()),
@@ -613,7 +836,7 @@ impl Client {
             self_public_key: self_public_key.to_bytes(),
             topic: session_topic,
             expiry: session_expiry,
-            relay_protocol: "irn".to_string(),
+            relay_protocol: relay.protocol,
             relay_data: None,
             controller_key: Some(self_public_key.to_bytes()),
             self_meta_data: self_metadata.clone(),
@@ -814,48 +1037,66 @@ impl Client {
         Ok(rpc.id)
     }
 
+    /// Responds over Link Mode instead of the relay when both sides advertise it in their `Redirect` metadata.
+    /// In that case nothing is published and the returned URL must be opened by the host app.
     #[tracing::instrument(skip_all, fields(group = self.probe_group.clone()))]
     pub async fn respond(
         &mut self,
         topic: Topic,
         response: SessionRequestJsonRpcResponse,
-    ) -> Result<(), RespondError> {
-        let shared_secret = self
+    ) -> Result<Option<String>, RespondError> {
+        let session = self
             .storage
             .get_session(topic.clone())
             .map_err(RespondError::Storage)?
-            .map(|s| s.session_sym_key)
             .ok_or(RespondError::SessionNotFound)?;
 
         let message = serialize_and_encrypt_message_type0_envelope(
-            shared_secret,
+            session.session_sym_key,
             &response,
         )
         .map_err(RespondError::ShouldNeverHappen)?;
 
-        self.do_request::<bool>(relay_rpc::rpc::Params::Publish(Publish {
-            topic,
-            message,
-            attestation: None,
-            ttl_secs: 300,
-            tag: 1109,
-            prompt: false,
-            analytics: Some(AnalyticsData {
-                correlation_id: Some(
-                    match &response {
-                        SessionRequestJsonRpcResponse::Result(r) => r.id,
-                        SessionRequestJsonRpcResponse::Error(e) => e.id,
-                    }
-                    .into_value() as i64,
-                ),
-                chain_id: None,
-                rpc_methods: None,
-                tx_hashes: None,
-                contract_addresses: None,
-            }),
-        }))
-        .await
-        .map_err(RespondError::Request)?;
+        // wc_sessionRequest response
+        let tag = 1109;
+        let link_mode_url = if let Some(universal_link) =
+            link_mode::should_use_link_mode(
+                &session.self_meta_data,
+                session.peer_meta_data.as_ref(),
+            ) {
+            Some(
+                link_mode::encode_url(universal_link, &topic, &message, tag)
+                    .map_err(RespondError::LinkMode)?,
+            )
+        } else {
+            None
+        };
+
+        if link_mode_url.is_none() {
+            self.do_request::<bool>(relay_rpc::rpc::Params::Publish(Publish {
+                topic,
+                message,
+                attestation: None,
+                ttl_secs: 300,
+                tag,
+                prompt: false,
+                analytics: Some(AnalyticsData {
+                    correlation_id: Some(
+                        match &response {
+                            SessionRequestJsonRpcResponse::Result(r) => r.id,
+                            SessionRequestJsonRpcResponse::Error(e) => e.id,
+                        }
+                        .into_value() as i64,
+                    ),
+                    chain_id: None,
+                    rpc_methods: None,
+                    tx_hashes: None,
+                    contract_addresses: None,
+                }),
+            }))
+            .await
+            .map_err(RespondError::Request)?;
+        }
 
         let response_result_json = serde_json::to_string_pretty(&response)
             .map_err(|e| RespondError::ShouldNeverHappen(e.to_string()))?;
@@ -875,7 +1116,45 @@ impl Client {
             }
         }
 
-        Ok(())
+        Ok(link_mode_url)
+    }
+
+    /// Session requests received by this wallet that were not responded to yet, e.g. because they arrived while the app was backgrounded or before a restart.
+    /// Expired requests, and requests on sessions that no longer exist, are skipped. Oldest requests come first.
+    /// Verify context is not persisted, so it has to be re-checked by the caller if needed.
+    #[tracing::instrument(skip_all, fields(group = self.probe_group.clone()))]
+    pub fn get_pending_session_requests(
+        &self,
+    ) -> Result<Vec<PendingSessionRequest>, StorageError> {
+        let session_topics = self
+            .storage
+            .get_all_sessions()?
+            .into_iter()
+            .map(|session| session.topic)
+            .collect::<HashSet<_>>();
+        let mut pending = Vec::new();
+        for entry in self.storage.get_pending_json_rpc_requests(
+            methods::SESSION_REQUEST.to_owned(),
+        )? {
+            if !session_topics.contains(&entry.topic) {
+                continue;
+            }
+            let request = match serde_json::from_str::<SessionRequestJsonRpc>(
+                &entry.body,
+            ) {
+                Ok(request) => request,
+                Err(e) => {
+                    tracing::warn!("Skipping unparsable session request: {e}");
+                    continue;
+                }
+            };
+            if request.params.request.expiry.is_some_and(is_expired) {
+                continue;
+            }
+            pending.push(PendingSessionRequest { topic: entry.topic, request });
+        }
+        pending.sort_by_key(|pending| pending.request.id.into_value());
+        Ok(pending)
     }
 
     #[tracing::instrument(skip_all, fields(group = self.probe_group.clone()))]
@@ -1026,11 +1305,85 @@ impl Client {
         Ok(())
     }
 
+    /// Sends `wc_sessionPing` and waits up to `PING_TIMEOUT` for the peer to respond.
+    /// Returns the round-trip time, which includes relay latency on both legs as well as the peer's processing time.
     #[tracing::instrument(skip_all, fields(group = self.probe_group.clone()))]
-    pub async fn _ping(&self) {
-        // TODO implement
-        // https://github.com/WalletConnect/walletconnect-monorepo/blob/5bef698dcf0ae910548481959a6a5d87eaf7aaa5/packages/sign-client/src/controllers/engine.ts#L727
-        unimplemented!()
+    pub async fn ping(
+        &mut self,
+        topic: Topic,
+    ) -> Result<crate::time::Duration, PingError> {
+        let shared_secret = self
+            .storage
+            .get_session(topic.clone())
+            .map_err(PingError::Storage)?
+            .map(|s| s.session_sym_key)
+            .ok_or(PingError::SessionNotFound)?;
+
+        let id = ProtocolRpcId::generate();
+        let rpc = SessionPingJsonRpc {
+            id,
+            jsonrpc: JsonRpcVersion::version_2(),
+            method: methods::SESSION_PING.to_owned(),
+            params: SessionPing {},
+        };
+        let rpc_json = serde_json::to_string_pretty(&rpc)
+            .map_err(|e| PingError::ShouldNeverHappen(e.to_string()))?;
+        let message =
+            serialize_and_encrypt_message_type0_envelope(shared_secret, &rpc)
+                .map_err(PingError::ShouldNeverHappen)?;
+
+        // Registered before publishing so a fast response can't be missed
+        self.storage
+            .insert_json_rpc_history(
+                id,
+                topic.clone(),
+                rpc.method.clone(),
+                rpc_json,
+                Some(TransportType::Relay),
+            )
+            .map_err(PingError::Storage)?;
+        let (response_tx, response_rx) = tokio::sync::oneshot::channel();
+        self.pending_pings
+            .lock()
+            .map_err(|e| PingError::ShouldNeverHappen(e.to_string()))?
+            .insert(id, response_tx);
+        let remove_pending = {
+            let pending_pings = self.pending_pings.clone();
+            move || {
+                if let Ok(mut pending_pings) = pending_pings.lock() {
+                    pending_pings.remove(&id);
+                }
+            }
+        };
+
+        let start = crate::time::Instant::now();
+        let result = self
+            .do_request::<bool>(relay_rpc::rpc::Params::Publish(Publish {
+                topic,
+                message,
+                attestation: None,
+                ttl_secs: 30,
+                tag: 1114,
+                prompt: false,
+                analytics: None,
+            }))
+            .await;
+        if let Err(e) = result {
+            remove_pending();
+            return Err(PingError::Request(e));
+        }
+
+        let response = crate::time::timeout(PING_TIMEOUT, response_rx).await;
+        let latency = start.elapsed();
+        match response {
+            Ok(Ok(Ok(()))) => Ok(latency),
+            Ok(Ok(Err(error))) => Err(PingError::Peer(error.message)),
+            Ok(Err(e)) => Err(PingError::ShouldNeverHappen(e.to_string())),
+            Err(_) => {
+                remove_pending();
+                Err(PingError::Timeout)
+            }
+        }
     }
 
     #[tracing::instrument(skip_all, fields(group = self.probe_group.clone()))]
@@ -1170,25 +1523,440 @@ impl Client {
         Ok(())
     }
 
+    async fn handle_session_authenticate_message(
+        &mut self,
+        pairing_topic: &Topic,
+        pairing_sym_key: [u8; 32],
+        message: &str,
+        attestation: Option<Arc<str>>,
+    ) -> Result<(SessionAuthenticateRequest, VerifyContext), PairError> {
+        let (decrypted, decrypted_hash, encrypted_hash) =
+            decrypt_type0_envelope_with_hashes(pairing_sym_key, message)?;
+        let request =
+            serde_json::from_slice::<SessionAuthenticateJsonRpc>(&decrypted)
+                .map_err(|e| {
+                    PairError::Internal(format!(
+                        "Failed to parse authenticate message: {e}"
+                    ))
+                })?;
+        if request.method != methods::SESSION_AUTHENTICATE {
+            return Err(PairError::Internal(format!(
+                "Expected {}, got {}",
+                methods::SESSION_AUTHENTICATE,
+                request.method
+            )));
+        }
+
+        if self.storage.does_json_rpc_exist(request.id).unwrap_or(false) {
+            return Err(PairError::Internal(format!(
+                "Duplicated JsonRpc RequestId for SessionAuthenticate {}",
+                request.id
+            )));
+        }
+        let request_json = serde_json::to_string_pretty(&request)
+            .map_err(|e| PairError::ShouldNeverHappen(e.to_string()))?;
+        self.storage.insert_json_rpc_history(
+            request.id,
+            pairing_topic.clone(),
+            request.method.clone(),
+            request_json,
+            Some(TransportType::Relay),
+        );
+
+        let attestation = handle_verify(
+            VERIFY_SERVER_URL.to_string(),
+            decrypted_hash,
+            self.http_client.clone(),
+            self.storage.clone(),
+            &self.verify_state,
+            attestation,
+            encrypted_hash,
+            request.params.requester.metadata.url.clone(),
+            self.probe_group.clone(),
+        )
+        .await;
+
+        let decision = self.verify_state.evaluate_origin(
+            &attestation,
+            &request.params.requester.metadata.url,
+        );
+        let request = SessionAuthenticateRequest {
+            id: request.id,
+            pairing_topic: pairing_topic.clone(),
+            requester: request.params.requester,
+            auth_payload: request.params.auth_payload,
+            expiry_timestamp: request.params.expiry_timestamp,
+        };
+        if decision.blocked {
+            tracing::debug!(
+                "Session authenticate {} blocked by origin policy: {}",
+                request.id,
+                decision.message
+            );
+            let mut error: GenericJsonRpcResponseErrorData =
+                RejectionReason::UserRejected.into();
+            error.data = Some(decision.message.clone());
+            // Best effort, the pairing is rejected either way
+            if let Err(e) = self
+                .publish_session_authenticate_rejection(&request, error)
+                .await
+            {
+                tracing::warn!(
+                    "Failed to reject blocked session authenticate {}: {e}",
+                    request.id
+                );
+            }
+            return Err(PairError::OriginBlocked(decision));
+        }
+
+        Ok((request, attestation))
+    }
+
+    /// Dapp-side One-Click Auth: request a SIWE signature and a session in a single round-trip.
+    /// The result is emitted as `IncomingSessionMessage::SessionAuthenticateResponse`, or `SessionReject` if the wallet rejects.
     #[tracing::instrument(skip_all, fields(group = self.probe_group.clone()))]
-    pub async fn _authenticate(&self) {
-        // TODO implement
-        // https://github.com/WalletConnect/walletconnect-monorepo/blob/5bef698dcf0ae910548481959a6a5d87eaf7aaa5/packages/sign-client/src/controllers/engine.ts#L817
-        unimplemented!()
+    pub async fn authenticate(
+        &mut self,
+        params: AuthenticateParams,
+        self_metadata: Metadata,
+    ) -> Result<ConnectResult, AuthenticateError> {
+        let pairing_info = Self::create_pairing(self.active_relay())
+            .await
+            .map_err(|e| AuthenticateError::Internal(e.to_string()))?;
+        let sym_key: [u8; 32] =
+            pairing_info.sym_key.clone().try_into().map_err(|_| {
+                AuthenticateError::ShouldNeverHappen(
+                    "pairing sym key is not 32 bytes".to_owned(),
+                )
+            })?;
+
+        let self_key = x25519_dalek::StaticSecret::random();
+        let self_public_key = PublicKey::from(&self_key);
+        // The wallet responds on the hash of our public key, since it has no shared key with us yet
+        let response_topic = topic_from_sym_key(self_public_key.as_bytes());
+
+        let now = crate::time::SystemTime::now()
+            .duration_since(crate::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let iat = chrono::DateTime::from_timestamp(now as i64, 0)
+            .ok_or_else(|| {
+                AuthenticateError::ShouldNeverHappen(
+                    "invalid timestamp".to_owned(),
+                )
+            })?
+            .to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+
+        let mut resources = params.resources.unwrap_or_default();
+        if let Some(recap_methods) = params.methods.filter(|m| !m.is_empty()) {
+            // ReCap must be the last resource
+            resources.push(
+                encode_recap(&recap_methods)
+                    .map_err(|e| AuthenticateError::Internal(e.to_string()))?,
+            );
+        }
+
+        let rpc = SessionAuthenticateJsonRpc {
+            id: ProtocolRpcId::generate(),
+            jsonrpc: JsonRpcVersion::version_2(),
+            method: methods::SESSION_AUTHENTICATE.to_string(),
+            params: SessionAuthenticate {
+                requester: Participant {
+                    public_key: hex::encode(self_public_key.to_bytes()),
+                    metadata: self_metadata,
+                },
+                auth_payload: AuthPayload {
+                    payload_type: Some(CACAO_HEADER_TYPE.to_owned()),
+                    chains: params.chains,
+                    domain: params.domain,
+                    aud: params.uri,
+                    nonce: params.nonce,
+                    version: "1".to_owned(),
+                    iat,
+                    nbf: params.nbf,
+                    exp: params.exp,
+                    statement: params.statement,
+                    request_id: params.request_id,
+                    resources: (!resources.is_empty()).then_some(resources),
+                    signature_types: None,
+                },
+                expiry_timestamp: now + 60 * 60,
+            },
+        };
+        let rpc_json = serde_json::to_string_pretty(&rpc)
+            .map_err(|e| AuthenticateError::ShouldNeverHappen(e.to_string()))?;
+
+        self.storage
+            .save_authenticate_request(
+                response_topic.clone(),
+                self_key.to_bytes(),
+                rpc.params.expiry_timestamp,
+            )
+            .map_err(AuthenticateError::Storage)?;
+        self.do_request::<serde_json::Value>(Params::Subscribe(Subscribe {
+            topic: response_topic,
+        }))
+        .await
+        .map_err(AuthenticateError::Request)?;
+
+        let (encrypted_id, decrypted_id, message) =
+            serialize_and_encrypt_message_type0_envelope_with_ids(
+                sym_key, &rpc,
+            )
+            .map_err(AuthenticateError::ShouldNeverHappen)?;
+
+        self.do_verified_request::<bool>(
+            encrypted_id,
+            decrypted_id,
+            Box::new({
+                let publish_topic = pairing_info.topic.clone();
+                let publish_message = message.clone();
+                let correlation_id = rpc.id;
+                move |attestation: Attestation| {
+                    Params::Publish(Publish {
+                        topic: publish_topic.clone(),
+                        message: publish_message.clone(),
+                        attestation,
+                        ttl_secs: 3600,
+                        tag: 1116,
+                        prompt: true,
+                        analytics: Some(AnalyticsData {
+                            correlation_id: Some(
+                                correlation_id.into_value() as i64
+                            ),
+                            chain_id: None,
+                            rpc_methods: None,
+                            tx_hashes: None,
+                            contract_addresses: None,
+                        }),
+                    })
+                }
+            }),
+        )
+        .await
+        .map_err(AuthenticateError::Request)?;
+
+        self.storage.insert_json_rpc_history(
+            rpc.id,
+            pairing_info.topic.clone(),
+            rpc.method,
+            rpc_json,
+            Some(TransportType::Relay),
+        );
+
+        Ok(ConnectResult { topic: pairing_info.topic, uri: pairing_info.uri })
     }
 
+    /// Wallet-side One-Click Auth: respond with the signed cacaos and create the session from them.
+    /// Namespaces are derived from the cacao issuers and the ReCap methods.
     #[tracing::instrument(skip_all, fields(group = self.probe_group.clone()))]
-    pub async fn _approve_session_authenticate(&self) {
-        // TODO implement
-        // https://github.com/WalletConnect/walletconnect-monorepo/blob/5bef698dcf0ae910548481959a6a5d87eaf7aaa5/packages/sign-client/src/controllers/engine.ts#L1123
-        unimplemented!()
+    pub async fn approve_session_authenticate(
+        &mut self,
+        request: SessionAuthenticateRequest,
+        cacaos: Vec<Cacao>,
+        self_metadata: Metadata,
+    ) -> Result<Session, ApproveSessionAuthenticateError> {
+        if is_expired(request.expiry_timestamp) {
+            return Err(ApproveSessionAuthenticateError::Expired);
+        }
+        if cacaos.is_empty() {
+            return Err(ApproveSessionAuthenticateError::InvalidCacao(
+                "no cacaos provided".to_owned(),
+            ));
+        }
+        for cacao in &cacaos {
+            validate_cacao(cacao, &request.auth_payload).map_err(|e| {
+                ApproveSessionAuthenticateError::InvalidCacao(e.to_string())
+            })?;
+        }
+        let session_namespaces =
+            namespaces_from_cacaos(&cacaos).map_err(|e| {
+                ApproveSessionAuthenticateError::InvalidCacao(e.to_string())
+            })?;
+
+        let requester_public_key =
+            decode_public_key(&request.requester.public_key)
+                .map_err(ApproveSessionAuthenticateError::Internal)?;
+        let self_key = x25519_dalek::StaticSecret::random();
+        let self_public_key = PublicKey::from(&self_key);
+        let shared_secret =
+            diffie_hellman(&requester_public_key.into(), &self_key);
+        let session_topic = topic_from_sym_key(&shared_secret);
+        let response_topic = topic_from_sym_key(&requester_public_key);
+
+        let response = SessionAuthenticateResultResponseJsonRpc {
+            id: request.id,
+            jsonrpc: JsonRpcVersion::version_2(),
+            result: SessionAuthenticateResponse {
+                cacaos,
+                responder: Participant {
+                    public_key: hex::encode(self_public_key.to_bytes()),
+                    metadata: self_metadata.clone(),
+                },
+            },
+        };
+        let response_json =
+            serde_json::to_string_pretty(&response).map_err(|e| {
+                ApproveSessionAuthenticateError::ShouldNeverHappen(
+                    e.to_string(),
+                )
+            })?;
+        let message = serialize_and_encrypt_message_type1_envelope(
+            shared_secret,
+            self_public_key.to_bytes(),
+            &response,
+        )
+        .map_err(ApproveSessionAuthenticateError::ShouldNeverHappen)?;
+
+        let session = Session {
+            request_id: request.id,
+            topic: session_topic.clone(),
+            expiry: compute_max_expiry(
+                crate::time::SystemTime::now()
+                    .duration_since(crate::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs(),
+            ),
+            relay_protocol: self.active_relay().protocol,
+            relay_data: None,
+            controller_key: Some(self_public_key.to_bytes()),
+            session_sym_key: shared_secret,
+            self_public_key: self_public_key.to_bytes(),
+            self_meta_data: self_metadata,
+            peer_public_key: Some(requester_public_key),
+            peer_meta_data: Some(request.requester.metadata),
+            session_namespaces,
+            required_namespaces: HashMap::new(),
+            optional_namespaces: HashMap::new(),
+            session_properties: HashMap::new(),
+            scoped_properties: HashMap::new(),
+            is_acknowledged: true,
+            pairing_topic: request.pairing_topic,
+            transport_type: Some(TransportType::Relay),
+        };
+        self.storage.add_session(session.clone());
+
+        // Subscribe before responding so the dapp's first request isn't missed
+        let result = async {
+            self.do_request::<serde_json::Value>(Params::Subscribe(
+                Subscribe { topic: session_topic.clone() },
+            ))
+            .await?;
+            self.do_request::<bool>(Params::Publish(Publish {
+                topic: response_topic,
+                message,
+                attestation: None,
+                ttl_secs: 3600,
+                tag: 1117,
+                prompt: false,
+                analytics: Some(AnalyticsData {
+                    correlation_id: Some(request.id.into_value() as i64),
+                    chain_id: None,
+                    rpc_methods: None,
+                    tx_hashes: None,
+                    contract_addresses: None,
+                }),
+            }))
+            .await
+        }
+        .await;
+
+        match result {
+            Ok(true) => {
+                self.storage.update_json_rpc_history_response(
+                    request.id,
+                    response_json,
+                );
+
+                if let Err(e) = self.tx.send((
+                    session_topic.clone(),
+                    IncomingSessionMessage::SessionConnect(
+                        request.id,
+                        session_topic,
+                    ),
+                )) {
+                    tracing::warn!("Failed to emit session connect event: {e}");
+                }
+                Ok(session)
+            }
+            Ok(false) => {
+                self.storage.delete_session(session.topic);
+                Err(ApproveSessionAuthenticateError::Internal(
+                    "Authenticate response rejected by relay".to_owned(),
+                ))
+            }
+            Err(e) => {
+                self.storage.delete_session(session.topic);
+                Err(ApproveSessionAuthenticateError::Request(e))
+            }
+        }
     }
 
     #[tracing::instrument(skip_all, fields(group = self.probe_group.clone()))]
-    pub async fn _reject_session_authenticate(&self) {
-        // TODO implement
-        // https://github.com/WalletConnect/walletconnect-monorepo/blob/5bef698dcf0ae910548481959a6a5d87eaf7aaa5/packages/sign-client/src/controllers/engine.ts#L1298
-        unimplemented!()
+    pub async fn reject_session_authenticate(
+        &mut self,
+        request: SessionAuthenticateRequest,
+        reason: RejectionReason,
+    ) -> Result<(), RejectSessionAuthenticateError> {
+        self.publish_session_authenticate_rejection(&request, reason.into())
+            .await
+    }
+
+    async fn publish_session_authenticate_rejection(
+        &mut self,
+        request: &SessionAuthenticateRequest,
+        error: GenericJsonRpcResponseErrorData,
+    ) -> Result<(), RejectSessionAuthenticateError> {
+        let requester_public_key =
+            decode_public_key(&request.requester.public_key)
+                .map_err(RejectSessionAuthenticateError::Internal)?;
+
+        let error_response = GenericJsonRpcResponseError {
+            id: request.id,
+            jsonrpc: JsonRpcVersion::version_2(),
+            error,
+        };
+        let response_json = serde_json::to_string_pretty(&error_response)
+            .map_err(|e| {
+                RejectSessionAuthenticateError::ShouldNeverHappen(e.to_string())
+            })?;
+        let (response_topic, message) =
+            serialize_and_encrypt_session_authenticate_rejection(
+                requester_public_key,
+                &error_response,
+            )
+            .map_err(RejectSessionAuthenticateError::ShouldNeverHappen)?;
+
+        match self
+            .do_request::<bool>(Params::Publish(Publish {
+                topic: response_topic,
+                message,
+                attestation: None,
+                ttl_secs: 3600,
+                tag: 1118,
+                prompt: false,
+                analytics: Some(AnalyticsData {
+                    correlation_id: Some(request.id.into_value() as i64),
+                    chain_id: None,
+                    rpc_methods: None,
+                    tx_hashes: None,
+                    contract_addresses: None,
+                }),
+            }))
+            .await
+        {
+            Ok(true) => {
+                self.storage.update_json_rpc_history_response(
+                    request.id,
+                    response_json,
+                );
+                Ok(())
+            }
+            Ok(false) => Err(RejectSessionAuthenticateError::Internal(
+                "Failed to send rejection to relay".to_string(),
+            )),
+            Err(e) => Err(RejectSessionAuthenticateError::Request(e)),
+        }
     }
 
     async fn do_request<T: DeserializeOwned>(
@@ -1263,6 +2031,134 @@ impl Drop for Client {
     }
 }
 
+pub(crate) fn decode_public_key(public_key: &str) -> Result<[u8; 32], String> {
+    hex::decode(public_key)
+        .map_err(|e| format!("Failed to decode public key: {e}"))?
+        .try_into()
+        .map_err(|_| {
+            "Failed to convert public key to fixed-size array".to_owned()
+        })
+}
+
 pub fn generate_client_id_key() -> SecretKey {
     SigningKey::generate(&mut rand::thread_rng()).to_bytes()
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::sign::{
+            protocol_types::SessionRequestRequest, storage::MemoryStorage,
+        },
+    };
+
+    fn session(seed: u8) -> Session {
+        let self_public_key = [seed; 32];
+        Session {
+            request_id: ProtocolRpcId::generate(),
+            topic: topic_from_sym_key(&[seed; 32]),
+            expiry: u64::MAX,
+            relay_protocol: "irn".to_owned(),
+            relay_data: None,
+            controller_key: Some(self_public_key),
+            session_sym_key: [seed; 32],
+            self_public_key,
+            self_meta_data: Metadata {
+                name: "Test".to_owned(),
+                description: "Test".to_owned(),
+                url: "https://example.com".to_owned(),
+                icons: vec![],
+                verify_url: None,
+                redirect: None,
+            },
+            peer_public_key: Some([seed.wrapping_add(1); 32]),
+            peer_meta_data: None,
+            session_namespaces: HashMap::new(),
+            required_namespaces: HashMap::new(),
+            optional_namespaces: HashMap::new(),
+            session_properties: HashMap::new(),
+            scoped_properties: HashMap::new(),
+            is_acknowledged: true,
+            pairing_topic: topic_from_sym_key(&[seed.wrapping_add(100); 32]),
+            transport_type: Some(TransportType::Relay),
+        }
+    }
+
+    fn insert_request(
+        storage: &dyn Storage,
+        topic: Topic,
+        expiry: Option<u64>,
+    ) -> ProtocolRpcId {
+        let rpc = SessionRequestJsonRpc {
+            id: ProtocolRpcId::generate(),
+            jsonrpc: JsonRpcVersion::version_2(),
+            method: methods::SESSION_REQUEST.to_owned(),
+            params: SessionRequest {
+                chain_id: "eip155:1".to_owned(),
+                request: SessionRequestRequest {
+                    method: "personal_sign".to_owned(),
+                    params: serde_json::json!(["0x", "0x"]),
+                    expiry,
+                },
+            },
+        };
+        storage
+            .insert_json_rpc_history(
+                rpc.id,
+                topic,
+                rpc.method.clone(),
+                serde_json::to_string(&rpc).unwrap(),
+                Some(TransportType::Relay),
+            )
+            .unwrap();
+        rpc.id
+    }
+
+    #[test]
+    fn test_get_pending_session_requests() {
+        let storage = Arc::new(MemoryStorage::new());
+        let first_session = session(1);
+        let second_session = session(2);
+        storage.add_session(first_session.clone()).unwrap();
+        storage.add_session(second_session.clone()).unwrap();
+
+        let first = insert_request(
+            storage.as_ref(),
+            first_session.topic.clone(),
+            Some(u64::MAX),
+        );
+        let second = insert_request(
+            storage.as_ref(),
+            second_session.topic.clone(),
+            None,
+        );
+        let answered =
+            insert_request(storage.as_ref(), first_session.topic.clone(), None);
+        storage
+            .update_json_rpc_history_response(answered, "{}".to_owned())
+            .unwrap();
+        insert_request(storage.as_ref(), first_session.topic.clone(), Some(1));
+        // Session no longer exists
+        insert_request(storage.as_ref(), topic_from_sym_key(&[3; 32]), None);
+
+        let (client, _rx) = Client::new(
+            ProjectId::from("0".repeat(32)),
+            generate_client_id_key(),
+            storage,
+        );
+        let requests = client
+            .get_pending_session_requests()
+            .unwrap()
+            .into_iter()
+            .map(|pending| (pending.topic, pending.request.id))
+            .collect::<HashSet<_>>();
+        assert_eq!(
+            requests,
+            HashSet::from([
+                (first_session.topic, first),
+                (second_session.topic, second)
+            ])
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use crate::sign::storage::StorageError;
+use crate::sign::{
+    link_mode::LinkModeError, namespaces::NamespaceError,
+    storage::StorageError, verify::policy::OriginDecision,
+};
 
 #[derive(Debug, thiserror::Error, Clone)]
 #[cfg_attr(feature = "uniffi", derive(uniffi_macros::Error))]
@@ -37,6 +40,15 @@ pub enum NextError {
 #[cfg_attr(feature = "uniffi", derive(uniffi_macros::Error))]
 #[error("Sign pair error: {0}")]
 pub enum PairError {
+    #[error("Pairing URI expired")]
+    Expired,
+
+    #[error("Active relay does not serve relay protocol: {0}")]
+    UnsupportedRelayProtocol(String),
+
+    #[error("Origin blocked: {}", .0.message)]
+    OriginBlocked(OriginDecision),
+
     #[error("Get public key: {0}")]
     GetPublicKey(String),
 
@@ -57,6 +69,9 @@ pub enum ApproveError {
     #[error("Request error: {0}")]
     Request(RequestError),
 
+    #[error("Invalid namespaces: {0}")]
+    InvalidNamespaces(NamespaceError),
+
     #[error("Internal: {0}")]
     Internal(String),
 
@@ -91,6 +106,9 @@ pub enum RespondError {
     #[error("Request: {0}")]
     Request(RequestError),
 
+    #[error("Link Mode: {0}")]
+    LinkMode(LinkModeError),
+
     #[error("Should never happen: {0}")]
     ShouldNeverHappen(String),
 }
@@ -133,6 +151,9 @@ pub enum ConnectError {
     #[error("Request error: {0}")]
     Request(RequestError),
 
+    #[error("Invalid namespaces: {0}")]
+    InvalidNamespaces(NamespaceError),
+
     #[error("Internal: {0}")]
     Internal(String),
 
@@ -160,6 +181,29 @@ pub enum ExtendError {
     ShouldNeverHappen(String),
 }
 
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi_macros::Error))]
+#[error("Sign ping error: {0}")]
+pub enum PingError {
+    #[error("Storage: {0}")]
+    Storage(StorageError),
+
+    #[error("Session not found")]
+    SessionNotFound,
+
+    #[error("Request: {0}")]
+    Request(RequestError),
+
+    #[error("Peer did not respond in time")]
+    Timeout,
+
+    #[error("Peer responded with an error: {0}")]
+    Peer(String),
+
+    #[error("Should never happen: {0}")]
+    ShouldNeverHappen(String),
+}
+
 #[derive(Debug, thiserror::Error)]
 #[cfg_attr(feature = "uniffi", derive(uniffi_macros::Error))]
 #[error("Sign update error: {0}")]
@@ -182,3 +226,54 @@ pub enum UpdateError {
     #[error("Should never happen: {0}")]
     ShouldNeverHappen(String),
 }
+
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi_macros::Error))]
+#[error("Sign authenticate error: {0}")]
+pub enum AuthenticateError {
+    #[error("Request error: {0}")]
+    Request(RequestError),
+
+    #[error("Storage: {0}")]
+    Storage(StorageError),
+
+    #[error("Internal: {0}")]
+    Internal(String),
+
+    #[error("Should never happen: {0}")]
+    ShouldNeverHappen(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi_macros::Error))]
+#[error("Sign approve session authenticate error: {0}")]
+pub enum ApproveSessionAuthenticateError {
+    #[error("Request expired")]
+    Expired,
+
+    #[error("Invalid cacao: {0}")]
+    InvalidCacao(String),
+
+    #[error("Request error: {0}")]
+    Request(RequestError),
+
+    #[error("Internal: {0}")]
+    Internal(String),
+
+    #[error("Should never happen: {0}")]
+    ShouldNeverHappen(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi_macros::Error))]
+#[error("Sign reject session authenticate error: {0}")]
+pub enum RejectSessionAuthenticateError {
+    #[error("Request error: {0}")]
+    Request(RequestError),
+
+    #[error("Internal: {0}")]
+    Internal(String),
+
+    #[error("Should never happen: {0}")]
+    ShouldNeverHappen(String),
+}
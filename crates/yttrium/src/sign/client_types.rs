@@ -1,7 +1,11 @@
 use {
-    crate::sign::protocol_types::{
-        GenericJsonRpcResponseErrorData, Metadata, ProposalNamespaces,
-        ProtocolRpcId, Relay, SettleNamespace,
+    crate::sign::{
+        protocol_types::{
+            AuthPayload, GenericJsonRpcResponseErrorData, Metadata,
+            Participant, ProposalNamespaces, ProtocolRpcId, Relay,
+            SessionRequestJsonRpc, SettleNamespace,
+        },
+        verify::validate::VerifyContext,
     },
     relay_rpc::domain::Topic,
     serde::{Deserialize, Serialize},
@@ -47,7 +51,7 @@ pub struct Session {
     pub transport_type: Option<TransportType>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "uniffi", derive(uniffi_macros::Enum))]
 pub enum TransportType {
     Relay,
@@ -69,6 +73,52 @@ pub struct ConnectResult {
     pub uri: String,
 }
 
+/// Dapp-side parameters for `wc_sessionAuthenticate` (One-Click Auth)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "uniffi", derive(uniffi_macros::Record))]
+pub struct AuthenticateParams {
+    pub chains: Vec<String>,
+    pub domain: String,
+    pub uri: String,
+    pub nonce: String,
+    pub statement: Option<String>,
+    pub nbf: Option<String>,
+    pub exp: Option<String>,
+    pub request_id: Option<String>,
+    pub resources: Option<Vec<String>>,
+    /// Methods requested for the session, encoded as a ReCap resource
+    pub methods: Option<Vec<String>>,
+}
+
+/// Wallet-side view of an incoming `wc_sessionAuthenticate` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAuthenticateRequest {
+    pub id: ProtocolRpcId,
+    pub pairing_topic: Topic,
+    pub requester: Participant,
+    pub auth_payload: AuthPayload,
+    pub expiry_timestamp: u64,
+}
+
+/// What the dapp asked for on the pairing topic passed to `Client::pair()`
+#[derive(Debug, Clone)]
+pub enum PairResult {
+    /// Approve with `Client::approve()` or reject with `Client::reject()`
+    Proposal(SessionProposal, VerifyContext),
+    /// One-Click Auth, answer with `Client::approve_session_authenticate()`
+    /// or `Client::reject_session_authenticate()`. The dapp's fallback
+    /// session proposal, if any, is not surfaced.
+    Authenticate(SessionAuthenticateRequest, VerifyContext),
+}
+
+/// Wallet-side view of a `wc_sessionRequest` that was received but not responded to yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingSessionRequest {
+    pub topic: Topic,
+    pub request: SessionRequestJsonRpc,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "uniffi", derive(uniffi_macros::Record))]
 pub struct PairingInfo {
@@ -0,0 +1,244 @@
+use {
+    crate::sign::{
+        client::MaybeVerifiedRequest,
+        client_errors::RequestError,
+        relay::IncomingSessionMessage,
+        storage::{Storage, StorageError},
+    },
+    relay_rpc::{
+        domain::Topic,
+        rpc::{Params, Response, Unsubscribe},
+    },
+    std::sync::Arc,
+    tokio::sync::mpsc::{UnboundedSender, WeakUnboundedSender},
+    tracing::Instrument,
+};
+
+/// How often a started client looks for expired sessions and pairings
+pub const SWEEP_INTERVAL: crate::time::Duration =
+    crate::time::Duration::from_secs(60);
+
+type SessionMessageSender = UnboundedSender<(Topic, IncomingSessionMessage)>;
+type RequestSender = UnboundedSender<(
+    MaybeVerifiedRequest,
+    tokio::sync::oneshot::Sender<Result<Response, RequestError>>,
+)>;
+
+/// Deletes expired sessions and pairings along with their JSON-RPC history, and unsubscribes from their topics.
+/// `SessionExpired` is emitted for each deleted session. Pairings and authenticate requests expire silently, as they were never surfaced to the app.
+pub fn sweep(
+    storage: &dyn Storage,
+    session_tx: &SessionMessageSender,
+    request_tx: &RequestSender,
+    probe_group: Option<String>,
+) -> Result<(), StorageError> {
+    let now = crate::time::SystemTime::now()
+        .duration_since(crate::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let expired_sessions = storage
+        .get_all_sessions()?
+        .into_iter()
+        .filter(|session| session.expiry <= now)
+        .map(|session| session.topic)
+        .collect::<Vec<_>>();
+    for topic in expired_sessions {
+        tracing::debug!("Session expired: {topic}");
+        storage.delete_session(topic.clone())?;
+        storage.delete_json_rpc_history_by_topic(topic.clone())?;
+        unsubscribe(request_tx, topic.clone(), probe_group.clone());
+        if let Err(e) = session_tx.send((
+            topic.clone(),
+            IncomingSessionMessage::SessionExpired(topic),
+        )) {
+            tracing::warn!("Failed to emit session expired: {e}");
+        }
+    }
+
+    for topic in storage.delete_expired_pairings(now)? {
+        tracing::debug!("Pairing expired: {topic}");
+        storage.delete_json_rpc_history_by_topic(topic.clone())?;
+        unsubscribe(request_tx, topic, probe_group.clone());
+    }
+
+    for topic in storage.delete_expired_authenticate_requests(now)? {
+        tracing::debug!("Authenticate request expired: {topic}");
+        unsubscribe(request_tx, topic, probe_group.clone());
+    }
+
+    Ok(())
+}
+
+/// Runs `sweep()` every `SWEEP_INTERVAL` until cleanup, or until the client's channels are closed.
+/// Only weak senders are held so the relay loop can still shut down when the client is dropped.
+pub async fn sweep_loop(
+    storage: Arc<dyn Storage>,
+    session_tx: WeakUnboundedSender<(Topic, IncomingSessionMessage)>,
+    request_tx: WeakUnboundedSender<(
+        MaybeVerifiedRequest,
+        tokio::sync::oneshot::Sender<Result<Response, RequestError>>,
+    )>,
+    cleanup_rx: tokio_util::sync::CancellationToken,
+    probe_group: Option<String>,
+) {
+    loop {
+        let (Some(session_tx), Some(request_tx)) =
+            (session_tx.upgrade(), request_tx.upgrade())
+        else {
+            break;
+        };
+        if let Err(e) = sweep(
+            storage.as_ref(),
+            &session_tx,
+            &request_tx,
+            probe_group.clone(),
+        ) {
+            tracing::warn!(
+                "Failed to sweep expired sessions and pairings: {e}"
+            );
+        }
+        drop((session_tx, request_tx));
+
+        tokio::select! {
+            () = crate::time::sleep(SWEEP_INTERVAL) => {}
+            _ = cleanup_rx.cancelled() => break,
+        }
+    }
+}
+
+fn unsubscribe(
+    request_tx: &RequestSender,
+    topic: Topic,
+    probe_group: Option<String>,
+) {
+    let params = Params::Unsubscribe(Unsubscribe { topic });
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    crate::spawn::spawn(
+        async move {
+            // Consume the response to avoid a publish error
+            let response = rx.await;
+            tracing::debug!("Received unsubscribe response: {:?}", response);
+        }
+        .instrument(tracing::debug_span!(
+            "unsubscribe_response",
+            group = probe_group
+        )),
+    );
+    if let Err(e) =
+        request_tx.send((MaybeVerifiedRequest::Unverified(params), tx))
+    {
+        tracing::warn!("Failed to send unsubscribe request: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::sign::{
+            client_types::{Session, TransportType},
+            protocol_types::{Metadata, ProtocolRpcId},
+            storage::MemoryStorage,
+            utils::topic_from_sym_key,
+        },
+        std::collections::HashMap,
+    };
+
+    fn session(seed: u8, expiry: u64) -> Session {
+        Session {
+            request_id: ProtocolRpcId::generate(),
+            topic: topic_from_sym_key(&[seed; 32]),
+            expiry,
+            relay_protocol: "irn".to_owned(),
+            relay_data: None,
+            controller_key: None,
+            session_sym_key: [seed; 32],
+            self_public_key: [seed; 32],
+            self_meta_data: Metadata {
+                name: "Test".to_owned(),
+                description: "Test".to_owned(),
+                url: "https://example.com".to_owned(),
+                icons: vec![],
+                verify_url: None,
+                redirect: None,
+            },
+            peer_public_key: None,
+            peer_meta_data: None,
+            session_namespaces: HashMap::new(),
+            required_namespaces: HashMap::new(),
+            optional_namespaces: HashMap::new(),
+            session_properties: HashMap::new(),
+            scoped_properties: HashMap::new(),
+            is_acknowledged: true,
+            pairing_topic: topic_from_sym_key(&[seed.wrapping_add(100); 32]),
+            transport_type: Some(TransportType::Relay),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sweep() {
+        let storage = MemoryStorage::new();
+        let expired = session(1, 1);
+        let live = session(2, u64::MAX);
+        storage.add_session(expired.clone()).unwrap();
+        storage.add_session(live.clone()).unwrap();
+        let request_id = ProtocolRpcId::generate();
+        storage
+            .insert_json_rpc_history(
+                request_id,
+                expired.topic.clone(),
+                "wc_sessionRequest".to_owned(),
+                "{}".to_owned(),
+                Some(TransportType::Relay),
+            )
+            .unwrap();
+        let pairing_topic = topic_from_sym_key(&[3; 32]);
+        storage
+            .save_pairing(
+                pairing_topic.clone(),
+                ProtocolRpcId::generate(),
+                [3; 32],
+                [3; 32],
+                1,
+            )
+            .unwrap();
+        let authenticate_topic = topic_from_sym_key(&[4; 32]);
+        storage
+            .save_authenticate_request(authenticate_topic.clone(), [4; 32], 1)
+            .unwrap();
+
+        let (session_tx, mut session_rx) =
+            tokio::sync::mpsc::unbounded_channel();
+        let (request_tx, mut request_rx) =
+            tokio::sync::mpsc::unbounded_channel();
+        sweep(&storage, &session_tx, &request_tx, None).unwrap();
+
+        assert!(storage.get_session(expired.topic.clone()).unwrap().is_none());
+        assert!(storage.get_session(live.topic.clone()).unwrap().is_some());
+        assert!(!storage.does_json_rpc_exist(request_id).unwrap());
+        assert_eq!(storage.get_all_topics().unwrap(), vec![live.topic]);
+
+        let (topic, message) = session_rx.try_recv().unwrap();
+        assert_eq!(topic, expired.topic);
+        assert!(matches!(
+            message,
+            IncomingSessionMessage::SessionExpired(t) if t == expired.topic
+        ));
+        assert!(session_rx.try_recv().is_err());
+
+        let mut unsubscribed = Vec::new();
+        while let Ok((request, _)) = request_rx.try_recv() {
+            match request {
+                MaybeVerifiedRequest::Unverified(Params::Unsubscribe(
+                    Unsubscribe { topic },
+                )) => unsubscribed.push(topic),
+                _ => panic!("unexpected request"),
+            }
+        }
+        assert_eq!(
+            unsubscribed,
+            vec![expired.topic, pairing_topic, authenticate_topic]
+        );
+    }
+}
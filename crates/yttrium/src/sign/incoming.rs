@@ -1,28 +1,41 @@
 use {
     crate::sign::{
-        client::MaybeVerifiedRequest,
+        cacao::{namespaces_from_cacaos, validate_cacao},
+        client::{MaybeVerifiedRequest, decode_public_key},
         client_errors::RequestError,
-        client_types::{Session, TransportType},
-        envelope_type0,
+        client_types::{
+            RejectionReason, Session, SessionAuthenticateRequest, TransportType,
+        },
+        envelope_type0, envelope_type1,
         protocol_types::{
             GenericJsonRpcMessage, GenericJsonRpcResponse,
-            GenericJsonRpcResponseError, Metadata, ProtocolRpcId,
-            SessionDeleteJsonRpc, SessionProposalJsonRpcResponse,
+            GenericJsonRpcResponseError, GenericJsonRpcResponseErrorData,
+            JsonRpcVersion, Metadata, ProtocolRpcId,
+            SessionAuthenticateJsonRpc,
+            SessionAuthenticateResultResponseJsonRpc, SessionDeleteJsonRpc,
+            SessionPingJsonRpc, SessionProposalJsonRpcResponse,
             SessionRequestJsonRpc, SessionRequestJsonRpcResponse,
-            SessionRequestJsonRpcResultResponse, SessionSettle, methods,
+            SessionRequestJsonRpcResultResponse, SessionSettle,
+            SimpleJsonRpcBoolResponse, methods,
         },
         relay::IncomingSessionMessage,
         storage::{Storage, StoragePairing},
         utils::{
-            DecryptedHash, EncryptedHash, diffie_hellman, topic_from_sym_key,
+            DecryptedHash, EncryptedHash, diffie_hellman,
+            serialize_and_encrypt_message_type0_envelope,
+            serialize_and_encrypt_session_authenticate_rejection,
+            topic_from_sym_key,
+        },
+        verify::{
+            VERIFY_SERVER_URL,
+            validate::{VerifyState, handle_verify},
         },
-        verify::{VERIFY_SERVER_URL, validate::handle_verify},
     },
     chacha20poly1305::{ChaCha20Poly1305, KeyInit, Nonce, aead::Aead},
     data_encoding::BASE64,
     relay_rpc::{
         domain::Topic,
-        rpc::{Params, Response, Subscribe, Subscription},
+        rpc::{Params, Publish, Response, Subscribe, Subscription},
     },
     sha2::Digest,
     std::{collections::HashMap, sync::Arc},
@@ -47,6 +60,28 @@ pub enum HandleError {
     AlreadyHandled,
 }
 
+// A relay-agnostic view of an `irn_subscription`, so messages received over Link Mode share the same handling
+#[derive(Clone)]
+pub struct IncomingMessage {
+    pub topic: Topic,
+    pub message: Arc<str>,
+    pub attestation: Option<Arc<str>>,
+    pub tag: u32,
+    pub transport_type: TransportType,
+}
+
+// `wc_sessionPing` requests sent by `Client::ping()` that are waiting for the peer's response, by request ID
+pub type PendingPings = Arc<
+    std::sync::Mutex<
+        HashMap<
+            ProtocolRpcId,
+            tokio::sync::oneshot::Sender<
+                Result<(), GenericJsonRpcResponseErrorData>,
+            >,
+        >,
+    >,
+>;
+
 pub async fn handle(
     storage: Arc<dyn Storage>,
     http_client: reqwest::Client,
@@ -59,36 +94,101 @@ pub async fn handle(
         MaybeVerifiedRequest,
         tokio::sync::oneshot::Sender<Result<Response, RequestError>>,
     )>,
+    pending_pings: PendingPings,
+    verify_state: VerifyState,
     probe_group: Option<String>,
 ) -> Result<(), HandleError> {
-    // WARNING: This function must complete in <4s not including network latency, so don't do blocking operations such as network requests
+    handle_message(
+        storage,
+        http_client,
+        IncomingMessage {
+            topic: sub_msg.data.topic,
+            message: sub_msg.data.message,
+            attestation: sub_msg.data.attestation,
+            tag: sub_msg.data.tag,
+            transport_type: TransportType::Relay,
+        },
+        session_request_tx,
+        priority_request_tx,
+        pending_pings,
+        verify_state,
+        probe_group,
+    )
+    .await
+}
 
-    let key = storage
-        .get_decryption_key_for_topic(sub_msg.data.topic.clone())
-        .map_err(|e| {
-            HandleError::Temporary(format!("get decryption key for topic: {e}"))
-        })?;
-    let session_sym_key = if let Some(session_sym_key) = key {
-        session_sym_key
-    } else {
-        return Err(HandleError::Dropped(
-            "No decryption key found to decrypt message, ACKing and ignoring"
-                .to_string(),
-        ));
-    };
+pub async fn handle_message(
+    storage: Arc<dyn Storage>,
+    http_client: reqwest::Client,
+    msg: IncomingMessage,
+    session_request_tx: tokio::sync::mpsc::UnboundedSender<(
+        Topic,
+        IncomingSessionMessage,
+    )>,
+    priority_request_tx: tokio::sync::mpsc::UnboundedSender<(
+        MaybeVerifiedRequest,
+        tokio::sync::oneshot::Sender<Result<Response, RequestError>>,
+    )>,
+    pending_pings: PendingPings,
+    verify_state: VerifyState,
+    probe_group: Option<String>,
+) -> Result<(), HandleError> {
+    // WARNING: This function must complete in <4s not including network latency, so don't do blocking operations such as network requests
 
     let encrypted_hash = EncryptedHash(hex::encode(sha2::Sha256::digest(
-        sub_msg.data.message.as_bytes(),
+        msg.message.as_bytes(),
     )));
     let decoded = BASE64
-        .decode(sub_msg.data.message.as_bytes())
+        .decode(msg.message.as_bytes())
         .map_err(|e| HandleError::Peer(format!("decode message: {e}")))?;
 
-    let envelope = envelope_type0::deserialize_envelope_type0(&decoded)
-        .map_err(|e| HandleError::Peer(format!("deserialize envelope: {e}")))?;
+    let (iv, sb, session_sym_key) = if decoded.first() == Some(&1) {
+        // Type 1 envelopes are only expected on wc_sessionAuthenticate response topics
+        let self_key = storage
+            .get_authenticate_request_key(msg.topic.clone())
+            .map_err(|e| {
+                HandleError::Temporary(format!(
+                    "get authenticate request key: {e}"
+                ))
+            })?
+            .ok_or_else(|| {
+                HandleError::Dropped(
+                    "No authenticate request found to decrypt message, ACKing and ignoring"
+                        .to_string(),
+                )
+            })?;
+        let envelope = envelope_type1::deserialize_envelope_type1(&decoded)
+            .map_err(|e| {
+                HandleError::Peer(format!("deserialize envelope: {e}"))
+            })?;
+        let sym_key = diffie_hellman(
+            &x25519_dalek::PublicKey::from(envelope.pk),
+            &x25519_dalek::StaticSecret::from(self_key),
+        );
+        (envelope.iv, envelope.sb, sym_key)
+    } else {
+        let session_sym_key = storage
+            .get_decryption_key_for_topic(msg.topic.clone())
+            .map_err(|e| {
+                HandleError::Temporary(format!(
+                    "get decryption key for topic: {e}"
+                ))
+            })?
+            .ok_or_else(|| {
+                HandleError::Dropped(
+                    "No decryption key found to decrypt message, ACKing and ignoring"
+                        .to_string(),
+                )
+            })?;
+        let envelope = envelope_type0::deserialize_envelope_type0(&decoded)
+            .map_err(|e| {
+                HandleError::Peer(format!("deserialize envelope: {e}"))
+            })?;
+        (envelope.iv, envelope.sb, session_sym_key)
+    };
     let key = ChaCha20Poly1305::new(&session_sym_key.into());
     let decrypted = key
-        .decrypt(&Nonce::from(envelope.iv), envelope.sb.as_slice())
+        .decrypt(&Nonce::from(iv), sb.as_slice())
         .map_err(|e| HandleError::Peer(format!("decrypt message: {e}")))?;
     tracing::debug!(
         "decrypted message: {}",
@@ -132,7 +232,7 @@ pub async fn handle(
 
                     let session = Session {
                         request_id: ProtocolRpcId::generate(), // TODO this is wrong
-                        topic: sub_msg.data.topic.clone(),
+                        topic: msg.topic.clone(),
                         expiry: request.expiry,
                         relay_protocol: "irn".to_string(),
                         relay_data: None,
@@ -176,10 +276,10 @@ pub async fn handle(
                     storage
                         .insert_json_rpc_history(
                             request_id,
-                            sub_msg.data.topic.clone(),
+                            msg.topic.clone(),
                             method,
                             value.to_string(),
-                            Some(TransportType::Relay),
+                            Some(msg.transport_type),
                         )
                         .map_err(|e| {
                             HandleError::Temporary(format!(
@@ -190,10 +290,10 @@ pub async fn handle(
                     // At-most-once delivery guarantee, app can call getConnections()
                     if let Err(e) = session_request_tx
                         .send((
-                            sub_msg.data.topic.clone(),
+                            msg.topic.clone(),
                             IncomingSessionMessage::SessionConnect(
                                 ProtocolRpcId::generate(), // TODO use real value? Do we need this?
-                                sub_msg.data.topic.clone(),
+                                msg.topic.clone(),
                             ),
                         ))
                         .map_err(|e| {
@@ -223,7 +323,7 @@ pub async fn handle(
                         })?;
 
                     let session = storage
-                        .get_session(sub_msg.data.topic.clone())
+                        .get_session(msg.topic.clone())
                         .map_err(|e| {
                             HandleError::Temporary(format!("get session: {e}"))
                         })?
@@ -240,7 +340,8 @@ pub async fn handle(
                         decrypted_hash,
                         http_client,
                         storage.clone(),
-                        sub_msg.data.attestation.clone(),
+                        &verify_state,
+                        msg.attestation.clone(),
                         encrypted_hash,
                         session.peer_meta_data.as_ref().unwrap().url.clone(),
                         probe_group.clone(),
@@ -250,10 +351,10 @@ pub async fn handle(
                     storage
                         .insert_json_rpc_history(
                             request_id,
-                            sub_msg.data.topic.clone(),
+                            msg.topic.clone(),
                             method,
                             value.to_string(),
-                            Some(TransportType::Relay),
+                            Some(msg.transport_type),
                         )
                         .map_err(|e| {
                             HandleError::Temporary(format!(
@@ -261,11 +362,98 @@ pub async fn handle(
                             ))
                         })?;
 
-                    // TODO implement request queue
-                    // No delivery guarantee, session request queue exists
+                    let decision = verify_state.evaluate_origin(
+                        &attestation,
+                        &session.peer_meta_data.as_ref().unwrap().url,
+                    );
+                    if decision.blocked {
+                        tracing::debug!(
+                            "Session request {request_id} blocked by origin policy: {}",
+                            decision.message
+                        );
+                        let mut error: GenericJsonRpcResponseErrorData =
+                            RejectionReason::UserRejected.into();
+                        error.data = Some(decision.message.clone());
+                        let response = SessionRequestJsonRpcResponse::Error(
+                            GenericJsonRpcResponseError {
+                                id: request_id,
+                                jsonrpc: JsonRpcVersion::version_2(),
+                                error,
+                            },
+                        );
+                        let message =
+                            serialize_and_encrypt_message_type0_envelope(
+                                session_sym_key,
+                                &response,
+                            )
+                            .map_err(HandleError::Temporary)?;
+                        let response_json = serde_json::to_string(&response)
+                            .map_err(|e| {
+                                HandleError::Temporary(format!(
+                                    "serialize response: {e}"
+                                ))
+                            })?;
+                        storage
+                            .update_json_rpc_history_response(
+                                request_id,
+                                response_json,
+                            )
+                            .map_err(|e| {
+                                HandleError::Temporary(format!(
+                                    "update history: {e}"
+                                ))
+                            })?;
+                        let params = Params::Publish(Publish {
+                            topic: msg.topic.clone(),
+                            message,
+                            attestation: None,
+                            ttl_secs: 300,
+                            tag: 1109,
+                            prompt: false,
+                            analytics: None,
+                        });
+                        let (tx, rx) = tokio::sync::oneshot::channel();
+                        crate::spawn::spawn(
+                            async move {
+                                // Consume the response to avoid a publish error
+                                let response = rx.await;
+                                tracing::debug!(
+                                    "Received blocked request response publish response: {:?}",
+                                    response
+                                );
+                            }
+                            .instrument(tracing::debug_span!(
+                                "blocked_request_response",
+                                group = probe_group.clone()
+                            )),
+                        );
+                        if let Err(e) = priority_request_tx.send((
+                            MaybeVerifiedRequest::Unverified(params),
+                            tx,
+                        )) {
+                            tracing::warn!(
+                                "Failed to send priority request: {e}"
+                            );
+                        }
+
+                        // At-most-once delivery guarantee, the request was already answered
+                        if let Err(e) = session_request_tx.send((
+                            msg.topic.clone(),
+                            IncomingSessionMessage::SessionRequestBlocked(
+                                request, decision,
+                            ),
+                        )) {
+                            tracing::debug!(
+                                "Failed to emit session request blocked event: {e}"
+                            );
+                        }
+                        return Ok(());
+                    }
+
+                    // No delivery guarantee, unanswered requests can be replayed with `Client::get_pending_session_requests()`
                     if let Err(e) = session_request_tx
                         .send((
-                            sub_msg.data.topic.clone(),
+                            msg.topic.clone(),
                             IncomingSessionMessage::SessionRequest(
                                 request,
                                 attestation,
@@ -295,9 +483,8 @@ pub async fn handle(
                     })?;
 
                     // Update local session namespaces
-                    if let Some(mut session) = storage
-                        .get_session(sub_msg.data.topic.clone())
-                        .map_err(|e| {
+                    if let Some(mut session) =
+                        storage.get_session(msg.topic.clone()).map_err(|e| {
                             HandleError::Temporary(format!("get session: {e}"))
                         })?
                     {
@@ -309,17 +496,17 @@ pub async fn handle(
                     } else {
                         tracing::warn!(
                             "wc_sessionUpdate received for unknown topic: {:?}",
-                            sub_msg.data.topic
+                            msg.topic
                         );
                     }
 
                     storage
                         .insert_json_rpc_history(
                             request_id,
-                            sub_msg.data.topic.clone(),
+                            msg.topic.clone(),
                             method,
                             value.to_string(),
-                            Some(TransportType::Relay),
+                            Some(msg.transport_type),
                         )
                         .map_err(|e| {
                             HandleError::Temporary(format!(
@@ -329,10 +516,10 @@ pub async fn handle(
 
                     // At-most-once delivery guarantee, app can call getConnections()
                     if let Err(e) = session_request_tx.send((
-                        sub_msg.data.topic.clone(),
+                        msg.topic.clone(),
                         IncomingSessionMessage::SessionUpdate(
                             update.id,
-                            sub_msg.data.topic,
+                            msg.topic,
                             update.params.namespaces,
                         ),
                     )) {
@@ -354,9 +541,8 @@ pub async fn handle(
                     })?;
 
                     // Update session expiry if session exists, peer is controller, and expiry is valid (> current and <= now+7d)
-                    if let Some(mut session) = storage
-                        .get_session(sub_msg.data.topic.clone())
-                        .map_err(|e| {
+                    if let Some(mut session) =
+                        storage.get_session(msg.topic.clone()).map_err(|e| {
                             HandleError::Temporary(format!("get session: {e}"))
                         })?
                     {
@@ -382,10 +568,10 @@ pub async fn handle(
                                 storage
                                     .insert_json_rpc_history(
                                         request_id,
-                                        sub_msg.data.topic.clone(),
+                                        msg.topic.clone(),
                                         method,
                                         value.to_string(),
-                                        Some(TransportType::Relay),
+                                        Some(msg.transport_type),
                                     )
                                     .map_err(|e| {
                                         HandleError::Temporary(format!(
@@ -396,10 +582,10 @@ pub async fn handle(
                                 // Emit extend event
                                 // At-most-once delivery guarantee, app can call getConnections()
                                 if let Err(e) = session_request_tx.send((
-                                    sub_msg.data.topic.clone(),
+                                    msg.topic.clone(),
                                     IncomingSessionMessage::SessionExtend(
                                         extend.id,
-                                        sub_msg.data.topic.clone(),
+                                        msg.topic.clone(),
                                     ),
                                 )) {
                                     // Don't need to trigger a delivery retry. App can call getConnections()
@@ -417,10 +603,10 @@ pub async fn handle(
                                 storage
                                     .insert_json_rpc_history(
                                         request_id,
-                                        sub_msg.data.topic.clone(),
+                                        msg.topic.clone(),
                                         method,
                                         value.to_string(),
-                                        Some(TransportType::Relay),
+                                        Some(msg.transport_type),
                                     )
                                     .map_err(|e| {
                                         HandleError::Temporary(format!(
@@ -432,16 +618,16 @@ pub async fn handle(
                     } else {
                         tracing::warn!(
                             "wc_sessionExtend received for unknown topic: {:?}",
-                            sub_msg.data.topic
+                            msg.topic
                         );
 
                         storage
                             .insert_json_rpc_history(
                                 request_id,
-                                sub_msg.data.topic,
+                                msg.topic,
                                 method,
                                 value.to_string(),
-                                Some(TransportType::Relay),
+                                Some(msg.transport_type),
                             )
                             .map_err(|e| {
                                 HandleError::Temporary(format!(
@@ -468,10 +654,10 @@ pub async fn handle(
                     storage
                         .insert_json_rpc_history(
                             request_id,
-                            sub_msg.data.topic.clone(),
+                            msg.topic.clone(),
                             method,
                             value.to_string(),
-                            Some(TransportType::Relay),
+                            Some(msg.transport_type),
                         )
                         .map_err(|e| {
                             HandleError::Temporary(format!(
@@ -480,9 +666,9 @@ pub async fn handle(
                         })?;
 
                     if let Err(e) = session_request_tx.send((
-                        sub_msg.data.topic.clone(),
+                        msg.topic.clone(),
                         IncomingSessionMessage::SessionEvent(
-                            sub_msg.data.topic.clone(),
+                            msg.topic.clone(),
                             name,
                             data_value,
                             chain_id,
@@ -494,8 +680,206 @@ pub async fn handle(
 
                     Ok(())
                 }
+                methods::SESSION_AUTHENTICATE => {
+                    let request = serde_json::from_value::<
+                        SessionAuthenticateJsonRpc,
+                    >(value.clone())
+                    .map_err(|e| {
+                        HandleError::Peer(format!(
+                            "parse authenticate message: {e}"
+                        ))
+                    })?;
+
+                    // Warning: this is a network call!!!!
+                    let decrypted_hash = DecryptedHash(hex::encode(
+                        sha2::Sha256::digest(&decrypted),
+                    ));
+                    let attestation = handle_verify(
+                        VERIFY_SERVER_URL.to_string(),
+                        decrypted_hash,
+                        http_client,
+                        storage.clone(),
+                        &verify_state,
+                        msg.attestation.clone(),
+                        encrypted_hash,
+                        request.params.requester.metadata.url.clone(),
+                        probe_group.clone(),
+                    )
+                    .await;
+
+                    storage
+                        .insert_json_rpc_history(
+                            request_id,
+                            msg.topic.clone(),
+                            method,
+                            value.to_string(),
+                            Some(msg.transport_type),
+                        )
+                        .map_err(|e| {
+                            HandleError::Temporary(format!(
+                                "insert history: {e}"
+                            ))
+                        })?;
+
+                    let decision = verify_state.evaluate_origin(
+                        &attestation,
+                        &request.params.requester.metadata.url,
+                    );
+                    if decision.blocked {
+                        tracing::debug!(
+                            "Session authenticate {request_id} blocked by origin policy: {}",
+                            decision.message
+                        );
+                        let requester_public_key = decode_public_key(
+                            &request.params.requester.public_key,
+                        )
+                        .map_err(HandleError::Peer)?;
+                        let mut error: GenericJsonRpcResponseErrorData =
+                            RejectionReason::UserRejected.into();
+                        error.data = Some(decision.message);
+                        let response = GenericJsonRpcResponseError {
+                            id: request_id,
+                            jsonrpc: JsonRpcVersion::version_2(),
+                            error,
+                        };
+                        let (response_topic, message) =
+                            serialize_and_encrypt_session_authenticate_rejection(
+                                requester_public_key,
+                                &response,
+                            )
+                            .map_err(HandleError::Temporary)?;
+                        let response_json = serde_json::to_string(&response)
+                            .map_err(|e| {
+                                HandleError::Temporary(format!(
+                                    "serialize response: {e}"
+                                ))
+                            })?;
+                        storage
+                            .update_json_rpc_history_response(
+                                request_id,
+                                response_json,
+                            )
+                            .map_err(|e| {
+                                HandleError::Temporary(format!(
+                                    "update history: {e}"
+                                ))
+                            })?;
+                        let params = Params::Publish(Publish {
+                            topic: response_topic,
+                            message,
+                            attestation: None,
+                            ttl_secs: 3600,
+                            tag: 1118,
+                            prompt: false,
+                            analytics: None,
+                        });
+                        let (tx, rx) = tokio::sync::oneshot::channel();
+                        crate::spawn::spawn(
+                            async move {
+                                // Consume the response to avoid a publish error
+                                let response = rx.await;
+                                tracing::debug!(
+                                    "Received blocked authenticate response publish response: {:?}",
+                                    response
+                                );
+                            }
+                            .instrument(tracing::debug_span!(
+                                "blocked_authenticate_response",
+                                group = probe_group.clone()
+                            )),
+                        );
+                        if let Err(e) = priority_request_tx.send((
+                            MaybeVerifiedRequest::Unverified(params),
+                            tx,
+                        )) {
+                            tracing::warn!(
+                                "Failed to send priority request: {e}"
+                            );
+                        }
+                        return Ok(());
+                    }
+
+                    // At-most-once delivery guarantee, the dapp falls back to its session proposal
+                    if let Err(e) = session_request_tx.send((
+                        msg.topic.clone(),
+                        IncomingSessionMessage::SessionAuthenticate(
+                            SessionAuthenticateRequest {
+                                id: request.id,
+                                pairing_topic: msg.topic,
+                                requester: request.params.requester,
+                                auth_payload: request.params.auth_payload,
+                                expiry_timestamp: request
+                                    .params
+                                    .expiry_timestamp,
+                            },
+                            attestation,
+                        ),
+                    )) {
+                        tracing::debug!(
+                            "Failed to emit session authenticate event: {e}"
+                        );
+                    }
+
+                    Ok(())
+                }
                 methods::SESSION_PING => {
-                    // no-op for pings
+                    storage
+                        .insert_json_rpc_history(
+                            request_id,
+                            msg.topic.clone(),
+                            method,
+                            value.to_string(),
+                            Some(msg.transport_type),
+                        )
+                        .map_err(|e| {
+                            HandleError::Temporary(format!(
+                                "insert history: {e}"
+                            ))
+                        })?;
+
+                    let response = SimpleJsonRpcBoolResponse {
+                        id: request_id,
+                        jsonrpc: JsonRpcVersion::version_2(),
+                        result: true,
+                    };
+                    let message = serialize_and_encrypt_message_type0_envelope(
+                        session_sym_key,
+                        &response,
+                    )
+                    .map_err(HandleError::Temporary)?;
+                    let params = Params::Publish(Publish {
+                        topic: msg.topic,
+                        message,
+                        attestation: None,
+                        ttl_secs: 30,
+                        tag: 1115,
+                        prompt: false,
+                        analytics: None,
+                    });
+                    let (tx, rx) = tokio::sync::oneshot::channel();
+                    crate::spawn::spawn(
+                        async move {
+                            // Consume the response to avoid a publish error
+                            let response = rx.await;
+                            tracing::debug!(
+                                "Received ping response publish response: {:?}",
+                                response
+                            );
+                        }
+                        .instrument(
+                            tracing::debug_span!(
+                                "ping_response",
+                                group = probe_group.clone()
+                            ),
+                        ),
+                    );
+                    // Best-effort, the peer will consider us offline if this is lost
+                    if let Err(e) = priority_request_tx
+                        .send((MaybeVerifiedRequest::Unverified(params), tx))
+                    {
+                        tracing::warn!("Failed to send priority request: {e}");
+                    }
+
                     Ok(())
                 }
                 methods::SESSION_DELETE => {
@@ -509,23 +893,19 @@ pub async fn handle(
                             ))
                         })?;
 
-                    storage
-                        .delete_session(sub_msg.data.topic.clone())
-                        .map_err(|e| {
-                            HandleError::Temporary(format!(
-                                "delete session: {e}"
-                            ))
-                        })?;
+                    storage.delete_session(msg.topic.clone()).map_err(|e| {
+                        HandleError::Temporary(format!("delete session: {e}"))
+                    })?;
 
                     // IMO delete_session and emitting disconnect events should be idempotent. But this is unfortunately not currently the API spec
                     // If they were, we would not need to record the JSON-RPC history here and could always just do both operations: delete and emit. No conditionals
                     storage
                         .insert_json_rpc_history(
                             request_id,
-                            sub_msg.data.topic.clone(),
+                            msg.topic.clone(),
                             method,
                             value.to_string(),
-                            Some(TransportType::Relay),
+                            Some(msg.transport_type),
                         )
                         .map_err(|e| {
                             HandleError::Temporary(format!(
@@ -535,10 +915,9 @@ pub async fn handle(
 
                     // At-most-once delivery guarantee, app can call getConnections()
                     if let Err(e) = session_request_tx.send((
-                        sub_msg.data.topic.clone(),
+                        msg.topic.clone(),
                         IncomingSessionMessage::Disconnect(
-                            delete.id,
-                            sub_msg.data.topic,
+                            delete.id, msg.topic,
                         ),
                     )) {
                         // Don't need to trigger a delivery retry. App can call getConnections()
@@ -553,16 +932,81 @@ pub async fn handle(
             }
         }
         GenericJsonRpcMessage::Response(response) => {
-            let rpc_id = match response {
+            let rpc_id = match &response {
                 GenericJsonRpcResponse::Success(success) => success.id,
                 GenericJsonRpcResponse::Error(error) => error.id,
             };
 
-            let pairing = storage
-                .get_pairing(sub_msg.data.topic.clone(), rpc_id)
-                .map_err(|e| {
-                    HandleError::Temporary(format!("get pairing: {e}"))
-                })?;
+            if msg.tag == 1115 {
+                // Only the first response to a ping we sent is handled, late duplicates are ignored
+                let is_pending = storage
+                    .get_pending_json_rpc_requests(
+                        methods::SESSION_PING.to_owned(),
+                    )
+                    .map_err(|e| {
+                        HandleError::Temporary(format!(
+                            "get pending pings: {e}"
+                        ))
+                    })?
+                    .iter()
+                    .filter_map(|entry| {
+                        serde_json::from_str::<SessionPingJsonRpc>(&entry.body)
+                            .ok()
+                    })
+                    .any(|ping| ping.id == rpc_id);
+                if !is_pending {
+                    tracing::debug!(
+                        "ignoring unknown or answered ping response"
+                    );
+                    return Ok(());
+                }
+                storage
+                    .update_json_rpc_history_response(rpc_id, value.to_string())
+                    .map_err(|e| {
+                        HandleError::Temporary(format!(
+                            "update history response: {e}"
+                        ))
+                    })?;
+
+                let sender = pending_pings
+                    .lock()
+                    .map_err(|e| {
+                        HandleError::Temporary(format!(
+                            "lock pending pings: {e}"
+                        ))
+                    })?
+                    .remove(&rpc_id);
+                let result = match response {
+                    GenericJsonRpcResponse::Success(_) => Ok(()),
+                    GenericJsonRpcResponse::Error(error) => Err(error.error),
+                };
+                match sender {
+                    // The receiver is gone if `ping()` already timed out
+                    Some(sender) => {
+                        let _ = sender.send(result);
+                    }
+                    None => tracing::debug!("ping response after timeout"),
+                }
+                return Ok(());
+            }
+
+            if msg.tag == 1117 || msg.tag == 1118 {
+                return handle_session_authenticate_response(
+                    storage,
+                    msg.topic,
+                    rpc_id,
+                    value,
+                    msg.transport_type,
+                    session_request_tx,
+                    priority_request_tx,
+                    probe_group,
+                );
+            }
+
+            let pairing =
+                storage.get_pairing(msg.topic.clone(), rpc_id).map_err(
+                    |e| HandleError::Temporary(format!("get pairing: {e}")),
+                )?;
             if let Some(StoragePairing { sym_key: _, self_key }) = pairing {
                 let response = serde_json::from_value::<
                     SessionProposalJsonRpcResponse,
@@ -590,10 +1034,9 @@ pub async fn handle(
                         // Emit SessionRejected event
                         // At-most-once delivery guarantee
                         if let Err(e) = session_request_tx.send((
-                            sub_msg.data.topic.clone(),
+                            msg.topic.clone(),
                             IncomingSessionMessage::SessionReject(
-                                rpc_id,
-                                sub_msg.data.topic,
+                                rpc_id, msg.topic,
                             ),
                         )) {
                             // If app dies, it must establish a new connection anyway rather than resuming the pending one
@@ -678,7 +1121,7 @@ pub async fn handle(
                 }
 
                 Ok(())
-            } else if sub_msg.data.tag == 1109 {
+            } else if msg.tag == 1109 {
                 let response = if value.get("error").is_some() {
                     // Parse as error response
                     let error_response = serde_json::from_value::<
@@ -714,11 +1157,9 @@ pub async fn handle(
 
                 // At-most-once delivery guarantee
                 if let Err(e) = session_request_tx.send((
-                    sub_msg.data.topic.clone(),
+                    msg.topic.clone(),
                     IncomingSessionMessage::SessionRequestResponse(
-                        rpc_id,
-                        sub_msg.data.topic,
-                        response,
+                        rpc_id, msg.topic, response,
                     ),
                 )) {
                     // If app dies, will have to send new session request
@@ -735,3 +1176,519 @@ pub async fn handle(
         }
     }
 }
+
+#[allow(clippy::too_many_arguments)]
+fn handle_session_authenticate_response(
+    storage: Arc<dyn Storage>,
+    response_topic: Topic,
+    rpc_id: ProtocolRpcId,
+    value: serde_json::Value,
+    transport_type: TransportType,
+    session_request_tx: tokio::sync::mpsc::UnboundedSender<(
+        Topic,
+        IncomingSessionMessage,
+    )>,
+    priority_request_tx: tokio::sync::mpsc::UnboundedSender<(
+        MaybeVerifiedRequest,
+        tokio::sync::oneshot::Sender<Result<Response, RequestError>>,
+    )>,
+    probe_group: Option<String>,
+) -> Result<(), HandleError> {
+    let self_key = storage
+        .get_authenticate_request_key(response_topic.clone())
+        .map_err(|e| {
+            HandleError::Temporary(format!("get authenticate request key: {e}"))
+        })?
+        .ok_or(HandleError::Dropped(
+            "No pending authenticate request for response".to_string(),
+        ))?;
+
+    if value.get("error").is_some() {
+        storage
+            .update_json_rpc_history_response(rpc_id, value.to_string())
+            .map_err(|e| {
+                HandleError::Temporary(format!("update history response: {e}"))
+            })?;
+
+        // At-most-once delivery guarantee
+        if let Err(e) = session_request_tx.send((
+            response_topic.clone(),
+            IncomingSessionMessage::SessionReject(rpc_id, response_topic),
+        )) {
+            tracing::debug!("Failed to emit session rejected event: {e}");
+        }
+
+        return Ok(());
+    }
+
+    let response = serde_json::from_value::<
+        SessionAuthenticateResultResponseJsonRpc,
+    >(value.clone())
+    .map_err(|e| {
+        HandleError::Peer(format!("parse authenticate response: {e}"))
+    })?;
+
+    // The request is still pending, as this is its response
+    let request = storage
+        .get_pending_json_rpc_requests(methods::SESSION_AUTHENTICATE.to_owned())
+        .map_err(|e| {
+            HandleError::Temporary(format!("get pending requests: {e}"))
+        })?
+        .into_iter()
+        .filter_map(|entry| {
+            serde_json::from_str::<SessionAuthenticateJsonRpc>(&entry.body).ok()
+        })
+        .find(|request| request.id == rpc_id)
+        .ok_or(HandleError::Dropped(
+            "No pending authenticate request for response".to_string(),
+        ))?;
+    if response.result.cacaos.is_empty() {
+        return Err(HandleError::Peer("no cacaos in response".to_string()));
+    }
+    for cacao in &response.result.cacaos {
+        validate_cacao(cacao, &request.params.auth_payload)
+            .map_err(|e| HandleError::Peer(format!("invalid cacao: {e}")))?;
+    }
+    let session_namespaces = namespaces_from_cacaos(&response.result.cacaos)
+        .map_err(|e| HandleError::Peer(format!("cacao namespaces: {e}")))?;
+
+    let responder_public_key: [u8; 32] =
+        hex::decode(&response.result.responder.public_key)
+            .map_err(|e| {
+                HandleError::Peer(format!("decode responder public key: {e}"))
+            })?
+            .try_into()
+            .map_err(|e| {
+                HandleError::Peer(format!(
+                    "convert responder public key to fixed-size array: {e:?}"
+                ))
+            })?;
+    let self_key = x25519_dalek::StaticSecret::from(self_key);
+    let shared_secret = diffie_hellman(
+        &x25519_dalek::PublicKey::from(responder_public_key),
+        &self_key,
+    );
+    let session_topic = topic_from_sym_key(&shared_secret);
+
+    let now = crate::time::SystemTime::now()
+        .duration_since(crate::time::UNIX_EPOCH)
+        .map_err(|e| HandleError::Temporary(format!("get now: {e}")))?
+        .as_secs();
+    let session = Session {
+        request_id: rpc_id,
+        topic: session_topic.clone(),
+        expiry: crate::sign::utils::compute_max_expiry(now),
+        relay_protocol: "irn".to_string(),
+        relay_data: None,
+        controller_key: Some(responder_public_key),
+        session_sym_key: shared_secret,
+        self_public_key: x25519_dalek::PublicKey::from(&self_key).to_bytes(),
+        self_meta_data: Metadata {
+            name: "".to_string(),
+            description: "".to_string(),
+            url: "".to_string(),
+            icons: vec![],
+            verify_url: None,
+            redirect: None,
+        },
+        peer_public_key: Some(responder_public_key),
+        peer_meta_data: Some(response.result.responder.metadata.clone()),
+        session_namespaces,
+        required_namespaces: HashMap::new(),
+        optional_namespaces: HashMap::new(),
+        session_properties: HashMap::new(),
+        scoped_properties: HashMap::new(),
+        is_acknowledged: true,
+        pairing_topic: response_topic.clone(),
+        transport_type: Some(transport_type),
+    };
+    storage
+        .add_session(session)
+        .map_err(|e| HandleError::Temporary(format!("add session: {e}")))?;
+
+    storage
+        .update_json_rpc_history_response(rpc_id, value.to_string())
+        .map_err(|e| {
+            HandleError::Temporary(format!("update history response: {e}"))
+        })?;
+
+    {
+        let params =
+            Params::Subscribe(Subscribe { topic: session_topic.clone() });
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        crate::spawn::spawn(
+            async move {
+                // Consume the response to avoid a publish error
+                let response = rx.await;
+                tracing::debug!("Received subscribe response: {:?}", response);
+            }
+            .instrument(tracing::debug_span!(
+                "subscribe_response",
+                group = probe_group.clone()
+            )),
+        );
+        if let Err(e) = priority_request_tx
+            .send((MaybeVerifiedRequest::Unverified(params), tx))
+        {
+            tracing::warn!("Failed to send priority request: {e}");
+        }
+    }
+
+    // At-most-once delivery guarantee, app can call getConnections()
+    if let Err(e) = session_request_tx.send((
+        session_topic.clone(),
+        IncomingSessionMessage::SessionAuthenticateResponse(
+            rpc_id,
+            session_topic,
+            response.result.cacaos,
+        ),
+    )) {
+        tracing::debug!(
+            "Failed to emit session authenticate response event: {e}"
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::sign::{
+            protocol_types::{
+                AuthPayload, Participant, SessionAuthenticate, SessionPing,
+                SessionRequest, SessionRequestRequest,
+            },
+            storage::MemoryStorage,
+            verify::{
+                policy::{OriginPolicy, OriginPolicyReason},
+                validate::VerifyMode,
+            },
+        },
+    };
+
+    fn session(sym_key: [u8; 32]) -> Session {
+        Session {
+            request_id: ProtocolRpcId::generate(),
+            topic: topic_from_sym_key(&sym_key),
+            expiry: u64::MAX,
+            relay_protocol: "irn".to_owned(),
+            relay_data: None,
+            controller_key: None,
+            session_sym_key: sym_key,
+            self_public_key: sym_key,
+            self_meta_data: Metadata {
+                name: "Test".to_owned(),
+                description: "Test".to_owned(),
+                url: "https://example.com".to_owned(),
+                icons: vec![],
+                verify_url: None,
+                redirect: None,
+            },
+            peer_public_key: None,
+            peer_meta_data: None,
+            session_namespaces: HashMap::new(),
+            required_namespaces: HashMap::new(),
+            optional_namespaces: HashMap::new(),
+            session_properties: HashMap::new(),
+            scoped_properties: HashMap::new(),
+            is_acknowledged: true,
+            pairing_topic: topic_from_sym_key(&[0; 32]),
+            transport_type: Some(TransportType::Relay),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ping_round_trip() {
+        let sym_key = [1; 32];
+        let topic = topic_from_sym_key(&sym_key);
+        let storage = Arc::new(MemoryStorage::new());
+        storage.add_session(session(sym_key)).unwrap();
+        let (session_tx, _session_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (request_tx, mut request_rx) =
+            tokio::sync::mpsc::unbounded_channel();
+        let pending_pings = PendingPings::default();
+
+        // Incoming pings are answered with a 1115 publish
+        let ping = SessionPingJsonRpc {
+            id: ProtocolRpcId::generate(),
+            jsonrpc: JsonRpcVersion::version_2(),
+            method: methods::SESSION_PING.to_owned(),
+            params: SessionPing {},
+        };
+        handle_message(
+            storage.clone(),
+            reqwest::Client::new(),
+            IncomingMessage {
+                topic: topic.clone(),
+                message: serialize_and_encrypt_message_type0_envelope(
+                    sym_key, &ping,
+                )
+                .unwrap(),
+                attestation: None,
+                tag: 1114,
+                transport_type: TransportType::Relay,
+            },
+            session_tx.clone(),
+            request_tx.clone(),
+            pending_pings.clone(),
+            VerifyState::default(),
+            None,
+        )
+        .await
+        .unwrap();
+        let (request, _) = request_rx.try_recv().unwrap();
+        let MaybeVerifiedRequest::Unverified(Params::Publish(publish)) =
+            request
+        else {
+            panic!("expected a publish");
+        };
+        assert_eq!(publish.topic, topic);
+        assert_eq!(publish.tag, 1115);
+
+        // The answer resolves the matching pending ping
+        let (ping_tx, mut ping_rx) = tokio::sync::oneshot::channel();
+        pending_pings.lock().unwrap().insert(ping.id, ping_tx);
+        let pong = IncomingMessage {
+            topic,
+            message: publish.message,
+            attestation: None,
+            tag: publish.tag,
+            transport_type: TransportType::Relay,
+        };
+        handle_message(
+            storage.clone(),
+            reqwest::Client::new(),
+            pong.clone(),
+            session_tx.clone(),
+            request_tx.clone(),
+            pending_pings.clone(),
+            VerifyState::default(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ping_rx.try_recv().unwrap(), Ok(()));
+        assert!(pending_pings.lock().unwrap().is_empty());
+
+        // A duplicate answer is ignored, the ping was already answered
+        let (ping_tx, mut ping_rx) = tokio::sync::oneshot::channel();
+        pending_pings.lock().unwrap().insert(ping.id, ping_tx);
+        handle_message(
+            storage,
+            reqwest::Client::new(),
+            pong,
+            session_tx,
+            request_tx,
+            pending_pings.clone(),
+            VerifyState::default(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(ping_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_session_request_blocked_by_origin_policy() {
+        let sym_key = [2; 32];
+        let topic = topic_from_sym_key(&sym_key);
+        let storage = Arc::new(MemoryStorage::new());
+        let mut session = session(sym_key);
+        session.peer_meta_data = Some(Metadata {
+            name: "Dapp".to_owned(),
+            description: "Dapp".to_owned(),
+            url: "https://dapp.example.org".to_owned(),
+            icons: vec![],
+            verify_url: None,
+            redirect: None,
+        });
+        storage.add_session(session).unwrap();
+        let (session_tx, mut session_rx) =
+            tokio::sync::mpsc::unbounded_channel();
+        let (request_tx, mut request_rx) =
+            tokio::sync::mpsc::unbounded_channel();
+        let verify_state = VerifyState::default();
+        verify_state.set_mode(VerifyMode::Offline);
+        verify_state.set_origin_policy(OriginPolicy {
+            allow_list: vec!["*.example.com".to_owned()],
+            ..Default::default()
+        });
+
+        let request = SessionRequestJsonRpc {
+            id: ProtocolRpcId::generate(),
+            jsonrpc: JsonRpcVersion::version_2(),
+            method: methods::SESSION_REQUEST.to_owned(),
+            params: SessionRequest {
+                chain_id: "eip155:1".to_owned(),
+                request: SessionRequestRequest {
+                    method: "personal_sign".to_owned(),
+                    params: serde_json::json!([]),
+                    expiry: None,
+                },
+            },
+        };
+        handle_message(
+            storage.clone(),
+            reqwest::Client::new(),
+            IncomingMessage {
+                topic: topic.clone(),
+                message: serialize_and_encrypt_message_type0_envelope(
+                    sym_key, &request,
+                )
+                .unwrap(),
+                attestation: None,
+                tag: 1108,
+                transport_type: TransportType::Relay,
+            },
+            session_tx,
+            request_tx,
+            PendingPings::default(),
+            verify_state,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let (_, message) = session_rx.try_recv().unwrap();
+        let IncomingSessionMessage::SessionRequestBlocked(blocked, decision) =
+            message
+        else {
+            panic!("expected a blocked session request");
+        };
+        assert_eq!(blocked.id, request.id);
+        assert_eq!(decision.reason, OriginPolicyReason::NotAllowed);
+
+        let (request, _) = request_rx.try_recv().unwrap();
+        let MaybeVerifiedRequest::Unverified(Params::Publish(publish)) =
+            request
+        else {
+            panic!("expected a publish");
+        };
+        assert_eq!(publish.tag, 1109);
+        assert!(
+            storage
+                .get_pending_json_rpc_requests(
+                    methods::SESSION_REQUEST.to_owned()
+                )
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_authenticate_blocked_by_origin_policy() {
+        let sym_key = [3; 32];
+        let topic = topic_from_sym_key(&sym_key);
+        let storage = Arc::new(MemoryStorage::new());
+        storage.add_session(session(sym_key)).unwrap();
+        let (session_tx, mut session_rx) =
+            tokio::sync::mpsc::unbounded_channel();
+        let (request_tx, mut request_rx) =
+            tokio::sync::mpsc::unbounded_channel();
+        let verify_state = VerifyState::default();
+        verify_state.set_mode(VerifyMode::Offline);
+        verify_state.set_origin_policy(OriginPolicy {
+            allow_list: vec!["*.example.com".to_owned()],
+            ..Default::default()
+        });
+
+        let requester_key = x25519_dalek::StaticSecret::from([4; 32]);
+        let requester_public_key =
+            x25519_dalek::PublicKey::from(&requester_key);
+        let request = SessionAuthenticateJsonRpc {
+            id: ProtocolRpcId::generate(),
+            jsonrpc: JsonRpcVersion::version_2(),
+            method: methods::SESSION_AUTHENTICATE.to_owned(),
+            params: SessionAuthenticate {
+                requester: Participant {
+                    public_key: hex::encode(requester_public_key.as_bytes()),
+                    metadata: Metadata {
+                        name: "Dapp".to_owned(),
+                        description: "Dapp".to_owned(),
+                        url: "https://dapp.example.org".to_owned(),
+                        icons: vec![],
+                        verify_url: None,
+                        redirect: None,
+                    },
+                },
+                auth_payload: AuthPayload {
+                    payload_type: None,
+                    chains: vec!["eip155:1".to_owned()],
+                    domain: "dapp.example.org".to_owned(),
+                    aud: "https://dapp.example.org/login".to_owned(),
+                    nonce: "1".to_owned(),
+                    version: "1".to_owned(),
+                    iat: "2024-01-01T00:00:00Z".to_owned(),
+                    nbf: None,
+                    exp: None,
+                    statement: None,
+                    request_id: None,
+                    resources: None,
+                    signature_types: None,
+                },
+                expiry_timestamp: u64::MAX,
+            },
+        };
+        handle_message(
+            storage.clone(),
+            reqwest::Client::new(),
+            IncomingMessage {
+                topic: topic.clone(),
+                message: serialize_and_encrypt_message_type0_envelope(
+                    sym_key, &request,
+                )
+                .unwrap(),
+                attestation: None,
+                tag: 1116,
+                transport_type: TransportType::Relay,
+            },
+            session_tx,
+            request_tx,
+            PendingPings::default(),
+            verify_state,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert!(session_rx.try_recv().is_err());
+
+        let (request, _) = request_rx.try_recv().unwrap();
+        let MaybeVerifiedRequest::Unverified(Params::Publish(publish)) =
+            request
+        else {
+            panic!("expected a publish");
+        };
+        assert_eq!(publish.tag, 1118);
+        assert_eq!(
+            publish.topic,
+            topic_from_sym_key(requester_public_key.as_bytes())
+        );
+
+        let decoded = BASE64.decode(publish.message.as_bytes()).unwrap();
+        let envelope =
+            envelope_type1::deserialize_envelope_type1(&decoded).unwrap();
+        let shared_secret = diffie_hellman(
+            &x25519_dalek::PublicKey::from(envelope.pk),
+            &requester_key,
+        );
+        let decrypted = ChaCha20Poly1305::new(&shared_secret.into())
+            .decrypt(&Nonce::from(envelope.iv), envelope.sb.as_slice())
+            .unwrap();
+        let response =
+            serde_json::from_slice::<GenericJsonRpcResponseError>(&decrypted)
+                .unwrap();
+        assert_eq!(response.id, request.id);
+        assert!(response.error.data.is_some());
+        assert!(
+            storage
+                .get_pending_json_rpc_requests(
+                    methods::SESSION_AUTHENTICATE.to_owned()
+                )
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use {
+    crate::sign::protocol_types::Metadata,
+    data_encoding::{BASE64, BASE64URL_NOPAD},
+    relay_rpc::domain::Topic,
+    std::{collections::HashMap, sync::Arc},
+    url::Url,
+};
+
+// Link Mode carries the same base64 envelopes the relay would, but as query params of the peer's universal link:
+// {universal_link}?wc_ev={base64url(envelope)}&topic={topic}&tag={tag}
+pub const ENVELOPE_QUERY_PARAM: &str = "wc_ev";
+pub const TOPIC_QUERY_PARAM: &str = "topic";
+pub const TAG_QUERY_PARAM: &str = "tag";
+
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi_macros::Error))]
+#[error("Link Mode: {0}")]
+pub enum LinkModeError {
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("Missing envelope")]
+    MissingEnvelope,
+
+    #[error("Invalid envelope: {0}")]
+    InvalidEnvelope(String),
+
+    #[error("Missing topic")]
+    MissingTopic,
+
+    #[error("Invalid tag: {0}")]
+    InvalidTag(String),
+
+    #[error("Handle message: {0}")]
+    Handle(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkModeMessage {
+    pub topic: Topic,
+    /// Standard base64 envelope, as it would be published to the relay
+    pub message: Arc<str>,
+    /// Relay tag the message would have been published with
+    pub tag: u32,
+}
+
+/// Peers that don't send a tag only use Link Mode for session request responses
+/// and One-Click Auth approvals, which the envelope type tells apart.
+fn legacy_tag(message: &str) -> u32 {
+    let is_type1 = BASE64
+        .decode(message.as_bytes())
+        .ok()
+        .and_then(|decoded| decoded.first().copied())
+        == Some(1);
+    if is_type1 { 1117 } else { 1109 }
+}
+
+/// Returns the peer's universal link if it can be reached over Link Mode.
+pub fn universal_link(metadata: &Metadata) -> Option<&str> {
+    metadata
+        .redirect
+        .as_ref()
+        .filter(|redirect| redirect.link_mode)
+        .and_then(|redirect| redirect.universal.as_deref())
+        .filter(|universal| !universal.is_empty())
+}
+
+/// Link Mode is only used when both sides opted in with `Redirect::link_mode`.
+pub fn should_use_link_mode<'a>(
+    self_metadata: &Metadata,
+    peer_metadata: Option<&'a Metadata>,
+) -> Option<&'a str> {
+    if !self_metadata.redirect.as_ref().is_some_and(|r| r.link_mode) {
+        return None;
+    }
+    peer_metadata.and_then(universal_link)
+}
+
+pub fn encode_url(
+    universal_link: &str,
+    topic: &Topic,
+    message: &str,
+    tag: u32,
+) -> Result<String, LinkModeError> {
+    let mut url = Url::parse(universal_link)
+        .map_err(|e| LinkModeError::InvalidUrl(e.to_string()))?;
+    let envelope = BASE64
+        .decode(message.as_bytes())
+        .map_err(|e| LinkModeError::InvalidEnvelope(e.to_string()))?;
+    url.query_pairs_mut()
+        .append_pair(ENVELOPE_QUERY_PARAM, &BASE64URL_NOPAD.encode(&envelope))
+        .append_pair(TOPIC_QUERY_PARAM, &topic.to_string())
+        .append_pair(TAG_QUERY_PARAM, &tag.to_string());
+    Ok(url.to_string())
+}
+
+pub fn decode_url(url: &str) -> Result<LinkModeMessage, LinkModeError> {
+    let url = Url::parse(url)
+        .map_err(|e| LinkModeError::InvalidUrl(e.to_string()))?;
+    let query = url.query_pairs().collect::<HashMap<_, _>>();
+
+    let envelope = query
+        .get(ENVELOPE_QUERY_PARAM)
+        .ok_or(LinkModeError::MissingEnvelope)?;
+    // Be lenient with padding, other SDKs don't agree on it
+    let envelope = BASE64URL_NOPAD
+        .decode(envelope.trim_end_matches('=').as_bytes())
+        .map_err(|e| LinkModeError::InvalidEnvelope(e.to_string()))?;
+    if envelope.is_empty() {
+        return Err(LinkModeError::InvalidEnvelope("empty".to_owned()));
+    }
+
+    let topic =
+        query.get(TOPIC_QUERY_PARAM).ok_or(LinkModeError::MissingTopic)?;
+    if topic.is_empty() {
+        return Err(LinkModeError::MissingTopic);
+    }
+
+    let message = BASE64.encode(&envelope);
+    let tag = match query.get(TAG_QUERY_PARAM) {
+        Some(tag) => tag
+            .parse()
+            .map_err(|_| LinkModeError::InvalidTag(tag.to_string()))?,
+        None => legacy_tag(&message),
+    };
+
+    Ok(LinkModeMessage {
+        topic: Topic::new(topic.to_string().into()),
+        message: message.into(),
+        tag,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::sign::{
+            protocol_types::Redirect,
+            utils::{
+                serialize_and_encrypt_message_type0_envelope,
+                serialize_and_encrypt_message_type1_envelope,
+                topic_from_sym_key,
+            },
+        },
+    };
+
+    fn metadata(redirect: Option<Redirect>) -> Metadata {
+        Metadata {
+            name: "Test".to_owned(),
+            description: "Test".to_owned(),
+            url: "https://example.com".to_owned(),
+            icons: vec![],
+            verify_url: None,
+            redirect,
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let topic = topic_from_sym_key(&[1u8; 32]);
+        let message = serialize_and_encrypt_message_type0_envelope(
+            [2u8; 32],
+            &serde_json::json!({"id": 1, "jsonrpc": "2.0", "result": true}),
+        )
+        .unwrap();
+
+        let url = encode_url(
+            "https://example.com/wc?foo=bar",
+            &topic,
+            &message,
+            1115,
+        )
+        .unwrap();
+        assert!(url.starts_with("https://example.com/wc?foo=bar&wc_ev="));
+        assert!(url.ends_with("&tag=1115"));
+        assert!(!url.contains('+') && !url.contains("%2F"));
+
+        let decoded = decode_url(&url).unwrap();
+        assert_eq!(decoded.topic, topic);
+        assert_eq!(decoded.message, message);
+        assert_eq!(decoded.tag, 1115);
+    }
+
+    #[test]
+    fn test_legacy_tag() {
+        let topic = topic_from_sym_key(&[1u8; 32]);
+        let message = serialize_and_encrypt_message_type1_envelope(
+            [2u8; 32],
+            [3u8; 32],
+            &serde_json::json!({"id": 1, "jsonrpc": "2.0", "result": true}),
+        )
+        .unwrap();
+        let url = format!(
+            "https://example.com/wc?wc_ev={}&topic={topic}",
+            BASE64URL_NOPAD.encode(&BASE64.decode(message.as_bytes()).unwrap())
+        );
+        assert_eq!(decode_url(&url).unwrap().tag, 1117);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(matches!(
+            decode_url("not a url"),
+            Err(LinkModeError::InvalidUrl(_))
+        ));
+        assert_eq!(
+            decode_url("https://example.com/wc?topic=abc"),
+            Err(LinkModeError::MissingEnvelope)
+        );
+        assert_eq!(
+            decode_url("https://example.com/wc?wc_ev=AAAA"),
+            Err(LinkModeError::MissingTopic)
+        );
+        assert!(matches!(
+            decode_url("https://example.com/wc?wc_ev=!!!&topic=abc"),
+            Err(LinkModeError::InvalidEnvelope(_))
+        ));
+        assert_eq!(
+            decode_url("https://example.com/wc?wc_ev=AAAA&topic=abc&tag=x"),
+            Err(LinkModeError::InvalidTag("x".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_should_use_link_mode() {
+        let enabled = metadata(Some(Redirect {
+            native: None,
+            universal: Some("https://example.com/wc".to_owned()),
+            link_mode: true,
+        }));
+        let disabled = metadata(Some(Redirect {
+            native: None,
+            universal: Some("https://example.com/wc".to_owned()),
+            link_mode: false,
+        }));
+        let no_universal = metadata(Some(Redirect {
+            native: Some("example://".to_owned()),
+            universal: None,
+            link_mode: true,
+        }));
+
+        assert_eq!(
+            should_use_link_mode(&enabled, Some(&enabled)),
+            Some("https://example.com/wc")
+        );
+        assert_eq!(should_use_link_mode(&disabled, Some(&enabled)), None);
+        assert_eq!(should_use_link_mode(&enabled, Some(&disabled)), None);
+        assert_eq!(should_use_link_mode(&enabled, Some(&no_universal)), None);
+        assert_eq!(should_use_link_mode(&enabled, None), None);
+        assert_eq!(should_use_link_mode(&metadata(None), Some(&enabled)), None);
+    }
+}
@@ -5,20 +5,28 @@ pub use {
         domain::{MessageId, Topic},
         rpc::ErrorData,
     },
-    verify::validate::VerifyContext,
+    verify::{
+        policy::{OriginDecision, OriginPolicy, OriginPolicyReason},
+        validate::{VerifyContext, VerifyKeyRotation, VerifyMode},
+    },
 };
 
+pub mod cacao;
 pub mod client;
 pub mod client_errors;
 pub mod client_types;
 pub mod envelope_type0;
 mod envelope_type1;
+mod expiry;
 mod incoming;
+pub mod link_mode;
+pub mod namespaces;
 mod pairing_uri;
 mod priority_future;
 pub mod protocol_types;
 pub mod pulse;
 mod relay;
+pub mod relay_pool;
 mod relay_url;
 pub mod storage;
 pub mod test_helpers;
@@ -0,0 +1,494 @@
+use {
+    crate::sign::{
+        client_types::RejectionReason,
+        protocol_types::{ProposalNamespaces, SettleNamespace},
+    },
+    serde::{Deserialize, Serialize},
+    std::collections::{BTreeSet, HashMap},
+};
+
+/// What the wallet is able to approve for a single CAIP-2 namespace e.g. `eip155`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "uniffi", derive(uniffi_macros::Record))]
+pub struct SupportedNamespace {
+    pub chains: Vec<String>,
+    pub methods: Vec<String>,
+    pub events: Vec<String>,
+    /// CAIP-10 accounts e.g. `eip155:1:0xab16a96D359eC26a11e2C2b3d8f8B8942d5Bfcdb`
+    pub accounts: Vec<String>,
+}
+
+pub type SupportedNamespaces = HashMap<String, SupportedNamespace>;
+
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi_macros::Error))]
+#[error("Namespace negotiation error: {0}")]
+pub enum NamespaceError {
+    #[error("Unsupported namespace: {0}")]
+    UnsupportedNamespace(String),
+
+    #[error("Unsupported chain: {0}")]
+    UnsupportedChain(String),
+
+    #[error("Unsupported method: {0}")]
+    UnsupportedMethod(String),
+
+    #[error("Unsupported event: {0}")]
+    UnsupportedEvent(String),
+
+    #[error("No accounts for chain: {0}")]
+    MissingAccounts(String),
+
+    #[error("Invalid chain: {0}")]
+    InvalidChain(String),
+
+    #[error("Invalid account: {0}")]
+    InvalidAccount(String),
+}
+
+impl NamespaceError {
+    /// The reason to send back to the dapp when rejecting the proposal because of this error
+    pub fn rejection_reason(&self) -> RejectionReason {
+        match self {
+            Self::UnsupportedNamespace(_)
+            | Self::UnsupportedChain(_)
+            | Self::InvalidChain(_) => RejectionReason::UnsupportedChains,
+            Self::UnsupportedMethod(_) => RejectionReason::UnsupportedMethods,
+            Self::UnsupportedEvent(_) => RejectionReason::UnsupportedEvents,
+            Self::MissingAccounts(_) | Self::InvalidAccount(_) => {
+                RejectionReason::UnsupportedAccounts
+            }
+        }
+    }
+}
+
+/// A proposal namespace after resolving the `"eip155:1": {}` shorthand where the chain is the key
+#[derive(Debug, Default)]
+struct NormalizedNamespace {
+    chains: BTreeSet<String>,
+    methods: BTreeSet<String>,
+    events: BTreeSet<String>,
+}
+
+fn normalize(
+    namespaces: &ProposalNamespaces,
+) -> Result<HashMap<String, NormalizedNamespace>, NamespaceError> {
+    let mut normalized = HashMap::<String, NormalizedNamespace>::new();
+    for (key, namespace) in namespaces {
+        let (namespace_key, chains) = if key.contains(':') {
+            validate_chain(key)?;
+            (namespace_of(key).to_owned(), vec![key.clone()])
+        } else {
+            (key.clone(), namespace.chains.clone())
+        };
+        let entry = normalized.entry(namespace_key.clone()).or_default();
+        for chain in chains {
+            validate_chain(&chain)?;
+            if namespace_of(&chain) != namespace_key {
+                return Err(NamespaceError::InvalidChain(chain));
+            }
+            entry.chains.insert(chain);
+        }
+        entry.methods.extend(namespace.methods.iter().cloned());
+        entry.events.extend(namespace.events.iter().cloned());
+    }
+    Ok(normalized)
+}
+
+/// The `supported` values that were `requested`, in the wallet's order
+fn intersect(
+    supported: &[String],
+    requested: &BTreeSet<String>,
+) -> Vec<String> {
+    supported
+        .iter()
+        .filter(|value| requested.contains(*value))
+        .cloned()
+        .collect()
+}
+
+fn namespace_of(chain: &str) -> &str {
+    chain.split(':').next().unwrap_or_default()
+}
+
+/// CAIP-2: `namespace:reference`
+fn validate_chain(chain: &str) -> Result<(), NamespaceError> {
+    let mut parts = chain.split(':');
+    let valid = match (parts.next(), parts.next(), parts.next()) {
+        (Some(namespace), Some(reference), None) => {
+            (3..=8).contains(&namespace.len())
+                && namespace.chars().all(|c| {
+                    c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'
+                })
+                && (1..=32).contains(&reference.len())
+                && reference
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        }
+        _ => false,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(NamespaceError::InvalidChain(chain.to_owned()))
+    }
+}
+
+/// CAIP-10: `namespace:reference:address`, returns the CAIP-2 chain
+fn chain_of_account(account: &str) -> Result<&str, NamespaceError> {
+    let invalid = || NamespaceError::InvalidAccount(account.to_owned());
+    let (chain, address) = account.rsplit_once(':').ok_or_else(invalid)?;
+    if address.is_empty() || validate_chain(chain).is_err() {
+        return Err(invalid());
+    }
+    Ok(chain)
+}
+
+/// Validates the shape of a proposal's namespaces before it is sent to a wallet.
+pub fn validate_proposal_namespaces(
+    namespaces: &ProposalNamespaces,
+) -> Result<(), NamespaceError> {
+    normalize(namespaces).map(|_| ())
+}
+
+/// Builds the namespaces to approve a proposal with from what the wallet supports.
+/// Each requested namespace is approved with the chains, methods and events that are both requested
+/// (required or optional) and supported, and the supported accounts on those chains.
+/// Fails if anything in `required_namespaces` is not supported.
+pub fn build_approved_namespaces(
+    required_namespaces: &ProposalNamespaces,
+    optional_namespaces: &ProposalNamespaces,
+    supported_namespaces: &SupportedNamespaces,
+) -> Result<HashMap<String, SettleNamespace>, NamespaceError> {
+    let required = normalize(required_namespaces)?;
+    let mut requested = normalize(optional_namespaces)?;
+    for (namespace_key, namespace) in &required {
+        let entry = requested.entry(namespace_key.clone()).or_default();
+        entry.chains.extend(namespace.chains.iter().cloned());
+        entry.methods.extend(namespace.methods.iter().cloned());
+        entry.events.extend(namespace.events.iter().cloned());
+    }
+
+    let mut approved = HashMap::new();
+    for (namespace_key, requested) in &requested {
+        let Some(supported) = supported_namespaces.get(namespace_key) else {
+            if required.contains_key(namespace_key) {
+                return Err(NamespaceError::UnsupportedNamespace(
+                    namespace_key.clone(),
+                ));
+            }
+            continue;
+        };
+        for chain in &supported.chains {
+            validate_chain(chain)?;
+        }
+        let mut accounts = Vec::new();
+        let mut account_chains = BTreeSet::new();
+        for account in &supported.accounts {
+            let chain = chain_of_account(account)?;
+            if requested.chains.contains(chain)
+                && supported.chains.iter().any(|c| c == chain)
+            {
+                accounts.push(account.clone());
+                account_chains.insert(chain);
+            }
+        }
+        if accounts.is_empty() {
+            if let Some(required) = required.get(namespace_key) {
+                return Err(
+                    match required
+                        .chains
+                        .iter()
+                        .find(|chain| !supported.chains.contains(chain))
+                    {
+                        Some(chain) => {
+                            NamespaceError::UnsupportedChain(chain.clone())
+                        }
+                        None => NamespaceError::MissingAccounts(
+                            namespace_key.clone(),
+                        ),
+                    },
+                );
+            }
+            continue;
+        }
+        // Chains without an account can't be part of the session
+        let chains = supported
+            .chains
+            .iter()
+            .filter(|chain| account_chains.contains(chain.as_str()))
+            .cloned()
+            .collect();
+        approved.insert(
+            namespace_key.clone(),
+            SettleNamespace {
+                accounts,
+                methods: intersect(&supported.methods, &requested.methods),
+                events: intersect(&supported.events, &requested.events),
+                chains,
+            },
+        );
+    }
+
+    validate_approved_namespaces(required_namespaces, &approved)?;
+    Ok(approved)
+}
+
+/// Validates namespaces a wallet is about to approve a proposal with.
+/// Every required chain, method and event must be approved, and every approved chain must have an account.
+pub fn validate_approved_namespaces(
+    required_namespaces: &ProposalNamespaces,
+    approved_namespaces: &HashMap<String, SettleNamespace>,
+) -> Result<(), NamespaceError> {
+    let mut approved = HashMap::<String, NormalizedNamespace>::new();
+    for (key, namespace) in approved_namespaces {
+        // Settle namespaces may also be keyed by chain
+        let (namespace_key, chains) = if key.contains(':') {
+            (namespace_of(key), vec![key.clone()])
+        } else {
+            (key.as_str(), namespace.chains.clone())
+        };
+        let mut account_chains = BTreeSet::new();
+        for account in &namespace.accounts {
+            let chain = chain_of_account(account)?;
+            if namespace_of(chain) != namespace_key {
+                return Err(NamespaceError::InvalidAccount(account.clone()));
+            }
+            account_chains.insert(chain);
+        }
+        let entry = approved.entry(namespace_key.to_owned()).or_default();
+        for chain in chains {
+            validate_chain(&chain)?;
+            if !account_chains.contains(chain.as_str()) {
+                return Err(NamespaceError::MissingAccounts(chain));
+            }
+            entry.chains.insert(chain);
+        }
+        entry.methods.extend(namespace.methods.iter().cloned());
+        entry.events.extend(namespace.events.iter().cloned());
+    }
+
+    for (namespace_key, required) in normalize(required_namespaces)? {
+        let approved = approved
+            .get(&namespace_key)
+            .ok_or(NamespaceError::UnsupportedNamespace(namespace_key))?;
+        if let Some(chain) = required.chains.difference(&approved.chains).next()
+        {
+            return Err(NamespaceError::UnsupportedChain(chain.clone()));
+        }
+        if let Some(method) =
+            required.methods.difference(&approved.methods).next()
+        {
+            return Err(NamespaceError::UnsupportedMethod(method.clone()));
+        }
+        if let Some(event) = required.events.difference(&approved.events).next()
+        {
+            return Err(NamespaceError::UnsupportedEvent(event.clone()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::sign::protocol_types::ProposalNamespace};
+
+    const ACCOUNT: &str = "0xab16a96D359eC26a11e2C2b3d8f8B8942d5Bfcdb";
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    fn proposal_namespace(
+        chains: &[&str],
+        methods: &[&str],
+        events: &[&str],
+    ) -> ProposalNamespace {
+        ProposalNamespace {
+            chains: strings(chains),
+            methods: strings(methods),
+            events: strings(events),
+        }
+    }
+
+    fn supported() -> SupportedNamespaces {
+        HashMap::from([(
+            "eip155".to_owned(),
+            SupportedNamespace {
+                chains: strings(&["eip155:1", "eip155:10"]),
+                methods: strings(&["personal_sign", "eth_sendTransaction"]),
+                events: strings(&["chainChanged", "accountsChanged"]),
+                accounts: vec![
+                    format!("eip155:1:{ACCOUNT}"),
+                    format!("eip155:10:{ACCOUNT}"),
+                ],
+            },
+        )])
+    }
+
+    #[test]
+    fn test_build_approved_namespaces() {
+        let required = HashMap::from([(
+            "eip155".to_owned(),
+            proposal_namespace(&["eip155:1"], &["personal_sign"], &[]),
+        )]);
+        let optional = HashMap::from([
+            (
+                "eip155".to_owned(),
+                proposal_namespace(&["eip155:10"], &[], &["chainChanged"]),
+            ),
+            (
+                "solana".to_owned(),
+                proposal_namespace(
+                    &["solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp"],
+                    &["solana_signMessage"],
+                    &[],
+                ),
+            ),
+        ]);
+
+        let approved =
+            build_approved_namespaces(&required, &optional, &supported())
+                .unwrap();
+        assert_eq!(approved.len(), 1);
+        let eip155 = &approved["eip155"];
+        assert_eq!(eip155.chains, strings(&["eip155:1", "eip155:10"]));
+        assert_eq!(eip155.accounts.len(), 2);
+        // Only what was requested, not everything the wallet supports
+        assert_eq!(eip155.methods, strings(&["personal_sign"]));
+        assert_eq!(eip155.events, strings(&["chainChanged"]));
+    }
+
+    #[test]
+    fn test_only_requested_chains() {
+        let required = HashMap::from([(
+            "eip155".to_owned(),
+            proposal_namespace(&["eip155:10"], &["personal_sign"], &[]),
+        )]);
+        let approved =
+            build_approved_namespaces(&required, &HashMap::new(), &supported())
+                .unwrap();
+        let eip155 = &approved["eip155"];
+        assert_eq!(eip155.chains, strings(&["eip155:10"]));
+        assert_eq!(eip155.accounts, vec![format!("eip155:10:{ACCOUNT}")]);
+    }
+
+    #[test]
+    fn test_chain_as_key() {
+        let required = HashMap::from([(
+            "eip155:10".to_owned(),
+            proposal_namespace(&[], &["personal_sign"], &[]),
+        )]);
+        let approved =
+            build_approved_namespaces(&required, &HashMap::new(), &supported())
+                .unwrap();
+        assert!(approved["eip155"].chains.contains(&"eip155:10".to_owned()));
+    }
+
+    #[test]
+    fn test_unsupported_required() {
+        let cases = [
+            (
+                "eip155",
+                proposal_namespace(&["eip155:137"], &[], &[]),
+                NamespaceError::UnsupportedChain("eip155:137".to_owned()),
+            ),
+            (
+                "eip155",
+                proposal_namespace(&["eip155:1"], &["eth_signTypedData"], &[]),
+                NamespaceError::UnsupportedMethod(
+                    "eth_signTypedData".to_owned(),
+                ),
+            ),
+            (
+                "eip155",
+                proposal_namespace(&["eip155:1"], &[], &["disconnect"]),
+                NamespaceError::UnsupportedEvent("disconnect".to_owned()),
+            ),
+            (
+                "cosmos",
+                proposal_namespace(&["cosmos:cosmoshub-4"], &[], &[]),
+                NamespaceError::UnsupportedNamespace("cosmos".to_owned()),
+            ),
+        ];
+        for (key, namespace, expected) in cases {
+            let required = HashMap::from([(key.to_owned(), namespace)]);
+            assert_eq!(
+                build_approved_namespaces(
+                    &required,
+                    &HashMap::new(),
+                    &supported()
+                )
+                .unwrap_err(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_approved_namespaces() {
+        let required = HashMap::from([(
+            "eip155".to_owned(),
+            proposal_namespace(&["eip155:1"], &["personal_sign"], &[]),
+        )]);
+        let mut approved = HashMap::from([(
+            "eip155".to_owned(),
+            SettleNamespace {
+                accounts: vec![format!("eip155:1:{ACCOUNT}")],
+                methods: strings(&["personal_sign"]),
+                events: vec![],
+                chains: strings(&["eip155:1"]),
+            },
+        )]);
+        assert_eq!(validate_approved_namespaces(&required, &approved), Ok(()));
+
+        approved.get_mut("eip155").unwrap().chains.push("eip155:10".to_owned());
+        assert_eq!(
+            validate_approved_namespaces(&required, &approved),
+            Err(NamespaceError::MissingAccounts("eip155:10".to_owned()))
+        );
+
+        approved.get_mut("eip155").unwrap().accounts =
+            vec!["not-an-account".to_owned()];
+        assert_eq!(
+            validate_approved_namespaces(&required, &approved),
+            Err(NamespaceError::InvalidAccount("not-an-account".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_validate_proposal_namespaces() {
+        let valid = HashMap::from([(
+            "eip155".to_owned(),
+            proposal_namespace(&["eip155:1"], &[], &[]),
+        )]);
+        assert_eq!(validate_proposal_namespaces(&valid), Ok(()));
+
+        let mismatched = HashMap::from([(
+            "eip155".to_owned(),
+            proposal_namespace(&["solana:mainnet"], &[], &[]),
+        )]);
+        assert_eq!(
+            validate_proposal_namespaces(&mismatched),
+            Err(NamespaceError::InvalidChain("solana:mainnet".to_owned()))
+        );
+
+        let invalid = HashMap::from([(
+            "eip155".to_owned(),
+            proposal_namespace(&["1"], &[], &[]),
+        )]);
+        assert_eq!(
+            validate_proposal_namespaces(&invalid),
+            Err(NamespaceError::InvalidChain("1".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_rejection_reason() {
+        assert!(matches!(
+            NamespaceError::UnsupportedMethod("x".to_owned())
+                .rejection_reason(),
+            RejectionReason::UnsupportedMethods
+        ));
+    }
+}
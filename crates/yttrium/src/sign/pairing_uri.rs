@@ -52,7 +52,7 @@ pub enum Error {
 pub struct PairingUri {
     pub topic: Topic,
     pub sym_key: [u8; 32],
-    #[allow(unused)]
+    pub relay_protocol: String,
     pub expiry_timestamp: Option<u64>,
 }
 
@@ -82,9 +82,11 @@ pub fn parse(uri: &str) -> Result<PairingUri, Error> {
     };
     let query = uri.query_pairs().collect::<HashMap<_, _>>();
 
-    let relay_protocol =
-        query.get("relay-protocol").ok_or(Error::MissingRelayProtocol)?;
-    if relay_protocol != "irn" {
+    let relay_protocol = query
+        .get("relay-protocol")
+        .ok_or(Error::MissingRelayProtocol)?
+        .to_string();
+    if relay_protocol.is_empty() {
         return Err(Error::InvalidRelayProtocol);
     }
 
@@ -100,7 +102,7 @@ pub fn parse(uri: &str) -> Result<PairingUri, Error> {
         .map(|s| s.parse::<u64>().map_err(|_| Error::InvalidExpiryTimestamp))
         .transpose()?;
 
-    Ok(PairingUri { topic, sym_key, expiry_timestamp })
+    Ok(PairingUri { topic, sym_key, relay_protocol, expiry_timestamp })
 }
 
 pub fn format(
@@ -133,6 +135,7 @@ mod tests {
             &hex::decode("d69745274f07e8619671a527943b38a11dce540be5c0965f04cdece9912bdfd5")
                 .unwrap()[..]
         );
+        assert_eq!(result.relay_protocol, "irn");
         assert_eq!(result.expiry_timestamp, Some(1752843899));
     }
 
@@ -25,6 +25,7 @@ pub mod methods {
     pub const SESSION_DELETE: &str = "wc_sessionDelete";
     pub const SESSION_PING: &str = "wc_sessionPing";
     pub const SESSION_SETTLE: &str = "wc_sessionSettle";
+    pub const SESSION_AUTHENTICATE: &str = "wc_sessionAuthenticate";
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -239,7 +240,9 @@ pub struct SessionRequest {
 pub struct SessionRequestRequest {
     pub method: String,
     pub params: serde_json::Value,
-    pub expiry: Option<u64>, // specs say optional
+    // Specs say optional, and name it `expiryTimestamp`
+    #[serde(alias = "expiryTimestamp")]
+    pub expiry: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -290,6 +293,19 @@ pub struct SessionExtendJsonRpc {
     pub params: SessionExtend,
 }
 
+// Serializes to `{}`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SessionPing {}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionPingJsonRpc {
+    pub id: ProtocolRpcId,
+    pub jsonrpc: JsonRpcVersion,
+    pub method: String,
+    pub params: SessionPing,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionEventVO {
@@ -313,6 +329,118 @@ pub struct SessionEventJsonRpc {
     pub params: EventParams,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAuthenticateJsonRpc {
+    pub id: ProtocolRpcId,
+    pub jsonrpc: JsonRpcVersion,
+    pub method: String,
+    pub params: SessionAuthenticate,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAuthenticate {
+    pub requester: Participant,
+    pub auth_payload: AuthPayload,
+    pub expiry_timestamp: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "uniffi", derive(uniffi_macros::Record))]
+pub struct Participant {
+    pub public_key: String,
+    pub metadata: Metadata,
+}
+
+/// CAIP-122 request parameters sent by the dapp in `wc_sessionAuthenticate`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "uniffi", derive(uniffi_macros::Record))]
+pub struct AuthPayload {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub payload_type: Option<String>,
+    pub chains: Vec<String>,
+    pub domain: String,
+    pub aud: String,
+    pub nonce: String,
+    pub version: String,
+    pub iat: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statement: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature_types: Option<HashMap<String, Vec<String>>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "uniffi", derive(uniffi_macros::Record))]
+pub struct Cacao {
+    pub h: CacaoHeader,
+    pub p: CacaoPayload,
+    pub s: CacaoSignature,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "uniffi", derive(uniffi_macros::Record))]
+pub struct CacaoHeader {
+    pub t: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "uniffi", derive(uniffi_macros::Record))]
+pub struct CacaoPayload {
+    pub iss: String,
+    pub domain: String,
+    pub aud: String,
+    pub version: String,
+    pub nonce: String,
+    pub iat: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statement: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "uniffi", derive(uniffi_macros::Record))]
+pub struct CacaoSignature {
+    pub t: String,
+    pub s: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub m: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAuthenticateResponse {
+    pub cacaos: Vec<Cacao>,
+    pub responder: Participant,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAuthenticateResultResponseJsonRpc {
+    pub id: ProtocolRpcId,
+    pub jsonrpc: JsonRpcVersion,
+    pub result: SessionAuthenticateResponse,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(untagged)]
 pub enum GenericJsonRpcMessage {
@@ -3,15 +3,20 @@ use {
         sign::{
             client::MaybeVerifiedRequest,
             client_errors::RequestError,
-            incoming::HandleError,
+            client_types::SessionAuthenticateRequest,
+            incoming::{HandleError, PendingPings},
             priority_future::PriorityReceiver,
             protocol_types::{
-                ProtocolRpcId, SessionRequestJsonRpc,
+                Cacao, ProtocolRpcId, SessionRequestJsonRpc,
                 SessionRequestJsonRpcResponse,
             },
+            relay_pool::{RelayConnectionState, SharedRelayPool},
             relay_url::ConnectionOptions,
             storage::Storage,
-            verify::validate::VerifyContext,
+            verify::{
+                policy::OriginDecision,
+                validate::{VerifyContext, VerifyState},
+            },
         },
         time::DurableSleep,
     },
@@ -149,6 +154,54 @@ type ConnectOutput = (
     ConnectWebSocket,
 );
 
+// Connect to the pool's active relay, recording the outcome for failover and publishing the connection state
+#[allow(clippy::too_many_arguments)]
+async fn connect_with_failover(
+    relay_pool: &SharedRelayPool,
+    connection_state_tx: &tokio::sync::watch::Sender<RelayConnectionState>,
+    project_id: ProjectId,
+    key: &SigningKey,
+    topics: Vec<Topic>,
+    initial_req: Option<PreparedMessage>,
+    cleanup_rx: tokio_util::sync::CancellationToken,
+    probe_group: Option<String>,
+) -> Result<ConnectOutput, ConnectError> {
+    let relay_url = relay_pool
+        .lock()
+        .map_err(|e| ConnectError::ShouldNeverHappen(e.to_string()))?
+        .active()
+        .url
+        .clone();
+    connection_state_tx.send_replace(RelayConnectionState::Connecting {
+        relay_url: relay_url.clone(),
+    });
+
+    let result = connect(
+        relay_url.clone(),
+        project_id,
+        key,
+        topics,
+        initial_req,
+        cleanup_rx,
+        probe_group,
+    )
+    .await;
+
+    if let Ok(mut relay_pool) = relay_pool.lock() {
+        match &result {
+            Ok(_) => relay_pool.report_success(),
+            Err(ConnectError::ConnectFail(_)) => relay_pool.report_failure(),
+            Err(_) => {}
+        }
+    }
+    connection_state_tx.send_replace(match &result {
+        Ok(_) => RelayConnectionState::Connected { relay_url },
+        Err(_) => RelayConnectionState::Disconnected,
+    });
+
+    result
+}
+
 // Connect to relay, optionally sending a prepared initial request
 // Returns (next_message_id, rx, ws)
 // - next_message_id is the next available ID for future messages
@@ -770,6 +823,30 @@ enum ConnectionState {
     ),
 }
 
+impl ConnectionState {
+    fn has_websocket(&self) -> bool {
+        match self {
+            ConnectionState::Idle
+            | ConnectionState::Poisoned
+            | ConnectionState::MaybeReconnect(_)
+            | ConnectionState::ConnectSubscribe(_)
+            | ConnectionState::Backoff(_)
+            | ConnectionState::ConnectRequest(_)
+            | ConnectionState::ConnectRetryRequest(_) => false,
+            ConnectionState::AwaitingSubscribeResponse(..)
+            | ConnectionState::ConnectRequestAttestationReady(..)
+            | ConnectionState::AwaitingConnectRequestResponse(..)
+            | ConnectionState::Connected(..)
+            | ConnectionState::ConnectedAttestationReady(..)
+            | ConnectionState::AwaitingRequestResponse(..)
+            | ConnectionState::AwaitingConnectRetryRequestResponse(..) => true,
+            #[cfg(target_arch = "wasm32")]
+            ConnectionState::ConnectRequestAwaitingAttestation(..)
+            | ConnectionState::ConnectedAwaitingAttestation(..) => true,
+        }
+    }
+}
+
 impl std::fmt::Debug for ConnectionState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let name = match self {
@@ -855,6 +932,13 @@ pub enum IncomingSessionMessage {
     SessionConnect(ProtocolRpcId, Topic),
     SessionReject(ProtocolRpcId, Topic),
     SessionRequestResponse(ProtocolRpcId, Topic, SessionRequestJsonRpcResponse),
+    SessionAuthenticate(SessionAuthenticateRequest, VerifyContext),
+    // Topic is the topic of the newly created session
+    SessionAuthenticateResponse(ProtocolRpcId, Topic, Vec<Cacao>),
+    // The session has already been deleted from storage
+    SessionExpired(Topic),
+    // Already rejected to the peer because of the origin policy
+    SessionRequestBlocked(SessionRequestJsonRpc, OriginDecision),
 }
 
 // MaybeVerifiedRequest is now defined in client.rs and imported via the parent module
@@ -862,7 +946,8 @@ pub enum IncomingSessionMessage {
 #[allow(clippy::too_many_arguments)]
 #[tracing::instrument(skip_all, fields(group = probe_group.clone()))]
 pub async fn connect_loop_state_machine(
-    relay_url: String,
+    relay_pool: SharedRelayPool,
+    connection_state_tx: tokio::sync::watch::Sender<RelayConnectionState>,
     project_id: ProjectId,
     key: SigningKey,
     session_store: Arc<dyn Storage>,
@@ -877,6 +962,8 @@ pub async fn connect_loop_state_machine(
     )>,
     mut online_rx: tokio::sync::mpsc::UnboundedReceiver<()>,
     cleanup_rx: tokio_util::sync::CancellationToken,
+    pending_pings: PendingPings,
+    verify_state: VerifyState,
     probe_group: Option<String>,
 ) {
     let (irn_subscription_ack_tx, mut irn_subscription_ack_rx) =
@@ -890,6 +977,8 @@ pub async fn connect_loop_state_machine(
         let http_client = http_client.clone();
         let session_request_tx = session_request_tx.clone();
         let irn_subscription_ack_tx = irn_subscription_ack_tx.clone();
+        let pending_pings = pending_pings.clone();
+        let verify_state = verify_state.clone();
         move |id: MessageId,
               sub_msg: Subscription,
               priority_request_tx: tokio::sync::mpsc::UnboundedSender<(
@@ -901,6 +990,8 @@ pub async fn connect_loop_state_machine(
             let http_client = http_client.clone();
             let session_request_tx = session_request_tx.clone();
             let irn_subscription_ack_tx = irn_subscription_ack_tx.clone();
+            let pending_pings = pending_pings.clone();
+            let verify_state = verify_state.clone();
             async move {
                 let result = crate::sign::incoming::handle(
                     session_store,
@@ -908,6 +999,8 @@ pub async fn connect_loop_state_machine(
                     sub_msg,
                     session_request_tx,
                     priority_request_tx.clone(),
+                    pending_pings,
+                    verify_state,
                     probe_group.clone(),
                 )
                 .await;
@@ -965,6 +1058,19 @@ pub async fn connect_loop_state_machine(
     let mut state = ConnectionState::Idle;
     loop {
         tracing::debug!("connect state: {state:?}");
+        if !state.has_websocket() {
+            connection_state_tx.send_if_modified(|connection_state| {
+                // Also covers a connection attempt that was cancelled mid-way
+                let disconnected = !matches!(
+                    connection_state,
+                    RelayConnectionState::Disconnected
+                );
+                if disconnected {
+                    *connection_state = RelayConnectionState::Disconnected;
+                }
+                disconnected
+            });
+        }
         state = match state {
             ConnectionState::Idle => {
                 // TODO avoid select! as it doesn't guarantee that `else` branch exists (it will panic otherwise)
@@ -1031,8 +1137,9 @@ pub async fn connect_loop_state_machine(
                     }
                 };
 
-                let connect_res = connect(
-                    relay_url.clone(),
+                let connect_res = connect_with_failover(
+                    &relay_pool,
+                    &connection_state_tx,
                     project_id.clone(),
                     &key,
                     vec![],
@@ -1186,8 +1293,9 @@ pub async fn connect_loop_state_machine(
                     );
 
                     // Connect with the prepared request
-                    let connect_res = connect(
-                        relay_url.clone(),
+                    let connect_res = connect_with_failover(
+                        &relay_pool,
+                        &connection_state_tx,
                         project_id.clone(),
                         &key,
                         topics,
@@ -1253,8 +1361,9 @@ pub async fn connect_loop_state_machine(
 
                     // Start websocket connection immediately (parallel with attestation)
                     // TODO remove the async here and handle internal stuff in the state machine
-                    let connect_res = connect(
-                        relay_url.clone(),
+                    let connect_res = connect_with_failover(
+                        &relay_pool,
+                        &connection_state_tx,
                         project_id.clone(),
                         &key,
                         topics,
@@ -2002,8 +2111,9 @@ pub async fn connect_loop_state_machine(
                     "Failed to serialize Params - this should never happen",
                 );
 
-                let connect_fut = connect(
-                    relay_url.clone(),
+                let connect_fut = connect_with_failover(
+                    &relay_pool,
+                    &connection_state_tx,
                     project_id.clone(),
                     &key,
                     topics,
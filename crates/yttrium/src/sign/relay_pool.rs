@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+pub const DEFAULT_RELAY_PROTOCOL: &str = "irn";
+pub const DEFAULT_RELAY_URL: &str = "wss://relay.walletconnect.org";
+
+/// Consecutive connection failures after which the next relay is tried
+pub const FAILOVER_THRESHOLD: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct RelayEndpoint {
+    pub url: String,
+    // The `relay-protocol` this relay serves, as advertised in pairing URIs
+    pub protocol: String,
+}
+
+impl RelayEndpoint {
+    pub fn new(url: String) -> Self {
+        Self { url, protocol: DEFAULT_RELAY_PROTOCOL.to_owned() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum RelayConnectionState {
+    Disconnected,
+    Connecting { relay_url: String },
+    Connected { relay_url: String },
+}
+
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi_macros::Error))]
+#[error("Relay pool error: {0}")]
+pub enum RelayPoolError {
+    #[error("At least one relay endpoint is required")]
+    NoEndpoints,
+}
+
+pub type SharedRelayPool = Arc<std::sync::Mutex<RelayPool>>;
+
+/// An ordered list of relays. The first relay is preferred, and after `FAILOVER_THRESHOLD` consecutive
+/// connection failures the next one (wrapping around) serving the same relay protocol becomes active.
+/// All sessions share the active relay, so this is failover only, not per-pairing routing.
+#[derive(Debug)]
+pub struct RelayPool {
+    endpoints: Vec<RelayEndpoint>,
+    active: usize,
+    consecutive_failures: u32,
+}
+
+impl RelayPool {
+    pub fn new(endpoints: Vec<RelayEndpoint>) -> Result<Self, RelayPoolError> {
+        if endpoints.is_empty() {
+            return Err(RelayPoolError::NoEndpoints);
+        }
+        Ok(Self { endpoints, active: 0, consecutive_failures: 0 })
+    }
+
+    pub fn active(&self) -> &RelayEndpoint {
+        &self.endpoints[self.active]
+    }
+
+    pub fn report_success(&mut self) {
+        self.consecutive_failures = 0;
+    }
+
+    pub fn report_failure(&mut self) {
+        self.consecutive_failures += 1;
+        if self.consecutive_failures >= FAILOVER_THRESHOLD {
+            self.consecutive_failures = 0;
+            let next = self.next_with_protocol(&self.active().protocol);
+            if next != self.active {
+                tracing::info!(
+                    "Failing over from relay {} to {}",
+                    self.active().url,
+                    self.endpoints[next].url
+                );
+                self.active = next;
+            }
+        }
+    }
+
+    /// Whether the active relay serves `protocol`. Failover never changes the protocol.
+    pub fn serves_protocol(&self, protocol: &str) -> bool {
+        self.active().protocol == protocol
+    }
+
+    // Fail over within the same protocol so sessions stay reachable
+    fn next_with_protocol(&self, protocol: &str) -> usize {
+        let len = self.endpoints.len();
+        (1..=len)
+            .map(|offset| (self.active + offset) % len)
+            .find(|&index| self.endpoints[index].protocol == protocol)
+            .unwrap_or(self.active)
+    }
+}
+
+impl Default for RelayPool {
+    fn default() -> Self {
+        Self {
+            endpoints: vec![RelayEndpoint::new(DEFAULT_RELAY_URL.to_owned())],
+            active: 0,
+            consecutive_failures: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(url: &str, protocol: &str) -> RelayEndpoint {
+        RelayEndpoint { url: url.to_owned(), protocol: protocol.to_owned() }
+    }
+
+    #[test]
+    fn test_failover() {
+        let mut pool = RelayPool::new(vec![
+            endpoint("wss://eu.example.com", "irn"),
+            endpoint("wss://other.example.com", "other"),
+            endpoint("wss://us.example.com", "irn"),
+        ])
+        .unwrap();
+        assert_eq!(pool.active().url, "wss://eu.example.com");
+
+        pool.report_failure();
+        pool.report_success();
+        pool.report_failure();
+        assert_eq!(pool.active().url, "wss://eu.example.com");
+
+        pool.report_failure();
+        assert_eq!(pool.active().url, "wss://us.example.com");
+
+        pool.report_failure();
+        pool.report_failure();
+        assert_eq!(pool.active().url, "wss://eu.example.com");
+    }
+
+    #[test]
+    fn test_single_endpoint() {
+        let mut pool =
+            RelayPool::new(vec![RelayEndpoint::new("wss://a".to_owned())])
+                .unwrap();
+        for _ in 0..5 {
+            pool.report_failure();
+        }
+        assert_eq!(pool.active().url, "wss://a");
+    }
+
+    #[test]
+    fn test_no_endpoints() {
+        assert!(matches!(
+            RelayPool::new(vec![]),
+            Err(RelayPoolError::NoEndpoints)
+        ));
+    }
+
+    #[test]
+    fn test_serves_protocol() {
+        let mut pool = RelayPool::new(vec![
+            endpoint("wss://a", "irn"),
+            endpoint("wss://b", "other"),
+            endpoint("wss://c", "irn"),
+        ])
+        .unwrap();
+        assert!(pool.serves_protocol("irn"));
+        assert!(!pool.serves_protocol("other"));
+        pool.report_failure();
+        pool.report_failure();
+        assert_eq!(pool.active().url, "wss://c");
+        assert!(pool.serves_protocol("irn"));
+    }
+}
@@ -0,0 +1,317 @@
+//! Behaviour every `Storage` implementation is expected to have.
+//! Run it from your implementation's tests with a factory returning empty storages:
+//! `conformance::run(|| Arc::new(MyStorage::new()))`
+//! Failures panic, like a regular test assertion.
+
+use {
+    super::{Jwk, Storage, StorageJsonRpcRequest},
+    crate::sign::{
+        client_types::{Session, TransportType},
+        protocol_types::{Metadata, ProtocolRpcId},
+        utils::topic_from_sym_key,
+    },
+    relay_rpc::domain::Topic,
+    std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+    },
+};
+
+// Far enough in the future for pairings that are not under test to never expire
+const NEVER: u64 = u64::MAX;
+
+const VERIFY_PUBLIC_KEY: &str = r#"{"crv":"P-256","ext":true,"key_ops":["verify"],"kty":"EC","x":"CbL4DOYOb1ntd-8OmExO-oS0DWCMC00DntrymJoB8tk","y":"KTFwjHtQxGTDR91VsOypcdBfvbo6sAMj5p4Wb-9hRA1"}"#;
+
+pub fn run(new_storage: impl Fn() -> Arc<dyn Storage>) {
+    sessions(new_storage().as_ref());
+    decryption_keys(new_storage().as_ref());
+    pairings(new_storage().as_ref());
+    expired_pairings(new_storage().as_ref());
+    topics(new_storage().as_ref());
+    authenticate_requests(new_storage().as_ref());
+    verify_public_key(new_storage().as_ref());
+    json_rpc_history(new_storage().as_ref());
+    concurrent_writes(new_storage());
+}
+
+fn topic(seed: u8) -> Topic {
+    topic_from_sym_key(&[seed; 32])
+}
+
+fn session(seed: u8) -> Session {
+    Session {
+        request_id: ProtocolRpcId::generate(),
+        topic: topic(seed),
+        expiry: 1752843899,
+        relay_protocol: "irn".to_owned(),
+        relay_data: None,
+        controller_key: Some([seed; 32]),
+        session_sym_key: [seed; 32],
+        self_public_key: [seed; 32],
+        self_meta_data: Metadata {
+            name: "Conformance".to_owned(),
+            description: "Conformance".to_owned(),
+            url: "https://reown.com".to_owned(),
+            icons: vec![],
+            verify_url: None,
+            redirect: None,
+        },
+        peer_public_key: None,
+        peer_meta_data: None,
+        session_namespaces: HashMap::new(),
+        required_namespaces: HashMap::new(),
+        optional_namespaces: HashMap::new(),
+        session_properties: HashMap::new(),
+        scoped_properties: HashMap::new(),
+        is_acknowledged: false,
+        pairing_topic: topic(seed.wrapping_add(100)),
+        transport_type: Some(TransportType::Relay),
+    }
+}
+
+fn sessions(storage: &dyn Storage) {
+    assert!(storage.get_session(topic(1)).unwrap().is_none());
+    assert!(storage.get_all_sessions().unwrap().is_empty());
+
+    storage.add_session(session(1)).unwrap();
+    storage.add_session(session(2)).unwrap();
+    let stored = storage.get_session(topic(1)).unwrap().unwrap();
+    assert_eq!(stored.topic, topic(1));
+    assert_eq!(stored.session_sym_key, [1; 32]);
+    assert_eq!(storage.get_all_sessions().unwrap().len(), 2);
+
+    // Adding a session with an existing topic replaces it
+    let mut updated = session(1);
+    updated.is_acknowledged = true;
+    storage.add_session(updated).unwrap();
+    assert_eq!(storage.get_all_sessions().unwrap().len(), 2);
+    assert!(storage.get_session(topic(1)).unwrap().unwrap().is_acknowledged);
+
+    storage.delete_session(topic(1)).unwrap();
+    assert!(storage.get_session(topic(1)).unwrap().is_none());
+    assert_eq!(storage.get_all_sessions().unwrap().len(), 1);
+    // Deleting an unknown session is not an error
+    storage.delete_session(topic(1)).unwrap();
+}
+
+fn decryption_keys(storage: &dyn Storage) {
+    assert_eq!(storage.get_decryption_key_for_topic(topic(1)).unwrap(), None);
+
+    storage.save_partial_session(topic(1), [2; 32]).unwrap();
+    assert_eq!(
+        storage.get_decryption_key_for_topic(topic(1)).unwrap(),
+        Some([2; 32])
+    );
+
+    storage
+        .save_pairing(
+            topic(1),
+            ProtocolRpcId::generate(),
+            [3; 32],
+            [4; 32],
+            NEVER,
+        )
+        .unwrap();
+    assert_eq!(
+        storage.get_decryption_key_for_topic(topic(1)).unwrap(),
+        Some([3; 32]),
+        "pairing keys take precedence over partial sessions"
+    );
+
+    storage.add_session(session(1)).unwrap();
+    assert_eq!(
+        storage.get_decryption_key_for_topic(topic(1)).unwrap(),
+        Some([1; 32]),
+        "session keys take precedence over pairings"
+    );
+}
+
+fn pairings(storage: &dyn Storage) {
+    let rpc_id = ProtocolRpcId::generate();
+    assert!(storage.get_pairing(topic(1), rpc_id).unwrap().is_none());
+
+    storage.save_pairing(topic(1), rpc_id, [1; 32], [2; 32], NEVER).unwrap();
+    let pairing = storage.get_pairing(topic(1), rpc_id).unwrap().unwrap();
+    assert_eq!(pairing.sym_key, [1; 32]);
+    assert_eq!(pairing.self_key, [2; 32]);
+
+    let other_rpc_id = loop {
+        let id = ProtocolRpcId::generate();
+        if id != rpc_id {
+            break id;
+        }
+    };
+    assert!(
+        storage.get_pairing(topic(1), other_rpc_id).unwrap().is_none(),
+        "pairings are looked up by topic and RPC ID"
+    );
+    assert!(storage.get_pairing(topic(2), rpc_id).unwrap().is_none());
+}
+
+fn expired_pairings(storage: &dyn Storage) {
+    assert!(storage.delete_expired_pairings(100).unwrap().is_empty());
+
+    let expired_rpc_id = ProtocolRpcId::generate();
+    let live_rpc_id = ProtocolRpcId::generate();
+    storage
+        .save_pairing(topic(1), expired_rpc_id, [1; 32], [1; 32], 100)
+        .unwrap();
+    storage
+        .save_pairing(topic(1), ProtocolRpcId::generate(), [1; 32], [1; 32], 50)
+        .unwrap();
+    storage.save_pairing(topic(2), live_rpc_id, [2; 32], [2; 32], 101).unwrap();
+    // Only one of the two pairings on this topic expires
+    storage
+        .save_pairing(
+            topic(3),
+            ProtocolRpcId::generate(),
+            [3; 32],
+            [3; 32],
+            100,
+        )
+        .unwrap();
+    storage.save_pairing(topic(3), live_rpc_id, [3; 32], [3; 32], 101).unwrap();
+
+    assert_eq!(
+        storage.delete_expired_pairings(100).unwrap(),
+        vec![topic(1)],
+        "expiry is inclusive, topics are not duplicated and topics still in use are not returned"
+    );
+    assert!(storage.get_pairing(topic(1), expired_rpc_id).unwrap().is_none());
+    assert!(storage.get_pairing(topic(2), live_rpc_id).unwrap().is_some());
+    assert!(storage.get_pairing(topic(3), live_rpc_id).unwrap().is_some());
+    let topics = storage.get_all_topics().unwrap().into_iter().collect();
+    assert_eq!(topics, HashSet::from([topic(2), topic(3)]));
+    assert!(storage.delete_expired_pairings(100).unwrap().is_empty());
+}
+
+fn topics(storage: &dyn Storage) {
+    assert!(storage.get_all_topics().unwrap().is_empty());
+
+    storage.add_session(session(1)).unwrap();
+    storage
+        .save_pairing(
+            topic(2),
+            ProtocolRpcId::generate(),
+            [2; 32],
+            [2; 32],
+            NEVER,
+        )
+        .unwrap();
+    storage.save_partial_session(topic(3), [3; 32]).unwrap();
+    // Same topic as the session
+    storage.save_partial_session(topic(1), [1; 32]).unwrap();
+    storage.save_authenticate_request(topic(4), [4; 32], NEVER).unwrap();
+
+    let topics = storage.get_all_topics().unwrap();
+    assert_eq!(topics.len(), 4, "topics are not duplicated");
+    let topics = topics.into_iter().collect::<HashSet<_>>();
+    assert_eq!(topics, HashSet::from([topic(1), topic(2), topic(3), topic(4)]));
+}
+
+fn authenticate_requests(storage: &dyn Storage) {
+    assert!(storage.get_authenticate_request_key(topic(1)).unwrap().is_none());
+
+    storage.save_authenticate_request(topic(1), [1; 32], 100).unwrap();
+    storage.save_authenticate_request(topic(2), [2; 32], 101).unwrap();
+    assert_eq!(
+        storage.get_authenticate_request_key(topic(1)).unwrap(),
+        Some([1; 32])
+    );
+    assert_eq!(
+        storage.get_decryption_key_for_topic(topic(1)).unwrap(),
+        None,
+        "authenticate request keys are private keys, not sym keys"
+    );
+
+    assert_eq!(
+        storage.delete_expired_authenticate_requests(100).unwrap(),
+        vec![topic(1)],
+        "expiry is inclusive"
+    );
+    assert!(storage.get_authenticate_request_key(topic(1)).unwrap().is_none());
+    assert_eq!(
+        storage.get_authenticate_request_key(topic(2)).unwrap(),
+        Some([2; 32])
+    );
+    assert_eq!(storage.get_all_topics().unwrap(), vec![topic(2)]);
+}
+
+fn verify_public_key(storage: &dyn Storage) {
+    assert!(storage.get_verify_public_key().unwrap().is_none());
+
+    let jwk = serde_json::from_str::<Jwk>(VERIFY_PUBLIC_KEY).unwrap();
+    storage.set_verify_public_key(jwk.clone()).unwrap();
+    assert_eq!(storage.get_verify_public_key().unwrap(), Some(jwk.clone()));
+
+    // Setting again replaces the key
+    storage.set_verify_public_key(jwk.clone()).unwrap();
+    assert_eq!(storage.get_verify_public_key().unwrap(), Some(jwk));
+}
+
+fn json_rpc_history(storage: &dyn Storage) {
+    let request_id = ProtocolRpcId::generate();
+    assert!(!storage.does_json_rpc_exist(request_id).unwrap());
+
+    storage
+        .insert_json_rpc_history(
+            request_id,
+            topic(1),
+            "wc_sessionRequest".to_owned(),
+            r#"{"id":1}"#.to_owned(),
+            Some(TransportType::LinkMode),
+        )
+        .unwrap();
+    assert!(storage.does_json_rpc_exist(request_id).unwrap());
+    storage
+        .insert_json_rpc_history(
+            ProtocolRpcId::generate(),
+            topic(1),
+            "wc_sessionEvent".to_owned(),
+            "{}".to_owned(),
+            None,
+        )
+        .unwrap();
+    assert_eq!(
+        storage
+            .get_pending_json_rpc_requests("wc_sessionRequest".to_owned())
+            .unwrap(),
+        vec![StorageJsonRpcRequest {
+            topic: topic(1),
+            body: r#"{"id":1}"#.to_owned()
+        }],
+        "pending requests are filtered by method"
+    );
+    storage
+        .update_json_rpc_history_response(request_id, "{}".to_owned())
+        .unwrap();
+    assert!(
+        storage
+            .get_pending_json_rpc_requests("wc_sessionRequest".to_owned())
+            .unwrap()
+            .is_empty(),
+        "requests with a response are not pending"
+    );
+
+    storage.delete_json_rpc_history_by_topic(topic(2)).unwrap();
+    assert!(storage.does_json_rpc_exist(request_id).unwrap());
+    storage.delete_json_rpc_history_by_topic(topic(1)).unwrap();
+    assert!(!storage.does_json_rpc_exist(request_id).unwrap());
+}
+
+fn concurrent_writes(storage: Arc<dyn Storage>) {
+    let handles = (0..16u8)
+        .map(|seed| {
+            let storage = storage.clone();
+            std::thread::spawn(move || {
+                storage.add_session(session(seed)).unwrap();
+                storage.save_partial_session(topic(seed), [seed; 32]).unwrap();
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(storage.get_all_sessions().unwrap().len(), 16);
+    assert_eq!(storage.get_all_topics().unwrap().len(), 16);
+}
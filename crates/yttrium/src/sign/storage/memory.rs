@@ -0,0 +1,274 @@
+use {
+    super::{
+        Jwk, Storage, StorageError, StorageJsonRpcRequest, StoragePairing,
+    },
+    crate::sign::{
+        client_types::{Session, TransportType},
+        protocol_types::ProtocolRpcId,
+    },
+    relay_rpc::domain::Topic,
+    std::{
+        collections::{HashMap, HashSet},
+        sync::{Mutex, MutexGuard},
+    },
+};
+
+// Only what the `Storage` trait reads back is kept
+struct JsonRpcHistoryEntry {
+    topic: Topic,
+    method: String,
+    body: String,
+    response: Option<String>,
+}
+
+#[derive(Default)]
+struct MemoryStorageInner {
+    sessions: HashMap<Topic, Session>,
+    pairings: HashMap<Topic, HashMap<ProtocolRpcId, (StoragePairing, u64)>>,
+    partial_sessions: HashMap<Topic, [u8; 32]>,
+    authenticate_requests: HashMap<Topic, ([u8; 32], u64)>,
+    verify_public_key: Option<Jwk>,
+    json_rpc_history: HashMap<ProtocolRpcId, JsonRpcHistoryEntry>,
+}
+
+/// Thread-safe `Storage` that keeps everything in memory, so nothing survives a restart.
+/// Writes are visible to all readers as soon as they return.
+#[derive(Default)]
+pub struct MemoryStorage(Mutex<MemoryStorageInner>);
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, MemoryStorageInner>, StorageError> {
+        self.0.lock().map_err(|e| StorageError::Runtime(e.to_string()))
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn add_session(&self, session: Session) -> Result<(), StorageError> {
+        self.lock()?.sessions.insert(session.topic.clone(), session);
+        Ok(())
+    }
+
+    fn delete_session(&self, topic: Topic) -> Result<(), StorageError> {
+        self.lock()?.sessions.remove(&topic);
+        Ok(())
+    }
+
+    fn get_session(
+        &self,
+        topic: Topic,
+    ) -> Result<Option<Session>, StorageError> {
+        Ok(self.lock()?.sessions.get(&topic).cloned())
+    }
+
+    fn get_all_sessions(&self) -> Result<Vec<Session>, StorageError> {
+        Ok(self.lock()?.sessions.values().cloned().collect())
+    }
+
+    fn get_all_topics(&self) -> Result<Vec<Topic>, StorageError> {
+        let inner = self.lock()?;
+        let topics = inner
+            .sessions
+            .keys()
+            .chain(inner.pairings.keys())
+            .chain(inner.partial_sessions.keys())
+            .chain(inner.authenticate_requests.keys())
+            .cloned()
+            .collect::<HashSet<_>>();
+        Ok(topics.into_iter().collect())
+    }
+
+    fn get_decryption_key_for_topic(
+        &self,
+        topic: Topic,
+    ) -> Result<Option<[u8; 32]>, StorageError> {
+        let inner = self.lock()?;
+        let key = inner
+            .sessions
+            .get(&topic)
+            .map(|session| session.session_sym_key)
+            .or_else(|| {
+                inner
+                    .pairings
+                    .get(&topic)
+                    .and_then(|pairings| pairings.values().next())
+                    .map(|(pairing, _)| pairing.sym_key)
+            })
+            .or_else(|| inner.partial_sessions.get(&topic).copied());
+        Ok(key)
+    }
+
+    fn save_pairing(
+        &self,
+        topic: Topic,
+        rpc_id: ProtocolRpcId,
+        sym_key: [u8; 32],
+        self_key: [u8; 32],
+        expiry: u64,
+    ) -> Result<(), StorageError> {
+        self.lock()?
+            .pairings
+            .entry(topic)
+            .or_default()
+            .insert(rpc_id, (StoragePairing { sym_key, self_key }, expiry));
+        Ok(())
+    }
+
+    fn get_pairing(
+        &self,
+        topic: Topic,
+        rpc_id: ProtocolRpcId,
+    ) -> Result<Option<StoragePairing>, StorageError> {
+        Ok(self
+            .lock()?
+            .pairings
+            .get(&topic)
+            .and_then(|pairings| pairings.get(&rpc_id))
+            .map(|(pairing, _)| pairing.clone()))
+    }
+
+    fn delete_expired_pairings(
+        &self,
+        now: u64,
+    ) -> Result<Vec<Topic>, StorageError> {
+        let mut inner = self.lock()?;
+        let mut expired = Vec::new();
+        inner.pairings.retain(|topic, pairings| {
+            let before = pairings.len();
+            pairings.retain(|_, (_, expiry)| *expiry > now);
+            if pairings.is_empty() && before != 0 {
+                expired.push(topic.clone());
+            }
+            !pairings.is_empty()
+        });
+        Ok(expired)
+    }
+
+    fn save_partial_session(
+        &self,
+        topic: Topic,
+        sym_key: [u8; 32],
+    ) -> Result<(), StorageError> {
+        self.lock()?.partial_sessions.insert(topic, sym_key);
+        Ok(())
+    }
+
+    fn save_authenticate_request(
+        &self,
+        topic: Topic,
+        self_key: [u8; 32],
+        expiry: u64,
+    ) -> Result<(), StorageError> {
+        self.lock()?.authenticate_requests.insert(topic, (self_key, expiry));
+        Ok(())
+    }
+
+    fn get_authenticate_request_key(
+        &self,
+        topic: Topic,
+    ) -> Result<Option<[u8; 32]>, StorageError> {
+        Ok(self
+            .lock()?
+            .authenticate_requests
+            .get(&topic)
+            .map(|(self_key, _)| *self_key))
+    }
+
+    fn delete_expired_authenticate_requests(
+        &self,
+        now: u64,
+    ) -> Result<Vec<Topic>, StorageError> {
+        let mut expired = Vec::new();
+        self.lock()?.authenticate_requests.retain(|topic, (_, expiry)| {
+            if *expiry <= now {
+                expired.push(topic.clone());
+            }
+            *expiry > now
+        });
+        Ok(expired)
+    }
+
+    fn get_verify_public_key(&self) -> Result<Option<Jwk>, StorageError> {
+        Ok(self.lock()?.verify_public_key.clone())
+    }
+
+    fn set_verify_public_key(&self, jwk: Jwk) -> Result<(), StorageError> {
+        self.lock()?.verify_public_key = Some(jwk);
+        Ok(())
+    }
+
+    fn insert_json_rpc_history(
+        &self,
+        request_id: ProtocolRpcId,
+        topic: Topic,
+        method: String,
+        body: String,
+        _transport_type: Option<TransportType>,
+    ) -> Result<(), StorageError> {
+        self.lock()?.json_rpc_history.insert(
+            request_id,
+            JsonRpcHistoryEntry { topic, method, body, response: None },
+        );
+        Ok(())
+    }
+
+    fn update_json_rpc_history_response(
+        &self,
+        request_id: ProtocolRpcId,
+        response: String,
+    ) -> Result<(), StorageError> {
+        let mut inner = self.lock()?;
+        let entry =
+            inner.json_rpc_history.get_mut(&request_id).ok_or_else(|| {
+                StorageError::Runtime(format!(
+                    "JSON-RPC history entry not found for request_id: {request_id}"
+                ))
+            })?;
+        entry.response = Some(response);
+        Ok(())
+    }
+
+    fn get_pending_json_rpc_requests(
+        &self,
+        method: String,
+    ) -> Result<Vec<StorageJsonRpcRequest>, StorageError> {
+        Ok(self
+            .lock()?
+            .json_rpc_history
+            .values()
+            .filter(|entry| entry.method == method && entry.response.is_none())
+            .map(|entry| StorageJsonRpcRequest {
+                topic: entry.topic.clone(),
+                body: entry.body.clone(),
+            })
+            .collect())
+    }
+
+    fn delete_json_rpc_history_by_topic(
+        &self,
+        topic: Topic,
+    ) -> Result<(), StorageError> {
+        self.lock()?.json_rpc_history.retain(|_, entry| entry.topic != topic);
+        Ok(())
+    }
+
+    fn does_json_rpc_exist(
+        &self,
+        request_id: ProtocolRpcId,
+    ) -> Result<bool, StorageError> {
+        Ok(self.lock()?.json_rpc_history.contains_key(&request_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::sign::storage::conformance, std::sync::Arc};
+
+    #[test]
+    fn test_conformance() {
+        conformance::run(|| Arc::new(MemoryStorage::new()));
+    }
+}
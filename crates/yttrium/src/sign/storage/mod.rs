@@ -8,6 +8,15 @@ use {
     serde::{Deserialize, Serialize},
 };
 
+pub mod conformance;
+mod memory;
+#[cfg(feature = "sign_storage_sqlite")]
+mod sqlite;
+
+pub use memory::MemoryStorage;
+#[cfg(feature = "sign_storage_sqlite")]
+pub use sqlite::SqliteStorage;
+
 // Implementation requirements:
 // - Storage writes must be synchronously flushed
 //
@@ -33,17 +42,40 @@ pub trait Storage: Send + Sync {
         rpc_id: ProtocolRpcId,
         sym_key: [u8; 32],
         self_key: [u8; 32],
+        expiry: u64,
     ) -> Result<(), StorageError>;
     fn get_pairing(
         &self,
         topic: Topic,
         rpc_id: ProtocolRpcId,
     ) -> Result<Option<StoragePairing>, StorageError>;
+    /// Deletes all pairings with `expiry <= now` and returns the topics that no longer have any pairing.
+    fn delete_expired_pairings(
+        &self,
+        now: u64,
+    ) -> Result<Vec<Topic>, StorageError>;
     fn save_partial_session(
         &self,
         topic: Topic,
         sym_key: [u8; 32],
     ) -> Result<(), StorageError>;
+    /// Keeps our private key for a `wc_sessionAuthenticate` request until `expiry`.
+    /// Responses to the request are sent to `topic` in type 1 envelopes, which are decrypted with this key.
+    fn save_authenticate_request(
+        &self,
+        topic: Topic,
+        self_key: [u8; 32],
+        expiry: u64,
+    ) -> Result<(), StorageError>;
+    fn get_authenticate_request_key(
+        &self,
+        topic: Topic,
+    ) -> Result<Option<[u8; 32]>, StorageError>;
+    /// Deletes all authenticate requests with `expiry <= now` and returns their topics.
+    fn delete_expired_authenticate_requests(
+        &self,
+        now: u64,
+    ) -> Result<Vec<Topic>, StorageError>;
     fn get_verify_public_key(&self) -> Result<Option<Jwk>, StorageError>;
     fn set_verify_public_key(&self, jwk: Jwk) -> Result<(), StorageError>;
 
@@ -63,6 +95,12 @@ pub trait Storage: Send + Sync {
         response: String,
     ) -> Result<(), StorageError>;
 
+    /// Returns the requests of `method` that have no response yet, in no particular order
+    fn get_pending_json_rpc_requests(
+        &self,
+        method: String,
+    ) -> Result<Vec<StorageJsonRpcRequest>, StorageError>;
+
     fn delete_json_rpc_history_by_topic(
         &self,
         topic: Topic,
@@ -86,3 +124,11 @@ pub struct StoragePairing {
     pub sym_key: [u8; 32],
     pub self_key: [u8; 32],
 }
+
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageJsonRpcRequest {
+    pub topic: Topic,
+    /// As passed to `insert_json_rpc_history()`
+    pub body: String,
+}
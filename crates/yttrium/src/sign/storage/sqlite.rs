@@ -0,0 +1,493 @@
+use {
+    super::{
+        Jwk, Storage, StorageError, StorageJsonRpcRequest, StoragePairing,
+    },
+    crate::sign::{
+        client_types::{Session, TransportType},
+        protocol_types::ProtocolRpcId,
+    },
+    relay_rpc::domain::Topic,
+    rusqlite::{Connection, OptionalExtension, params},
+    std::{
+        path::Path,
+        sync::{Mutex, MutexGuard},
+    },
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    topic TEXT PRIMARY KEY NOT NULL,
+    session TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS pairings (
+    topic TEXT NOT NULL,
+    rpc_id INTEGER NOT NULL,
+    sym_key BLOB NOT NULL,
+    self_key BLOB NOT NULL,
+    expiry INTEGER NOT NULL,
+    PRIMARY KEY (topic, rpc_id)
+);
+CREATE TABLE IF NOT EXISTS partial_sessions (
+    topic TEXT PRIMARY KEY NOT NULL,
+    sym_key BLOB NOT NULL
+);
+CREATE TABLE IF NOT EXISTS authenticate_requests (
+    topic TEXT PRIMARY KEY NOT NULL,
+    self_key BLOB NOT NULL,
+    expiry INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS verify_public_key (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    jwk TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS json_rpc_history (
+    request_id INTEGER PRIMARY KEY NOT NULL,
+    topic TEXT NOT NULL,
+    method TEXT NOT NULL,
+    body TEXT NOT NULL,
+    transport_type TEXT,
+    response TEXT
+);
+CREATE INDEX IF NOT EXISTS json_rpc_history_topic ON json_rpc_history (topic);
+";
+
+/// Thread-safe `Storage` backed by a SQLite database.
+/// Every write is its own transaction and `synchronous = FULL` is set, so writes are flushed to disk before returning.
+pub struct SqliteStorage(Mutex<Connection>);
+
+impl SqliteStorage {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        Self::init(Connection::open(path).map_err(runtime)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, StorageError> {
+        Self::init(Connection::open_in_memory().map_err(runtime)?)
+    }
+
+    fn init(connection: Connection) -> Result<Self, StorageError> {
+        connection
+            .pragma_update(None, "synchronous", "FULL")
+            .map_err(runtime)?;
+        connection.execute_batch(SCHEMA).map_err(runtime)?;
+        Ok(Self(Mutex::new(connection)))
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Connection>, StorageError> {
+        self.0.lock().map_err(|e| StorageError::Runtime(e.to_string()))
+    }
+}
+
+fn runtime(e: impl std::fmt::Display) -> StorageError {
+    StorageError::Runtime(e.to_string())
+}
+
+// SQLite integers are signed, the bits are stored as-is
+fn rpc_id_to_sql(rpc_id: ProtocolRpcId) -> i64 {
+    rpc_id.into_value() as i64
+}
+
+// Saturate rather than wrap, so far-future expiries stay in the future
+fn timestamp_to_sql(timestamp: u64) -> i64 {
+    timestamp.min(i64::MAX as u64) as i64
+}
+
+fn key_from_sql(key: Vec<u8>) -> Result<[u8; 32], StorageError> {
+    key.try_into().map_err(|key: Vec<u8>| {
+        StorageError::Runtime(format!("invalid key length: {}", key.len()))
+    })
+}
+
+fn query_session(
+    connection: &Connection,
+    topic: &Topic,
+) -> Result<Option<Session>, StorageError> {
+    let json = connection
+        .query_row(
+            "SELECT session FROM sessions WHERE topic = ?1",
+            params![topic.to_string()],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()
+        .map_err(runtime)?;
+    json.map(|json| serde_json::from_str(&json).map_err(runtime)).transpose()
+}
+
+impl Storage for SqliteStorage {
+    fn add_session(&self, session: Session) -> Result<(), StorageError> {
+        let json = serde_json::to_string(&session).map_err(runtime)?;
+        self.lock()?
+            .execute(
+                "INSERT OR REPLACE INTO sessions (topic, session) VALUES (?1, ?2)",
+                params![session.topic.to_string(), json],
+            )
+            .map_err(runtime)?;
+        Ok(())
+    }
+
+    fn delete_session(&self, topic: Topic) -> Result<(), StorageError> {
+        self.lock()?
+            .execute(
+                "DELETE FROM sessions WHERE topic = ?1",
+                params![topic.to_string()],
+            )
+            .map_err(runtime)?;
+        Ok(())
+    }
+
+    fn get_session(
+        &self,
+        topic: Topic,
+    ) -> Result<Option<Session>, StorageError> {
+        query_session(&self.lock()?, &topic)
+    }
+
+    fn get_all_sessions(&self) -> Result<Vec<Session>, StorageError> {
+        let connection = self.lock()?;
+        let mut statement = connection
+            .prepare("SELECT session FROM sessions")
+            .map_err(runtime)?;
+        let rows = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(runtime)?;
+        rows.map(|json| {
+            serde_json::from_str(&json.map_err(runtime)?).map_err(runtime)
+        })
+        .collect()
+    }
+
+    fn get_all_topics(&self) -> Result<Vec<Topic>, StorageError> {
+        let connection = self.lock()?;
+        let mut statement = connection
+            .prepare(
+                "SELECT topic FROM sessions
+                UNION SELECT topic FROM pairings
+                UNION SELECT topic FROM partial_sessions
+                UNION SELECT topic FROM authenticate_requests",
+            )
+            .map_err(runtime)?;
+        let rows = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(runtime)?;
+        rows.map(|topic| Ok(Topic::new(topic.map_err(runtime)?.into())))
+            .collect()
+    }
+
+    fn get_decryption_key_for_topic(
+        &self,
+        topic: Topic,
+    ) -> Result<Option<[u8; 32]>, StorageError> {
+        let connection = self.lock()?;
+        if let Some(session) = query_session(&connection, &topic)? {
+            return Ok(Some(session.session_sym_key));
+        }
+        let key = connection
+            .query_row(
+                "SELECT sym_key FROM (
+                    SELECT 0 AS priority, sym_key FROM pairings WHERE topic = ?1
+                    UNION ALL SELECT 1, sym_key FROM partial_sessions WHERE topic = ?1
+                ) ORDER BY priority LIMIT 1",
+                params![topic.to_string()],
+                |row| row.get::<_, Vec<u8>>(0),
+            )
+            .optional()
+            .map_err(runtime)?;
+        key.map(key_from_sql).transpose()
+    }
+
+    fn save_pairing(
+        &self,
+        topic: Topic,
+        rpc_id: ProtocolRpcId,
+        sym_key: [u8; 32],
+        self_key: [u8; 32],
+        expiry: u64,
+    ) -> Result<(), StorageError> {
+        self.lock()?
+            .execute(
+                "INSERT OR REPLACE INTO pairings (topic, rpc_id, sym_key, self_key, expiry) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    topic.to_string(),
+                    rpc_id_to_sql(rpc_id),
+                    sym_key.as_slice(),
+                    self_key.as_slice(),
+                    timestamp_to_sql(expiry)
+                ],
+            )
+            .map_err(runtime)?;
+        Ok(())
+    }
+
+    fn get_pairing(
+        &self,
+        topic: Topic,
+        rpc_id: ProtocolRpcId,
+    ) -> Result<Option<StoragePairing>, StorageError> {
+        let keys = self
+            .lock()?
+            .query_row(
+                "SELECT sym_key, self_key FROM pairings WHERE topic = ?1 AND rpc_id = ?2",
+                params![topic.to_string(), rpc_id_to_sql(rpc_id)],
+                |row| {
+                    Ok((row.get::<_, Vec<u8>>(0)?, row.get::<_, Vec<u8>>(1)?))
+                },
+            )
+            .optional()
+            .map_err(runtime)?;
+        keys.map(|(sym_key, self_key)| {
+            Ok(StoragePairing {
+                sym_key: key_from_sql(sym_key)?,
+                self_key: key_from_sql(self_key)?,
+            })
+        })
+        .transpose()
+    }
+
+    fn delete_expired_pairings(
+        &self,
+        now: u64,
+    ) -> Result<Vec<Topic>, StorageError> {
+        let mut connection = self.lock()?;
+        let transaction = connection.transaction().map_err(runtime)?;
+        let topics = {
+            let mut statement = transaction
+                .prepare(
+                    "SELECT DISTINCT topic FROM pairings WHERE expiry <= ?1
+                    AND topic NOT IN (SELECT topic FROM pairings WHERE expiry > ?1)",
+                )
+                .map_err(runtime)?;
+            let rows = statement
+                .query_map(params![timestamp_to_sql(now)], |row| {
+                    row.get::<_, String>(0)
+                })
+                .map_err(runtime)?;
+            rows.map(|topic| Ok(Topic::new(topic.map_err(runtime)?.into())))
+                .collect::<Result<Vec<_>, StorageError>>()?
+        };
+        transaction
+            .execute(
+                "DELETE FROM pairings WHERE expiry <= ?1",
+                params![timestamp_to_sql(now)],
+            )
+            .map_err(runtime)?;
+        transaction.commit().map_err(runtime)?;
+        Ok(topics)
+    }
+
+    fn save_partial_session(
+        &self,
+        topic: Topic,
+        sym_key: [u8; 32],
+    ) -> Result<(), StorageError> {
+        self.lock()?
+            .execute(
+                "INSERT OR REPLACE INTO partial_sessions (topic, sym_key) VALUES (?1, ?2)",
+                params![topic.to_string(), sym_key.as_slice()],
+            )
+            .map_err(runtime)?;
+        Ok(())
+    }
+
+    fn save_authenticate_request(
+        &self,
+        topic: Topic,
+        self_key: [u8; 32],
+        expiry: u64,
+    ) -> Result<(), StorageError> {
+        self.lock()?
+            .execute(
+                "INSERT OR REPLACE INTO authenticate_requests (topic, self_key, expiry) VALUES (?1, ?2, ?3)",
+                params![
+                    topic.to_string(),
+                    self_key.as_slice(),
+                    timestamp_to_sql(expiry)
+                ],
+            )
+            .map_err(runtime)?;
+        Ok(())
+    }
+
+    fn get_authenticate_request_key(
+        &self,
+        topic: Topic,
+    ) -> Result<Option<[u8; 32]>, StorageError> {
+        let key = self
+            .lock()?
+            .query_row(
+                "SELECT self_key FROM authenticate_requests WHERE topic = ?1",
+                params![topic.to_string()],
+                |row| row.get::<_, Vec<u8>>(0),
+            )
+            .optional()
+            .map_err(runtime)?;
+        key.map(key_from_sql).transpose()
+    }
+
+    fn delete_expired_authenticate_requests(
+        &self,
+        now: u64,
+    ) -> Result<Vec<Topic>, StorageError> {
+        let connection = self.lock()?;
+        let mut statement = connection
+            .prepare(
+                "DELETE FROM authenticate_requests WHERE expiry <= ?1 RETURNING topic",
+            )
+            .map_err(runtime)?;
+        let rows = statement
+            .query_map(params![timestamp_to_sql(now)], |row| {
+                row.get::<_, String>(0)
+            })
+            .map_err(runtime)?;
+        rows.map(|topic| Ok(Topic::new(topic.map_err(runtime)?.into())))
+            .collect()
+    }
+
+    fn get_verify_public_key(&self) -> Result<Option<Jwk>, StorageError> {
+        let json = self
+            .lock()?
+            .query_row(
+                "SELECT jwk FROM verify_public_key WHERE id = 0",
+                [],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .map_err(runtime)?;
+        json.map(|json| serde_json::from_str(&json).map_err(runtime))
+            .transpose()
+    }
+
+    fn set_verify_public_key(&self, jwk: Jwk) -> Result<(), StorageError> {
+        let json = serde_json::to_string(&jwk).map_err(runtime)?;
+        self.lock()?
+            .execute(
+                "INSERT OR REPLACE INTO verify_public_key (id, jwk) VALUES (0, ?1)",
+                params![json],
+            )
+            .map_err(runtime)?;
+        Ok(())
+    }
+
+    fn insert_json_rpc_history(
+        &self,
+        request_id: ProtocolRpcId,
+        topic: Topic,
+        method: String,
+        body: String,
+        transport_type: Option<TransportType>,
+    ) -> Result<(), StorageError> {
+        let transport_type = transport_type
+            .map(|transport_type| serde_json::to_string(&transport_type))
+            .transpose()
+            .map_err(runtime)?;
+        self.lock()?
+            .execute(
+                "INSERT OR REPLACE INTO json_rpc_history (request_id, topic, method, body, transport_type) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    rpc_id_to_sql(request_id),
+                    topic.to_string(),
+                    method,
+                    body,
+                    transport_type
+                ],
+            )
+            .map_err(runtime)?;
+        Ok(())
+    }
+
+    fn update_json_rpc_history_response(
+        &self,
+        request_id: ProtocolRpcId,
+        response: String,
+    ) -> Result<(), StorageError> {
+        let updated = self
+            .lock()?
+            .execute(
+                "UPDATE json_rpc_history SET response = ?1 WHERE request_id = ?2",
+                params![response, rpc_id_to_sql(request_id)],
+            )
+            .map_err(runtime)?;
+        if updated == 0 {
+            return Err(StorageError::Runtime(format!(
+                "JSON-RPC history entry not found for request_id: {request_id}"
+            )));
+        }
+        Ok(())
+    }
+
+    fn get_pending_json_rpc_requests(
+        &self,
+        method: String,
+    ) -> Result<Vec<StorageJsonRpcRequest>, StorageError> {
+        let connection = self.lock()?;
+        let mut statement = connection
+            .prepare(
+                "SELECT topic, body FROM json_rpc_history WHERE method = ?1 AND response IS NULL",
+            )
+            .map_err(runtime)?;
+        let rows = statement
+            .query_map(params![method], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(runtime)?;
+        rows.map(|row| {
+            let (topic, body) = row.map_err(runtime)?;
+            Ok(StorageJsonRpcRequest { topic: Topic::new(topic.into()), body })
+        })
+        .collect()
+    }
+
+    fn delete_json_rpc_history_by_topic(
+        &self,
+        topic: Topic,
+    ) -> Result<(), StorageError> {
+        self.lock()?
+            .execute(
+                "DELETE FROM json_rpc_history WHERE topic = ?1",
+                params![topic.to_string()],
+            )
+            .map_err(runtime)?;
+        Ok(())
+    }
+
+    fn does_json_rpc_exist(
+        &self,
+        request_id: ProtocolRpcId,
+    ) -> Result<bool, StorageError> {
+        self.lock()?
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM json_rpc_history WHERE request_id = ?1)",
+                params![rpc_id_to_sql(request_id)],
+                |row| row.get::<_, bool>(0),
+            )
+            .map_err(runtime)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::sign::storage::conformance, std::sync::Arc};
+
+    #[test]
+    fn test_conformance() {
+        conformance::run(|| Arc::new(SqliteStorage::open_in_memory().unwrap()));
+    }
+
+    #[test]
+    fn test_persists_across_reopen() {
+        let path = std::env::temp_dir().join(format!(
+            "yttrium-sign-storage-{}.sqlite",
+            ProtocolRpcId::generate()
+        ));
+        let topic = Topic::new("a".repeat(64).into());
+        {
+            let storage = SqliteStorage::open(&path).unwrap();
+            storage.save_partial_session(topic.clone(), [1; 32]).unwrap();
+        }
+        let storage = SqliteStorage::open(&path).unwrap();
+        assert_eq!(
+            storage.get_decryption_key_for_topic(topic).unwrap(),
+            Some([1; 32])
+        );
+        drop(storage);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,215 +1,26 @@
 use {
     crate::sign::{
         client::{Client, generate_client_id_key},
-        client_types::{ConnectParams, Session, TransportType},
+        client_types::{ConnectParams, PairResult},
         protocol_types::{
-            JsonRpcVersion, Metadata, ProposalNamespace, ProtocolRpcId,
-            SessionRequest, SessionRequestJsonRpcResponse,
-            SessionRequestJsonRpcResultResponse, SessionRequestRequest,
-            SettleNamespace,
+            JsonRpcVersion, Metadata, ProposalNamespace, SessionRequest,
+            SessionRequestJsonRpcResponse, SessionRequestJsonRpcResultResponse,
+            SessionRequestRequest, SettleNamespace,
         },
         relay::IncomingSessionMessage,
-        storage::{Jwk, Storage, StorageError, StoragePairing},
+        storage::MemoryStorage,
         verify::validate::VerifyValidation,
     },
-    relay_rpc::domain::Topic,
-    std::{
-        collections::HashMap,
-        sync::{Arc, Mutex},
-    },
+    std::{collections::HashMap, sync::Arc},
 };
 
-#[derive(Clone)]
-struct JsonRpcHistoryEntry {
-    topic: Topic,
-    response: Option<String>,
-}
-
-struct MySessionStoreInner {
-    sessions: Vec<Session>,
-    pairing_keys: HashMap<Topic, (ProtocolRpcId, StoragePairing)>,
-    partial_sessions: HashMap<Topic, [u8; 32]>,
-    json_rpc_history: HashMap<ProtocolRpcId, JsonRpcHistoryEntry>,
-}
-
-struct MySessionStore(Arc<Mutex<MySessionStoreInner>>);
-
-impl MySessionStore {
-    pub fn new() -> Self {
-        Self(Arc::new(Mutex::new(MySessionStoreInner {
-            sessions: vec![],
-            pairing_keys: HashMap::new(),
-            partial_sessions: HashMap::new(),
-            json_rpc_history: HashMap::new(),
-        })))
-    }
-}
-
-impl Storage for MySessionStore {
-    fn add_session(&self, session: Session) -> Result<(), StorageError> {
-        let mut inner = self.0.lock().unwrap();
-        inner.sessions.push(session);
-        Ok(())
-    }
-
-    fn delete_session(&self, topic: Topic) -> Result<(), StorageError> {
-        let mut inner = self.0.lock().unwrap();
-        inner.sessions.retain(|s| s.topic != topic);
-        Ok(())
-    }
-
-    fn get_session(
-        &self,
-        topic: Topic,
-    ) -> Result<Option<Session>, StorageError> {
-        let inner = self.0.lock().unwrap();
-        let session = inner.sessions.iter().find(|s| s.topic == topic).cloned();
-        Ok(session)
-    }
-
-    fn get_all_sessions(&self) -> Result<Vec<Session>, StorageError> {
-        let inner = self.0.lock().unwrap();
-        let sessions = inner.sessions.clone();
-        Ok(sessions)
-    }
-
-    fn get_all_topics(&self) -> Result<Vec<Topic>, StorageError> {
-        let inner = self.0.lock().unwrap();
-        let topics = inner
-            .sessions
-            .iter()
-            .map(|s| s.topic.clone())
-            .chain(inner.pairing_keys.keys().cloned())
-            .chain(inner.partial_sessions.keys().cloned())
-            .collect();
-        Ok(topics)
-    }
-
-    fn get_decryption_key_for_topic(
-        &self,
-        topic: Topic,
-    ) -> Result<Option<[u8; 32]>, StorageError> {
-        let inner = self.0.lock().unwrap();
-        let decryption_key = inner
-            .sessions
-            .iter()
-            .find(|session| session.topic == topic)
-            .map(|session| session.session_sym_key)
-            .or_else(|| {
-                inner.pairing_keys.get(&topic).map(
-                    |(_, StoragePairing { sym_key, self_key: _ })| *sym_key,
-                )
-            })
-            .or_else(|| inner.partial_sessions.get(&topic).copied());
-        Ok(decryption_key)
-    }
-
-    fn save_pairing(
-        &self,
-        topic: Topic,
-        rpc_id: ProtocolRpcId,
-        sym_key: [u8; 32],
-        self_key: [u8; 32],
-    ) -> Result<(), StorageError> {
-        let mut inner = self.0.lock().unwrap();
-        inner
-            .pairing_keys
-            .insert(topic, (rpc_id, StoragePairing { sym_key, self_key }));
-        Ok(())
-    }
-
-    fn get_pairing(
-        &self,
-        topic: Topic,
-        _rpc_id: ProtocolRpcId,
-    ) -> Result<Option<StoragePairing>, StorageError> {
-        let inner = self.0.lock().unwrap();
-        let pairing = inner
-            .pairing_keys
-            .get(&topic)
-            .map(|(_, storage_pairing)| storage_pairing)
-            .cloned();
-        Ok(pairing)
-    }
-
-    fn save_partial_session(
-        &self,
-        topic: Topic,
-        sym_key: [u8; 32],
-    ) -> Result<(), StorageError> {
-        let mut inner = self.0.lock().unwrap();
-        inner.partial_sessions.insert(topic, sym_key);
-        Ok(())
-    }
-
-    fn get_verify_public_key(&self) -> Result<Option<Jwk>, StorageError> {
-        Ok(None)
-    }
-
-    fn set_verify_public_key(
-        &self,
-        _public_key: Jwk,
-    ) -> Result<(), StorageError> {
-        Ok(())
-    }
-
-    fn insert_json_rpc_history(
-        &self,
-        request_id: ProtocolRpcId,
-        topic: Topic,
-        _method: String,
-        _body: String,
-        _transport_type: Option<TransportType>,
-    ) -> Result<(), StorageError> {
-        let mut inner = self.0.lock().unwrap();
-        inner
-            .json_rpc_history
-            .insert(request_id, JsonRpcHistoryEntry { topic, response: None });
-        Ok(())
-    }
-
-    fn update_json_rpc_history_response(
-        &self,
-        request_id: ProtocolRpcId,
-        response: String,
-    ) -> Result<(), StorageError> {
-        let mut inner = self.0.lock().unwrap();
-        let entry =
-            inner.json_rpc_history.get_mut(&request_id).ok_or_else(|| {
-                StorageError::Runtime(format!(
-                    "JSON-RPC history entry not found for request_id: {}",
-                    request_id
-                ))
-            })?;
-        entry.response = Some(response);
-        Ok(())
-    }
-
-    fn delete_json_rpc_history_by_topic(
-        &self,
-        topic: Topic,
-    ) -> Result<(), StorageError> {
-        let mut inner = self.0.lock().unwrap();
-        inner.json_rpc_history.retain(|_, entry| entry.topic != topic);
-        Ok(())
-    }
-
-    fn does_json_rpc_exist(
-        &self,
-        request_id: ProtocolRpcId,
-    ) -> Result<bool, StorageError> {
-        let inner = self.0.lock().unwrap();
-        Ok(inner.json_rpc_history.contains_key(&request_id))
-    }
-}
-
 pub async fn test_sign_impl() -> Result<(), String> {
     let app_client_id = generate_client_id_key();
     tracing::debug!(group = "app", probe = "client_id_generated");
     let (mut app_client, mut app_session_request_rx) = Client::new(
         std::env::var("REOWN_PROJECT_ID").unwrap().into(),
         app_client_id,
-        Arc::new(MySessionStore::new()),
+        Arc::new(MemoryStorage::new()),
     );
     app_client.set_probe_group("app".to_string());
     tracing::debug!(group = "app", probe = "client_created");
@@ -247,22 +58,25 @@ pub async fn test_sign_impl() -> Result<(), String> {
     let (mut wallet_client, mut wallet_session_request_rx) = Client::new(
         std::env::var("REOWN_PROJECT_ID").unwrap().into(),
         wallet_client_id,
-        Arc::new(MySessionStore::new()),
+        Arc::new(MemoryStorage::new()),
     );
     wallet_client.set_probe_group("wallet".to_string());
     tracing::debug!(group = "wallet", probe = "client_created");
     wallet_client.start();
     tracing::debug!(group = "wallet", probe = "client_started");
-    let pairing = wallet_client
+    let PairResult::Proposal(proposal, verify_context) = wallet_client
         .pair(&connect_result.uri)
         .await
-        .map_err(|e| format!("Failed to pair: {e}"))?;
+        .map_err(|e| format!("Failed to pair: {e}"))?
+    else {
+        return Err("Expected a session proposal".to_owned());
+    };
     tracing::debug!(group = "wallet", probe = "pair_finished");
 
-    assert_eq!(pairing.1.validation, VerifyValidation::Unknown);
+    assert_eq!(verify_context.validation, VerifyValidation::Unknown);
 
     let mut namespaces = HashMap::new();
-    for (namespace, namespace_proposal) in pairing.0.required_namespaces.clone()
+    for (namespace, namespace_proposal) in proposal.required_namespaces.clone()
     {
         let accounts = namespace_proposal
             .chains
@@ -294,7 +108,7 @@ pub async fn test_sign_impl() -> Result<(), String> {
     tracing::debug!(group = "wallet", probe = "metadata");
 
     wallet_client
-        .approve(pairing.0, namespaces, metadata)
+        .approve(proposal, namespaces, metadata)
         .await
         .map_err(|e| format!("Failed to approve: {e}"))?;
     tracing::debug!(group = "wallet", probe = "approve_finished");
@@ -27,6 +27,7 @@ use {
     crate::sign::{
         client_types::Session,
         envelope_type0::{EnvelopeType0, encode_envelope_type0},
+        envelope_type1::{EnvelopeType1, encode_envelope_type1},
     },
     chacha20poly1305::{
         ChaCha20Poly1305, KeyInit,
@@ -161,6 +162,14 @@ fn encrypt(
     shared_secret: [u8; 32],
     serialized: &[u8],
 ) -> Result<Vec<u8>, String> {
+    let (iv, sb) = encrypt_raw(shared_secret, serialized)?;
+    Ok(encode_envelope_type0(&EnvelopeType0 { iv, sb }))
+}
+
+fn encrypt_raw(
+    shared_secret: [u8; 32],
+    serialized: &[u8],
+) -> Result<([u8; 12], Vec<u8>), String> {
     tracing::debug!(
         "encrypting message: {}",
         String::from_utf8_lossy(serialized)
@@ -170,11 +179,39 @@ fn encrypt(
     let encrypted = key
         .encrypt(&nonce, serialized)
         .map_err(|e| format!("Failed to encrypt message: {e}"))?;
-    let encoded = encode_envelope_type0(&EnvelopeType0 {
-        iv: nonce.into(),
-        sb: encrypted,
-    });
-    Ok(encoded)
+    Ok((nonce.into(), encrypted))
+}
+
+// base64_encode(type1_envelope(sender_public_key, encrypt(json_serialize(rpc))))
+// Used when the receiver doesn't know the sender's public key yet, e.g. wc_sessionAuthenticate responses
+pub fn serialize_and_encrypt_message_type1_envelope<T: Serialize>(
+    shared_secret: [u8; 32],
+    sender_public_key: [u8; 32],
+    message: &T,
+) -> Result<Arc<str>, String> {
+    let serialized = serialize(message)?;
+    let (iv, sb) = encrypt_raw(shared_secret, &serialized)?;
+    let encrypted =
+        encode_envelope_type1(&EnvelopeType1 { iv, pk: sender_public_key, sb })
+            .map_err(|e| format!("Failed to encode envelope: {e}"))?;
+    Ok(encode(&encrypted))
+}
+
+// A type1 envelope from a throwaway key, for wc_sessionAuthenticate rejections where no session is created
+// Returns the response topic, derived from the requester's public key, and the message
+pub fn serialize_and_encrypt_session_authenticate_rejection<T: Serialize>(
+    requester_public_key: [u8; 32],
+    message: &T,
+) -> Result<(Topic, Arc<str>), String> {
+    let self_key = x25519_dalek::StaticSecret::random();
+    let self_public_key = x25519_dalek::PublicKey::from(&self_key);
+    let shared_secret = diffie_hellman(&requester_public_key.into(), &self_key);
+    let message = serialize_and_encrypt_message_type1_envelope(
+        shared_secret,
+        self_public_key.to_bytes(),
+        message,
+    )?;
+    Ok((topic_from_sym_key(&requester_public_key), message))
 }
 
 fn encode(encrypted: &[u8]) -> Arc<str> {
@@ -0,0 +1,110 @@
+use {
+    crate::{
+        sign::{utils::DecryptedHash, verify::validate::VerifyContext},
+        time::{Duration, Instant},
+    },
+    std::collections::HashMap,
+};
+
+/// How long a Verify API result is reused for the same message hash
+pub const ATTESTATION_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+// Bounds memory if many distinct messages are verified within one TTL
+const ATTESTATION_CACHE_MAX_ENTRIES: usize = 1000;
+
+/// Verify results keyed by the decrypted message hash, so redelivered or replayed messages don't hit the verify server again
+#[derive(Debug)]
+pub struct AttestationCache {
+    ttl: Duration,
+    entries: HashMap<String, (VerifyContext, Instant)>,
+}
+
+impl Default for AttestationCache {
+    fn default() -> Self {
+        Self::new(ATTESTATION_CACHE_TTL)
+    }
+}
+
+impl AttestationCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, entries: HashMap::new() }
+    }
+
+    pub fn get(&mut self, hash: &DecryptedHash) -> Option<VerifyContext> {
+        let now = Instant::now();
+        match self.entries.get(hash.as_str()) {
+            Some((context, expires_at)) if *expires_at > now => {
+                Some(context.clone())
+            }
+            Some(_) => {
+                self.entries.remove(hash.as_str());
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&mut self, hash: &DecryptedHash, context: VerifyContext) {
+        let now = Instant::now();
+        if self.entries.len() >= ATTESTATION_CACHE_MAX_ENTRIES {
+            self.entries.retain(|_, (_, expires_at)| *expires_at > now);
+        }
+        if self.entries.len() >= ATTESTATION_CACHE_MAX_ENTRIES {
+            // Still full of live entries; drop the one closest to expiring
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, expires_at))| *expires_at)
+                .map(|(key, _)| key.clone())
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries
+            .insert(hash.as_str().to_owned(), (context, now + self.ttl));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::sign::verify::validate::VerifyValidation};
+
+    fn context() -> VerifyContext {
+        VerifyContext {
+            origin: Some("https://app.walletconnect.org".to_owned()),
+            validation: VerifyValidation::Valid,
+            is_scam: false,
+        }
+    }
+
+    #[test]
+    fn test_get_insert() {
+        let mut cache = AttestationCache::default();
+        let hash = DecryptedHash("a".to_owned());
+        assert!(cache.get(&hash).is_none());
+        cache.insert(&hash, context());
+        assert_eq!(
+            cache.get(&hash).unwrap().origin,
+            Some("https://app.walletconnect.org".to_owned())
+        );
+        assert!(cache.get(&DecryptedHash("b".to_owned())).is_none());
+    }
+
+    #[test]
+    fn test_expiry() {
+        let mut cache = AttestationCache::new(Duration::ZERO);
+        let hash = DecryptedHash("a".to_owned());
+        cache.insert(&hash, context());
+        assert!(cache.get(&hash).is_none());
+        assert!(cache.entries.is_empty());
+    }
+
+    #[test]
+    fn test_max_entries() {
+        let mut cache = AttestationCache::default();
+        for i in 0..ATTESTATION_CACHE_MAX_ENTRIES + 10 {
+            cache.insert(&DecryptedHash(i.to_string()), context());
+        }
+        assert_eq!(cache.entries.len(), ATTESTATION_CACHE_MAX_ENTRIES);
+    }
+}
@@ -0,0 +1,319 @@
+use {
+    crate::sign::verify::validate::{VerifyContext, VerifyValidation},
+    url::Url,
+};
+
+/// Host-configured rules applied to the Verify result of proposals and requests.
+/// Patterns are either an origin (`https://app.example.com`) or a host (`app.example.com`), and `*` matches any characters,
+/// e.g. `*.example.com` matches subdomains of `example.com` but not `example.com` itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct OriginPolicy {
+    // Reject origins whose attestation doesn't match the dapp's metadata URL
+    pub block_invalid: bool,
+    // When not empty, only matching origins are allowed
+    pub allow_list: Vec<String>,
+    // Takes precedence over `allow_list`
+    pub deny_list: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum OriginPolicyReason {
+    Scam,
+    Invalid,
+    Denied { pattern: String },
+    NotAllowed,
+    Allowed { pattern: String },
+    Verified,
+    Unverified,
+}
+
+impl std::fmt::Display for OriginPolicyReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OriginPolicyReason::Scam => {
+                write!(f, "This domain is flagged as malicious")
+            }
+            OriginPolicyReason::Invalid => {
+                write!(f, "This domain doesn't match the verified origin")
+            }
+            OriginPolicyReason::Denied { pattern } => {
+                write!(f, "This domain is blocked ({pattern})")
+            }
+            OriginPolicyReason::NotAllowed => {
+                write!(f, "This domain is not on the allow list")
+            }
+            OriginPolicyReason::Allowed { pattern } => {
+                write!(f, "This domain is allowed ({pattern})")
+            }
+            OriginPolicyReason::Verified => {
+                write!(f, "This domain is verified")
+            }
+            OriginPolicyReason::Unverified => {
+                write!(f, "This domain cannot be verified")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct OriginDecision {
+    // The attested origin, or the origin of the dapp's metadata URL if there is no attestation
+    pub origin: Option<String>,
+    pub blocked: bool,
+    pub reason: OriginPolicyReason,
+    // `reason` as text for display
+    pub message: String,
+}
+
+impl OriginDecision {
+    fn new(
+        origin: Option<String>,
+        blocked: bool,
+        reason: OriginPolicyReason,
+    ) -> Self {
+        let message = reason.to_string();
+        Self { origin, blocked, reason, message }
+    }
+}
+
+impl OriginPolicy {
+    /// Scam origins are always blocked. Then the deny list, `block_invalid` and the allow list are applied, in that order.
+    pub fn evaluate(
+        &self,
+        verify_context: &VerifyContext,
+        app_metadata_url: &str,
+    ) -> OriginDecision {
+        let origin = verify_context.origin.clone().or_else(|| {
+            Url::parse(app_metadata_url)
+                .ok()
+                .map(|url| url.origin().ascii_serialization())
+        });
+
+        if verify_context.is_scam {
+            return OriginDecision::new(origin, true, OriginPolicyReason::Scam);
+        }
+
+        let find = |patterns: &[String]| {
+            origin.as_deref().and_then(|origin| {
+                patterns
+                    .iter()
+                    .find(|pattern| matches_origin(pattern, origin))
+                    .cloned()
+            })
+        };
+
+        if let Some(pattern) = find(&self.deny_list) {
+            return OriginDecision::new(
+                origin,
+                true,
+                OriginPolicyReason::Denied { pattern },
+            );
+        }
+
+        if self.block_invalid
+            && verify_context.validation == VerifyValidation::Invalid
+        {
+            return OriginDecision::new(
+                origin,
+                true,
+                OriginPolicyReason::Invalid,
+            );
+        }
+
+        if !self.allow_list.is_empty() {
+            return match find(&self.allow_list) {
+                Some(pattern) => OriginDecision::new(
+                    origin,
+                    false,
+                    OriginPolicyReason::Allowed { pattern },
+                ),
+                None => OriginDecision::new(
+                    origin,
+                    true,
+                    OriginPolicyReason::NotAllowed,
+                ),
+            };
+        }
+
+        let reason = match verify_context.validation {
+            VerifyValidation::Valid => OriginPolicyReason::Verified,
+            VerifyValidation::Invalid => OriginPolicyReason::Invalid,
+            VerifyValidation::Unknown => OriginPolicyReason::Unverified,
+        };
+        OriginDecision::new(origin, false, reason)
+    }
+}
+
+fn matches_origin(pattern: &str, origin: &str) -> bool {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    let origin = origin.to_ascii_lowercase();
+    if pattern.contains("://") {
+        glob_match(&pattern, &origin)
+    } else {
+        Url::parse(&origin)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_owned))
+            .is_some_and(|host| glob_match(&pattern, &host))
+    }
+}
+
+// `*` matches any sequence of characters, everything else is literal
+fn glob_match(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = value.strip_prefix(first) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(
+        origin: Option<&str>,
+        validation: VerifyValidation,
+        is_scam: bool,
+    ) -> VerifyContext {
+        VerifyContext { origin: origin.map(str::to_owned), validation, is_scam }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("app.example.com", "app.example.com"));
+        assert!(!glob_match("app.example.com", "app.example.com.evil"));
+        assert!(glob_match("*.example.com", "app.example.com"));
+        assert!(glob_match("*.example.com", "a.b.example.com"));
+        assert!(!glob_match("*.example.com", "example.com"));
+        assert!(!glob_match("*.example.com", "evilexample.com"));
+        assert!(glob_match("https://*", "https://app.example.com"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "abc"));
+        assert!(!glob_match("a*b*c", "acb"));
+    }
+
+    #[test]
+    fn test_matches_origin() {
+        assert!(matches_origin("*.example.com", "https://app.example.com"));
+        assert!(matches_origin("App.Example.com", "https://app.example.com"));
+        assert!(matches_origin(
+            "https://app.example.com",
+            "https://app.example.com"
+        ));
+        assert!(!matches_origin(
+            "http://app.example.com",
+            "https://app.example.com"
+        ));
+    }
+
+    #[test]
+    fn test_scam_always_blocked() {
+        let policy = OriginPolicy {
+            allow_list: vec!["app.example.com".to_owned()],
+            ..Default::default()
+        };
+        let decision = policy.evaluate(
+            &context(
+                Some("https://app.example.com"),
+                VerifyValidation::Valid,
+                true,
+            ),
+            "https://app.example.com",
+        );
+        assert!(decision.blocked);
+        assert_eq!(decision.reason, OriginPolicyReason::Scam);
+        assert_eq!(decision.message, OriginPolicyReason::Scam.to_string());
+    }
+
+    #[test]
+    fn test_default_policy() {
+        let policy = OriginPolicy::default();
+        let decision = policy.evaluate(
+            &context(None, VerifyValidation::Unknown, false),
+            "https://app.example.com/path",
+        );
+        assert!(!decision.blocked);
+        assert_eq!(decision.reason, OriginPolicyReason::Unverified);
+        assert_eq!(decision.origin.as_deref(), Some("https://app.example.com"));
+
+        let decision = policy.evaluate(
+            &context(
+                Some("https://evil.com"),
+                VerifyValidation::Invalid,
+                false,
+            ),
+            "https://app.example.com",
+        );
+        assert!(!decision.blocked);
+        assert_eq!(decision.reason, OriginPolicyReason::Invalid);
+    }
+
+    #[test]
+    fn test_block_invalid() {
+        let policy = OriginPolicy { block_invalid: true, ..Default::default() };
+        let decision = policy.evaluate(
+            &context(
+                Some("https://evil.com"),
+                VerifyValidation::Invalid,
+                false,
+            ),
+            "https://app.example.com",
+        );
+        assert!(decision.blocked);
+        assert_eq!(decision.reason, OriginPolicyReason::Invalid);
+    }
+
+    #[test]
+    fn test_allow_and_deny_lists() {
+        let policy = OriginPolicy {
+            block_invalid: false,
+            allow_list: vec!["*.example.com".to_owned()],
+            deny_list: vec!["bad.example.com".to_owned()],
+        };
+        let valid = |origin: &str| {
+            context(Some(origin), VerifyValidation::Valid, false)
+        };
+
+        let decision = policy.evaluate(
+            &valid("https://app.example.com"),
+            "https://app.example.com",
+        );
+        assert!(!decision.blocked);
+        assert_eq!(
+            decision.reason,
+            OriginPolicyReason::Allowed { pattern: "*.example.com".to_owned() }
+        );
+
+        let decision = policy.evaluate(
+            &valid("https://bad.example.com"),
+            "https://bad.example.com",
+        );
+        assert!(decision.blocked);
+        assert_eq!(
+            decision.reason,
+            OriginPolicyReason::Denied {
+                pattern: "bad.example.com".to_owned()
+            }
+        );
+
+        let decision =
+            policy.evaluate(&valid("https://other.com"), "https://other.com");
+        assert!(decision.blocked);
+        assert_eq!(decision.reason, OriginPolicyReason::NotAllowed);
+    }
+}
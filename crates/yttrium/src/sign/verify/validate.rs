@@ -2,6 +2,10 @@ use {
     crate::sign::{
         storage::{Storage, StorageError},
         utils::{DecryptedHash, EncryptedHash},
+        verify::{
+            cache::AttestationCache,
+            policy::{OriginDecision, OriginPolicy},
+        },
     },
     jsonwebtoken::{Algorithm, DecodingKey, Validation, jwk::Jwk},
     serde::{Deserialize, Serialize},
@@ -123,6 +127,30 @@ pub async fn get_latest_public_key(
     Ok(public_key)
 }
 
+// Fetches the latest public key, emitting a key rotation if it differs from the stored one
+async fn refresh_public_key(
+    verify_server_url: String,
+    http_client: reqwest::Client,
+    storage: Arc<dyn Storage>,
+    verify_state: &VerifyState,
+    probe_group: Option<String>,
+) -> Result<Jwk, GetPublicKeyError> {
+    let previous_key = storage
+        .get_verify_public_key()
+        .map_err(GetPublicKeyError::GetFromStorage)?;
+    let public_key = get_latest_public_key(
+        verify_server_url,
+        http_client,
+        storage,
+        probe_group,
+    )
+    .await?;
+    if previous_key.as_ref() != Some(&public_key) {
+        verify_state.emit_key_rotation(previous_key.as_ref(), &public_key);
+    }
+    Ok(public_key)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct VerifyPublicKey {
@@ -139,6 +167,7 @@ async fn decode_attestation_into_verify_context(
     encrypted_id: EncryptedHash,
     http_client: reqwest::Client,
     storage: Arc<dyn Storage>,
+    verify_state: &VerifyState,
     probe_group: Option<String>,
 ) -> VerifyContext {
     let decoding_key = match DecodingKey::from_jwk(public_key) {
@@ -162,13 +191,24 @@ async fn decode_attestation_into_verify_context(
         Ok(token_data) => token_data.claims,
         Err(e) => {
             if e.kind() == &jsonwebtoken::errors::ErrorKind::InvalidSignature {
+                if verify_state.mode() == VerifyMode::Offline {
+                    tracing::debug!(
+                        "decode_attestation_into_verify_context: invalid signature, not fetching latest key while offline"
+                    );
+                    return VerifyContext {
+                        origin: None,
+                        validation: VerifyValidation::Unknown,
+                        is_scam: false,
+                    };
+                }
                 tracing::debug!(
                     "decode_attestation_into_verify_context: invalid signature, fetching latest key"
                 );
-                let public_key = match get_latest_public_key(
+                let public_key = match refresh_public_key(
                     verify_server_url,
                     http_client,
                     storage,
+                    verify_state,
                     probe_group.clone(),
                 )
                 .await
@@ -176,7 +216,7 @@ async fn decode_attestation_into_verify_context(
                     Ok(public_key) => public_key,
                     Err(e) => {
                         tracing::error!(
-                            "decode_attestation_into_verify_context: get_latest_public_key: {e}"
+                            "decode_attestation_into_verify_context: refresh_public_key: {e}"
                         );
                         return VerifyContext {
                             origin: None,
@@ -307,6 +347,42 @@ pub async fn handle_verify(
     decrypted_hash: DecryptedHash,
     http_client: reqwest::Client,
     storage: Arc<dyn Storage>,
+    verify_state: &VerifyState,
+    attestation: Option<Arc<str>>,
+    encrypted_hash: EncryptedHash,
+    app_metadata_url: String,
+    probe_group: Option<String>,
+) -> VerifyContext {
+    if let Some(verify_context) = verify_state.get_cached(&decrypted_hash) {
+        tracing::debug!("handle_verify: using cached verify context");
+        return verify_context;
+    }
+    let verify_context = handle_verify_uncached(
+        verify_server_url,
+        decrypted_hash.clone(),
+        http_client,
+        storage,
+        verify_state,
+        attestation,
+        encrypted_hash,
+        app_metadata_url,
+        probe_group,
+    )
+    .await;
+    // Unknown results are usually failures, so they are retried next time
+    if verify_context.validation != VerifyValidation::Unknown {
+        verify_state.insert_cached(&decrypted_hash, verify_context.clone());
+    }
+    verify_context
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn handle_verify_uncached(
+    verify_server_url: String,
+    decrypted_hash: DecryptedHash,
+    http_client: reqwest::Client,
+    storage: Arc<dyn Storage>,
+    verify_state: &VerifyState,
     attestation: Option<Arc<str>>,
     encrypted_hash: EncryptedHash,
     app_metadata_url: String,
@@ -349,9 +425,18 @@ pub async fn handle_verify(
             encrypted_hash.clone(),
             http_client,
             storage,
+            verify_state,
             probe_group.clone(),
         )
         .await
+    } else if verify_state.mode() == VerifyMode::Offline {
+        // Without a JWT attestation validation requires the verify server
+        tracing::debug!("handle_verify: no attestation while offline");
+        VerifyContext {
+            origin: None,
+            validation: VerifyValidation::Unknown,
+            is_scam: false,
+        }
     } else {
         let attestation_result = {
             let url = format!(
@@ -459,6 +544,104 @@ pub enum VerifyValidation {
     Invalid,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum VerifyMode {
+    /// Fetch attestations and the latest public key from the verify server as needed
+    #[default]
+    Online,
+    /// Never wait on the verify server. JWT attestations are checked against the already-known public key and everything else is `Unknown`.
+    Offline,
+}
+
+/// The verify public key changed. Keys are JWK JSON; `previous_key` is `None` when the bundled key was in use.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct VerifyKeyRotation {
+    pub previous_key: Option<String>,
+    pub new_key: String,
+}
+
+/// Verify state shared between the client and the relay loop
+#[derive(Clone)]
+pub struct VerifyState {
+    mode: Arc<std::sync::RwLock<VerifyMode>>,
+    origin_policy: Arc<std::sync::RwLock<OriginPolicy>>,
+    cache: Arc<std::sync::Mutex<AttestationCache>>,
+    key_rotation_tx: tokio::sync::broadcast::Sender<VerifyKeyRotation>,
+}
+
+impl Default for VerifyState {
+    fn default() -> Self {
+        Self {
+            mode: Default::default(),
+            origin_policy: Default::default(),
+            cache: Default::default(),
+            key_rotation_tx: tokio::sync::broadcast::channel(16).0,
+        }
+    }
+}
+
+impl VerifyState {
+    pub fn mode(&self) -> VerifyMode {
+        *self.mode.read().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    pub fn set_mode(&self, mode: VerifyMode) {
+        *self.mode.write().unwrap_or_else(std::sync::PoisonError::into_inner) =
+            mode;
+    }
+
+    pub fn set_origin_policy(&self, origin_policy: OriginPolicy) {
+        *self
+            .origin_policy
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = origin_policy;
+    }
+
+    pub fn evaluate_origin(
+        &self,
+        verify_context: &VerifyContext,
+        app_metadata_url: &str,
+    ) -> OriginDecision {
+        self.origin_policy
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .evaluate(verify_context, app_metadata_url)
+    }
+
+    pub fn subscribe_key_rotations(
+        &self,
+    ) -> tokio::sync::broadcast::Receiver<VerifyKeyRotation> {
+        self.key_rotation_tx.subscribe()
+    }
+
+    fn get_cached(&self, hash: &DecryptedHash) -> Option<VerifyContext> {
+        self.cache
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(hash)
+    }
+
+    fn insert_cached(&self, hash: &DecryptedHash, context: VerifyContext) {
+        self.cache
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(hash, context);
+    }
+
+    fn emit_key_rotation(&self, previous_key: Option<&Jwk>, new_key: &Jwk) {
+        let to_json =
+            |key: &Jwk| serde_json::to_string(key).unwrap_or_default();
+        tracing::info!("Verify public key rotated");
+        // No receivers is fine
+        let _ = self.key_rotation_tx.send(VerifyKeyRotation {
+            previous_key: previous_key.map(to_json),
+            new_key: to_json(new_key),
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -466,7 +649,7 @@ mod tests {
         crate::sign::{
             client_types::{Session, TransportType},
             protocol_types::ProtocolRpcId,
-            storage::StoragePairing,
+            storage::{StorageJsonRpcRequest, StoragePairing},
         },
         relay_rpc::domain::Topic,
         sha2::Digest,
@@ -530,9 +713,16 @@ mod tests {
                 _rpc_id: ProtocolRpcId,
                 _sym_key: [u8; 32],
                 _self_key: [u8; 32],
+                _expiry: u64,
             ) -> Result<(), StorageError> {
                 Err(StorageError::Runtime("unimplemented".to_string()))
             }
+            fn delete_expired_pairings(
+                &self,
+                _now: u64,
+            ) -> Result<Vec<Topic>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
             fn get_pairing(
                 &self,
                 _topic: Topic,
@@ -547,6 +737,26 @@ mod tests {
             ) -> Result<(), StorageError> {
                 Err(StorageError::Runtime("unimplemented".to_string()))
             }
+            fn save_authenticate_request(
+                &self,
+                _topic: Topic,
+                _self_key: [u8; 32],
+                _expiry: u64,
+            ) -> Result<(), StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
+            fn get_authenticate_request_key(
+                &self,
+                _topic: Topic,
+            ) -> Result<Option<[u8; 32]>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
+            fn delete_expired_authenticate_requests(
+                &self,
+                _now: u64,
+            ) -> Result<Vec<Topic>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
             fn insert_json_rpc_history(
                 &self,
                 _request_id: ProtocolRpcId,
@@ -564,6 +774,12 @@ mod tests {
             ) -> Result<(), StorageError> {
                 Err(StorageError::Runtime("unimplemented".to_string()))
             }
+            fn get_pending_json_rpc_requests(
+                &self,
+                _method: String,
+            ) -> Result<Vec<StorageJsonRpcRequest>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
             fn delete_json_rpc_history_by_topic(
                 &self,
                 _topic: Topic,
@@ -645,9 +861,16 @@ mod tests {
                 _rpc_id: ProtocolRpcId,
                 _sym_key: [u8; 32],
                 _self_key: [u8; 32],
+                _expiry: u64,
             ) -> Result<(), StorageError> {
                 Err(StorageError::Runtime("unimplemented".to_string()))
             }
+            fn delete_expired_pairings(
+                &self,
+                _now: u64,
+            ) -> Result<Vec<Topic>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
             fn get_pairing(
                 &self,
                 _topic: Topic,
@@ -662,6 +885,26 @@ mod tests {
             ) -> Result<(), StorageError> {
                 Err(StorageError::Runtime("unimplemented".to_string()))
             }
+            fn save_authenticate_request(
+                &self,
+                _topic: Topic,
+                _self_key: [u8; 32],
+                _expiry: u64,
+            ) -> Result<(), StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
+            fn get_authenticate_request_key(
+                &self,
+                _topic: Topic,
+            ) -> Result<Option<[u8; 32]>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
+            fn delete_expired_authenticate_requests(
+                &self,
+                _now: u64,
+            ) -> Result<Vec<Topic>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
             fn insert_json_rpc_history(
                 &self,
                 _request_id: ProtocolRpcId,
@@ -679,6 +922,12 @@ mod tests {
             ) -> Result<(), StorageError> {
                 Err(StorageError::Runtime("unimplemented".to_string()))
             }
+            fn get_pending_json_rpc_requests(
+                &self,
+                _method: String,
+            ) -> Result<Vec<StorageJsonRpcRequest>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
             fn delete_json_rpc_history_by_topic(
                 &self,
                 _topic: Topic,
@@ -763,9 +1012,16 @@ mod tests {
                 _rpc_id: ProtocolRpcId,
                 _sym_key: [u8; 32],
                 _self_key: [u8; 32],
+                _expiry: u64,
             ) -> Result<(), StorageError> {
                 Err(StorageError::Runtime("unimplemented".to_string()))
             }
+            fn delete_expired_pairings(
+                &self,
+                _now: u64,
+            ) -> Result<Vec<Topic>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
             fn get_pairing(
                 &self,
                 _topic: Topic,
@@ -780,6 +1036,26 @@ mod tests {
             ) -> Result<(), StorageError> {
                 Err(StorageError::Runtime("unimplemented".to_string()))
             }
+            fn save_authenticate_request(
+                &self,
+                _topic: Topic,
+                _self_key: [u8; 32],
+                _expiry: u64,
+            ) -> Result<(), StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
+            fn get_authenticate_request_key(
+                &self,
+                _topic: Topic,
+            ) -> Result<Option<[u8; 32]>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
+            fn delete_expired_authenticate_requests(
+                &self,
+                _now: u64,
+            ) -> Result<Vec<Topic>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
             fn insert_json_rpc_history(
                 &self,
                 _request_id: ProtocolRpcId,
@@ -797,6 +1073,12 @@ mod tests {
             ) -> Result<(), StorageError> {
                 Err(StorageError::Runtime("unimplemented".to_string()))
             }
+            fn get_pending_json_rpc_requests(
+                &self,
+                _method: String,
+            ) -> Result<Vec<StorageJsonRpcRequest>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
             fn delete_json_rpc_history_by_topic(
                 &self,
                 _topic: Topic,
@@ -879,9 +1161,16 @@ mod tests {
                 _rpc_id: ProtocolRpcId,
                 _sym_key: [u8; 32],
                 _self_key: [u8; 32],
+                _expiry: u64,
             ) -> Result<(), StorageError> {
                 Err(StorageError::Runtime("unimplemented".to_string()))
             }
+            fn delete_expired_pairings(
+                &self,
+                _now: u64,
+            ) -> Result<Vec<Topic>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
             fn get_pairing(
                 &self,
                 _topic: Topic,
@@ -896,6 +1185,26 @@ mod tests {
             ) -> Result<(), StorageError> {
                 Err(StorageError::Runtime("unimplemented".to_string()))
             }
+            fn save_authenticate_request(
+                &self,
+                _topic: Topic,
+                _self_key: [u8; 32],
+                _expiry: u64,
+            ) -> Result<(), StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
+            fn get_authenticate_request_key(
+                &self,
+                _topic: Topic,
+            ) -> Result<Option<[u8; 32]>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
+            fn delete_expired_authenticate_requests(
+                &self,
+                _now: u64,
+            ) -> Result<Vec<Topic>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
             fn insert_json_rpc_history(
                 &self,
                 _request_id: ProtocolRpcId,
@@ -913,6 +1222,12 @@ mod tests {
             ) -> Result<(), StorageError> {
                 Err(StorageError::Runtime("unimplemented".to_string()))
             }
+            fn get_pending_json_rpc_requests(
+                &self,
+                _method: String,
+            ) -> Result<Vec<StorageJsonRpcRequest>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
             fn delete_json_rpc_history_by_topic(
                 &self,
                 _topic: Topic,
@@ -932,6 +1247,7 @@ mod tests {
             DecryptedHash(hex::encode(sha2::Sha256::digest([0; 32]))),
             http_client,
             storage,
+            &VerifyState::default(),
             Some("".to_string().into()),
             EncryptedHash(hex::encode(sha2::Sha256::digest([1; 32]))),
             "https://app.walletconnect.org".to_string(),
@@ -998,9 +1314,16 @@ mod tests {
                 _rpc_id: ProtocolRpcId,
                 _sym_key: [u8; 32],
                 _self_key: [u8; 32],
+                _expiry: u64,
             ) -> Result<(), StorageError> {
                 Err(StorageError::Runtime("unimplemented".to_string()))
             }
+            fn delete_expired_pairings(
+                &self,
+                _now: u64,
+            ) -> Result<Vec<Topic>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
             fn get_pairing(
                 &self,
                 _topic: Topic,
@@ -1015,6 +1338,26 @@ mod tests {
             ) -> Result<(), StorageError> {
                 Err(StorageError::Runtime("unimplemented".to_string()))
             }
+            fn save_authenticate_request(
+                &self,
+                _topic: Topic,
+                _self_key: [u8; 32],
+                _expiry: u64,
+            ) -> Result<(), StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
+            fn get_authenticate_request_key(
+                &self,
+                _topic: Topic,
+            ) -> Result<Option<[u8; 32]>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
+            fn delete_expired_authenticate_requests(
+                &self,
+                _now: u64,
+            ) -> Result<Vec<Topic>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
             fn insert_json_rpc_history(
                 &self,
                 _request_id: ProtocolRpcId,
@@ -1032,6 +1375,12 @@ mod tests {
             ) -> Result<(), StorageError> {
                 Err(StorageError::Runtime("unimplemented".to_string()))
             }
+            fn get_pending_json_rpc_requests(
+                &self,
+                _method: String,
+            ) -> Result<Vec<StorageJsonRpcRequest>, StorageError> {
+                Err(StorageError::Runtime("unimplemented".to_string()))
+            }
             fn delete_json_rpc_history_by_topic(
                 &self,
                 _topic: Topic,
@@ -1082,6 +1431,7 @@ mod tests {
             decrypted_hash,
             http_client,
             storage,
+            &VerifyState::default(),
             None,
             EncryptedHash(hex::encode(sha2::Sha256::digest([0; 32]))),
             app_origin.to_string(),
@@ -1121,6 +1471,7 @@ mod tests {
             decrypted_hash,
             http_client,
             storage,
+            &VerifyState::default(),
             None,
             EncryptedHash(hex::encode(sha2::Sha256::digest([0; 32]))),
             app_origin.to_string(),
@@ -1167,6 +1518,7 @@ mod tests {
             decrypted_hash,
             http_client,
             storage,
+            &VerifyState::default(),
             None,
             EncryptedHash(hex::encode(sha2::Sha256::digest([0; 32]))),
             app_origin.to_string(),
@@ -1212,6 +1564,7 @@ mod tests {
             decrypted_hash,
             http_client,
             storage,
+            &VerifyState::default(),
             None,
             EncryptedHash(hex::encode(sha2::Sha256::digest([0; 32]))),
             app_origin.to_string(),
@@ -1257,6 +1610,7 @@ mod tests {
             decrypted_hash,
             http_client,
             storage,
+            &VerifyState::default(),
             None,
             EncryptedHash(hex::encode(sha2::Sha256::digest([0; 32]))),
             app_origin.to_string(),
@@ -1268,4 +1622,85 @@ mod tests {
         assert!(matches!(verify_context.validation, VerifyValidation::Valid));
         assert!(verify_context.is_scam); // Scam flag should be true
     }
+
+    #[tokio::test]
+    async fn test_handle_verify_caches_by_hash() {
+        let mock_server = MockServer::start().await;
+        let verify_url = mock_server.uri();
+        let http_client = reqwest::Client::new();
+        let storage = create_mock_storage_with_public_key();
+        let verify_state = VerifyState::default();
+
+        let decrypted_hash =
+            DecryptedHash(hex::encode(sha2::Sha256::digest([1; 32])));
+        let app_origin = "https://app.walletconnect.org";
+
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "{}/{}",
+                ATTESTATION_ENDPOINT.trim_end_matches('/'),
+                decrypted_hash.as_str()
+            )))
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                VerifyAttestation {
+                    attestation_id: decrypted_hash.as_str().to_string(),
+                    origin: app_origin.to_string(),
+                    is_scam: Some(false),
+                },
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        for _ in 0..2 {
+            let verify_context = handle_verify(
+                verify_url.clone(),
+                decrypted_hash.clone(),
+                http_client.clone(),
+                storage.clone(),
+                &verify_state,
+                None,
+                EncryptedHash(hex::encode(sha2::Sha256::digest([0; 32]))),
+                app_origin.to_string(),
+                None,
+            )
+            .await;
+            assert!(matches!(
+                verify_context.validation,
+                VerifyValidation::Valid
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_verify_offline_no_attestation() {
+        let mock_server = MockServer::start().await;
+        let verify_url = mock_server.uri();
+        let http_client = reqwest::Client::new();
+        let storage = create_mock_storage_with_public_key();
+        let verify_state = VerifyState::default();
+        verify_state.set_mode(VerifyMode::Offline);
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let verify_context = handle_verify(
+            verify_url,
+            DecryptedHash(hex::encode(sha2::Sha256::digest([1; 32]))),
+            http_client,
+            storage,
+            &verify_state,
+            None,
+            EncryptedHash(hex::encode(sha2::Sha256::digest([0; 32]))),
+            "https://app.walletconnect.org".to_string(),
+            None,
+        )
+        .await;
+
+        assert!(verify_context.origin.is_none());
+        assert!(matches!(verify_context.validation, VerifyValidation::Unknown));
+    }
 }
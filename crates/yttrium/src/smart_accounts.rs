@@ -1,3 +1,4 @@
 pub mod account_address;
+pub mod multisig;
 pub mod nonce;
 pub mod safe;
@@ -0,0 +1,257 @@
+use {
+    crate::{
+        call::send::safe_test::{
+            DoSendTransactionParams, OwnerSignature, PreparedSendTransaction,
+            encode_send_transactions,
+        },
+        smart_accounts::safe::{Owners, SafeOp},
+        user_operation::UserOperationV07,
+    },
+    alloy::{
+        dyn_abi::Eip712Domain,
+        primitives::{Address, B256, Signature},
+    },
+    serde::{Deserialize, Serialize},
+    std::collections::BTreeMap,
+    thiserror::Error,
+};
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum MultisigError {
+    #[error("{0} is not an owner of the account")]
+    NotAnOwner(Address),
+    #[error("signature was made by {recovered}, not by {owner}")]
+    WrongSigner { owner: Address, recovered: Address },
+    #[error("invalid signature: {0}")]
+    InvalidSignature(String),
+    #[error("{0} already signed")]
+    AlreadySigned(Address),
+    #[error("{collected} of {threshold} required signatures collected")]
+    BelowThreshold { collected: usize, threshold: u8 },
+}
+
+/// Collects the owners' signatures of a Safe user operation until the
+/// threshold is met. Serializable, so it can be passed between owners that
+/// sign on different devices or at different times.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafeSigningSession {
+    owners: Vec<Address>,
+    threshold: u8,
+    prepared: PreparedSendTransaction,
+    /// Keyed by owner, so already in the order Safe expects
+    signatures: BTreeMap<Address, Signature>,
+}
+
+impl SafeSigningSession {
+    pub fn new(owners: Owners, prepared: PreparedSendTransaction) -> Self {
+        Self {
+            owners: owners.owners,
+            threshold: owners.threshold,
+            prepared,
+            signatures: BTreeMap::new(),
+        }
+    }
+
+    /// EIP-712 hash of `safe_op` each owner signs
+    pub fn hash(&self) -> B256 {
+        self.prepared.hash
+    }
+
+    pub fn safe_op(&self) -> &SafeOp {
+        &self.prepared.safe_op
+    }
+
+    pub fn domain(&self) -> &Eip712Domain {
+        &self.prepared.domain
+    }
+
+    /// Checks that `signature` is `owner`'s signature of `hash()` and adds
+    /// it. Returns whether the threshold is now met.
+    pub fn add_signature(
+        &mut self,
+        OwnerSignature { owner, signature }: OwnerSignature,
+    ) -> Result<bool, MultisigError> {
+        if !self.owners.contains(&owner) {
+            return Err(MultisigError::NotAnOwner(owner));
+        }
+        if self.signatures.contains_key(&owner) {
+            return Err(MultisigError::AlreadySigned(owner));
+        }
+        let recovered = signature
+            .recover_address_from_prehash(&self.prepared.hash)
+            .map_err(|e| MultisigError::InvalidSignature(e.to_string()))?;
+        if recovered != owner {
+            return Err(MultisigError::WrongSigner { owner, recovered });
+        }
+        self.signatures.insert(owner, signature);
+        Ok(self.is_complete())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.signatures.len() >= usize::from(self.threshold)
+    }
+
+    pub fn signers(&self) -> Vec<Address> {
+        self.signatures.keys().copied().collect()
+    }
+
+    /// Owners that haven't signed yet
+    pub fn pending_signers(&self) -> Vec<Address> {
+        self.owners
+            .iter()
+            .filter(|owner| !self.signatures.contains_key(owner))
+            .copied()
+            .collect()
+    }
+
+    /// Owner signatures to pass to `do_send_transactions`, once the
+    /// threshold is met. Signatures beyond the threshold are left out.
+    pub fn signatures(&self) -> Result<Vec<OwnerSignature>, MultisigError> {
+        if !self.is_complete() {
+            return Err(MultisigError::BelowThreshold {
+                collected: self.signatures.len(),
+                threshold: self.threshold,
+            });
+        }
+        Ok(self
+            .signatures
+            .iter()
+            .take(usize::from(self.threshold))
+            .map(|(owner, signature)| OwnerSignature {
+                owner: *owner,
+                signature: *signature,
+            })
+            .collect())
+    }
+
+    pub fn do_send_transaction_params(&self) -> &DoSendTransactionParams {
+        &self.prepared.do_send_transaction_params
+    }
+
+    /// The user operation with the combined owner signatures
+    pub async fn user_operation(&self) -> eyre::Result<UserOperationV07> {
+        encode_send_transactions(
+            self.signatures()?,
+            self.prepared.do_send_transaction_params.clone(),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        alloy::{
+            primitives::aliases::U48,
+            signers::{SignerSync, local::LocalSigner},
+        },
+    };
+
+    fn session(
+        owners: &[LocalSigner<alloy::signers::k256::ecdsa::SigningKey>],
+        threshold: u8,
+    ) -> SafeSigningSession {
+        SafeSigningSession::new(
+            Owners {
+                owners: owners.iter().map(|owner| owner.address()).collect(),
+                threshold,
+            },
+            PreparedSendTransaction {
+                safe_op: SafeOp {
+                    safe: Address::ZERO,
+                    nonce: Default::default(),
+                    initCode: Default::default(),
+                    callData: Default::default(),
+                    verificationGasLimit: 0,
+                    callGasLimit: 0,
+                    preVerificationGas: Default::default(),
+                    maxPriorityFeePerGas: 0,
+                    maxFeePerGas: 0,
+                    paymasterAndData: Default::default(),
+                    validAfter: U48::ZERO,
+                    validUntil: U48::ZERO,
+                    entryPoint: Address::ZERO,
+                },
+                domain: Eip712Domain::default(),
+                hash: B256::repeat_byte(0x42),
+                do_send_transaction_params: DoSendTransactionParams {
+                    user_op: UserOperationV07::mock(),
+                    valid_after: U48::ZERO,
+                    valid_until: U48::ZERO,
+                },
+            },
+        )
+    }
+
+    fn sign(
+        owner: &LocalSigner<alloy::signers::k256::ecdsa::SigningKey>,
+        hash: B256,
+    ) -> OwnerSignature {
+        OwnerSignature {
+            owner: owner.address(),
+            signature: owner.sign_hash_sync(&hash).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_collects_until_threshold() {
+        let owners = (0..3).map(|_| LocalSigner::random()).collect::<Vec<_>>();
+        let mut session = session(&owners, 2);
+        let hash = session.hash();
+
+        assert_eq!(
+            session.signatures().err(),
+            Some(MultisigError::BelowThreshold { collected: 0, threshold: 2 })
+        );
+        assert!(!session.add_signature(sign(&owners[2], hash)).unwrap());
+        assert_eq!(
+            session.add_signature(sign(&owners[2], hash)).unwrap_err(),
+            MultisigError::AlreadySigned(owners[2].address())
+        );
+        assert!(session.add_signature(sign(&owners[0], hash)).unwrap());
+        assert_eq!(session.pending_signers(), vec![owners[1].address()]);
+
+        let signatures = session.signatures().unwrap();
+        assert_eq!(signatures.len(), 2);
+        assert!(signatures[0].owner < signatures[1].owner);
+
+        // validAfter, validUntil and the two 65-byte signatures
+        let user_op = session.user_operation().await.unwrap();
+        assert_eq!(user_op.signature.len(), 6 + 6 + 2 * 65);
+    }
+
+    #[test]
+    fn test_rejects_invalid_signatures() {
+        let owners = (0..2).map(|_| LocalSigner::random()).collect::<Vec<_>>();
+        let outsider = LocalSigner::random();
+        let mut session = session(&owners, 2);
+        let hash = session.hash();
+
+        assert_eq!(
+            session.add_signature(sign(&outsider, hash)).unwrap_err(),
+            MultisigError::NotAnOwner(outsider.address())
+        );
+        assert_eq!(
+            session
+                .add_signature(OwnerSignature {
+                    owner: owners[0].address(),
+                    ..sign(&owners[1], hash)
+                })
+                .unwrap_err(),
+            MultisigError::WrongSigner {
+                owner: owners[0].address(),
+                recovered: owners[1].address(),
+            }
+        );
+        assert!(
+            session
+                .add_signature(OwnerSignature {
+                    owner: owners[0].address(),
+                    ..sign(&owners[0], B256::ZERO)
+                })
+                .is_err()
+        );
+        assert!(session.signers().is_empty());
+    }
+}
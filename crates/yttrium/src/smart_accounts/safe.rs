@@ -5,8 +5,8 @@ use {
             Call,
             send::safe_test::{
                 DoSendTransactionParams, OwnerSignature,
-                PreparedSendTransaction, encode_send_transactions,
-                prepare_send_transactions_inner,
+                PreparedSendTransaction, combine_owner_signatures,
+                encode_send_transactions, prepare_send_transactions_inner,
             },
         },
         entry_point::{
@@ -424,11 +424,11 @@ pub async fn sign(
     provider: &impl Provider,
     paymaster_client: PaymasterClient,
 ) -> eyre::Result<SignOutputEnum> {
-    if signatures.len() > 1 {
-        unimplemented!("multi-signature is not yet supported");
+    if signatures.is_empty() {
+        return Err(eyre::eyre!("At least one signature is required"));
     }
 
-    let signature = Bytes::from(signatures[0].signature.as_bytes());
+    let signature = combine_owner_signatures(signatures);
 
     // Null validator address for regular Safe signature
     let signature = (Address::ZERO, signature).abi_encode_packed().into();
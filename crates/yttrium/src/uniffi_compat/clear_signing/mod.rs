@@ -1,6 +1,20 @@
-use crate::clear_signing::{
-    DisplayModel, Eip712Error, EngineError, TypedData,
-    format as format_without_value, format_typed_data, format_with_value,
+use {
+    crate::{
+        call::Call,
+        clear_signing::{
+            AddressLabelProvider, BatchPreview, DisplayModel, Eip712Error,
+            EngineError, Locale, RegistryBundleInfo, RegistryError,
+            RegistryTrust, TokenMetadataProvider, TransactionError, TypedData,
+            configure_registry, format as format_without_value,
+            format_authorization, format_batch, format_transaction,
+            format_typed_data, format_with_sender, format_with_value,
+            load_registry_bundle, load_registry_bundle_file,
+            registry_bundle_info, reset_registry, set_address_label_provider,
+            set_token_metadata_provider,
+        },
+    },
+    alloy::rpc::types::Authorization,
+    std::sync::Arc,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, uniffi::Enum)]
@@ -52,13 +66,15 @@ impl From<Eip712Error> for TypedEngineErrorFfi {
     }
 }
 
+/// `locale` is a BCP 47 tag such as `de-DE`; `None` formats in English.
 #[uniffi::export]
 pub fn clear_signing_format(
     chain_id: u64,
     to: String,
     calldata_hex: String,
+    locale: Option<String>,
 ) -> Result<DisplayModel, EngineErrorFfi> {
-    clear_signing_format_with_value(chain_id, to, None, calldata_hex)
+    clear_signing_format_with_value(chain_id, to, None, calldata_hex, locale)
 }
 
 #[uniffi::export]
@@ -67,7 +83,9 @@ pub fn clear_signing_format_with_value(
     to: String,
     value_hex: Option<String>,
     calldata_hex: String,
+    locale: Option<String>,
 ) -> Result<DisplayModel, EngineErrorFfi> {
+    let locale = parse_locale(locale);
     let calldata = decode_hex(&calldata_hex, "calldata")?;
     let value_bytes = match value_hex {
         Some(value_hex) => Some(decode_hex(&value_hex, "value")?),
@@ -75,16 +93,79 @@ pub fn clear_signing_format_with_value(
     };
 
     let model = match value_bytes {
-        Some(bytes) => {
-            format_with_value(chain_id, &to, Some(bytes.as_slice()), &calldata)
-        }
-        None => format_without_value(chain_id, &to, &calldata),
+        Some(bytes) => format_with_value(
+            chain_id,
+            &to,
+            Some(bytes.as_slice()),
+            &calldata,
+            &locale,
+        ),
+        None => format_without_value(chain_id, &to, &calldata, &locale),
     }
     .map_err(EngineErrorFfi::from)?;
 
     Ok(model)
 }
 
+/// Like `clear_signing_format_with_value`, for a call sent from `from`, which
+/// enables sender-dependent risk rules.
+#[uniffi::export]
+pub fn clear_signing_format_with_sender(
+    chain_id: u64,
+    from: String,
+    to: String,
+    value_hex: Option<String>,
+    calldata_hex: String,
+    locale: Option<String>,
+) -> Result<DisplayModel, EngineErrorFfi> {
+    let calldata = decode_hex(&calldata_hex, "calldata")?;
+    let value_bytes = match value_hex {
+        Some(value_hex) => Some(decode_hex(&value_hex, "value")?),
+        None => None,
+    };
+
+    format_with_sender(
+        chain_id,
+        &from,
+        &to,
+        value_bytes.as_deref(),
+        &calldata,
+        &parse_locale(locale),
+    )
+    .map_err(EngineErrorFfi::from)
+}
+
+/// Formats a `wallet_sendCalls` batch sent from `from`, with the EIP-7702
+/// `authorization` that upgrades `from`, if the request carries one.
+#[uniffi::export]
+pub fn clear_signing_format_batch(
+    chain_id: u64,
+    from: String,
+    calls: Vec<Call>,
+    authorization: Option<Authorization>,
+    locale: Option<String>,
+) -> BatchPreview {
+    format_batch(
+        chain_id,
+        &from,
+        &calls,
+        authorization.as_ref(),
+        &parse_locale(locale),
+    )
+}
+
+#[uniffi::export]
+pub fn clear_signing_format_authorization(
+    authorization: Authorization,
+    locale: Option<String>,
+) -> DisplayModel {
+    format_authorization(&authorization, &parse_locale(locale))
+}
+
+fn parse_locale(locale: Option<String>) -> Locale {
+    locale.as_deref().map(Locale::parse).unwrap_or_default()
+}
+
 fn decode_hex(input: &str, context: &str) -> Result<Vec<u8>, EngineErrorFfi> {
     let trimmed = input.trim();
     let without_prefix = trimmed.strip_prefix("0x").unwrap_or(trimmed);
@@ -96,8 +177,162 @@ fn decode_hex(input: &str, context: &str) -> Result<Vec<u8>, EngineErrorFfi> {
 #[uniffi::export]
 pub fn clear_signing_format_typed(
     typed_data_json: String,
+    locale: Option<String>,
 ) -> Result<DisplayModel, TypedEngineErrorFfi> {
     let typed: TypedData = serde_json::from_str(&typed_data_json)
         .map_err(|err| TypedEngineErrorFfi::TypedData(err.to_string()))?;
-    format_typed_data(&typed).map_err(Into::into)
+    format_typed_data(&typed, &parse_locale(locale)).map_err(Into::into)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, uniffi::Enum)]
+pub enum TransactionErrorFfi {
+    #[error("unsupported namespace: {0}")]
+    UnsupportedNamespace(String),
+    #[error("transaction decode error: {0}")]
+    Decode(String),
+}
+
+impl From<TransactionError> for TransactionErrorFfi {
+    fn from(value: TransactionError) -> Self {
+        match value {
+            TransactionError::UnsupportedNamespace(err) => {
+                Self::UnsupportedNamespace(err)
+            }
+            TransactionError::Decode(err) => Self::Decode(err),
+        }
+    }
+}
+
+/// Formats a Solana, Sui or TRON transaction. `chain_id` is a CAIP-2 chain
+/// such as `solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp`.
+#[uniffi::export]
+pub fn clear_signing_format_transaction(
+    chain_id: String,
+    transaction: Vec<u8>,
+    locale: Option<String>,
+) -> Result<DisplayModel, TransactionErrorFfi> {
+    format_transaction(&chain_id, &transaction, &parse_locale(locale))
+        .map_err(Into::into)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, uniffi::Enum)]
+pub enum RegistryErrorFfi {
+    #[error("no registry public key configured")]
+    NotConfigured,
+    #[error("invalid registry public key: {0}")]
+    InvalidPublicKey(String),
+    #[error("invalid bundle signature: {0}")]
+    InvalidSignature(String),
+    #[error("bundle hash {0} is not pinned")]
+    HashNotPinned(String),
+    #[error("bundle version {version} is older than loaded version {loaded}")]
+    Downgrade { version: u64, loaded: u64 },
+    #[error("bundle parse error: {0}")]
+    Parse(String),
+    #[error("io error: {0}")]
+    Io(String),
+}
+
+impl From<RegistryError> for RegistryErrorFfi {
+    fn from(value: RegistryError) -> Self {
+        match value {
+            RegistryError::NotConfigured => Self::NotConfigured,
+            RegistryError::InvalidPublicKey(err) => Self::InvalidPublicKey(err),
+            RegistryError::InvalidSignature(err) => Self::InvalidSignature(err),
+            RegistryError::HashNotPinned(err) => Self::HashNotPinned(err),
+            RegistryError::Downgrade { version, loaded } => {
+                Self::Downgrade { version, loaded }
+            }
+            RegistryError::Parse(err) => Self::Parse(err),
+            RegistryError::Io(err) => Self::Io(err),
+        }
+    }
+}
+
+/// Sets the Ed25519 public key (and optional SHA-256 pins) runtime registry
+/// bundles must match. Drops any loaded bundle.
+#[uniffi::export]
+pub fn clear_signing_configure_registry(
+    public_key_hex: String,
+    pinned_sha256_hex: Vec<String>,
+) -> Result<(), RegistryErrorFfi> {
+    let public_key = decode_hex_32(&public_key_hex)
+        .map_err(RegistryErrorFfi::InvalidPublicKey)?;
+    let pinned_sha256 = pinned_sha256_hex
+        .iter()
+        .map(|hash| decode_hex_32(hash))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(RegistryErrorFfi::Parse)?;
+    configure_registry(RegistryTrust { public_key, pinned_sha256 });
+    Ok(())
+}
+
+#[uniffi::export]
+pub fn clear_signing_load_registry(
+    bundle: Vec<u8>,
+    signature_hex: String,
+) -> Result<RegistryBundleInfo, RegistryErrorFfi> {
+    let signature = decode_signature(&signature_hex)?;
+    load_registry_bundle(&bundle, &signature).map_err(Into::into)
+}
+
+#[uniffi::export]
+pub fn clear_signing_load_registry_file(
+    path: String,
+    signature_hex: String,
+) -> Result<RegistryBundleInfo, RegistryErrorFfi> {
+    let signature = decode_signature(&signature_hex)?;
+    load_registry_bundle_file(path, &signature).map_err(Into::into)
+}
+
+/// Falls back to the descriptors bundled with the SDK.
+#[uniffi::export]
+pub fn clear_signing_reset_registry() {
+    reset_registry();
+}
+
+#[uniffi::export]
+pub fn clear_signing_registry_info() -> Option<RegistryBundleInfo> {
+    registry_bundle_info()
+}
+
+fn decode_signature(input: &str) -> Result<Vec<u8>, RegistryErrorFfi> {
+    let trimmed = input.trim();
+    let without_prefix = trimmed.strip_prefix("0x").unwrap_or(trimmed);
+    hex::decode(without_prefix)
+        .map_err(|err| RegistryErrorFfi::InvalidSignature(err.to_string()))
+}
+
+fn decode_hex_32(input: &str) -> Result<[u8; 32], String> {
+    let trimmed = input.trim();
+    let without_prefix = trimmed.strip_prefix("0x").unwrap_or(trimmed);
+    let bytes = hex::decode(without_prefix).map_err(|err| err.to_string())?;
+    <[u8; 32]>::try_from(bytes)
+        .map_err(|bytes| format!("expected 32 bytes, got {}", bytes.len()))
+}
+
+/// Token metadata from `provider` is used before the bundled token list.
+#[uniffi::export]
+pub fn clear_signing_set_token_metadata_provider(
+    provider: Arc<dyn TokenMetadataProvider>,
+) {
+    set_token_metadata_provider(Some(provider));
+}
+
+#[uniffi::export]
+pub fn clear_signing_clear_token_metadata_provider() {
+    set_token_metadata_provider(None);
+}
+
+/// Labels from `provider` are used before descriptor and bundled labels.
+#[uniffi::export]
+pub fn clear_signing_set_address_label_provider(
+    provider: Arc<dyn AddressLabelProvider>,
+) {
+    set_address_label_provider(Some(provider));
+}
+
+#[uniffi::export]
+pub fn clear_signing_clear_address_label_provider() {
+    set_address_label_provider(None);
 }
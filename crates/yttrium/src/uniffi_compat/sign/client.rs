@@ -1,22 +1,34 @@
 use {
     crate::{
         sign::{
-            IncomingSessionMessage,
+            IncomingSessionMessage, OriginDecision, OriginPolicy,
+            VerifyKeyRotation, VerifyMode,
+            cacao::{self, CacaoError},
             client::{Client, generate_client_id_key},
             client_errors::{
-                ApproveError, ConnectError, DisconnectError, EmitError,
-                ExtendError, PairError, RejectError, RequestError,
-                RespondError, UpdateError,
+                ApproveError, ApproveSessionAuthenticateError,
+                AuthenticateError, ConnectError, DisconnectError, EmitError,
+                ExtendError, PairError, PingError, RejectError,
+                RejectSessionAuthenticateError, RequestError, RespondError,
+                UpdateError,
             },
-            client_types::{ConnectParams, SessionProposal},
+            client_types::{
+                AuthenticateParams, ConnectParams, SessionProposal,
+            },
+            link_mode::LinkModeError,
+            namespaces::{self, NamespaceError, SupportedNamespace},
             protocol_types::{
-                Metadata, ProtocolRpcId, SessionRequest, SettleNamespace,
+                AuthPayload, Cacao, CacaoPayload, Metadata, ProtocolRpcId,
+                SessionRequest, SettleNamespace,
             },
+            relay_pool::{RelayConnectionState, RelayEndpoint, RelayPoolError},
+            storage::StorageError,
         },
         uniffi_compat::sign::{
             ffi_types::{
-                ConnectParamsFfi, ConnectResultFfi, SessionFfi,
-                SessionProposalFfi, SessionRequestFfi,
+                ConnectParamsFfi, ConnectResultFfi, PairResultFfi,
+                PendingSessionRequestFfi, SessionAuthenticateRequestFfi,
+                SessionFfi, SessionProposalFfi, SessionRequestFfi,
                 SessionRequestJsonRpcFfi, SessionRequestJsonRpcResponseFfi,
             },
             storage::{StorageFfi, StorageFfiProxy},
@@ -57,6 +69,26 @@ pub trait SignListener: Send + Sync {
         topic: Topic,
         response: SessionRequestJsonRpcResponseFfi,
     );
+    fn on_session_authenticate(
+        &self,
+        topic: Topic,
+        request: SessionAuthenticateRequestFfi,
+    );
+    fn on_session_authenticate_response(
+        &self,
+        id: ProtocolRpcId,
+        topic: Topic,
+        cacaos: Vec<Cacao>,
+    );
+    fn on_session_expired(&self, topic: Topic);
+    fn on_session_request_blocked(
+        &self,
+        topic: Topic,
+        session_request: SessionRequestJsonRpcFfi,
+        decision: OriginDecision,
+    );
+    fn on_relay_connection_state(&self, state: RelayConnectionState);
+    fn on_verify_key_rotated(&self, rotation: VerifyKeyRotation);
 }
 
 #[derive(uniffi::Object)]
@@ -103,12 +135,68 @@ impl SignClient {
         generate_client_id_key().to_vec()
     }
 
+    pub async fn set_verify_mode(&self, mode: VerifyMode) {
+        let client = self.client.lock().await;
+        client.set_verify_mode(mode);
+    }
+
+    pub async fn set_origin_policy(&self, origin_policy: OriginPolicy) {
+        let client = self.client.lock().await;
+        client.set_origin_policy(origin_policy);
+    }
+
+    pub async fn set_relay_endpoints(
+        &self,
+        endpoints: Vec<RelayEndpoint>,
+    ) -> Result<(), RelayPoolError> {
+        let mut client = self.client.lock().await;
+        client.set_relay_endpoints(endpoints)
+    }
+
     pub async fn register_sign_listener(
         &self,
         listener: Arc<dyn SignListener>,
     ) {
+        let (mut connection_state_rx, mut verify_key_rotation_rx) = {
+            let client = self.client.lock().await;
+            (client.connection_state(), client.verify_key_rotations())
+        };
         let mut rx_guard = self.session_request_rx.lock().unwrap();
         if let Some(mut rx) = rx_guard.take() {
+            let connection_listener = listener.clone();
+            let verify_listener = listener.clone();
+            tokio::spawn(async move {
+                loop {
+                    match verify_key_rotation_rx.recv().await {
+                        Ok(rotation) => {
+                            verify_listener.on_verify_key_rotated(rotation);
+                        }
+                        Err(
+                            tokio::sync::broadcast::error::RecvError::Lagged(
+                                skipped,
+                            ),
+                        ) => {
+                            tracing::warn!(
+                                "Skipped {skipped} verify key rotations"
+                            );
+                        }
+                        Err(
+                            tokio::sync::broadcast::error::RecvError::Closed,
+                        ) => {
+                            break;
+                        }
+                    }
+                }
+            });
+            tokio::spawn(async move {
+                loop {
+                    let state = connection_state_rx.borrow_and_update().clone();
+                    connection_listener.on_relay_connection_state(state);
+                    if connection_state_rx.changed().await.is_err() {
+                        break;
+                    }
+                }
+            });
             tokio::spawn(async move {
                 tracing::info!(
                     "Starting session request listener with debug logging"
@@ -170,6 +258,35 @@ impl SignClient {
                                 response.into(),
                             );
                         }
+                        IncomingSessionMessage::SessionAuthenticate(
+                            request,
+                            _,
+                        ) => {
+                            listener
+                                .on_session_authenticate(topic, request.into());
+                        }
+                        IncomingSessionMessage::SessionAuthenticateResponse(
+                            id,
+                            topic,
+                            cacaos,
+                        ) => {
+                            listener.on_session_authenticate_response(
+                                id, topic, cacaos,
+                            );
+                        }
+                        IncomingSessionMessage::SessionExpired(topic) => {
+                            listener.on_session_expired(topic);
+                        }
+                        IncomingSessionMessage::SessionRequestBlocked(
+                            request,
+                            decision,
+                        ) => {
+                            listener.on_session_request_blocked(
+                                topic,
+                                request.into(),
+                                decision,
+                            );
+                        }
                     }
                 }
                 tracing::info!("Session request listener stopped");
@@ -187,15 +304,28 @@ impl SignClient {
         client.online();
     }
 
-    pub async fn pair(
+    pub async fn prune_expired(&self) -> Result<(), StorageError> {
+        let client = self.client.lock().await;
+        client.prune_expired()
+    }
+
+    pub async fn get_pending_session_requests(
         &self,
-        uri: String,
-    ) -> Result<SessionProposalFfi, PairError> {
-        let proposal = {
+    ) -> Result<Vec<PendingSessionRequestFfi>, StorageError> {
+        let client = self.client.lock().await;
+        Ok(client
+            .get_pending_session_requests()?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    pub async fn pair(&self, uri: String) -> Result<PairResultFfi, PairError> {
+        let result = {
             let mut client = self.client.lock().await;
             client.pair(&uri).await?
         };
-        Ok(proposal.0.into())
+        Ok(result.into())
     }
 
     pub async fn connect(
@@ -230,6 +360,19 @@ impl SignClient {
         Ok(session.into())
     }
 
+    /// Build the namespaces to pass to `approve()` from what the wallet supports
+    pub fn build_approved_namespaces(
+        &self,
+        proposal: SessionProposalFfi,
+        supported_namespaces: HashMap<String, SupportedNamespace>,
+    ) -> Result<HashMap<String, SettleNamespace>, NamespaceError> {
+        namespaces::build_approved_namespaces(
+            &proposal.required_namespaces,
+            &proposal.optional_namespaces,
+            &supported_namespaces,
+        )
+    }
+
     pub async fn reject(
         &self,
         proposal: SessionProposalFfi,
@@ -243,20 +386,30 @@ impl SignClient {
         Ok(())
     }
 
+    /// Returns the Link Mode URL to open when the response was routed over Link Mode instead of the relay
     pub async fn respond(
         &self,
         topic: String,
         response: SessionRequestJsonRpcResponseFfi,
-    ) -> Result<String, RespondError> {
+    ) -> Result<Option<String>, RespondError> {
         use crate::sign::protocol_types::SessionRequestJsonRpcResponse;
 
         tracing::debug!("responding session request: {:?}", response);
 
         let mut client = self.client.lock().await;
         let response_internal: SessionRequestJsonRpcResponse = response.into();
-        let topic_topic: Topic = topic.clone().into();
-        client.respond(topic_topic, response_internal).await?;
-        Ok(topic)
+        let topic_topic: Topic = topic.into();
+        let link_mode_url =
+            client.respond(topic_topic, response_internal).await?;
+        Ok(link_mode_url)
+    }
+
+    pub async fn dispatch_link_mode_url(
+        &self,
+        url: String,
+    ) -> Result<(), LinkModeError> {
+        let mut client = self.client.lock().await;
+        client.dispatch_link_mode_url(&url).await
     }
 
     pub async fn emit(
@@ -298,6 +451,14 @@ impl SignClient {
         client.extend(topic.into()).await
     }
 
+    pub async fn ping(
+        &self,
+        topic: String,
+    ) -> Result<std::time::Duration, PingError> {
+        let mut client = self.client.lock().await;
+        client.ping(topic.into()).await
+    }
+
     pub async fn request(
         &self,
         topic: String,
@@ -307,4 +468,63 @@ impl SignClient {
         let session_request: SessionRequest = session_request.into();
         client.request(topic.into(), session_request).await
     }
+
+    pub async fn authenticate(
+        &self,
+        params: AuthenticateParams,
+        self_metadata: Metadata,
+    ) -> Result<ConnectResultFfi, AuthenticateError> {
+        tracing::debug!("authenticate params: {:?}", params);
+        let result = {
+            let mut client = self.client.lock().await;
+            client.authenticate(params, self_metadata).await?
+        };
+        Ok(result.into())
+    }
+
+    pub async fn approve_session_authenticate(
+        &self,
+        request: SessionAuthenticateRequestFfi,
+        cacaos: Vec<Cacao>,
+        self_metadata: Metadata,
+    ) -> Result<SessionFfi, ApproveSessionAuthenticateError> {
+        let session = {
+            let mut client = self.client.lock().await;
+            client
+                .approve_session_authenticate(
+                    request.into(),
+                    cacaos,
+                    self_metadata,
+                )
+                .await?
+        };
+        Ok(session.into())
+    }
+
+    pub async fn reject_session_authenticate(
+        &self,
+        request: SessionAuthenticateRequestFfi,
+        reason: crate::sign::client_types::RejectionReason,
+    ) -> Result<(), RejectSessionAuthenticateError> {
+        tracing::debug!("reject session authenticate: {:?}", reason);
+        let mut client = self.client.lock().await;
+        client.reject_session_authenticate(request.into(), reason).await
+    }
+
+    /// Build the payload to sign for `account` (CAIP-10) from a `wc_sessionAuthenticate` request
+    pub fn build_auth_payload(
+        &self,
+        auth_payload: AuthPayload,
+        account: String,
+    ) -> CacaoPayload {
+        cacao::build_cacao_payload(&auth_payload, &account)
+    }
+
+    /// Format the SIWE message the wallet signs for the payload
+    pub fn format_auth_message(
+        &self,
+        payload: CacaoPayload,
+    ) -> Result<String, CacaoError> {
+        cacao::format_message(&payload)
+    }
 }
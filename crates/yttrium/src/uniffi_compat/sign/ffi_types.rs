@@ -2,8 +2,8 @@ use {
     crate::sign::{
         client_types::TransportType,
         protocol_types::{
-            JsonRpcVersion, Metadata, ProposalNamespaces, ProtocolRpcId,
-            SettleNamespace,
+            AuthPayload, JsonRpcVersion, Metadata, Participant,
+            ProposalNamespaces, ProtocolRpcId, SettleNamespace,
         },
     },
     relay_rpc::domain::Topic,
@@ -33,6 +33,22 @@ pub struct SessionProposalFfi {
     pub expiry_timestamp: Option<u64>,
 }
 
+#[derive(uniffi_macros::Record, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAuthenticateRequestFfi {
+    pub id: ProtocolRpcId,
+    pub topic: String,
+    pub requester: Participant,
+    pub auth_payload: AuthPayload,
+    pub expiry_timestamp: u64,
+}
+
+#[derive(uniffi_macros::Enum, Debug)]
+pub enum PairResultFfi {
+    Proposal(SessionProposalFfi),
+    Authenticate(SessionAuthenticateRequestFfi),
+}
+
 #[derive(uniffi_macros::Record, Serialize, Deserialize)]
 pub struct SessionRequestRequestFfi {
     pub method: String,
@@ -54,6 +70,12 @@ pub struct SessionRequestJsonRpcFfi {
     pub params: SessionRequestFfi,
 }
 
+#[derive(uniffi_macros::Record, Serialize, Deserialize)]
+pub struct PendingSessionRequestFfi {
+    pub topic: Topic,
+    pub request: SessionRequestJsonRpcFfi,
+}
+
 #[derive(uniffi_macros::Record, Debug, Serialize, Deserialize)]
 pub struct SessionRequestJsonRpcResultResponseFfi {
     pub id: ProtocolRpcId,
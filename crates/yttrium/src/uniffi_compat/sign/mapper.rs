@@ -2,7 +2,9 @@ use {
     crate::{
         sign::{
             client_types::{
-                ConnectParams, ConnectResult, Session, SessionProposal,
+                ConnectParams, ConnectResult, PairResult,
+                PendingSessionRequest, Session, SessionAuthenticateRequest,
+                SessionProposal,
             },
             protocol_types::{
                 SessionRequest, SessionRequestJsonRpc,
@@ -10,8 +12,9 @@ use {
             },
         },
         uniffi_compat::sign::ffi_types::{
-            ConnectParamsFfi, ConnectResultFfi, ErrorDataFfi, SessionFfi,
-            SessionProposalFfi, SessionRequestFfi,
+            ConnectParamsFfi, ConnectResultFfi, ErrorDataFfi, PairResultFfi,
+            PendingSessionRequestFfi, SessionAuthenticateRequestFfi,
+            SessionFfi, SessionProposalFfi, SessionRequestFfi,
             SessionRequestJsonRpcErrorResponseFfi, SessionRequestJsonRpcFfi,
             SessionRequestJsonRpcResponseFfi,
             SessionRequestJsonRpcResultResponseFfi, SessionRequestRequestFfi,
@@ -41,6 +44,43 @@ impl From<SessionProposalFfi> for SessionProposal {
     }
 }
 
+impl From<SessionAuthenticateRequest> for SessionAuthenticateRequestFfi {
+    fn from(request: SessionAuthenticateRequest) -> Self {
+        Self {
+            id: request.id,
+            topic: request.pairing_topic.to_string(),
+            requester: request.requester,
+            auth_payload: request.auth_payload,
+            expiry_timestamp: request.expiry_timestamp,
+        }
+    }
+}
+
+impl From<PairResult> for PairResultFfi {
+    fn from(result: PairResult) -> Self {
+        match result {
+            PairResult::Proposal(proposal, _) => {
+                Self::Proposal(proposal.into())
+            }
+            PairResult::Authenticate(request, _) => {
+                Self::Authenticate(request.into())
+            }
+        }
+    }
+}
+
+impl From<SessionAuthenticateRequestFfi> for SessionAuthenticateRequest {
+    fn from(request: SessionAuthenticateRequestFfi) -> Self {
+        Self {
+            id: request.id,
+            pairing_topic: request.topic.into(),
+            requester: request.requester,
+            auth_payload: request.auth_payload,
+            expiry_timestamp: request.expiry_timestamp,
+        }
+    }
+}
+
 impl From<SessionRequestJsonRpc> for SessionRequestJsonRpcFfi {
     fn from(request: SessionRequestJsonRpc) -> Self {
         Self {
@@ -61,6 +101,12 @@ impl From<SessionRequestJsonRpc> for SessionRequestJsonRpcFfi {
     }
 }
 
+impl From<PendingSessionRequest> for PendingSessionRequestFfi {
+    fn from(pending: PendingSessionRequest) -> Self {
+        Self { topic: pending.topic, request: pending.request.into() }
+    }
+}
+
 impl From<SessionProposal> for SessionProposalFfi {
     fn from(proposal: SessionProposal) -> Self {
         // Ensure both id and topic are properly converted to valid UTF-8 strings
@@ -23,6 +23,22 @@ pub fn session_ffi_from_json(json: &str) -> super::ffi_types::SessionFfi {
     serde_json::from_str(json).expect("Failed to deserialize session")
 }
 
+#[uniffi::export]
+pub fn session_authenticate_request_ffi_to_json(
+    object: &super::ffi_types::SessionAuthenticateRequestFfi,
+) -> String {
+    serde_json::to_string(object)
+        .expect("Failed to serialize session authenticate request")
+}
+
+#[uniffi::export]
+pub fn session_authenticate_request_ffi_from_json(
+    json: &str,
+) -> super::ffi_types::SessionAuthenticateRequestFfi {
+    serde_json::from_str(json)
+        .expect("Failed to deserialize session authenticate request")
+}
+
 #[uniffi::export]
 pub fn session_request_request_ffi_to_json(
     object: &super::ffi_types::SessionRequestRequestFfi,
@@ -178,3 +194,13 @@ pub fn redirect_to_json(
 pub fn redirect_from_json(json: &str) -> crate::sign::protocol_types::Redirect {
     serde_json::from_str(json).expect("Failed to deserialize redirect")
 }
+
+#[uniffi::export]
+pub fn cacao_to_json(object: &crate::sign::protocol_types::Cacao) -> String {
+    serde_json::to_string(object).expect("Failed to serialize cacao")
+}
+
+#[uniffi::export]
+pub fn cacao_from_json(json: &str) -> crate::sign::protocol_types::Cacao {
+    serde_json::from_str(json).expect("Failed to deserialize cacao")
+}
@@ -3,7 +3,9 @@ use {
         sign::{
             client_types::{Session, TransportType},
             protocol_types::ProtocolRpcId,
-            storage::{Storage, StorageError, StoragePairing},
+            storage::{
+                Storage, StorageError, StorageJsonRpcRequest, StoragePairing,
+            },
         },
         uniffi_compat::sign::ffi_types::SessionFfi,
     },
@@ -33,17 +35,36 @@ pub trait StorageFfi: Send + Sync {
         rpc_id: ProtocolRpcId,
         sym_key: Vec<u8>,
         self_key: Vec<u8>,
+        expiry: u64,
     ) -> Result<(), StorageError>;
     fn get_pairing(
         &self,
         topic: Topic,
         rpc_id: ProtocolRpcId,
     ) -> Result<Option<PairingFfi>, StorageError>;
+    fn delete_expired_pairings(
+        &self,
+        now: u64,
+    ) -> Result<Vec<Topic>, StorageError>;
     fn save_partial_session(
         &self,
         topic: Topic,
         sym_key: Vec<u8>,
     ) -> Result<(), StorageError>;
+    fn save_authenticate_request(
+        &self,
+        topic: Topic,
+        self_key: Vec<u8>,
+        expiry: u64,
+    ) -> Result<(), StorageError>;
+    fn get_authenticate_request_key(
+        &self,
+        topic: Topic,
+    ) -> Result<Option<Vec<u8>>, StorageError>;
+    fn delete_expired_authenticate_requests(
+        &self,
+        now: u64,
+    ) -> Result<Vec<Topic>, StorageError>;
     fn get_verify_public_key(&self) -> Result<Option<String>, StorageError>;
     fn set_verify_public_key(&self, jwk: String) -> Result<(), StorageError>;
 
@@ -63,6 +84,11 @@ pub trait StorageFfi: Send + Sync {
         response: String,
     ) -> Result<(), StorageError>;
 
+    fn get_pending_json_rpc_requests(
+        &self,
+        method: String,
+    ) -> Result<Vec<StorageJsonRpcRequest>, StorageError>;
+
     fn delete_json_rpc_history_by_topic(
         &self,
         topic: Topic,
@@ -116,8 +142,15 @@ impl Storage for StorageFfiProxy {
         rpc_id: ProtocolRpcId,
         sym_key: [u8; 32],
         self_key: [u8; 32],
+        expiry: u64,
     ) -> Result<(), StorageError> {
-        self.0.save_pairing(topic, rpc_id, sym_key.to_vec(), self_key.to_vec())
+        self.0.save_pairing(
+            topic,
+            rpc_id,
+            sym_key.to_vec(),
+            self_key.to_vec(),
+            expiry,
+        )
     }
 
     fn get_pairing(
@@ -131,6 +164,13 @@ impl Storage for StorageFfiProxy {
         }))
     }
 
+    fn delete_expired_pairings(
+        &self,
+        now: u64,
+    ) -> Result<Vec<Topic>, StorageError> {
+        self.0.delete_expired_pairings(now)
+    }
+
     fn save_partial_session(
         &self,
         topic: Topic,
@@ -139,6 +179,32 @@ impl Storage for StorageFfiProxy {
         self.0.save_partial_session(topic, sym_key.to_vec())
     }
 
+    fn save_authenticate_request(
+        &self,
+        topic: Topic,
+        self_key: [u8; 32],
+        expiry: u64,
+    ) -> Result<(), StorageError> {
+        self.0.save_authenticate_request(topic, self_key.to_vec(), expiry)
+    }
+
+    fn get_authenticate_request_key(
+        &self,
+        topic: Topic,
+    ) -> Result<Option<[u8; 32]>, StorageError> {
+        Ok(self
+            .0
+            .get_authenticate_request_key(topic)?
+            .map(|s| s.try_into().unwrap()))
+    }
+
+    fn delete_expired_authenticate_requests(
+        &self,
+        now: u64,
+    ) -> Result<Vec<Topic>, StorageError> {
+        self.0.delete_expired_authenticate_requests(now)
+    }
+
     fn get_verify_public_key(&self) -> Result<Option<Jwk>, StorageError> {
         let jwk = self.0.get_verify_public_key()?;
         if let Some(jwk) = jwk {
@@ -180,6 +246,13 @@ impl Storage for StorageFfiProxy {
         self.0.update_json_rpc_history_response(request_id, response)
     }
 
+    fn get_pending_json_rpc_requests(
+        &self,
+        method: String,
+    ) -> Result<Vec<StorageJsonRpcRequest>, StorageError> {
+        self.0.get_pending_json_rpc_requests(method)
+    }
+
     fn delete_json_rpc_history_by_topic(
         &self,
         topic: Topic,
@@ -1,12 +1,19 @@
 use {
     crate::{
+        entry_point::INITCODE_EIP7702_MARKER,
         smart_accounts::account_address::AccountAddress,
         user_operation::{
-            hash::get_user_operation_hash_v07,
+            hash::{
+                get_user_operation_hash_v07, get_user_operation_hash_v08,
+                pack_v08::PackUserOperationError,
+            },
             user_operation_hash::UserOperationHash,
         },
     },
-    alloy::primitives::{Address, Bytes, U256, address},
+    alloy::{
+        eips::eip7702::SignedAuthorization,
+        primitives::{Address, Bytes, U256, address},
+    },
     serde::{Deserialize, Serialize},
 };
 
@@ -138,3 +145,127 @@ impl UserOperationV07 {
         }
     }
 }
+
+/// Signed EIP-7702 authorization sent alongside an EntryPoint v0.8 user
+/// operation, serialized as the bundler's `eip7702Auth` field.
+#[derive(
+    Default,
+    Clone,
+    Debug,
+    Ord,
+    PartialOrd,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct Eip7702Auth {
+    pub chain_id: U256,
+    /// The delegate contract the sender's code points to
+    pub address: Address,
+    pub nonce: U256,
+    pub y_parity: U256,
+    pub r: U256,
+    pub s: U256,
+}
+
+impl From<SignedAuthorization> for Eip7702Auth {
+    fn from(authorization: SignedAuthorization) -> Self {
+        Self {
+            chain_id: *authorization.chain_id(),
+            address: *authorization.address(),
+            nonce: U256::from(authorization.nonce()),
+            y_parity: U256::from(authorization.y_parity()),
+            r: authorization.r(),
+            s: authorization.s(),
+        }
+    }
+}
+
+/// User operation for EntryPoint v0.8. Same fields as `UserOperationV07`,
+/// plus the EIP-7702 authorization of senders that are delegated EOAs. Such
+/// operations set `factory` to `INITCODE_EIP7702_MARKER`.
+#[derive(
+    Default,
+    Clone,
+    Debug,
+    Ord,
+    PartialOrd,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct UserOperationV08 {
+    #[serde(serialize_with = "as_checksum_addr")]
+    pub sender: AccountAddress,
+    pub nonce: U256,
+    pub factory: Option<Address>,
+    pub factory_data: Option<Bytes>,
+    pub call_data: Bytes,
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub paymaster: Option<Address>,
+    pub paymaster_verification_gas_limit: Option<U256>,
+    pub paymaster_post_op_gas_limit: Option<U256>,
+    pub paymaster_data: Option<Bytes>,
+    pub signature: Bytes,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub eip7702_auth: Option<Eip7702Auth>,
+}
+
+impl UserOperationV08 {
+    /// Calculates the EIP-712 hash of the user operation. Fails for EIP-7702
+    /// senders without `eip7702_auth`.
+    pub fn hash(
+        &self,
+        entry_point: &Address,
+        chain_id: u64,
+    ) -> Result<UserOperationHash, PackUserOperationError> {
+        get_user_operation_hash_v08(self, entry_point, chain_id)
+    }
+
+    /// Whether the sender is an EIP-7702 delegated EOA
+    pub fn is_eip7702(&self) -> bool {
+        self.factory == Some(INITCODE_EIP7702_MARKER)
+    }
+
+    pub fn mock() -> Self {
+        Self::from_v07(UserOperationV07::mock(), None)
+    }
+
+    /// Upgrades a v0.7 user operation; the signature must be redone since
+    /// the hash differs.
+    pub fn from_v07(
+        user_operation: UserOperationV07,
+        eip7702_auth: Option<Eip7702Auth>,
+    ) -> Self {
+        Self {
+            sender: user_operation.sender,
+            nonce: user_operation.nonce,
+            factory: user_operation.factory,
+            factory_data: user_operation.factory_data,
+            call_data: user_operation.call_data,
+            call_gas_limit: user_operation.call_gas_limit,
+            verification_gas_limit: user_operation.verification_gas_limit,
+            pre_verification_gas: user_operation.pre_verification_gas,
+            max_fee_per_gas: user_operation.max_fee_per_gas,
+            max_priority_fee_per_gas: user_operation.max_priority_fee_per_gas,
+            paymaster: user_operation.paymaster,
+            paymaster_verification_gas_limit: user_operation
+                .paymaster_verification_gas_limit,
+            paymaster_post_op_gas_limit: user_operation
+                .paymaster_post_op_gas_limit,
+            paymaster_data: user_operation.paymaster_data,
+            signature: user_operation.signature,
+            eip7702_auth,
+        }
+    }
+}
@@ -1,14 +1,16 @@
 use {
     crate::user_operation::{
-        UserOperationV07, user_operation_hash::UserOperationHash,
+        UserOperationV07, UserOperationV08,
+        user_operation_hash::UserOperationHash,
     },
     alloy::{
         primitives::{Address, B256, Bytes, U256, keccak256},
-        sol_types::SolValue,
+        sol_types::{SolStruct, SolValue, eip712_domain},
     },
 };
 
 pub mod pack_v07;
+pub mod pack_v08;
 
 pub fn get_user_operation_hash_v07(
     user_operation: &UserOperationV07,
@@ -32,6 +34,23 @@ pub fn get_user_operation_hash_v07(
     UserOperationHash(hash)
 }
 
+/// EntryPoint v0.8 hashes user operations as EIP-712 typed data, in the
+/// domain `ERC4337` version `1` of the entry point.
+pub fn get_user_operation_hash_v08(
+    user_operation: &UserOperationV08,
+    entry_point: &Address,
+    chain_id: u64,
+) -> Result<UserOperationHash, pack_v08::PackUserOperationError> {
+    let domain = eip712_domain! {
+        name: "ERC4337",
+        version: "1",
+        chain_id: chain_id,
+        verifying_contract: *entry_point,
+    };
+    let packed = pack_v08::pack_user_operation_v08(user_operation)?;
+    Ok(UserOperationHash(packed.eip712_signing_hash(&domain)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,4 +70,61 @@ mod tests {
         );
         assert_eq!(hash.0.to_string(), expected_hash);
     }
+
+    #[test]
+    fn test_get_user_operation_hash_v08() {
+        let user_operation = UserOperationV08::mock();
+        let entry_point = "0x4337084D9E255Ff0702461CF8895CE9E3b5Ff108"
+            .parse::<Address>()
+            .unwrap();
+        let chain_id = 11155111;
+        let hash = get_user_operation_hash_v08(
+            &user_operation,
+            &entry_point,
+            chain_id,
+        )
+        .unwrap();
+
+        let domain_separator = keccak256(
+            (
+                keccak256(
+                    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)",
+                ),
+                keccak256("ERC4337"),
+                keccak256("1"),
+                U256::from(chain_id),
+                entry_point,
+            )
+                .abi_encode(),
+        );
+        let struct_hash = keccak256(
+            [
+                keccak256(
+                    "PackedUserOperation(address sender,uint256 nonce,bytes initCode,bytes callData,bytes32 accountGasLimits,uint256 preVerificationGas,bytes32 gasFees,bytes paymasterAndData)",
+                )
+                .as_slice(),
+                // Same fields as v0.7, which the v0.7 packing already encodes
+                pack_v07::pack_user_operation_v07(&UserOperationV07::mock())
+                    .as_slice(),
+            ]
+            .concat(),
+        );
+        let expected = keccak256(
+            [
+                [0x19, 0x01].as_slice(),
+                domain_separator.as_slice(),
+                struct_hash.as_slice(),
+            ]
+            .concat(),
+        );
+        assert_eq!(hash.0, expected);
+        assert_ne!(
+            hash,
+            get_user_operation_hash_v07(
+                &UserOperationV07::mock(),
+                &entry_point,
+                chain_id
+            )
+        );
+    }
 }
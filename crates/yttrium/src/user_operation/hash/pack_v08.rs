@@ -0,0 +1,169 @@
+use {
+    super::pack_v07::{
+        hashed_paymaster_and_data,
+        max_priority_fee_per_gas_and_max_fee_per_gas,
+        verificaction_gas_limit_and_call_gas_limit,
+    },
+    crate::user_operation::{UserOperationV07, UserOperationV08},
+    alloy::{primitives::Bytes, sol},
+};
+
+sol! {
+    /// EIP-712 struct the v0.8 EntryPoint hashes user operations as
+    #[allow(missing_docs)]
+    #[derive(Debug, PartialEq, Eq)]
+    struct PackedUserOperation {
+        address sender;
+        uint256 nonce;
+        bytes initCode;
+        bytes callData;
+        bytes32 accountGasLimits;
+        uint256 preVerificationGas;
+        bytes32 gasFees;
+        bytes paymasterAndData;
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PackUserOperationError {
+    /// The delegate can't be read from the marker, so already-delegated
+    /// EOAs must still pass their authorization
+    #[error("EIP-7702 user operation is missing eip7702Auth")]
+    MissingEip7702Auth,
+}
+
+/// `initCode` as hashed by the EntryPoint. For EIP-7702 senders the marker
+/// is replaced by the delegate from `eip7702Auth`, so the signature commits
+/// to the delegation.
+pub fn get_init_code(
+    user_operation: &UserOperationV08,
+) -> Result<Bytes, PackUserOperationError> {
+    let Some(factory) = user_operation.factory else {
+        return Ok(Bytes::new());
+    };
+    let factory = if user_operation.is_eip7702() {
+        user_operation
+            .eip7702_auth
+            .as_ref()
+            .ok_or(PackUserOperationError::MissingEip7702Auth)?
+            .address
+    } else {
+        factory
+    };
+    Ok(factory
+        .iter()
+        .chain(user_operation.factory_data.as_ref().unwrap_or_default().iter())
+        .copied()
+        .collect())
+}
+
+pub fn pack_user_operation_v08(
+    user_operation: &UserOperationV08,
+) -> Result<PackedUserOperation, PackUserOperationError> {
+    // The gas and paymaster fields are packed exactly like v0.7
+    let v07 = UserOperationV07 {
+        call_gas_limit: user_operation.call_gas_limit,
+        verification_gas_limit: user_operation.verification_gas_limit,
+        max_fee_per_gas: user_operation.max_fee_per_gas,
+        max_priority_fee_per_gas: user_operation.max_priority_fee_per_gas,
+        paymaster: user_operation.paymaster,
+        paymaster_verification_gas_limit: user_operation
+            .paymaster_verification_gas_limit,
+        paymaster_post_op_gas_limit: user_operation.paymaster_post_op_gas_limit,
+        paymaster_data: user_operation.paymaster_data.clone(),
+        ..Default::default()
+    };
+
+    Ok(PackedUserOperation {
+        sender: user_operation.sender.to_address(),
+        nonce: user_operation.nonce,
+        initCode: get_init_code(user_operation)?,
+        callData: user_operation.call_data.clone(),
+        accountGasLimits: verificaction_gas_limit_and_call_gas_limit::get_verificaction_gas_limit_and_call_gas_limit(&v07),
+        preVerificationGas: user_operation.pre_verification_gas,
+        gasFees: max_priority_fee_per_gas_and_max_fee_per_gas::get_max_priority_fee_per_gas_and_max_fee_per_gas(&v07),
+        paymasterAndData: hashed_paymaster_and_data::get_data(&v07),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            entry_point::INITCODE_EIP7702_MARKER, user_operation::Eip7702Auth,
+        },
+        alloy::{
+            primitives::{address, bytes, keccak256},
+            sol_types::SolStruct,
+        },
+    };
+
+    #[test]
+    fn test_type_hash() {
+        assert_eq!(
+            pack_user_operation_v08(&UserOperationV08::mock())
+                .unwrap()
+                .eip712_type_hash(),
+            keccak256(
+                "PackedUserOperation(address sender,uint256 nonce,bytes initCode,bytes callData,bytes32 accountGasLimits,uint256 preVerificationGas,bytes32 gasFees,bytes paymasterAndData)"
+            )
+        );
+    }
+
+    #[test]
+    fn test_pack_matches_v07() {
+        let user_operation = UserOperationV07::mock();
+        let packed = pack_user_operation_v08(&UserOperationV08::from_v07(
+            user_operation.clone(),
+            None,
+        ))
+        .unwrap();
+        let encoded = alloy::sol_types::SolValue::abi_encode(&(
+            packed.sender,
+            packed.nonce,
+            keccak256(&packed.initCode),
+            keccak256(&packed.callData),
+            packed.accountGasLimits,
+            packed.preVerificationGas,
+            packed.gasFees,
+            keccak256(&packed.paymasterAndData),
+        ));
+        assert_eq!(
+            encoded,
+            super::super::pack_v07::pack_user_operation_v07(&user_operation)
+        );
+    }
+
+    #[test]
+    fn test_eip7702_init_code() {
+        let delegate = address!("4Cd241E8d1510e30b2076397afc7508Ae59C66c9");
+        let mut user_operation = UserOperationV08::mock();
+        user_operation.factory = Some(INITCODE_EIP7702_MARKER);
+        assert!(matches!(
+            get_init_code(&user_operation),
+            Err(PackUserOperationError::MissingEip7702Auth)
+        ));
+
+        user_operation.eip7702_auth =
+            Some(Eip7702Auth { address: delegate, ..Default::default() });
+        assert_eq!(
+            get_init_code(&user_operation).unwrap().to_vec(),
+            delegate.to_vec()
+        );
+
+        user_operation.factory_data = Some(bytes!("c0ffee"));
+        let mut expected = delegate.to_vec();
+        expected.extend([0xc0, 0xff, 0xee]);
+        assert_eq!(get_init_code(&user_operation).unwrap().to_vec(), expected);
+
+        // Without the marker the factory is used as-is
+        let factory = address!("0000000000000000000000000000000000001234");
+        user_operation.factory = Some(factory);
+        assert!(
+            get_init_code(&user_operation)
+                .unwrap()
+                .starts_with(factory.as_slice())
+        );
+    }
+}
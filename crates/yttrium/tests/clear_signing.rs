@@ -1,10 +1,24 @@
 use {
+    alloy::{
+        primitives::{Address, U256},
+        rpc::types::Authorization,
+    },
     num_bigint::BigUint,
     serde_json::json,
+    std::{collections::HashMap, sync::Arc},
     tiny_keccak::{Hasher, Keccak},
-    yttrium::clear_signing::{
-        DisplayItem, EngineError, TypedData, format_typed_data,
-        format_with_value,
+    yttrium::{
+        call::Call,
+        clear_signing::{
+            AddressLabelProvider, DisplayItem, EngineError, FlowDirection,
+            LintSeverity, Locale, MAX_CALL_DEPTH, ResolvedCall,
+            ResolvedDescriptor, RiskCode, RiskSeverity, TokenFlow, TokenMeta,
+            TokenMetadataProvider, TypedData, format_authorization,
+            format_batch, format_typed_data, format_with_resolved_call,
+            format_with_sender, format_with_value, lint_descriptor,
+            lint_registry, set_address_label_provider,
+            set_token_metadata_provider,
+        },
     },
 };
 
@@ -26,6 +40,9 @@ const UNIVERSAL_ROUTER_OPTIMISM: &str =
 const STAKEWEIGHT_OPTIMISM: &str = "0x521B4C065Bbdbe3E20B3727340730936912DfA46";
 const USDT_OPTIMISM_CANONICAL: &str =
     "0x94b008aa00579c1307b0ef2c499ad98a8ce58e58";
+const SAFE: &str = "0x5afe000000000000000000000000000000000001";
+const MULTI_SEND: &str = "0x40A2aCCbd92BCA938b02010E17A5b8929b49130D";
+const UNKNOWN_CONTRACT: &str = "0x2222222222222222222222222222222222222222";
 const STAKEWEIGHT_INCREASE_UNLOCK_TIME_CALLDATA: &str = "0x7c616fe6000000000000000000000000000000000000000000000000000000006945563d";
 const UNIVERSAL_ROUTER_CALLDATA_HEX: &str = concat!(
     "3593564c00000000000000000000000000000000000000000000000000000000",
@@ -86,8 +103,9 @@ fn approve_usdt_spender() {
     let amount = uint_word_u128(1_000_000_000_000u128);
     let calldata = build_calldata(selector, &[spender, amount]);
 
-    let model = format_with_value(1, USDT_MAINNET, None, &calldata)
-        .expect("format succeeds");
+    let model =
+        format_with_value(1, USDT_MAINNET, None, &calldata, &Locale::default())
+            .expect("format succeeds");
 
     assert_eq!(model.intent, "Approve USDT spending");
     assert_eq!(
@@ -129,8 +147,14 @@ fn swap_usdc_to_weth_exact_input_single() {
 
     let calldata = build_calldata(selector, &params);
 
-    let model = format_with_value(1, UNISWAP_V3_ROUTER, None, &calldata)
-        .expect("format succeeds");
+    let model = format_with_value(
+        1,
+        UNISWAP_V3_ROUTER,
+        None,
+        &calldata,
+        &Locale::default(),
+    )
+    .expect("format succeeds");
 
     assert_eq!(model.intent, "Swap tokens");
     assert_eq!(
@@ -172,8 +196,14 @@ fn aave_deposit_formats_usdc_amount() {
         ],
     );
 
-    let model = format_with_value(1, AAVE_LPV2_MAINNET, None, &calldata)
-        .expect("format succeeds");
+    let model = format_with_value(
+        1,
+        AAVE_LPV2_MAINNET,
+        None,
+        &calldata,
+        &Locale::default(),
+    )
+    .expect("format succeeds");
 
     assert_eq!(model.intent, "Supply");
     assert!(model.warnings.is_empty());
@@ -215,8 +245,14 @@ fn aave_repay_all_uses_message() {
         ],
     );
 
-    let model = format_with_value(1, AAVE_LPV2_MAINNET, None, &calldata)
-        .expect("format succeeds");
+    let model = format_with_value(
+        1,
+        AAVE_LPV2_MAINNET,
+        None,
+        &calldata,
+        &Locale::default(),
+    )
+    .expect("format succeeds");
 
     assert_eq!(model.intent, "Repay loan");
     assert_eq!(
@@ -235,8 +271,14 @@ fn walletconnect_increase_unlock_time_renders_date() {
     )
     .expect("call data hex");
 
-    let model = format_with_value(10, STAKEWEIGHT_OPTIMISM, None, &calldata)
-        .expect("format succeeds");
+    let model = format_with_value(
+        10,
+        STAKEWEIGHT_OPTIMISM,
+        None,
+        &calldata,
+        &Locale::default(),
+    )
+    .expect("format succeeds");
 
     assert_eq!(model.intent, "Increase Unlock Time");
     assert_eq!(
@@ -254,6 +296,88 @@ fn walletconnect_increase_unlock_time_renders_date() {
     assert!(model.raw.is_none());
 }
 
+#[test]
+fn localized_approve_uses_translations_and_separators() {
+    let calldata = build_calldata(
+        selector("approve(address,uint256)"),
+        &[
+            address_word(UNISWAP_V3_ROUTER),
+            uint_word_u128(1_234_567_500_000u128),
+        ],
+    );
+
+    // `de-AT` has no translation of its own and falls back to `de`
+    let model = format_with_value(
+        1,
+        USDT_MAINNET,
+        None,
+        &calldata,
+        &Locale::parse("de-AT"),
+    )
+    .expect("format succeeds");
+    assert_eq!(model.intent, "USDT-Ausgaben genehmigen");
+    assert_eq!(
+        model.items,
+        vec![
+            DisplayItem {
+                label: "Berechtigter".to_string(),
+                value: "Uniswap V3 Router".to_string(),
+            },
+            DisplayItem {
+                label: "Betrag".to_string(),
+                value: "1.234.567,5 USDT".to_string(),
+            },
+        ]
+    );
+    assert_eq!(
+        model.interpolated_intent.as_deref(),
+        Some("Uniswap V3 Router erlauben, 1.234.567,5 USDT auszugeben")
+    );
+
+    // Without a translation the descriptor text stays, numbers still localize
+    let model = format_with_value(
+        1,
+        USDT_MAINNET,
+        None,
+        &calldata,
+        &Locale::parse("fr"),
+    )
+    .expect("format succeeds");
+    assert_eq!(model.intent, "Approve USDT spending");
+    assert_eq!(model.items[1].label, "Amount");
+    assert_eq!(model.items[1].value, "1\u{202f}234\u{202f}567,5 USDT");
+}
+
+#[test]
+fn localized_date_follows_locale() {
+    let calldata = hex::decode(
+        STAKEWEIGHT_INCREASE_UNLOCK_TIME_CALLDATA.trim_start_matches("0x"),
+    )
+    .expect("call data hex");
+
+    let model = format_with_value(
+        10,
+        STAKEWEIGHT_OPTIMISM,
+        None,
+        &calldata,
+        &Locale::parse("de_DE.UTF-8"),
+    )
+    .expect("format succeeds");
+
+    assert_eq!(model.intent, "Sperrfrist verlängern");
+    assert_eq!(
+        model.items,
+        vec![DisplayItem {
+            label: "Neues Entsperrdatum".to_string(),
+            value: "19.12.2025 13:42:21 UTC".to_string(),
+        }]
+    );
+    assert_eq!(
+        model.interpolated_intent.as_deref(),
+        Some("Sperrfrist bis 19.12.2025 13:42:21 UTC verlängern")
+    );
+}
+
 #[test]
 fn aave_missing_token_errors() {
     let calldata = build_calldata(
@@ -266,12 +390,81 @@ fn aave_missing_token_errors() {
         ],
     );
 
-    let err = format_with_value(1, AAVE_LPV2_MAINNET, None, &calldata)
-        .expect_err("token lookup should fail");
+    let err = format_with_value(
+        1,
+        AAVE_LPV2_MAINNET,
+        None,
+        &calldata,
+        &Locale::default(),
+    )
+    .expect_err("token lookup should fail");
 
     assert!(matches!(err, EngineError::TokenRegistry(_)));
 }
 
+// Providers are process-wide, so they only answer for addresses no other test uses
+struct HostProvider;
+
+const HOST_TOKEN: &str = "0x000000000000000000000000000000000000bEEF";
+const HOST_CONTACT: &str = "0x3333333333333333333333333333333333333333";
+
+impl TokenMetadataProvider for HostProvider {
+    fn token_metadata(&self, caip19: String) -> Option<TokenMeta> {
+        (caip19 == format!("eip155:1/erc20:{}", HOST_TOKEN.to_lowercase()))
+            .then(|| TokenMeta {
+                symbol: "HOST".to_string(),
+                decimals: 2,
+                name: "Host Token".to_string(),
+            })
+    }
+}
+
+impl AddressLabelProvider for HostProvider {
+    fn address_label(&self, chain_id: u64, address: String) -> Option<String> {
+        (chain_id == 1 && address == HOST_CONTACT).then(|| "Alice".to_string())
+    }
+}
+
+#[test]
+fn host_providers_supply_tokens_and_labels() {
+    let calldata = build_calldata(
+        selector("deposit(address,uint256,address,uint16)"),
+        &[
+            address_word(HOST_TOKEN),
+            uint_word_u128(12_345),
+            address_word(HOST_CONTACT),
+            uint_word_u128(0),
+        ],
+    );
+
+    set_token_metadata_provider(Some(Arc::new(HostProvider)));
+    set_address_label_provider(Some(Arc::new(HostProvider)));
+    let result = format_with_value(
+        1,
+        AAVE_LPV2_MAINNET,
+        None,
+        &calldata,
+        &Locale::default(),
+    );
+    set_token_metadata_provider(None);
+    set_address_label_provider(None);
+
+    let model = result.expect("format succeeds");
+    assert_eq!(
+        model.items,
+        vec![
+            DisplayItem {
+                label: "Amount to supply".to_string(),
+                value: "123.45 HOST".to_string(),
+            },
+            DisplayItem {
+                label: "Collateral recipient".to_string(),
+                value: "Alice".to_string(),
+            },
+        ]
+    );
+}
+
 #[test]
 fn aave_borrow_variable_on_optimism() {
     let calldata = hex::decode("a415bcad00000000000000000000000094b008aa00579c1307b0ef2c499ad98a8ce58e5800000000000000000000000000000000000000000000000000000000000f424000000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000000000000000000000000000bf01daf454dce008d3e2bfd47d5e186f71477253").expect("valid hex");
@@ -281,6 +474,7 @@ fn aave_borrow_variable_on_optimism() {
         "0x794a61358D6845594F94dc1DB02A252b5b4814aD",
         None,
         &calldata,
+        &Locale::default(),
     )
     .expect("format succeeds");
 
@@ -309,239 +503,1382 @@ fn aave_borrow_variable_on_optimism() {
 }
 
 #[test]
-fn uniswap_universal_router_missing_descriptor() {
+fn uniswap_universal_router_lists_commands() {
     let calldata =
         hex::decode(UNIVERSAL_ROUTER_CALLDATA_HEX).expect("valid hex");
     let call_value = uint_word_u128(
         u128::from_str_radix("1e1264f50cc87", 16).expect("value"),
     );
 
-    let err = format_with_value(
+    let model = format_with_value(
         10,
         UNIVERSAL_ROUTER_OPTIMISM,
         Some(&call_value),
         &calldata,
+        &Locale::default(),
     )
-    .expect_err("descriptor lookup should fail");
-
-    match err {
-        EngineError::Resolver(message) => {
-            assert!(
-                message.contains("descriptor not found"),
-                "unexpected resolver error: {message}"
-            );
+    .expect("format succeeds");
+
+    assert_eq!(model.intent, "Execute Universal Router commands");
+    assert_eq!(
+        model.items[0],
+        DisplayItem { label: "Commands".to_string(), value: "3".to_string() }
+    );
+    assert_eq!(
+        model.items[2],
+        DisplayItem {
+            label: "Deadline".to_string(),
+            value: "2025-11-03 10:03:03 UTC".to_string()
         }
-        other => panic!("expected resolver error, got {other:?}"),
-    }
+    );
+    let intents =
+        model.calls.iter().map(|call| call.intent.as_str()).collect::<Vec<_>>();
+    assert_eq!(
+        intents,
+        vec!["Uniswap V4 swap", "Pay portion of balance", "Sweep tokens"]
+    );
+    assert_eq!(model.calls[0].raw.as_ref().unwrap().selector, "0x10");
 }
 
 #[test]
-fn deposit_weth_uses_call_value() {
-    let selector = selector("deposit()");
-    let calldata = build_calldata(selector, &[]);
-    let value = uint_word_u128(500_000_000_000_000_000u128);
+fn safe_exec_transaction_formats_inner_call() {
+    let calldata = safe_exec_transaction(USDT_MAINNET, 0, usdt_approve(), 0);
 
-    let model = format_with_value(1, WETH_MAINNET, Some(&value), &calldata)
+    let model = format_with_value(1, SAFE, None, &calldata, &Locale::default())
         .expect("format succeeds");
 
-    assert_eq!(model.intent, "Wrap ETH into WETH");
+    assert_eq!(model.intent, "Execute Safe transaction");
+    assert_eq!(
+        model.interpolated_intent.as_deref(),
+        Some(
+            "Execute Safe transaction: Approve Uniswap V3 Router to spend 1,000,000 USDT"
+        )
+    );
+    assert_eq!(
+        model.items[2],
+        DisplayItem {
+            label: "Operation".to_string(),
+            value: "Call".to_string()
+        }
+    );
+    assert!(model.warnings.is_empty());
+    assert_eq!(model.calls.len(), 1);
+    assert_eq!(model.calls[0].intent, "Approve USDT spending");
+    assert!(model.calls[0].warnings.is_empty());
+}
+
+#[test]
+fn safe_multi_send_formats_each_call() {
+    let unknown_call =
+        build_calldata([0x12, 0x34, 0x56, 0x78], &[uint_word_u128(1)]);
+    let mut packed = Vec::new();
+    packed.extend(multi_send_transaction(USDT_MAINNET, 0, &usdt_approve()));
+    packed.extend(multi_send_transaction(
+        ON_BEHALF_OF,
+        1_000_000_000_000_000_000,
+        &[],
+    ));
+    packed.extend(multi_send_transaction(UNKNOWN_CONTRACT, 0, &unknown_call));
+    let multi_send = encode_call("multiSend(bytes)", &[AbiArg::Bytes(packed)]);
+    let calldata = safe_exec_transaction(MULTI_SEND, 0, multi_send, 1);
+
+    let model = format_with_value(1, SAFE, None, &calldata, &Locale::default())
+        .expect("format succeeds");
+
+    assert!(model.warnings.iter().any(|w| w.contains("delegate-calls")));
+    let batch = &model.calls[0];
+    assert_eq!(batch.intent, "Batch transaction");
+    assert_eq!(batch.calls.len(), 3);
+    assert_eq!(batch.calls[0].intent, "Approve USDT spending");
+    assert_eq!(batch.calls[1].intent, "Send");
+    assert_eq!(
+        batch.calls[1].items[1],
+        DisplayItem { label: "Amount".to_string(), value: "1 ETH".to_string() }
+    );
+    assert_eq!(batch.calls[2].intent, "Unknown call");
+    assert!(
+        batch.calls[2]
+            .warnings
+            .iter()
+            .any(|w| w.contains("descriptor not found"))
+    );
+    assert_eq!(batch.calls[2].raw.as_ref().unwrap().selector, "0x12345678");
+}
+
+#[test]
+fn nested_calls_stop_at_max_depth() {
+    let mut calldata = usdt_approve();
+    let mut target = USDT_MAINNET;
+    for _ in 0..=MAX_CALL_DEPTH {
+        calldata = safe_exec_transaction(target, 0, calldata, 0);
+        target = SAFE;
+    }
+
+    let model = format_with_value(1, SAFE, None, &calldata, &Locale::default())
+        .expect("format succeeds");
+
+    let mut innermost = &model;
+    for _ in 0..=MAX_CALL_DEPTH {
+        assert_eq!(innermost.intent, "Execute Safe transaction");
+        innermost = &innermost.calls[0];
+    }
+    assert_eq!(innermost.intent, "Unknown call");
+    assert!(innermost.warnings.iter().any(|w| w.contains("maximum depth")));
+}
+
+#[test]
+fn multicall_formats_self_calls() {
+    let calldata = encode_call(
+        "multicall(bytes[])",
+        &[AbiArg::BytesArray(vec![usdt_approve(), usdt_approve()])],
+    );
+
+    let model =
+        format_with_value(1, USDT_MAINNET, None, &calldata, &Locale::default())
+            .expect("format succeeds");
+
+    assert_eq!(model.intent, "Multicall");
+    assert_eq!(model.calls.len(), 2);
+    assert!(
+        model.calls.iter().all(|call| call.intent == "Approve USDT spending")
+    );
+}
+
+#[test]
+fn calldata_field_formats_inner_call() {
+    let calldata = encode_call(
+        "forward(address,uint256,bytes)",
+        &[
+            AbiArg::Word(address_word(USDT_MAINNET)),
+            AbiArg::Word(uint_word_u128(0)),
+            AbiArg::Bytes(usdt_approve()),
+        ],
+    );
+
+    let model =
+        format_with_value(1, TEST_ROUTER, None, &calldata, &Locale::default())
+            .expect("format succeeds");
+
+    assert_eq!(model.intent, "Forward call");
     assert_eq!(
         model.items,
         vec![DisplayItem {
-            label: "Amount".to_string(),
-            value: "0.5 WETH".to_string(),
+            label: "Call".to_string(),
+            value: "Approve Uniswap V3 Router to spend 1,000,000 USDT"
+                .to_string(),
         }]
     );
+    assert_eq!(
+        model.interpolated_intent.as_deref(),
+        Some("Forward: Approve Uniswap V3 Router to spend 1,000,000 USDT")
+    );
     assert!(model.warnings.is_empty());
-    assert!(model.raw.is_none());
+    assert_eq!(model.calls[0].intent, "Approve USDT spending");
 }
 
 #[test]
-fn unknown_selector_returns_fallback() {
-    let selector = [0u8, 1u8, 2u8, 3u8];
-    let calldata = build_calldata(selector, &[uint_word_u128(42u128)]);
+fn erc7730_duration_unit_and_chain_formats() {
+    let calldata = build_calldata(
+        selector("schedule(uint256,uint256,uint256,uint256,uint256,uint256)"),
+        &[
+            uint_word_u128(5_405),
+            uint_word_u128(1_250),
+            uint_word_u128(1_500_000),
+            uint_word_u128(42_161),
+            uint_word_u128(19_000_000),
+            uint_word_u128(0),
+        ],
+    );
 
-    let model = format_with_value(1, USDT_MAINNET, None, &calldata)
-        .expect("format succeeds");
+    let model =
+        format_with_value(1, TEST_ROUTER, None, &calldata, &Locale::default())
+            .expect("format succeeds");
 
-    assert_eq!(model.intent, "Unknown transaction");
-    assert!(model.items.is_empty());
-    assert!(model.warnings.iter().any(|w| w.contains("No ABI match")));
-    assert!(model.raw.is_some());
+    let item = |label: &str, value: &str| DisplayItem {
+        label: label.to_string(),
+        value: value.to_string(),
+    };
+    assert_eq!(
+        model.items,
+        vec![
+            item("Delay", "01:30:05"),
+            item("Rate", "12.5%"),
+            item("Power", "1.5MW"),
+            item("Target chain", "Arbitrum One"),
+            item("Start", "Block 19000000"),
+        ]
+    );
+    assert_eq!(
+        model.interpolated_intent.as_deref(),
+        Some("Schedule in 01:30:05")
+    );
+    // `@.value` is optional, so its absence is not reported
+    assert!(model.warnings.is_empty(), "{:?}", model.warnings);
+}
+
+#[test]
+fn erc7730_visible_rules_hide_fields() {
+    let calldata = build_calldata(
+        selector("schedule(uint256,uint256,uint256,uint256,uint256,uint256)"),
+        &[
+            uint_word_u128(60),
+            uint_word_u128(5),
+            uint_word_u128(999),
+            uint_word_u128(7_777),
+            uint_word_u128(0),
+            uint_word_u128(1),
+        ],
+    );
+    let value = uint_word_u128(1_000_000_000_000_000_000);
+
+    let model = format_with_value(
+        1,
+        TEST_ROUTER,
+        Some(&value),
+        &calldata,
+        &Locale::default(),
+    )
+    .expect("format succeeds");
+
+    let labels =
+        model.items.iter().map(|item| item.label.as_str()).collect::<Vec<_>>();
+    // `Start` is hidden by `ifNotIn`, `Mode` by `mustMatch`
+    assert_eq!(labels, vec!["Delay", "Rate", "Power", "Target chain", "Value"]);
+    assert_eq!(model.items[2].value, "999W");
+    assert_eq!(model.items[3].value, "7777");
+    assert_eq!(model.items[4].value, "1 ETH");
+    assert_eq!(
+        model.warnings,
+        vec!["Field 'Mode' has unexpected value 1".to_string()]
+    );
+}
+
+#[test]
+fn erc7730_nft_name_sender_and_native_currency() {
+    let calldata = build_calldata(
+        selector("transferNft(address,uint256,address,address,uint256)"),
+        &[
+            address_word("0x5555555555555555555555555555555555555555"),
+            uint_word_u128(7),
+            address_word("0x0000000000000000000000000000000000000000"),
+            address_word("0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE"),
+            uint_word_u128(1_500_000_000_000_000_000),
+        ],
+    );
+
+    let model =
+        format_with_value(1, TEST_ROUTER, None, &calldata, &Locale::default())
+            .expect("format succeeds");
+
+    let item = |label: &str, value: &str| DisplayItem {
+        label: label.to_string(),
+        value: value.to_string(),
+    };
+    // `Token` is `visible: never`
+    assert_eq!(
+        model.items,
+        vec![
+            item("NFT", "Test Collection #7"),
+            item("Recipient", "Sender"),
+            item("Price", "1.5 ETH"),
+        ]
+    );
+    assert!(model.warnings.is_empty(), "{:?}", model.warnings);
+}
+
+#[test]
+fn risk_unlimited_approval_flagged() {
+    let calldata = build_calldata(
+        selector("approve(address,uint256)"),
+        &[address_word(UNISWAP_V3_ROUTER), [0xff; 32]],
+    );
+
+    let model =
+        format_with_value(1, USDT_MAINNET, None, &calldata, &Locale::default())
+            .expect("format succeeds");
+
+    let risks = model
+        .risks
+        .iter()
+        .map(|risk| (risk.code, risk.severity))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        risks,
+        vec![(RiskCode::UnlimitedApproval, RiskSeverity::Warning)]
+    );
+    assert_eq!(model.risks[0].code.as_str(), "unlimited_approval");
+
+    let limited = format_with_value(
+        1,
+        USDT_MAINNET,
+        None,
+        &usdt_approve(),
+        &Locale::default(),
+    )
+    .expect("format succeeds");
+    assert!(limited.risks.is_empty());
+}
+
+#[test]
+fn risk_approval_for_all_and_transfer_to_token_contract() {
+    let descriptor = json!({
+        "context": {
+            "contract": {
+                "deployments": [{ "chainId": 1, "address": UNKNOWN_CONTRACT }],
+                "abi": [
+                    {
+                        "type": "function",
+                        "name": "setApprovalForAll",
+                        "inputs": [
+                            { "name": "operator", "type": "address" },
+                            { "name": "approved", "type": "bool" }
+                        ]
+                    },
+                    {
+                        "type": "function",
+                        "name": "transfer",
+                        "inputs": [
+                            { "name": "to", "type": "address" },
+                            { "name": "amount", "type": "uint256" }
+                        ]
+                    }
+                ]
+            }
+        },
+        "display": { "formats": {} }
+    })
+    .to_string();
+    let format = |calldata: &[u8]| {
+        let resolved = ResolvedCall {
+            descriptor: ResolvedDescriptor {
+                descriptor_json: descriptor.as_str().into(),
+                abi_json: None,
+                includes: Vec::new(),
+            },
+            token_metadata: HashMap::new(),
+            address_book: HashMap::new(),
+        };
+        format_with_resolved_call(
+            resolved,
+            1,
+            UNKNOWN_CONTRACT,
+            None,
+            calldata,
+            &Locale::default(),
+        )
+        .expect("format succeeds")
+    };
+
+    let approval = format(&build_calldata(
+        selector("setApprovalForAll(address,bool)"),
+        &[address_word(HOST_CONTACT), uint_word_u32(1)],
+    ));
+    assert_eq!(approval.risks.len(), 1);
+    assert_eq!(approval.risks[0].code, RiskCode::ApprovalForAll);
+
+    let revoke = format(&build_calldata(
+        selector("setApprovalForAll(address,bool)"),
+        &[address_word(HOST_CONTACT), uint_word_u32(0)],
+    ));
+    assert!(revoke.risks.is_empty());
+
+    let transfer = format(&build_calldata(
+        selector("transfer(address,uint256)"),
+        &[address_word(UNKNOWN_CONTRACT), uint_word_u32(1)],
+    ));
+    assert_eq!(transfer.risks.len(), 1);
+    assert_eq!(transfer.risks[0].code, RiskCode::TransferToTokenContract);
+    assert_eq!(transfer.risks[0].severity, RiskSeverity::Critical);
 }
 
-#[test]
-fn includes_descriptor_merges_display() {
-    let selector = selector("setAmount(uint256)");
-    let amount = uint_word_u128(42u128);
-    let calldata = build_calldata(selector, &[amount]);
+#[test]
+fn risk_swap_recipient_differs_from_sender() {
+    let recipient = "0x1234567890abcdef1234567890abcdef12345678";
+    let calldata = build_calldata(
+        selector(
+            "exactInputSingle((address,address,uint24,address,uint256,uint256,uint160))",
+        ),
+        &[
+            address_word(USDC),
+            address_word(WETH_MAINNET),
+            uint_word_u32(3_000u32),
+            address_word(recipient),
+            uint_word_u128(1_000_000_000_000u128),
+            uint_word_u128(1_000_000_000_000_000_000u128),
+            uint_word_u128(0u128),
+        ],
+    );
+
+    let model = format_with_sender(
+        1,
+        ON_BEHALF_OF,
+        UNISWAP_V3_ROUTER,
+        None,
+        &calldata,
+        &Locale::default(),
+    )
+    .expect("format succeeds");
+    assert_eq!(model.risks.len(), 1);
+    assert_eq!(model.risks[0].code, RiskCode::RecipientNotSender);
+    assert_eq!(
+        model.risks[0].message,
+        format!("Swap output goes to {recipient}, not the sender")
+    );
+
+    let own = format_with_sender(
+        1,
+        recipient,
+        UNISWAP_V3_ROUTER,
+        None,
+        &calldata,
+        &Locale::default(),
+    )
+    .expect("format succeeds");
+    assert!(own.risks.is_empty());
+    // Without a sender the rule can't apply
+    let unknown = format_with_value(
+        1,
+        UNISWAP_V3_ROUTER,
+        None,
+        &calldata,
+        &Locale::default(),
+    )
+    .expect("format succeeds");
+    assert!(unknown.risks.is_empty());
+}
+
+#[test]
+fn risk_long_lived_unlimited_permit() {
+    let typed_data_json = json!({
+        "types": {
+            "EIP712Domain": [
+                { "name": "name", "type": "string" },
+                { "name": "version", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" }
+            ],
+            "Permit": [
+                { "name": "owner", "type": "address" },
+                { "name": "spender", "type": "address" },
+                { "name": "value", "type": "uint256" },
+                { "name": "nonce", "type": "uint256" },
+                { "name": "deadline", "type": "uint256" }
+            ]
+        },
+        "primaryType": "Permit",
+        "domain": {
+            "name": "USD Coin",
+            "version": "2",
+            "chainId": 1,
+            "verifyingContract": USDC
+        },
+        "message": {
+            "owner": ON_BEHALF_OF,
+            "spender": UNISWAP_V3_ROUTER,
+            "value": "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "nonce": "0",
+            // 2100-01-01
+            "deadline": "4102444800"
+        }
+    });
+    let typed: TypedData = serde_json::from_value(typed_data_json)
+        .expect("typed data should parse");
+
+    let model =
+        format_typed_data(&typed, &Locale::default()).expect("format succeeds");
+
+    let codes = model.risks.iter().map(|risk| risk.code).collect::<Vec<_>>();
+    assert_eq!(
+        codes,
+        vec![RiskCode::UnlimitedApproval, RiskCode::LongLivedPermit]
+    );
+    assert!(model.risks[1].message.starts_with("Permit stays valid for "));
+}
+
+#[test]
+fn batch_formats_calls_and_nets_flows() {
+    let usdc_transfer = build_calldata(
+        selector("transfer(address,uint256)"),
+        &[address_word(UNKNOWN_CONTRACT), uint_word_u32(5_000_000)],
+    );
+    let usdc_pulled_in = build_calldata(
+        selector("transferFrom(address,address,uint256)"),
+        &[
+            address_word(HOST_CONTACT),
+            address_word(ON_BEHALF_OF),
+            uint_word_u32(2_000_000),
+        ],
+    );
+    let calls = vec![
+        Call::new(
+            USDT_MAINNET.parse().unwrap(),
+            U256::ZERO,
+            usdt_approve().into(),
+        ),
+        Call::new(USDC.parse().unwrap(), U256::ZERO, usdc_transfer.into()),
+        Call::new(
+            UNKNOWN_CONTRACT.parse().unwrap(),
+            U256::from(500_000_000_000_000_000u128),
+            Default::default(),
+        ),
+        Call::new(USDC.parse().unwrap(), U256::ZERO, usdc_pulled_in.into()),
+    ];
+
+    let preview =
+        format_batch(1, ON_BEHALF_OF, &calls, None, &Locale::default());
+
+    assert_eq!(preview.model.intent, "Batch transaction");
+    assert_eq!(
+        preview.model.interpolated_intent.as_deref(),
+        Some("Execute 4 calls")
+    );
+    assert_eq!(preview.model.calls.len(), 4);
+    assert_eq!(preview.model.calls[0].intent, "Approve USDT spending");
+    assert_eq!(preview.model.calls[2].intent, "Send");
+    assert_eq!(
+        preview.flows,
+        vec![
+            TokenFlow {
+                asset: format!("eip155:1/erc20:{}", USDC.to_ascii_lowercase()),
+                direction: FlowDirection::Outgoing,
+                amount: "3 USDC".to_string(),
+            },
+            TokenFlow {
+                asset: "eip155:1/slip44:60".to_string(),
+                direction: FlowDirection::Outgoing,
+                amount: "0.5 ETH".to_string(),
+            },
+        ]
+    );
+    assert_eq!(
+        preview.model.items[1..],
+        [
+            DisplayItem {
+                label: "Sends".to_string(),
+                value: "3 USDC".to_string()
+            },
+            DisplayItem {
+                label: "Sends".to_string(),
+                value: "0.5 ETH".to_string()
+            },
+        ]
+    );
+    assert!(preview.authorization.is_none());
+}
+
+#[test]
+fn authorization_flags_unknown_delegate() {
+    let safe = Authorization {
+        chain_id: U256::from(1),
+        address: "0x29fcB43b46531BcA003ddC8FCB67FFE91900C762".parse().unwrap(),
+        nonce: 7,
+    };
+    let model = format_authorization(&safe, &Locale::default());
+    assert_eq!(model.intent, "Upgrade account");
+    assert!(model.items[0].value.starts_with("Safe 1.4.1 (L2) (0x"));
+    assert_eq!(
+        model.items[1],
+        DisplayItem {
+            label: "Chain".to_string(),
+            value: "Ethereum".to_string()
+        }
+    );
+    assert!(model.risks.is_empty());
+
+    let unknown = Authorization {
+        chain_id: U256::ZERO,
+        address: UNKNOWN_CONTRACT.parse().unwrap(),
+        nonce: 0,
+    };
+    let preview =
+        format_batch(1, ON_BEHALF_OF, &[], Some(&unknown), &Locale::default());
+    let model = preview.authorization.expect("authorization preview");
+    let codes = model.risks.iter().map(|risk| risk.code).collect::<Vec<_>>();
+    assert_eq!(
+        codes,
+        vec![RiskCode::CrossChainAuthorization, RiskCode::UnknownDelegate]
+    );
+    assert_eq!(model.risks[1].severity, RiskSeverity::Critical);
+    assert_eq!(model.items[1].value, "All chains");
+
+    let revoke = Authorization {
+        chain_id: U256::from(1),
+        address: Address::ZERO,
+        nonce: 8,
+    };
+    let model = format_authorization(&revoke, &Locale::default());
+    assert_eq!(model.intent, "Remove account delegation");
+    assert!(model.risks.is_empty());
+}
+
+#[test]
+fn lint_bundled_registry_has_no_errors() {
+    let report = lint_registry();
+
+    assert!(report.entries > 0);
+    let errors = report.errors().map(ToString::to_string).collect::<Vec<_>>();
+    assert!(errors.is_empty(), "{}", errors.join("\n"));
+    // Known gap: StakeWeight uses an address as its `token` constant
+    let stakeweight =
+        format!("eip155:10:{}", STAKEWEIGHT_OPTIMISM.to_ascii_lowercase());
+    assert!(report.warnings().any(|issue| issue.entry == stakeweight
+        && issue.message.contains("is not in the token registry")));
+}
+
+#[test]
+fn lint_descriptor_reports_broken_entries() {
+    let descriptor = json!({
+        "context": {
+            "contract": {
+                "deployments": [{ "chainId": 1, "address": UNKNOWN_CONTRACT }],
+                "abi": [{
+                    "type": "function",
+                    "name": "transfer",
+                    "inputs": [
+                        { "name": "to", "type": "address" },
+                        { "name": "amount", "type": "uint256" }
+                    ]
+                }]
+            }
+        },
+        "display": {
+            "formats": {
+                "transfer(address to,uint256 amount)": {
+                    "intent": "Send",
+                    "fields": [
+                        { "path": "to", "$ref": "$.display.definitions.missing" },
+                        {
+                            "path": "amount",
+                            "format": "tokenAmount",
+                            "params": { "tokenPath": "token" }
+                        }
+                    ]
+                },
+                "approve(address,uint256)": { "intent": "Approve", "fields": [] }
+            }
+        }
+    })
+    .to_string();
+    let resolved = ResolvedDescriptor {
+        descriptor_json: descriptor.as_str().into(),
+        abi_json: None,
+        includes: Vec::new(),
+    };
+
+    let issues = lint_descriptor(1, UNKNOWN_CONTRACT, &resolved);
+    let messages = issues
+        .iter()
+        .map(|issue| (issue.severity, issue.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![
+            (
+                LintSeverity::Warning,
+                "Display format approve(address,uint256) matches no ABI function"
+            ),
+            (
+                LintSeverity::Error,
+                "transfer(address,uint256): Unknown display definition \
+                 reference '$.display.definitions.missing'"
+            ),
+            (
+                LintSeverity::Warning,
+                "transfer(address,uint256): field 'amount': token path \
+                 'token' not found for field 'amount'"
+            ),
+        ]
+    );
+
+    let issues = lint_descriptor(1, USDT_MAINNET, &resolved);
+    assert!(issues.iter().any(|issue| issue.severity == LintSeverity::Error
+        && issue.message
+            == "Descriptor has no deployment for this chain and address"));
+}
+
+#[test]
+fn deposit_weth_uses_call_value() {
+    let selector = selector("deposit()");
+    let calldata = build_calldata(selector, &[]);
+    let value = uint_word_u128(500_000_000_000_000_000u128);
+
+    let model = format_with_value(
+        1,
+        WETH_MAINNET,
+        Some(&value),
+        &calldata,
+        &Locale::default(),
+    )
+    .expect("format succeeds");
+
+    assert_eq!(model.intent, "Wrap ETH into WETH");
+    assert_eq!(
+        model.items,
+        vec![DisplayItem {
+            label: "Amount".to_string(),
+            value: "0.5 WETH".to_string(),
+        }]
+    );
+    assert!(model.warnings.is_empty());
+    assert!(model.raw.is_none());
+}
+
+#[test]
+fn unknown_selector_returns_fallback() {
+    let selector = [0u8, 1u8, 2u8, 3u8];
+    let calldata = build_calldata(selector, &[uint_word_u128(42u128)]);
+
+    let model =
+        format_with_value(1, USDT_MAINNET, None, &calldata, &Locale::default())
+            .expect("format succeeds");
+
+    assert_eq!(model.intent, "Unknown transaction");
+    assert!(model.items.is_empty());
+    assert!(model.warnings.iter().any(|w| w.contains("No ABI match")));
+    assert!(model.raw.is_some());
+}
+
+#[test]
+fn includes_descriptor_merges_display() {
+    let selector = selector("setAmount(uint256)");
+    let amount = uint_word_u128(42u128);
+    let calldata = build_calldata(selector, &[amount]);
+
+    let model =
+        format_with_value(1, TEST_ROUTER, None, &calldata, &Locale::default())
+            .expect("format succeeds");
+
+    assert_eq!(model.intent, "Set router amount");
+    assert_eq!(
+        model.items,
+        vec![DisplayItem {
+            label: "Amount".to_string(),
+            value: "42".to_string()
+        }]
+    );
+    assert!(model.warnings.is_empty());
+    assert!(model.raw.is_none());
+}
+
+#[test]
+fn eip712_limit_order_formats_tokens() {
+    let typed_data_json = json!({
+        "types": {
+            "EIP712Domain": [
+                {"name": "name", "type": "string"},
+                {"name": "version", "type": "string"},
+                {"name": "chainId", "type": "uint256"},
+                {"name": "verifyingContract", "type": "address"}
+            ],
+            "OrderStructure": [
+                {"name": "salt", "type": "uint256"},
+                {"name": "maker", "type": "address"},
+                {"name": "receiver", "type": "address"},
+                {"name": "makerAsset", "type": "address"},
+                {"name": "takerAsset", "type": "address"},
+                {"name": "makingAmount", "type": "uint256"},
+                {"name": "takingAmount", "type": "uint256"},
+                {"name": "makerTraits", "type": "uint256"}
+            ]
+        },
+        "primaryType": "OrderStructure",
+        "domain": {
+            "name": "1inch",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0x119c71d3BbAc22029622CBaEC24854d3D32D2828"
+        },
+        "message": {
+            "salt": "1",
+            "maker": "0xabc0000000000000000000000000000000000001",
+            "receiver": "0xabc0000000000000000000000000000000000002",
+            "makerAsset": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+            "takerAsset": "0xC02aaA39b223FE8D0A0E5C4F27eAD9083C756Cc2",
+            "makingAmount": "1000000",
+            "takingAmount": "1000000000000000000",
+            "makerTraits": "0"
+        }
+    });
+
+    let typed: TypedData = serde_json::from_value(typed_data_json)
+        .expect("typed data should parse");
+
+    let model =
+        format_typed_data(&typed, &Locale::default()).expect("format succeeds");
+
+    assert_eq!(model.intent, "1inch Order");
+    assert_eq!(
+        model.items,
+        vec![
+            DisplayItem {
+                label: "From".to_string(),
+                value: "0xabc0000000000000000000000000000000000001".to_string(),
+            },
+            DisplayItem {
+                label: "Send".to_string(),
+                value: "1 USDC".to_string(),
+            },
+            DisplayItem {
+                label: "Receive minimum".to_string(),
+                value: "1 WETH".to_string(),
+            },
+            DisplayItem {
+                label: "To".to_string(),
+                value: "0xabc0000000000000000000000000000000000002".to_string(),
+            },
+        ]
+    );
+    assert_eq!(
+        model.interpolated_intent.as_deref(),
+        Some(
+            "Send 1 USDC for at least 1 WETH to 0xabc0000000000000000000000000000000000002"
+        )
+    );
+    assert!(model.warnings.is_empty());
+    assert!(model.raw.is_none());
+}
+
+#[test]
+fn eip712_uniswap_permit2_formats_allowance() {
+    let typed_data_json = json!({
+        "types": {
+            "EIP712Domain": [
+                { "name": "name", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" }
+            ],
+            "PermitDetails": [
+                { "name": "token", "type": "address" },
+                { "name": "amount", "type": "uint160" },
+                { "name": "expiration", "type": "uint48" },
+                { "name": "nonce", "type": "uint48" }
+            ],
+            "PermitSingle": [
+                { "name": "details", "type": "PermitDetails" },
+                { "name": "spender", "type": "address" },
+                { "name": "sigDeadline", "type": "uint256" }
+            ]
+        },
+        "primaryType": "PermitSingle",
+        "domain": {
+            "name": "Permit2",
+            "chainId": "10",
+            "verifyingContract": "0x000000000022d473030f116ddee9f6b43ac78ba3"
+        },
+        "message": {
+            "details": {
+                "token": "0x0b2c639c533813f4aa9d7837caf62653d097ff85",
+                "amount": "1461501637330902918203684832716283019655932542975",
+                "expiration": "1765546694",
+                "nonce": "0"
+            },
+            "spender": "0x851116d9223fabed8e56c0e6b8ad0c31d98b3507",
+            "sigDeadline": "1762956494"
+        }
+    });
+
+    let typed: TypedData = serde_json::from_value(typed_data_json)
+        .expect("typed data should parse");
+
+    let model =
+        format_typed_data(&typed, &Locale::default()).expect("format succeeds");
+
+    assert_eq!(model.intent, "Authorize spending of token");
+    assert_eq!(
+        model.items,
+        vec![
+            DisplayItem {
+                label: "Spender".to_string(),
+                value: "Uniswap Universal Router".to_string(),
+            },
+            DisplayItem {
+                label: "Amount allowance".to_string(),
+                value: "Unlimited USDC".to_string(),
+            },
+            DisplayItem {
+                label: "Approval expires".to_string(),
+                value: "2025-12-12 13:38:14 UTC".to_string(),
+            },
+            DisplayItem {
+                label: "Signature deadline".to_string(),
+                value: "2025-11-12 14:08:14 UTC".to_string(),
+            },
+        ]
+    );
+    assert_eq!(
+        model.interpolated_intent.as_deref(),
+        Some(
+            "Authorize Uniswap Universal Router to spend Unlimited USDC until 2025-11-12 14:08:14 UTC"
+        )
+    );
+    assert!(model.warnings.is_empty());
+    assert!(model.raw.is_none());
+}
+
+#[test]
+fn eip712_permit2_batch_expands_details() {
+    let typed_data_json = json!({
+        "types": {
+            "EIP712Domain": [
+                { "name": "name", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" }
+            ],
+            "PermitDetails": [
+                { "name": "token", "type": "address" },
+                { "name": "amount", "type": "uint160" },
+                { "name": "expiration", "type": "uint48" },
+                { "name": "nonce", "type": "uint48" }
+            ],
+            "PermitBatch": [
+                { "name": "details", "type": "PermitDetails[]" },
+                { "name": "spender", "type": "address" },
+                { "name": "sigDeadline", "type": "uint256" }
+            ]
+        },
+        "primaryType": "PermitBatch",
+        "domain": {
+            "name": "Permit2",
+            "chainId": "10",
+            "verifyingContract": "0x000000000022d473030f116ddee9f6b43ac78ba3"
+        },
+        "message": {
+            "details": [
+                {
+                    "token": USDC_OPTIMISM_NATIVE,
+                    "amount": "1461501637330902918203684832716283019655932542975",
+                    "expiration": "1765546694",
+                    "nonce": "0"
+                },
+                {
+                    "token": "0x4200000000000000000000000000000000000006",
+                    "amount": "500000000000000000",
+                    "expiration": "1767225600",
+                    "nonce": "1"
+                }
+            ],
+            "spender": "0x851116d9223fabed8e56c0e6b8ad0c31d98b3507",
+            "sigDeadline": "1762956494"
+        }
+    });
+
+    let typed: TypedData = serde_json::from_value(typed_data_json)
+        .expect("typed data should parse");
 
-    let model = format_with_value(1, TEST_ROUTER, None, &calldata)
-        .expect("format succeeds");
+    let model =
+        format_typed_data(&typed, &Locale::default()).expect("format succeeds");
 
-    assert_eq!(model.intent, "Set router amount");
+    assert_eq!(model.intent, "Authorize spending of tokens");
+    let values =
+        model.items.iter().map(|item| item.value.as_str()).collect::<Vec<_>>();
     assert_eq!(
-        model.items,
-        vec![DisplayItem {
-            label: "Amount".to_string(),
-            value: "42".to_string()
-        }]
+        values,
+        vec![
+            "Uniswap Universal Router",
+            "Unlimited USDC",
+            "2025-12-12 13:38:14 UTC",
+            "0.5 WETH",
+            "2026-01-01 00:00:00 UTC",
+            "2025-11-12 14:08:14 UTC",
+        ]
     );
-    assert!(model.warnings.is_empty());
-    assert!(model.raw.is_none());
+    assert!(model.warnings.is_empty(), "{:?}", model.warnings);
 }
 
 #[test]
-fn eip712_limit_order_formats_tokens() {
+fn eip712_erc2612_permit_matches_schema() {
     let typed_data_json = json!({
         "types": {
             "EIP712Domain": [
-                {"name": "name", "type": "string"},
-                {"name": "version", "type": "string"},
-                {"name": "chainId", "type": "uint256"},
-                {"name": "verifyingContract", "type": "address"}
+                { "name": "name", "type": "string" },
+                { "name": "version", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" }
             ],
-            "OrderStructure": [
-                {"name": "salt", "type": "uint256"},
-                {"name": "maker", "type": "address"},
-                {"name": "receiver", "type": "address"},
-                {"name": "makerAsset", "type": "address"},
-                {"name": "takerAsset", "type": "address"},
-                {"name": "makingAmount", "type": "uint256"},
-                {"name": "takingAmount", "type": "uint256"},
-                {"name": "makerTraits", "type": "uint256"}
+            "Permit": [
+                { "name": "owner", "type": "address" },
+                { "name": "spender", "type": "address" },
+                { "name": "value", "type": "uint256" },
+                { "name": "nonce", "type": "uint256" },
+                { "name": "deadline", "type": "uint256" }
             ]
         },
-        "primaryType": "OrderStructure",
+        "primaryType": "Permit",
         "domain": {
-            "name": "1inch",
-            "version": "1",
+            "name": "USD Coin",
+            "version": "2",
             "chainId": 1,
-            "verifyingContract": "0x119c71d3BbAc22029622CBaEC24854d3D32D2828"
+            "verifyingContract": USDC
         },
         "message": {
-            "salt": "1",
-            "maker": "0xabc0000000000000000000000000000000000001",
-            "receiver": "0xabc0000000000000000000000000000000000002",
-            "makerAsset": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
-            "takerAsset": "0xC02aaA39b223FE8D0A0E5C4F27eAD9083C756Cc2",
-            "makingAmount": "1000000",
-            "takingAmount": "1000000000000000000",
-            "makerTraits": "0"
+            "owner": ON_BEHALF_OF,
+            "spender": UNISWAP_V3_ROUTER,
+            "value": "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "nonce": "0",
+            "deadline": "1762956494"
         }
     });
 
     let typed: TypedData = serde_json::from_value(typed_data_json)
         .expect("typed data should parse");
 
-    let model = format_typed_data(&typed).expect("format succeeds");
+    let model =
+        format_typed_data(&typed, &Locale::default()).expect("format succeeds");
 
-    assert_eq!(model.intent, "1inch Order");
+    assert_eq!(model.intent, "Authorize spending of token");
     assert_eq!(
         model.items,
         vec![
             DisplayItem {
-                label: "From".to_string(),
-                value: "0xabc0000000000000000000000000000000000001".to_string(),
+                label: "Token".to_string(),
+                value: "USDC".to_string(),
             },
             DisplayItem {
-                label: "Send".to_string(),
-                value: "1 USDC".to_string(),
+                label: "Spender".to_string(),
+                value: "Uniswap V3 Router".to_string(),
             },
             DisplayItem {
-                label: "Receive minimum".to_string(),
-                value: "1 WETH".to_string(),
+                label: "Amount allowance".to_string(),
+                value: "Unlimited USDC".to_string(),
             },
             DisplayItem {
-                label: "To".to_string(),
-                value: "0xabc0000000000000000000000000000000000002".to_string(),
+                label: "Signature deadline".to_string(),
+                value: "2025-11-12 14:08:14 UTC".to_string(),
             },
         ]
     );
     assert_eq!(
         model.interpolated_intent.as_deref(),
         Some(
-            "Send 1 USDC for at least 1 WETH to 0xabc0000000000000000000000000000000000002"
+            "Authorize Uniswap V3 Router to spend Unlimited USDC until 2025-11-12 14:08:14 UTC"
         )
     );
-    assert!(model.warnings.is_empty());
-    assert!(model.raw.is_none());
+    assert!(model.warnings.is_empty(), "{:?}", model.warnings);
 }
 
 #[test]
-fn eip712_uniswap_permit2_formats_allowance() {
+fn eip712_dai_permit_hides_zero_expiry() {
     let typed_data_json = json!({
         "types": {
             "EIP712Domain": [
                 { "name": "name", "type": "string" },
+                { "name": "version", "type": "string" },
                 { "name": "chainId", "type": "uint256" },
                 { "name": "verifyingContract", "type": "address" }
             ],
-            "PermitDetails": [
-                { "name": "token", "type": "address" },
-                { "name": "amount", "type": "uint160" },
-                { "name": "expiration", "type": "uint48" },
-                { "name": "nonce", "type": "uint48" }
-            ],
-            "PermitSingle": [
-                { "name": "details", "type": "PermitDetails" },
+            "Permit": [
+                { "name": "holder", "type": "address" },
                 { "name": "spender", "type": "address" },
-                { "name": "sigDeadline", "type": "uint256" }
+                { "name": "nonce", "type": "uint256" },
+                { "name": "expiry", "type": "uint256" },
+                { "name": "allowed", "type": "bool" }
             ]
         },
-        "primaryType": "PermitSingle",
+        "primaryType": "Permit",
         "domain": {
-            "name": "Permit2",
-            "chainId": "10",
-            "verifyingContract": "0x000000000022d473030f116ddee9f6b43ac78ba3"
+            "name": "Dai Stablecoin",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": DAI
         },
         "message": {
-            "details": {
-                "token": "0x0b2c639c533813f4aa9d7837caf62653d097ff85",
-                "amount": "1461501637330902918203684832716283019655932542975",
-                "expiration": "1765546694",
-                "nonce": "0"
-            },
-            "spender": "0x851116d9223fabed8e56c0e6b8ad0c31d98b3507",
-            "sigDeadline": "1762956494"
+            "holder": ON_BEHALF_OF,
+            "spender": UNISWAP_V3_ROUTER,
+            "nonce": 3,
+            "expiry": 0,
+            "allowed": true
         }
     });
 
     let typed: TypedData = serde_json::from_value(typed_data_json)
         .expect("typed data should parse");
 
-    let model = format_typed_data(&typed).expect("format succeeds");
+    let model =
+        format_typed_data(&typed, &Locale::default()).expect("format succeeds");
 
-    assert_eq!(model.intent, "Authorize spending of token");
     assert_eq!(
         model.items,
         vec![
             DisplayItem {
-                label: "Spender".to_string(),
-                value: "Uniswap Universal Router".to_string(),
-            },
-            DisplayItem {
-                label: "Amount allowance".to_string(),
-                value: "Unlimited USDC".to_string(),
+                label: "Token".to_string(),
+                value: "DAI".to_string(),
             },
             DisplayItem {
-                label: "Approval expires".to_string(),
-                value: "2025-12-12 13:38:14 UTC".to_string(),
+                label: "Spender".to_string(),
+                value: "Uniswap V3 Router".to_string(),
             },
             DisplayItem {
-                label: "Signature deadline".to_string(),
-                value: "2025-11-12 14:08:14 UTC".to_string(),
+                label: "Amount allowance".to_string(),
+                value: "Unlimited".to_string(),
             },
         ]
     );
+    assert_eq!(
+        model.interpolated_intent.as_deref(),
+        Some("Set allowance of Uniswap V3 Router to Unlimited")
+    );
+    assert!(model.warnings.is_empty(), "{:?}", model.warnings);
+}
+
+#[test]
+fn eip712_safe_tx_formats_inner_call() {
+    let typed_data_json = json!({
+        "types": {
+            "EIP712Domain": [
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" }
+            ],
+            "SafeTx": [
+                { "name": "to", "type": "address" },
+                { "name": "value", "type": "uint256" },
+                { "name": "data", "type": "bytes" },
+                { "name": "operation", "type": "uint8" },
+                { "name": "safeTxGas", "type": "uint256" },
+                { "name": "baseGas", "type": "uint256" },
+                { "name": "gasPrice", "type": "uint256" },
+                { "name": "gasToken", "type": "address" },
+                { "name": "refundReceiver", "type": "address" },
+                { "name": "nonce", "type": "uint256" }
+            ]
+        },
+        "primaryType": "SafeTx",
+        "domain": { "chainId": 1, "verifyingContract": SAFE },
+        "message": {
+            "to": USDT_MAINNET,
+            "value": "0",
+            "data": format!("0x{}", hex::encode(usdt_approve())),
+            "operation": 0,
+            "safeTxGas": "0",
+            "baseGas": "0",
+            "gasPrice": "0",
+            "gasToken": "0x0000000000000000000000000000000000000000",
+            "refundReceiver": "0x0000000000000000000000000000000000000000",
+            "nonce": "5"
+        }
+    });
+
+    let typed: TypedData = serde_json::from_value(typed_data_json)
+        .expect("typed data should parse");
+
+    let model =
+        format_typed_data(&typed, &Locale::default()).expect("format succeeds");
+
+    assert_eq!(model.intent, "Execute Safe transaction");
     assert_eq!(
         model.interpolated_intent.as_deref(),
         Some(
-            "Authorize Uniswap Universal Router to spend Unlimited USDC until 2025-11-12 14:08:14 UTC"
+            "Execute Safe transaction: Approve Uniswap V3 Router to spend 1,000,000 USDT"
         )
     );
-    assert!(model.warnings.is_empty());
-    assert!(model.raw.is_none());
+    let labels =
+        model.items.iter().map(|item| item.label.as_str()).collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        vec!["Transaction", "To", "Amount", "Operation", "Nonce"]
+    );
+    assert_eq!(model.items[1].value, USDT_MAINNET);
+    assert_eq!(model.items[2].value, "0 ETH");
+    assert_eq!(model.items[3].value, "Call");
+    assert!(model.warnings.is_empty(), "{:?}", model.warnings);
+    assert_eq!(model.calls.len(), 1);
+    assert_eq!(model.calls[0].intent, "Approve USDT spending");
+}
+
+#[test]
+fn eip712_seaport_order_expands_item_arrays() {
+    let offerer = "0x1111111111111111111111111111111111111111";
+    let fee_recipient = "0x4444444444444444444444444444444444444444";
+    let typed_data_json = json!({
+        "types": {
+            "EIP712Domain": [
+                { "name": "name", "type": "string" },
+                { "name": "version", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" }
+            ],
+            "OrderComponents": [
+                { "name": "offerer", "type": "address" },
+                { "name": "zone", "type": "address" },
+                { "name": "offer", "type": "OfferItem[]" },
+                { "name": "consideration", "type": "ConsiderationItem[]" },
+                { "name": "orderType", "type": "uint8" },
+                { "name": "startTime", "type": "uint256" },
+                { "name": "endTime", "type": "uint256" },
+                { "name": "zoneHash", "type": "bytes32" },
+                { "name": "salt", "type": "uint256" },
+                { "name": "conduitKey", "type": "bytes32" },
+                { "name": "counter", "type": "uint256" }
+            ],
+            "OfferItem": [
+                { "name": "itemType", "type": "uint8" },
+                { "name": "token", "type": "address" },
+                { "name": "identifierOrCriteria", "type": "uint256" },
+                { "name": "startAmount", "type": "uint256" },
+                { "name": "endAmount", "type": "uint256" }
+            ],
+            "ConsiderationItem": [
+                { "name": "itemType", "type": "uint8" },
+                { "name": "token", "type": "address" },
+                { "name": "identifierOrCriteria", "type": "uint256" },
+                { "name": "startAmount", "type": "uint256" },
+                { "name": "endAmount", "type": "uint256" },
+                { "name": "recipient", "type": "address" }
+            ]
+        },
+        "primaryType": "OrderComponents",
+        "domain": {
+            "name": "Seaport",
+            "version": "1.6",
+            "chainId": 1,
+            "verifyingContract": "0x0000000000000068F116a894984e2DB1123eB395"
+        },
+        "message": {
+            "offerer": offerer,
+            "zone": "0x0000000000000000000000000000000000000000",
+            "offer": [{
+                "itemType": 2,
+                "token": "0x5555555555555555555555555555555555555555",
+                "identifierOrCriteria": "42",
+                "startAmount": "1",
+                "endAmount": "1"
+            }],
+            "consideration": [
+                {
+                    "itemType": 0,
+                    "token": "0x0000000000000000000000000000000000000000",
+                    "identifierOrCriteria": "0",
+                    "startAmount": "975000000000000000",
+                    "endAmount": "975000000000000000",
+                    "recipient": offerer
+                },
+                {
+                    "itemType": 0,
+                    "token": "0x0000000000000000000000000000000000000000",
+                    "identifierOrCriteria": "0",
+                    "startAmount": "25000000000000000",
+                    "endAmount": "25000000000000000",
+                    "recipient": fee_recipient
+                }
+            ],
+            "orderType": 0,
+            "startTime": "1762956494",
+            "endTime": "1767225600",
+            "zoneHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "salt": "1",
+            "conduitKey": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "counter": "0"
+        }
+    });
+
+    let typed: TypedData = serde_json::from_value(typed_data_json)
+        .expect("typed data should parse");
+
+    let model =
+        format_typed_data(&typed, &Locale::default()).expect("format succeeds");
+
+    assert_eq!(model.intent, "List on Seaport");
+    let items = model
+        .items
+        .iter()
+        .map(|item| (item.label.as_str(), item.value.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        items,
+        vec![
+            ("Offerer", offerer),
+            ("Item type", "ERC-721"),
+            ("Token", "0x5555555555555555555555555555555555555555"),
+            ("Token ID", "42"),
+            ("Amount", "1"),
+            ("Item type", "Native"),
+            ("Amount", "975000000000000000"),
+            ("Recipient", offerer),
+            ("Item type", "Native"),
+            ("Amount", "25000000000000000"),
+            ("Recipient", fee_recipient),
+            ("Start time", "2025-11-12 14:08:14 UTC"),
+            ("End time", "2026-01-01 00:00:00 UTC"),
+        ]
+    );
+    assert!(model.warnings.is_empty(), "{:?}", model.warnings);
+}
+
+#[test]
+fn eip712_cow_order_formats_swap() {
+    let typed_data_json = json!({
+        "types": {
+            "EIP712Domain": [
+                { "name": "name", "type": "string" },
+                { "name": "version", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" }
+            ],
+            "Order": [
+                { "name": "sellToken", "type": "address" },
+                { "name": "buyToken", "type": "address" },
+                { "name": "receiver", "type": "address" },
+                { "name": "sellAmount", "type": "uint256" },
+                { "name": "buyAmount", "type": "uint256" },
+                { "name": "validTo", "type": "uint32" },
+                { "name": "appData", "type": "bytes32" },
+                { "name": "feeAmount", "type": "uint256" },
+                { "name": "kind", "type": "string" },
+                { "name": "partiallyFillable", "type": "bool" },
+                { "name": "sellTokenBalance", "type": "string" },
+                { "name": "buyTokenBalance", "type": "string" }
+            ]
+        },
+        "primaryType": "Order",
+        "domain": {
+            "name": "Gnosis Protocol",
+            "version": "v2",
+            "chainId": 1,
+            "verifyingContract": "0x9008D19f58AAbD9eD0D60971565AA8510560ab41"
+        },
+        "message": {
+            "sellToken": USDC,
+            "buyToken": "0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE",
+            "receiver": "0x0000000000000000000000000000000000000000",
+            "sellAmount": "1500000000",
+            "buyAmount": "500000000000000000",
+            "validTo": 1762956494,
+            "appData": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "feeAmount": "0",
+            "kind": "sell",
+            "partiallyFillable": false,
+            "sellTokenBalance": "erc20",
+            "buyTokenBalance": "erc20"
+        }
+    });
+
+    let typed: TypedData = serde_json::from_value(typed_data_json)
+        .expect("typed data should parse");
+
+    let model =
+        format_typed_data(&typed, &Locale::default()).expect("format succeeds");
+
+    assert_eq!(model.intent, "Swap order");
+    assert_eq!(
+        model.interpolated_intent.as_deref(),
+        Some("Swap 1,500 USDC for 0.5 ETH")
+    );
+    let items = model
+        .items
+        .iter()
+        .map(|item| (item.label.as_str(), item.value.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        items,
+        vec![
+            ("Send", "1,500 USDC"),
+            ("Receive", "0.5 ETH"),
+            ("Recipient", "Sender"),
+            ("Valid until", "2025-11-12 14:08:14 UTC"),
+            ("Order type", "Sell"),
+            ("Partially fillable", "No"),
+        ]
+    );
+    assert!(model.warnings.is_empty(), "{:?}", model.warnings);
 }
 
 #[test]
@@ -560,8 +1897,9 @@ fn usdt_approve_all_displays_all_message() {
         ],
     );
 
-    let model = format_with_value(1, USDT_MAINNET, None, &calldata)
-        .expect("format succeeds");
+    let model =
+        format_with_value(1, USDT_MAINNET, None, &calldata, &Locale::default())
+            .expect("format succeeds");
 
     assert_eq!(model.intent, "Approve USDT spending");
     assert!(model.warnings.is_empty());
@@ -587,8 +1925,9 @@ fn usdt_approve_specific_amount_formats_decimals() {
         &[address_word(AAVE_LPV2_MAINNET), uint_word_u128(85_031)],
     );
 
-    let model = format_with_value(1, USDT_MAINNET, None, &calldata)
-        .expect("format succeeds");
+    let model =
+        format_with_value(1, USDT_MAINNET, None, &calldata, &Locale::default())
+            .expect("format succeeds");
 
     assert_eq!(model.intent, "Approve USDT spending");
     assert!(model.warnings.is_empty());
@@ -623,8 +1962,14 @@ fn usdt_approve_all_on_optimism() {
         ],
     );
 
-    let model = format_with_value(10, USDT_OPTIMISM_CANONICAL, None, &calldata)
-        .expect("format succeeds");
+    let model = format_with_value(
+        10,
+        USDT_OPTIMISM_CANONICAL,
+        None,
+        &calldata,
+        &Locale::default(),
+    )
+    .expect("format succeeds");
 
     assert_eq!(model.intent, "Approve USDT spending");
     assert!(model.warnings.is_empty());
@@ -664,6 +2009,7 @@ fn usdc_approve_all_on_optimism() {
         "0x7f5c764cBc14f9669B88837CA1490cCa17C31607",
         None,
         &calldata,
+        &Locale::default(),
     )
     .expect("format succeeds");
 
@@ -700,8 +2046,14 @@ fn permit2_spender_labeled_on_optimism() {
         ],
     );
 
-    let model = format_with_value(10, USDT_OPTIMISM_CANONICAL, None, &calldata)
-        .expect("format succeeds");
+    let model = format_with_value(
+        10,
+        USDT_OPTIMISM_CANONICAL,
+        None,
+        &calldata,
+        &Locale::default(),
+    )
+    .expect("format succeeds");
 
     assert_eq!(model.intent, "Approve USDT spending");
     assert!(model.warnings.is_empty());
@@ -738,8 +2090,14 @@ fn native_usdc_approve_all_on_optimism() {
         ],
     );
 
-    let model = format_with_value(10, USDC_OPTIMISM_NATIVE, None, &calldata)
-        .expect("format succeeds");
+    let model = format_with_value(
+        10,
+        USDC_OPTIMISM_NATIVE,
+        None,
+        &calldata,
+        &Locale::default(),
+    )
+    .expect("format succeeds");
 
     assert_eq!(model.intent, "Approve USDC spending");
     assert!(model.warnings.is_empty());
@@ -774,8 +2132,14 @@ fn usdc_approve_all_on_arbitrum() {
         ],
     );
 
-    let model = format_with_value(42161, USDC_ARBITRUM, None, &calldata)
-        .expect("format succeeds");
+    let model = format_with_value(
+        42161,
+        USDC_ARBITRUM,
+        None,
+        &calldata,
+        &Locale::default(),
+    )
+    .expect("format succeeds");
 
     assert_eq!(model.intent, "Approve USDC spending");
     assert!(model.warnings.is_empty());
@@ -810,8 +2174,9 @@ fn usdc_approve_all_on_base() {
         ],
     );
 
-    let model = format_with_value(8453, USDC_BASE, None, &calldata)
-        .expect("format succeeds");
+    let model =
+        format_with_value(8453, USDC_BASE, None, &calldata, &Locale::default())
+            .expect("format succeeds");
 
     assert_eq!(model.intent, "Approve USDC spending");
     assert!(model.warnings.is_empty());
@@ -846,8 +2211,14 @@ fn usdt_approve_all_on_arbitrum() {
         ],
     );
 
-    let model = format_with_value(42161, USDT_ARBITRUM, None, &calldata)
-        .expect("format succeeds");
+    let model = format_with_value(
+        42161,
+        USDT_ARBITRUM,
+        None,
+        &calldata,
+        &Locale::default(),
+    )
+    .expect("format succeeds");
 
     assert_eq!(model.intent, "Approve USDT spending");
     assert!(model.warnings.is_empty());
@@ -907,3 +2278,96 @@ fn uint_word_biguint(value: BigUint) -> [u8; 32] {
     word[32 - bytes.len()..].copy_from_slice(&bytes);
     word
 }
+
+enum AbiArg {
+    Word([u8; 32]),
+    Bytes(Vec<u8>),
+    BytesArray(Vec<Vec<u8>>),
+}
+
+fn encode_call(signature: &str, args: &[AbiArg]) -> Vec<u8> {
+    let mut data = selector(signature).to_vec();
+    data.extend(abi_encode(args));
+    data
+}
+
+fn abi_encode(args: &[AbiArg]) -> Vec<u8> {
+    let head_len = args.len() * 32;
+    let mut head = Vec::new();
+    let mut tail = Vec::new();
+    for arg in args {
+        match arg {
+            AbiArg::Word(word) => head.extend_from_slice(word),
+            AbiArg::Bytes(bytes) => {
+                head.extend(uint_word_u128((head_len + tail.len()) as u128));
+                tail.extend(encode_bytes(bytes));
+            }
+            AbiArg::BytesArray(items) => {
+                head.extend(uint_word_u128((head_len + tail.len()) as u128));
+                tail.extend(uint_word_u128(items.len() as u128));
+                let encoded = items
+                    .iter()
+                    .map(|item| encode_bytes(item))
+                    .collect::<Vec<_>>();
+                let mut offset = items.len() * 32;
+                for item in &encoded {
+                    tail.extend(uint_word_u128(offset as u128));
+                    offset += item.len();
+                }
+                tail.extend(encoded.concat());
+            }
+        }
+    }
+    head.extend(tail);
+    head
+}
+
+fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = uint_word_u128(bytes.len() as u128).to_vec();
+    encoded.extend_from_slice(bytes);
+    encoded.resize(32 + bytes.len().div_ceil(32) * 32, 0);
+    encoded
+}
+
+fn usdt_approve() -> Vec<u8> {
+    build_calldata(
+        selector("approve(address,uint256)"),
+        &[
+            address_word(UNISWAP_V3_ROUTER),
+            uint_word_u128(1_000_000_000_000u128),
+        ],
+    )
+}
+
+fn safe_exec_transaction(
+    to: &str,
+    value: u128,
+    data: Vec<u8>,
+    operation: u8,
+) -> Vec<u8> {
+    let zero_address = address_word(&format!("0x{}", "00".repeat(20)));
+    encode_call(
+        "execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)",
+        &[
+            AbiArg::Word(address_word(to)),
+            AbiArg::Word(uint_word_u128(value)),
+            AbiArg::Bytes(data),
+            AbiArg::Word(uint_word_u128(operation as u128)),
+            AbiArg::Word(uint_word_u128(0)),
+            AbiArg::Word(uint_word_u128(0)),
+            AbiArg::Word(uint_word_u128(0)),
+            AbiArg::Word(zero_address),
+            AbiArg::Word(zero_address),
+            AbiArg::Bytes(Vec::new()),
+        ],
+    )
+}
+
+fn multi_send_transaction(to: &str, value: u128, data: &[u8]) -> Vec<u8> {
+    let mut packed = vec![0u8];
+    packed.extend_from_slice(&address_word(to)[12..]);
+    packed.extend(uint_word_u128(value));
+    packed.extend(uint_word_u128(data.len() as u128));
+    packed.extend_from_slice(data);
+    packed
+}